@@ -74,6 +74,32 @@ impl RatioCalculator {
         })
     }
 
+    /// ROE、ROA、资产负债率，按报表顺序对齐各期的资产负债表和利润表
+    pub fn calculate_strength(&self, statements: &[FinancialStatement]) -> StrengthAnalysis {
+        let of_type = |report_type: ReportType| -> Vec<&FinancialStatement> {
+            statements.iter().filter(|s| s.report_type == report_type).collect()
+        };
+        let balance_sheets = of_type(ReportType::BalanceSheet);
+        let income_statements = of_type(ReportType::IncomeStatement);
+
+        let mut strength = StrengthAnalysis {
+            years: balance_sheets.iter().map(|bs| bs.report_date.year()).collect(),
+            ..StrengthAnalysis::default()
+        };
+        for (i, bs) in balance_sheets.iter().enumerate() {
+            let net_profit = income_statements.get(i).and_then(|is| is.items.get("净利润")).copied();
+            let balance = |account: &str| bs.items.get(account).copied();
+            let ratio = |numerator: Option<Decimal>, denominator: Option<Decimal>| match (numerator, denominator) {
+                (Some(a), Some(b)) if b > Decimal::ZERO => Some(a / b),
+                _ => None,
+            };
+            strength.roe.push(ratio(net_profit, balance("所有者权益合计")));
+            strength.roa.push(ratio(net_profit, balance("资产总计")));
+            strength.debt_ratio.push(ratio(balance("负债合计"), balance("资产总计")));
+        }
+        strength
+    }
+
     pub fn calculate_leverage(
         &self,
         income_statements: &[IncomeStatement],
@@ -107,12 +133,6 @@ impl RatioCalculator {
             if i + 1 < income_statements.len() {
                 let prev = &income_statements[i + 1];
                 let prev_ebit = prev.core_profit;
-                let prev_interest = prev.statement.items
-                    .get("财务费用")
-                    .or_else(|| prev.statement.items.get("利息费用"))
-                    .copied()
-                    .unwrap_or(Decimal::ZERO);
-                let prev_ebt = prev_ebit - prev_interest;
                 
                 // 经营杠杆 DOL = (EBIT变化率) / (销售收入变化率)
                 let revenue_change = if prev.revenue != Decimal::ZERO {
@@ -0,0 +1,265 @@
+//! 多公司对比：对齐报告期、并列指标、排名与共同比报表
+//!
+//! 各公司分别分析后，只保留所有公司都有数据的报告期（按第一家公司的顺序，最新一期在前），
+//! 每个指标按期给出各公司的数值和排名。
+
+use crate::domain::*;
+use anyhow::{bail, Result};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+/// 指标的优劣方向，决定排名顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// 越高越好
+    Higher,
+    /// 越低越好
+    Lower,
+}
+
+/// 所有公司共有的报告期
+#[derive(Debug, Clone)]
+pub struct AlignedPeriod {
+    /// 表头文字，如 2023年、2024Q3
+    pub label: String,
+    /// 该期在各公司分析结果中的序号（与 `Comparison::results` 顺序一致）
+    pub indices: Vec<usize>,
+}
+
+/// 一个对比指标：`values[期][公司]`，`ranks` 与之对应，1 为最优
+#[derive(Debug, Clone)]
+pub struct MetricComparison {
+    pub name: &'static str,
+    pub direction: Direction,
+    /// 比率（百分数显示）还是倍数
+    pub percent: bool,
+    pub values: Vec<Vec<Option<f64>>>,
+    pub ranks: Vec<Vec<Option<usize>>>,
+}
+
+/// 共同比报表的一行：科目占基数的比例，`values[期][公司]`
+#[derive(Debug, Clone)]
+pub struct CommonSizeRow {
+    pub item: &'static str,
+    pub values: Vec<Vec<Option<f64>>>,
+}
+
+/// 共同比报表的一部分（资产负债表以资产总计为基数，利润表以营业收入为基数）
+#[derive(Debug, Clone)]
+pub struct CommonSizeSection {
+    pub title: &'static str,
+    pub base: &'static str,
+    pub rows: Vec<CommonSizeRow>,
+}
+
+const BALANCE_ITEMS: [&str; 10] = [
+    "货币资金",
+    "应收账款",
+    "存货",
+    "固定资产",
+    "无形资产",
+    "短期借款",
+    "应付账款",
+    "长期借款",
+    "负债合计",
+    "所有者权益合计",
+];
+
+const INCOME_ITEMS: [&str; 8] = ["营业成本", "税金及附加", "销售费用", "管理费用", "研发费用", "财务费用", "营业利润", "净利润"];
+
+/// 多公司对比
+pub struct Comparison<'a> {
+    pub results: &'a [AnalysisResult],
+    pub periods: Vec<AlignedPeriod>,
+}
+
+impl<'a> Comparison<'a> {
+    /// 对齐各公司的报告期；少于两家公司或没有共同报告期时报错
+    pub fn new(results: &'a [AnalysisResult]) -> Result<Self> {
+        if results.len() < 2 {
+            bail!("对比分析至少需要两家公司，当前为 {} 家", results.len());
+        }
+        if results.iter().any(|r| r.period != results[0].period) {
+            bail!("各公司的分析期间不一致，无法对比");
+        }
+
+        let labels: Vec<Vec<String>> = results.iter().map(period_keys).collect();
+        let periods: Vec<AlignedPeriod> = labels[0]
+            .iter()
+            .enumerate()
+            .filter_map(|(first, key)| {
+                let indices: Option<Vec<usize>> = labels
+                    .iter()
+                    .map(|keys| keys.iter().position(|k| k == key))
+                    .collect();
+                indices.map(|indices| AlignedPeriod {
+                    label: results[0].period_label(first),
+                    indices,
+                })
+            })
+            .collect();
+
+        if periods.is_empty() {
+            let codes: Vec<&str> = results.iter().map(|r| r.stock_code.as_str()).collect();
+            bail!("{} 没有共同的报告期", codes.join("、"));
+        }
+        Ok(Self { results, periods })
+    }
+
+    /// 股票代码（与 `results` 顺序一致）
+    pub fn stock_codes(&self) -> Vec<&str> {
+        self.results.iter().map(|r| r.stock_code.as_str()).collect()
+    }
+
+    /// 并列的关键比率及各期排名
+    pub fn metrics(&self) -> Vec<MetricComparison> {
+        let margin = |values: fn(&AnalysisResult) -> &Vec<Decimal>| {
+            move |r: &AnalysisResult, i: usize| values(r).get(i).and_then(|v| v.to_f64())
+        };
+        let ratio = |numerator: (ReportType, &'static str), denominator: (ReportType, &'static str)| {
+            move |r: &AnalysisResult, i: usize| match (item(r, numerator.0, i, numerator.1), item(r, denominator.0, i, denominator.1)) {
+                (Some(a), Some(b)) if b > 0.0 => Some(a / b),
+                _ => None,
+            }
+        };
+        let strength = |values: fn(&AnalysisResult) -> &Vec<Option<Decimal>>| {
+            move |r: &AnalysisResult, i: usize| values(r).get(i).copied().flatten().and_then(|v| v.to_f64())
+        };
+        let net_profit = (ReportType::IncomeStatement, "净利润");
+
+        vec![
+            self.metric("毛利率", Direction::Higher, true, margin(|r| &r.profit_analysis.gross_margin)),
+            self.metric("核心利润率", Direction::Higher, true, margin(|r| &r.profit_analysis.core_profit_margin)),
+            self.metric("净利润率", Direction::Higher, true, margin(|r| &r.profit_analysis.net_profit_margin)),
+            self.metric("ROE(净资产收益率)", Direction::Higher, true, strength(|r| &r.strength.roe)),
+            self.metric("ROA(总资产收益率)", Direction::Higher, true, strength(|r| &r.strength.roa)),
+            self.metric("资产负债率", Direction::Lower, true, strength(|r| &r.strength.debt_ratio)),
+            self.metric(
+                "经营性资产占比",
+                Direction::Higher,
+                true,
+                margin(|r| &r.asset_structure.operating_asset_ratio),
+            ),
+            self.metric(
+                "净现比(经营现金流/净利润)",
+                Direction::Higher,
+                false,
+                ratio((ReportType::CashflowStatement, "经营活动产生的现金流量净额"), net_profit),
+            ),
+        ]
+    }
+
+    fn metric(
+        &self,
+        name: &'static str,
+        direction: Direction,
+        percent: bool,
+        value: impl Fn(&AnalysisResult, usize) -> Option<f64>,
+    ) -> MetricComparison {
+        let values: Vec<Vec<Option<f64>>> = self
+            .periods
+            .iter()
+            .map(|period| {
+                self.results
+                    .iter()
+                    .zip(&period.indices)
+                    .map(|(result, &i)| value(result, i))
+                    .collect()
+            })
+            .collect();
+        let ranks = values.iter().map(|row| rank(row, direction)).collect();
+        MetricComparison {
+            name,
+            direction,
+            percent,
+            values,
+            ranks,
+        }
+    }
+
+    /// 共同比资产负债表和利润表
+    pub fn common_size(&self) -> Vec<CommonSizeSection> {
+        vec![
+            self.common_size_section("资产负债表", "资产总计", ReportType::BalanceSheet, &BALANCE_ITEMS),
+            self.common_size_section("利润表", "营业收入", ReportType::IncomeStatement, &INCOME_ITEMS),
+        ]
+    }
+
+    fn common_size_section(
+        &self,
+        title: &'static str,
+        base: &'static str,
+        report_type: ReportType,
+        items: &[&'static str],
+    ) -> CommonSizeSection {
+        let rows = items
+            .iter()
+            .map(|&account| CommonSizeRow {
+                item: account,
+                values: self
+                    .periods
+                    .iter()
+                    .map(|period| {
+                        self.results
+                            .iter()
+                            .zip(&period.indices)
+                            .map(|(result, &i)| {
+                                let base = common_size_base(result, report_type, i)?;
+                                item(result, report_type, i, account).map(|v| v / base)
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            })
+            .collect();
+        CommonSizeSection { title, base, rows }
+    }
+}
+
+/// 比较报告期用的键：优先使用期间标签，旧结果没有标签时使用年份
+fn period_keys(result: &AnalysisResult) -> Vec<String> {
+    if result.period_labels.is_empty() {
+        result.asset_structure.years.iter().map(|y| y.to_string()).collect()
+    } else {
+        result.period_labels.clone()
+    }
+}
+
+/// 第 i 期报表中的科目值
+fn item(result: &AnalysisResult, report_type: ReportType, i: usize, account: &str) -> Option<f64> {
+    result
+        .statements
+        .iter()
+        .filter(|s| s.report_type == report_type)
+        .nth(i)
+        .and_then(|s| s.items.get(account))
+        .and_then(|v| v.to_f64())
+}
+
+/// 共同比的基数：资产总计或营业收入（缺失时用营业总收入），不为正时为 None
+fn common_size_base(result: &AnalysisResult, report_type: ReportType, i: usize) -> Option<f64> {
+    let base = match report_type {
+        ReportType::BalanceSheet => item(result, report_type, i, "资产总计"),
+        _ => item(result, report_type, i, "营业收入").or_else(|| item(result, report_type, i, "营业总收入")),
+    };
+    base.filter(|b| *b > 0.0)
+}
+
+/// 竞争排名（并列同名次，下一名次顺延），缺失值不参与排名
+pub fn rank(values: &[Option<f64>], direction: Direction) -> Vec<Option<usize>> {
+    values
+        .iter()
+        .map(|value| {
+            let value = (*value)?;
+            let better = values
+                .iter()
+                .flatten()
+                .filter(|other| match direction {
+                    Direction::Higher => **other > value,
+                    Direction::Lower => **other < value,
+                })
+                .count();
+            Some(better + 1)
+        })
+        .collect()
+}
@@ -1,19 +1,59 @@
 use crate::data_source::DataSource;
 use crate::domain::*;
-use crate::validation::{DataValidator, ValidationResult};
+use crate::validation::{DataValidator, StatementValidation};
 use anyhow::Result;
-use chrono::Datelike;
+use chrono::{Datelike, NaiveDate};
 use rust_decimal::Decimal;
 
 mod calculator;
 mod valuation;
 mod sensitivity;
+mod period;
+mod compare;
+mod wacc;
+mod reverse;
 #[cfg(test)]
 mod tests;
 
-use calculator::RatioCalculator;
-pub use valuation::{Valuator, ValuationResult, ValuationParams};
+pub(crate) use calculator::RatioCalculator;
+pub use valuation::{BridgeAdjustment, DCFValuation, EquityModelValuation, ForecastStage, Valuator, ValuationModel, ValuationResult, ValuationParams};
 pub use sensitivity::{SensitivityParams, SensitivityResult};
+pub use compare::{Comparison, Direction};
+pub use reverse::ImpliedGrowth;
+pub use period::PeriodStatement;
+pub use wacc::{load_closes, BetaEstimate, CapitalWeights, Wacc, WaccParams};
+
+/// 解析分析年份及报表日期区间
+///
+/// 年份为空时默认取最近3年（季度/TTM模式包含当年）；返回 (年份列表, 起始日期, 截止日期)，
+/// 分析与缓存预热共用，保证两者请求的日期区间一致。
+/// 季度/TTM模式额外取上一年的报表，用于拆分单季度和计算TTM。
+pub fn report_period(years: Vec<i32>, period: ReportPeriod) -> (Vec<i32>, NaiveDate, NaiveDate) {
+    let years = if years.is_empty() {
+        let current_year = chrono::Local::now().year();
+        match period {
+            ReportPeriod::Annual => vec![current_year - 1, current_year - 2, current_year - 3],
+            _ => vec![current_year, current_year - 1, current_year - 2],
+        }
+    } else {
+        years
+    };
+
+    let earliest = *years.iter().min().unwrap();
+    let latest = *years.iter().max().unwrap();
+    let (start_date, end_date) = match period {
+        ReportPeriod::Annual => (
+            NaiveDate::from_ymd_opt(earliest, 12, 31).unwrap(),
+            NaiveDate::from_ymd_opt(latest, 12, 31).unwrap(),
+        ),
+        _ => (
+            NaiveDate::from_ymd_opt(earliest - 1, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(latest, 12, 31).unwrap(),
+        ),
+    };
+
+    (years, start_date, end_date)
+}
 
 /// 财务分析器
 /// 
@@ -38,6 +78,7 @@ pub struct FinancialAnalyzer {
     calculator: RatioCalculator,
     validator: Option<DataValidator>,
     valuator: Valuator,
+    period: ReportPeriod,
 }
 
 impl FinancialAnalyzer {
@@ -49,6 +90,7 @@ impl FinancialAnalyzer {
             calculator: RatioCalculator::new(),
             validator: None,
             valuator: Valuator::with_default(),
+            period: ReportPeriod::Annual,
         }
     }
 
@@ -67,11 +109,18 @@ impl FinancialAnalyzer {
     /// # Arguments
     /// 
     /// * `params` - 估值参数（DCF和唐朝估值法）
+    #[allow(dead_code)]
     pub fn with_valuation_params(mut self, params: ValuationParams) -> Self {
         self.valuator = Valuator::new(params);
         self
     }
 
+    /// 设置分析期间模式（年报 / 单季度 / TTM）
+    pub fn with_period(mut self, period: ReportPeriod) -> Self {
+        self.period = period;
+        self
+    }
+
     /// 执行财务分析
     /// 
     /// # Arguments
@@ -94,17 +143,27 @@ impl FinancialAnalyzer {
         years: Vec<i32>,
         data_source: &dyn DataSource,
     ) -> Result<AnalysisResult> {
-        // 如果years为空，使用默认的最近3年
-        let years = if years.is_empty() {
-            let current_year = chrono::Local::now().year();
-            vec![current_year - 1, current_year - 2, current_year - 3]
-        } else {
-            years
-        };
-        
+        self.analyze_with_params(stock_code, years, data_source, self.valuator.params.clone()).await
+    }
+
+    /// 分析器当前的估值参数
+    pub fn valuation_params(&self) -> &ValuationParams {
+        &self.valuator.params
+    }
+
+    /// 用指定的估值参数执行财务分析（批量分析中每只股票可单独指定参数）
+    pub async fn analyze_with_params(
+        &self,
+        stock_code: &str,
+        years: Vec<i32>,
+        data_source: &dyn DataSource,
+        params: ValuationParams,
+    ) -> Result<AnalysisResult> {
+        let (years, start_date, end_date) = report_period(years, self.period);
+        let earliest_year = *years.iter().min().unwrap();
+
         // 获取数据
-        let start_date = chrono::NaiveDate::from_ymd_opt(years[years.len() - 1], 12, 31).unwrap();
-        let end_date = chrono::NaiveDate::from_ymd_opt(years[0], 12, 31).unwrap();
+        tracing::info!("从 {} 获取 {} 的财务数据", data_source.name(), stock_code);
 
         let balance_sheets = data_source
             .fetch_balance_sheet(stock_code, start_date, end_date)
@@ -118,31 +177,54 @@ impl FinancialAnalyzer {
             .fetch_cashflow_statement(stock_code, start_date, end_date)
             .await?;
 
+        // 按期间模式整理（年报筛选 / 单季度拆分 / TTM）
+        let arranged = period::arrange(
+            self.period,
+            earliest_year,
+            balance_sheets,
+            income_statements,
+            cashflow_statements,
+        );
+        let balance_sheets = arranged.balance_sheets;
+        let income_statements = arranged.income_statements;
+        let cashflow_statements = arranged.cashflow_statements;
+
         // 数据验证（如果启用）
+        let mut validation = Vec::new();
         if let Some(validator) = &self.validator {
             for bs in &balance_sheets {
-                let validation = validator.validate_balance_sheet(bs);
-                if !validation.is_valid {
+                let result = validator.validate_balance_sheet(bs);
+                if !result.is_valid {
                     tracing::warn!(
                         "资产负债表验证失败 ({}): {} 个错误",
                         bs.statement.report_date,
-                        validation.errors.len()
+                        result.errors.len()
                     );
-                    for error in &validation.errors {
+                    for error in &result.errors {
                         tracing::warn!("  - {}: {}", error.field, error.message);
                     }
                 }
+                validation.push(StatementValidation {
+                    report_type: ReportType::BalanceSheet,
+                    report_date: bs.statement.report_date,
+                    result,
+                });
             }
 
             for is in &income_statements {
-                let validation = validator.validate_income_statement(is);
-                if !validation.is_valid {
+                let result = validator.validate_income_statement(is);
+                if !result.is_valid {
                     tracing::warn!(
                         "利润表验证失败 ({}): {} 个错误",
                         is.statement.report_date,
-                        validation.errors.len()
+                        result.errors.len()
                     );
                 }
+                validation.push(StatementValidation {
+                    report_type: ReportType::IncomeStatement,
+                    report_date: is.statement.report_date,
+                    result,
+                });
             }
         }
 
@@ -161,27 +243,40 @@ impl FinancialAnalyzer {
             });
 
         // 更新估值器的总股本
-        let mut valuator = Valuator::new(self.valuator.params.clone());
+        let mut valuator = Valuator::new(params);
         valuator.params.total_shares = total_shares;
 
-        // 计算估值
-        let valuation = valuator.calculate(&income_statements, &cashflow_statements)?;
+        // 计算估值（季度模式使用最近四个季度之和，避免用单季数据估值）
+        let latest_balance = balance_sheets.first().map(|bs| &bs.statement);
+        let valuation = match arranged.period {
+            ReportPeriod::Quarterly => {
+                let (income, cashflow) = trailing_twelve_months(&income_statements, &cashflow_statements)?;
+                valuator.calculate(latest_balance, &income, &cashflow)?
+            }
+            _ => valuator.calculate(latest_balance, &income_statements, &cashflow_statements)?,
+        };
 
         // 合并所有报表
         let mut statements = Vec::new();
         statements.extend(balance_sheets.into_iter().map(|bs| bs.statement));
         statements.extend(income_statements.into_iter().map(|is| is.statement));
         statements.extend(cashflow_statements.into_iter().map(|cs| cs.statement));
+        let strength = self.calculator.calculate_strength(&statements);
 
         Ok(AnalysisResult {
             stock_code: stock_code.to_string(),
             years,
             asset_structure,
             profit_analysis,
+            strength,
             leverage_analysis,
             valuation: Some(valuation),
             statements,
             sensitivity: None,  // 默认不计算敏感性分析
+            period: arranged.period,
+            period_labels: arranged.labels,
+            valuation_params: Some(valuator.params),
+            validation,
         })
     }
 
@@ -191,32 +286,58 @@ impl FinancialAnalyzer {
         result: &mut AnalysisResult,
         params: SensitivityParams,
     ) -> Result<()> {
-        // 从原始报表中提取数据
-        let income_statements: Vec<_> = result.statements.iter()
-            .filter(|s| s.report_type == crate::domain::ReportType::IncomeStatement)
-            .cloned()
-            .collect();
-        
-        let cashflow_statements: Vec<_> = result.statements.iter()
-            .filter(|s| s.report_type == crate::domain::ReportType::CashflowStatement)
-            .cloned()
-            .collect();
-
-        // 获取总股本（从资产负债表中读取）
-        let total_shares = result.statements.iter()
-            .find(|s| s.report_type == crate::domain::ReportType::BalanceSheet)
-            .and_then(|s| s.items.get("股本"))
-            .copied()
-            .unwrap_or_else(|| {
-                tracing::warn!("敏感性分析：未找到股本数据，使用默认值1亿股");
-                Decimal::new(100_000_000, 0)
-            });
+        let inputs = ValuationInputs::from_result(result)?;
 
         // 使用新参数创建临时估值器
-        let temp_valuator = Valuator::new(params.to_valuation_params(total_shares));
-        
+        let temp_valuator = Valuator::new(params.to_valuation_params(inputs.total_shares()));
+        let valuation = temp_valuator.calculate(inputs.balance_sheet, &inputs.income_statements, &inputs.cashflow_statements)?;
+        let implied_growth = params.market_price.map(|price| {
+            ImpliedGrowth::solve(&temp_valuator, price, inputs.balance_sheet, &inputs.income_statements, &inputs.cashflow_statements)
+        });
+
+        // 保存敏感性分析结果
+        result.sensitivity = Some(SensitivityResult {
+            params,
+            dcf_enterprise_value: valuation.dcf.enterprise_value,
+            dcf_price_per_share: valuation.dcf.price_per_share,
+            tangchao_low_estimate: valuation.tangchao.low_estimate,
+            tangchao_high_estimate: valuation.tangchao.high_estimate,
+            tangchao_safety_margin_price: valuation.tangchao.safety_margin_price,
+            dcf_detail: Some(valuation.dcf),
+            implied_growth,
+            ddm: valuation.ddm,
+            fcfe: valuation.fcfe,
+        });
+
+        Ok(())
+    }
+
+    /// 按最新一期资产负债表和利润表计算 WACC（季度模式使用最近四季合计的利润表）
+    pub fn calculate_wacc(&self, result: &AnalysisResult, params: WaccParams) -> Result<Wacc> {
+        let inputs = ValuationInputs::from_result(result)?;
+        let balance_sheet = inputs.balance_sheet.ok_or_else(|| anyhow::anyhow!("缺少资产负债表，无法计算WACC"))?;
+        let income = inputs.income_statements.first().ok_or_else(|| anyhow::anyhow!("缺少利润表，无法计算WACC"))?;
+        Wacc::calculate(params, balance_sheet, &income.statement)
+    }
+}
+
+/// 从分析结果的原始报表整理出的估值输入
+struct ValuationInputs<'a> {
+    /// 最新一期资产负债表
+    balance_sheet: Option<&'a FinancialStatement>,
+    income_statements: Vec<IncomeStatement>,
+    cashflow_statements: Vec<CashflowStatement>,
+}
+
+impl<'a> ValuationInputs<'a> {
+    /// 季度模式使用最近四个季度之和，避免用单季数据估值
+    fn from_result(result: &'a AnalysisResult) -> Result<Self> {
+        let balance_sheet = result.statements.iter()
+            .find(|s| s.report_type == ReportType::BalanceSheet);
+
         // 从FinancialStatement构造IncomeStatement和CashflowStatement
-        let income_stmts: Vec<IncomeStatement> = income_statements.iter()
+        let income_statements: Vec<IncomeStatement> = result.statements.iter()
+            .filter(|s| s.report_type == ReportType::IncomeStatement)
             .map(|s| {
                 let revenue = s.items.get("营业收入").copied().unwrap_or(Decimal::ZERO);
                 let operating_cost = s.items.get("营业成本").copied().unwrap_or(Decimal::ZERO);
@@ -235,7 +356,8 @@ impl FinancialAnalyzer {
             })
             .collect();
         
-        let cashflow_stmts: Vec<CashflowStatement> = cashflow_statements.iter()
+        let cashflow_statements: Vec<CashflowStatement> = result.statements.iter()
+            .filter(|s| s.report_type == ReportType::CashflowStatement)
             .map(|s| {
                 let operating_cashflow = s.items.get("经营活动产生的现金流量净额").copied().unwrap_or(Decimal::ZERO);
                 let investing_cashflow = s.items.get("投资活动产生的现金流量净额").copied().unwrap_or(Decimal::ZERO);
@@ -253,20 +375,38 @@ impl FinancialAnalyzer {
             })
             .collect();
 
-        // 计算估值
-        let valuation = temp_valuator.calculate(&income_stmts, &cashflow_stmts)?;
+        let (income_statements, cashflow_statements) = match result.period {
+            ReportPeriod::Quarterly => trailing_twelve_months(&income_statements, &cashflow_statements)?,
+            _ => (income_statements, cashflow_statements),
+        };
 
-        // 保存敏感性分析结果
-        result.sensitivity = Some(SensitivityResult {
-            params,
-            dcf_enterprise_value: valuation.dcf.enterprise_value,
-            dcf_price_per_share: valuation.dcf.price_per_share,
-            tangchao_low_estimate: valuation.tangchao.low_estimate,
-            tangchao_high_estimate: valuation.tangchao.high_estimate,
-            tangchao_safety_margin_price: valuation.tangchao.safety_margin_price,
-        });
+        Ok(Self { balance_sheet, income_statements, cashflow_statements })
+    }
 
-        Ok(())
+    /// 总股本（从资产负债表中读取）
+    fn total_shares(&self) -> Decimal {
+        self.balance_sheet
+            .and_then(|s| s.items.get("股本"))
+            .copied()
+            .unwrap_or_else(|| {
+                tracing::warn!("敏感性分析：未找到股本数据，使用默认值1亿股");
+                Decimal::new(100_000_000, 0)
+            })
     }
 }
 
+/// 单季度序列的最近四季合计
+///
+/// 不足四个连续季度时报错：单季的利润和现金流只有全年的约1/4，用于估值会严重低估。
+fn trailing_twelve_months(
+    income_statements: &[IncomeStatement],
+    cashflow_statements: &[CashflowStatement],
+) -> Result<(Vec<IncomeStatement>, Vec<CashflowStatement>)> {
+    match (
+        period::trailing_sum(income_statements),
+        period::trailing_sum(cashflow_statements),
+    ) {
+        (Some(income), Some(cashflow)) => Ok((vec![income], vec![cashflow])),
+        _ => anyhow::bail!("季度模式下最近不足四个连续季度，无法按最近四季合计估值；请增加分析年份或改用 --period ttm"),
+    }
+}
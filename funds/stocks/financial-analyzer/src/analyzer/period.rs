@@ -0,0 +1,273 @@
+//! 期间换算：年报筛选、累计报表拆分单季度、滚动十二个月(TTM)
+//!
+//! A股利润表和现金流量表按年初至报告期末累计披露（Q3 报表即前三季度合计），
+//! 单季度 = 本期累计 − 上期累计，TTM = 最新累计 + 上年年报 − 上年同期累计。
+
+use crate::domain::*;
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// 带报告日期的报表
+pub trait PeriodStatement: Clone {
+    fn statement(&self) -> &FinancialStatement;
+
+    fn report_date(&self) -> NaiveDate {
+        self.statement().report_date
+    }
+}
+
+/// 可按期间加减的流量类报表（利润表、现金流量表）
+pub trait FlowStatement: PeriodStatement {
+    /// 逐项计算 self + sign × other，报告日期沿用 self
+    fn combine(&self, other: &Self, sign: Decimal) -> Self;
+}
+
+impl PeriodStatement for BalanceSheet {
+    fn statement(&self) -> &FinancialStatement {
+        &self.statement
+    }
+}
+
+impl PeriodStatement for IncomeStatement {
+    fn statement(&self) -> &FinancialStatement {
+        &self.statement
+    }
+}
+
+impl PeriodStatement for CashflowStatement {
+    fn statement(&self) -> &FinancialStatement {
+        &self.statement
+    }
+}
+
+impl FlowStatement for IncomeStatement {
+    fn combine(&self, other: &Self, sign: Decimal) -> Self {
+        Self {
+            statement: combine_statement(&self.statement, &other.statement, sign),
+            revenue: self.revenue + sign * other.revenue,
+            operating_cost: self.operating_cost + sign * other.operating_cost,
+            gross_profit: self.gross_profit + sign * other.gross_profit,
+            core_profit: self.core_profit + sign * other.core_profit,
+            net_profit: self.net_profit + sign * other.net_profit,
+        }
+    }
+}
+
+impl FlowStatement for CashflowStatement {
+    fn combine(&self, other: &Self, sign: Decimal) -> Self {
+        Self {
+            statement: combine_statement(&self.statement, &other.statement, sign),
+            operating_cashflow: self.operating_cashflow + sign * other.operating_cashflow,
+            investing_cashflow: self.investing_cashflow + sign * other.investing_cashflow,
+            financing_cashflow: self.financing_cashflow + sign * other.financing_cashflow,
+            free_cashflow: self.free_cashflow + sign * other.free_cashflow,
+        }
+    }
+}
+
+fn combine_statement(base: &FinancialStatement, other: &FinancialStatement, sign: Decimal) -> FinancialStatement {
+    let mut items: HashMap<String, Decimal> = base.items.clone();
+    for (name, value) in &other.items {
+        *items.entry(name.clone()).or_insert(Decimal::ZERO) += sign * value;
+    }
+    let mut sources = base.sources.clone();
+    for (name, source) in &other.sources {
+        sources.entry(name.clone()).or_insert_with(|| source.clone());
+    }
+
+    FinancialStatement {
+        items,
+        sources,
+        ..base.clone()
+    }
+}
+
+/// 报告期所在季度（1-4）
+pub fn quarter_of(date: NaiveDate) -> u32 {
+    date.month().div_ceil(3)
+}
+
+fn is_annual(date: NaiveDate) -> bool {
+    date.month() == 12 && date.day() == 31
+}
+
+/// 按报告日期倒序排列，同一报告期只保留第一份
+pub fn sort_desc<T: PeriodStatement>(mut statements: Vec<T>) -> Vec<T> {
+    statements.sort_by_key(|s| std::cmp::Reverse(s.report_date()));
+    statements.dedup_by_key(|s| s.report_date());
+    statements
+}
+
+/// 只保留年报
+pub fn annual_only<T: PeriodStatement>(statements: Vec<T>) -> Vec<T> {
+    statements.into_iter().filter(|s| is_annual(s.report_date())).collect()
+}
+
+fn find_by_date<T: PeriodStatement>(statements: &[T], date: NaiveDate) -> Option<&T> {
+    statements.iter().find(|s| s.report_date() == date)
+}
+
+/// 同一年份上一季度末的日期（Q1 返回 None）
+fn previous_quarter_end(date: NaiveDate) -> Option<NaiveDate> {
+    match quarter_of(date) {
+        2 => NaiveDate::from_ymd_opt(date.year(), 3, 31),
+        3 => NaiveDate::from_ymd_opt(date.year(), 6, 30),
+        4 => NaiveDate::from_ymd_opt(date.year(), 9, 30),
+        _ => None,
+    }
+}
+
+/// 由累计报表推算单季度数据，缺少上期累计数的季度被跳过
+pub fn single_quarters<T: FlowStatement>(ytd: &[T]) -> Vec<T> {
+    ytd.iter()
+        .filter_map(|current| match previous_quarter_end(current.report_date()) {
+            None => Some(current.clone()),
+            Some(prev_date) => find_by_date(ytd, prev_date)
+                .map(|prev| current.combine(prev, Decimal::NEGATIVE_ONE)),
+        })
+        .collect()
+}
+
+/// 由累计报表计算最新一期的TTM，缺少上年年报或上年同期数据时返回 None
+pub fn ttm<T: FlowStatement>(ytd: &[T]) -> Option<T> {
+    let latest = ytd.first()?;
+    let date = latest.report_date();
+    if is_annual(date) {
+        return Some(latest.clone());
+    }
+
+    let last_annual = find_by_date(ytd, NaiveDate::from_ymd_opt(date.year() - 1, 12, 31)?)?;
+    let last_same_period = find_by_date(ytd, NaiveDate::from_ymd_opt(date.year() - 1, date.month(), date.day())?)?;
+
+    Some(latest.combine(last_annual, Decimal::ONE).combine(last_same_period, Decimal::NEGATIVE_ONE))
+}
+
+/// 最近连续四个单季度之和，不足四个季度时返回 None
+pub fn trailing_sum<T: FlowStatement>(quarters: &[T]) -> Option<T> {
+    let window = quarters.get(..4)?;
+    let consecutive = window.windows(2).all(|pair| {
+        let (newer, older) = (pair[0].report_date(), pair[1].report_date());
+        previous_quarter_end(newer)
+            .unwrap_or_else(|| NaiveDate::from_ymd_opt(newer.year() - 1, 12, 31).unwrap())
+            == older
+    });
+    if !consecutive {
+        return None;
+    }
+
+    let mut sum = window[0].clone();
+    for quarter in &window[1..] {
+        sum = sum.combine(quarter, Decimal::ONE);
+    }
+    Some(sum)
+}
+
+/// 期间标签：2023、2024Q3、TTM 2024Q3
+pub fn label(period: ReportPeriod, date: NaiveDate, is_latest: bool) -> String {
+    match period {
+        ReportPeriod::Annual => date.year().to_string(),
+        ReportPeriod::Quarterly => format!("{}Q{}", date.year(), quarter_of(date)),
+        ReportPeriod::Ttm if is_latest => format!("TTM {}Q{}", date.year(), quarter_of(date)),
+        ReportPeriod::Ttm => date.year().to_string(),
+    }
+}
+
+/// 按期间模式整理后的报表，倒序排列
+pub struct ArrangedStatements {
+    /// 实际采用的期间模式（TTM 数据不足时退回年报）
+    pub period: ReportPeriod,
+    pub balance_sheets: Vec<BalanceSheet>,
+    pub income_statements: Vec<IncomeStatement>,
+    pub cashflow_statements: Vec<CashflowStatement>,
+    pub labels: Vec<String>,
+}
+
+/// 将数据源返回的报表（含中期报告）整理为指定期间模式
+pub fn arrange(
+    period: ReportPeriod,
+    earliest_year: i32,
+    balance_sheets: Vec<BalanceSheet>,
+    income_statements: Vec<IncomeStatement>,
+    cashflow_statements: Vec<CashflowStatement>,
+) -> ArrangedStatements {
+    let balance_sheets = sort_desc(balance_sheets);
+    let income_statements = sort_desc(income_statements);
+    let cashflow_statements = sort_desc(cashflow_statements);
+
+    let ttm_statements = match period {
+        ReportPeriod::Ttm => match (ttm(&income_statements), ttm(&cashflow_statements)) {
+            (Some(income), Some(cashflow)) => Some((income, cashflow)),
+            _ => {
+                tracing::warn!("缺少上年年报或上年同期数据，无法计算TTM，改用年报数据");
+                None
+            }
+        },
+        _ => None,
+    };
+
+    let (balance_sheets, income_statements, cashflow_statements) = match (period, ttm_statements) {
+        (ReportPeriod::Quarterly, _) => (
+            balance_sheets,
+            single_quarters(&income_statements),
+            single_quarters(&cashflow_statements),
+        ),
+        (ReportPeriod::Ttm, Some((income, cashflow))) => {
+            let latest_date = income.report_date();
+            let mut sheets: Vec<BalanceSheet> = find_by_date(&balance_sheets, latest_date).cloned().into_iter().collect();
+            let mut incomes = vec![income];
+            let mut cashflows = vec![cashflow];
+            sheets.extend(annual_only(balance_sheets).into_iter().filter(|s| s.report_date() < latest_date));
+            incomes.extend(annual_only(income_statements).into_iter().filter(|s| s.report_date() < latest_date));
+            cashflows.extend(annual_only(cashflow_statements).into_iter().filter(|s| s.report_date() < latest_date));
+            (sheets, incomes, cashflows)
+        }
+        _ => {
+            // 年报模式不做对齐，保持各报表原有年份
+            let balance_sheets = annual_only(balance_sheets);
+            let labels = balance_sheets
+                .iter()
+                .map(|s| label(ReportPeriod::Annual, s.report_date(), false))
+                .collect();
+            return ArrangedStatements {
+                period: ReportPeriod::Annual,
+                balance_sheets,
+                income_statements: annual_only(income_statements),
+                cashflow_statements: annual_only(cashflow_statements),
+                labels,
+            };
+        }
+    };
+
+    // 季度/TTM 模式下三张表按报告日期对齐，并剔除早于分析区间、仅用于推算的报表
+    let dates: Vec<NaiveDate> = income_statements
+        .iter()
+        .map(|s| s.report_date())
+        .filter(|date| {
+            date.year() >= earliest_year
+                && find_by_date(&balance_sheets, *date).is_some()
+                && find_by_date(&cashflow_statements, *date).is_some()
+        })
+        .collect();
+
+    let labels = dates
+        .iter()
+        .enumerate()
+        .map(|(i, date)| label(period, *date, i == 0))
+        .collect();
+
+    ArrangedStatements {
+        period,
+        balance_sheets: retain_dates(balance_sheets, &dates),
+        income_statements: retain_dates(income_statements, &dates),
+        cashflow_statements: retain_dates(cashflow_statements, &dates),
+        labels,
+    }
+}
+
+fn retain_dates<T: PeriodStatement>(statements: Vec<T>, dates: &[NaiveDate]) -> Vec<T> {
+    statements
+        .into_iter()
+        .filter(|s| dates.contains(&s.report_date()))
+        .collect()
+}
@@ -0,0 +1,94 @@
+//! 反向DCF：由当前股价反推市场隐含的增长率
+//!
+//! 沿用估值器的DCF和唐朝估值计算，二分求解使估值等于股价的增长率。
+
+use crate::analyzer::valuation::{ForecastStage, Valuator};
+use crate::domain::*;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 求解的增长率区间（上限同时避免长预测期的FCF复利超出 Decimal 范围）
+const GROWTH_RANGE: (f64, f64) = (-0.95, 1.0);
+/// 二分求解的收敛精度（增长率）
+const TOLERANCE: f64 = 1e-8;
+const MAX_ITERATIONS: usize = 200;
+
+/// 当前股价隐含的增长率；估值在求解区间内无法达到股价时为空
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImpliedGrowth {
+    pub market_price: f64,
+    /// 使DCF每股价值等于股价的FCF增长率（预测总年数不变，多阶段预测也按单一增长率逐年增长）
+    pub fcf_growth_rate: Option<f64>,
+    /// 使唐朝低估价等于股价的净利润增长率
+    pub net_profit_growth_rate: Option<f64>,
+}
+
+impl ImpliedGrowth {
+    pub fn solve(
+        valuator: &Valuator,
+        market_price: f64,
+        balance_sheet: Option<&FinancialStatement>,
+        income_statements: &[IncomeStatement],
+        cashflow_statements: &[CashflowStatement],
+    ) -> Self {
+        Self {
+            market_price,
+            fcf_growth_rate: implied_fcf_growth(valuator, market_price, balance_sheet, cashflow_statements),
+            net_profit_growth_rate: implied_net_profit_growth(valuator, market_price, income_statements),
+        }
+    }
+}
+
+fn implied_fcf_growth(
+    valuator: &Valuator,
+    market_price: f64,
+    balance_sheet: Option<&FinancialStatement>,
+    cashflows: &[CashflowStatement],
+) -> Option<f64> {
+    // 基准FCF不为正时估值不随增长率上升，没有有意义的隐含增长率
+    let base = valuator.calculate_dcf(balance_sheet, cashflows).ok()?;
+    if cashflows.is_empty() || base.base_fcf <= Decimal::ZERO {
+        return None;
+    }
+
+    let years: u32 = valuator.params.dcf.forecast_stages().iter().map(|stage| stage.years).sum();
+    let mut trial = Valuator::new(valuator.params.clone());
+    solve(market_price, |growth| {
+        trial.params.dcf.fcf_growth_rate = growth;
+        trial.params.dcf.stages = vec![ForecastStage { years, growth_rate: growth, fade: false }];
+        trial.calculate_dcf(balance_sheet, cashflows).ok()?.price_per_share.to_f64()
+    })
+}
+
+fn implied_net_profit_growth(valuator: &Valuator, market_price: f64, income_statements: &[IncomeStatement]) -> Option<f64> {
+    if income_statements.first()?.net_profit <= Decimal::ZERO {
+        return None;
+    }
+
+    let mut trial = Valuator::new(valuator.params.clone());
+    solve(market_price, |growth| {
+        trial.params.tangchao.net_profit_growth_rate = growth;
+        trial.calculate_tangchao(income_statements).ok()?.low_estimate.to_f64()
+    })
+}
+
+/// 在增长率区间内二分求解 value(g) = target，value 须随 g 单调递增
+fn solve(target: f64, mut value: impl FnMut(f64) -> Option<f64>) -> Option<f64> {
+    let (mut low, mut high) = GROWTH_RANGE;
+    if target < value(low)? || target > value(high)? {
+        return None;
+    }
+    for _ in 0..MAX_ITERATIONS {
+        let mid = (low + high) / 2.0;
+        if value(mid)? < target {
+            low = mid;
+        } else {
+            high = mid;
+        }
+        if high - low < TOLERANCE {
+            break;
+        }
+    }
+    Some((low + high) / 2.0)
+}
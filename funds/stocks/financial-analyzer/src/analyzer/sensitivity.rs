@@ -1,11 +1,12 @@
-use crate::analyzer::valuation::{ValuationParams, DCFParams, TangchaoParams};
-use crate::domain::*;
-use anyhow::Result;
+use crate::analyzer::valuation::{ValuationParams, DCFParams, DCFValuation, EquityModelParams, EquityModelValuation, ForecastStage, TangchaoParams, ValuationModel};
+use crate::analyzer::reverse::ImpliedGrowth;
+use crate::analyzer::wacc::Wacc;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-/// 敏感性分析参数
+/// 敏感性分析参数（反序列化时缺省字段取默认值）
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct SensitivityParams {
     pub discount_rate: f64,
     pub perpetual_growth_rate: f64,
@@ -13,6 +14,23 @@ pub struct SensitivityParams {
     pub net_profit_growth_rate: f64,
     pub low_risk_free_rate: f64,
     pub high_risk_free_rate: f64,
+    /// DCF预测阶段，为空时按 fcf_growth_rate 预测3年
+    pub forecast_stages: Vec<ForecastStage>,
+    /// 基准FCF取最近几年的平均值
+    pub base_fcf_years: usize,
+    /// 折现率由 WACC 计算得到时的计算过程
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wacc: Option<Wacc>,
+    /// 当前股价，提供时反推市场隐含的增长率
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market_price: Option<f64>,
+    /// 另行计算的估值模型（ddm, fcfe）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<ValuationModel>,
+    /// DDM/FCFE 的股权成本、高速增长期年数和增长率
+    pub cost_of_equity: f64,
+    pub high_growth_years: u32,
+    pub high_growth_rate: f64,
 }
 
 impl Default for SensitivityParams {
@@ -24,17 +42,35 @@ impl Default for SensitivityParams {
             net_profit_growth_rate: 0.10,
             low_risk_free_rate: 0.04,
             high_risk_free_rate: 0.02,
+            forecast_stages: Vec::new(),
+            base_fcf_years: 1,
+            wacc: None,
+            market_price: None,
+            models: Vec::new(),
+            cost_of_equity: EquityModelParams::default().cost_of_equity,
+            high_growth_years: EquityModelParams::default().high_growth_years,
+            high_growth_rate: EquityModelParams::default().high_growth_rate,
         }
     }
 }
 
 impl SensitivityParams {
+    /// 用 WACC 作为DCF折现率，其中的股权成本用于DDM/FCFE
+    pub fn with_wacc(mut self, wacc: Wacc) -> Self {
+        self.discount_rate = wacc.wacc;
+        self.cost_of_equity = wacc.cost_of_equity;
+        self.wacc = Some(wacc);
+        self
+    }
+
     pub fn to_valuation_params(&self, total_shares: Decimal) -> ValuationParams {
         ValuationParams {
             dcf: DCFParams {
                 discount_rate: self.discount_rate,
                 perpetual_growth_rate: self.perpetual_growth_rate,
                 fcf_growth_rate: self.fcf_growth_rate,
+                stages: self.forecast_stages.clone(),
+                base_fcf_years: self.base_fcf_years,
             },
             tangchao: TangchaoParams {
                 net_profit_growth_rate: self.net_profit_growth_rate,
@@ -43,6 +79,12 @@ impl SensitivityParams {
                 safety_margin: 0.7,
             },
             total_shares,
+            models: self.models.clone(),
+            equity: EquityModelParams {
+                cost_of_equity: self.cost_of_equity,
+                high_growth_years: self.high_growth_years,
+                high_growth_rate: self.high_growth_rate,
+            },
         }
     }
 }
@@ -56,4 +98,40 @@ pub struct SensitivityResult {
     pub tangchao_low_estimate: Decimal,
     pub tangchao_high_estimate: Decimal,
     pub tangchao_safety_margin_price: Decimal,
+    /// DCF预测明细
+    #[serde(default)]
+    pub dcf_detail: Option<DCFValuation>,
+    /// 当前股价隐含的增长率（反向DCF），未提供股价时为空
+    #[serde(default)]
+    pub implied_growth: Option<ImpliedGrowth>,
+    /// 股利折现和FCFE估值，未选择该模型时为空
+    #[serde(default)]
+    pub ddm: Option<EquityModelValuation>,
+    #[serde(default)]
+    pub fcfe: Option<EquityModelValuation>,
+}
+
+impl SensitivityResult {
+    /// 隐含FCF增长率的求解口径：反向DCF以单一增长率代替设定的多个预测阶段，预测总年数不变
+    pub fn implied_fcf_growth_note(&self) -> String {
+        let stages = self.params.to_valuation_params(Decimal::ONE).dcf.forecast_stages();
+        let years: u32 = stages.iter().map(|stage| stage.years).sum();
+        if stages.len() > 1 || stages.iter().any(|stage| stage.fade) {
+            format!(
+                "隐含FCF增长率：以单一增长率代替设定的{}个预测阶段，{}年逐年按此增长时DCF每股价值等于当前股价",
+                stages.len(),
+                years
+            )
+        } else {
+            format!("隐含FCF增长率：预测期{}年逐年按此增长时，DCF每股价值等于当前股价", years)
+        }
+    }
+
+    /// 已计算的股权估值模型，按 DDM、FCFE 顺序
+    pub fn equity_models(&self) -> Vec<(ValuationModel, &EquityModelValuation)> {
+        [(ValuationModel::Ddm, &self.ddm), (ValuationModel::Fcfe, &self.fcfe)]
+            .into_iter()
+            .filter_map(|(model, valuation)| Some((model, valuation.as_ref()?)))
+            .collect()
+    }
 }
@@ -4,31 +4,657 @@
 //! 完整的功能测试应该作为集成测试运行。
 //! 这里只包含基本的单元测试。
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::analyzer::{RatioCalculator, FinancialAnalyzer, ValuationParams};
-
-    #[test]
-    fn test_ratio_calculator_creation() {
-        let calculator = RatioCalculator::new();
-        // 验证创建成功
-        assert!(true);
+use crate::analyzer::compare::rank;
+use crate::analyzer::{period, report_period, BetaEstimate, CapitalWeights, Comparison, Direction, ForecastStage, ImpliedGrowth, RatioCalculator, FinancialAnalyzer, SensitivityParams, ValuationModel, ValuationParams, Valuator, Wacc, WaccParams};
+use std::collections::BTreeMap;
+use crate::data_source::MockDataSource;
+use crate::domain::*;
+use chrono::NaiveDate;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+#[test]
+fn test_ratio_calculator_creation() {
+    let calculator = RatioCalculator::new();
+    let analysis = calculator.calculate_profit_ratios(&[]).unwrap();
+    assert!(analysis.years.is_empty());
+}
+
+#[test]
+fn test_strength_ratios_align_balance_and_income() {
+    let statement = |year: i32, report_type: ReportType, items: &[(&str, i64)]| FinancialStatement {
+        stock_code: "600519.SH".to_string(),
+        report_date: NaiveDate::from_ymd_opt(year, 12, 31).unwrap(),
+        report_type,
+        items: items.iter().map(|(item, amount)| (item.to_string(), Decimal::new(*amount, 0))).collect(),
+        sources: HashMap::new(),
+    };
+    let statements = vec![
+        statement(2023, ReportType::BalanceSheet, &[("资产总计", 1000), ("负债合计", 400), ("所有者权益合计", 600)]),
+        statement(2022, ReportType::BalanceSheet, &[("资产总计", 800), ("负债合计", 300), ("所有者权益合计", 0)]),
+        statement(2023, ReportType::IncomeStatement, &[("净利润", 120)]),
+        statement(2022, ReportType::IncomeStatement, &[("净利润", 80)]),
+    ];
+
+    let strength = RatioCalculator::new().calculate_strength(&statements);
+    assert_eq!(strength.years, vec![2023, 2022]);
+    assert_eq!(strength.roe, vec![Some(Decimal::new(2, 1)), None]);
+    assert_eq!(strength.roa, vec![Some(Decimal::new(12, 2)), Some(Decimal::new(1, 1))]);
+    assert_eq!(strength.debt_ratio, vec![Some(Decimal::new(4, 1)), Some(Decimal::new(375, 3))]);
+}
+
+#[test]
+fn test_analyzer_creation() {
+    let analyzer = FinancialAnalyzer::new();
+    assert!(analyzer.validator.is_none());
+}
+
+#[test]
+fn test_analyzer_with_params() {
+    let mut params = ValuationParams::default();
+    params.dcf.discount_rate = 0.12;
+    let analyzer = FinancialAnalyzer::new()
+        .with_valuation_params(params);
+    assert_eq!(analyzer.valuator.params.dcf.discount_rate, 0.12);
+}
+
+fn income(date: (i32, u32, u32), revenue: i64, net_profit: i64) -> IncomeStatement {
+    let mut items = HashMap::new();
+    items.insert("营业收入".to_string(), Decimal::new(revenue, 0));
+    items.insert("净利润".to_string(), Decimal::new(net_profit, 0));
+    IncomeStatement {
+        statement: FinancialStatement {
+            stock_code: "600519.SH".to_string(),
+            report_date: NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap(),
+            report_type: ReportType::IncomeStatement,
+            items,
+            sources: HashMap::new(),
+        },
+        revenue: Decimal::new(revenue, 0),
+        operating_cost: Decimal::ZERO,
+        gross_profit: Decimal::new(revenue, 0),
+        core_profit: Decimal::new(net_profit, 0),
+        net_profit: Decimal::new(net_profit, 0),
+    }
+}
+
+#[test]
+fn test_single_quarters_from_ytd() {
+    let ytd = period::sort_desc(vec![
+        income((2023, 3, 31), 100, 10),
+        income((2023, 6, 30), 250, 25),
+        income((2023, 9, 30), 330, 40),
+    ]);
+    let quarters = period::single_quarters(&ytd);
+
+    let revenues: Vec<Decimal> = quarters.iter().map(|q| q.revenue).collect();
+    assert_eq!(revenues, vec![Decimal::new(80, 0), Decimal::new(150, 0), Decimal::new(100, 0)]);
+    assert_eq!(quarters[0].statement.items["净利润"], Decimal::new(15, 0));
+}
+
+#[test]
+fn test_ttm_from_ytd() {
+    let ytd = period::sort_desc(vec![
+        income((2023, 9, 30), 300, 30),
+        income((2023, 12, 31), 400, 40),
+        income((2024, 9, 30), 360, 33),
+    ]);
+
+    // TTM = 2024Q3累计 + 2023年报 − 2023Q3累计
+    let ttm = period::ttm(&ytd).unwrap();
+    assert_eq!(ttm.revenue, Decimal::new(460, 0));
+    assert_eq!(ttm.net_profit, Decimal::new(43, 0));
+    assert_eq!(ttm.statement.report_date, NaiveDate::from_ymd_opt(2024, 9, 30).unwrap());
+
+    // 缺少上年同期数据时无法计算
+    assert!(period::ttm(&ytd[..2]).is_none());
+}
+
+#[test]
+fn test_trailing_sum_requires_consecutive_quarters() {
+    let quarters = period::sort_desc(vec![
+        income((2023, 12, 31), 100, 10),
+        income((2023, 9, 30), 100, 10),
+        income((2023, 6, 30), 100, 10),
+        income((2023, 3, 31), 100, 10),
+    ]);
+    assert_eq!(period::trailing_sum(&quarters).unwrap().revenue, Decimal::new(400, 0));
+
+    let gapped = period::sort_desc(vec![
+        income((2023, 12, 31), 100, 10),
+        income((2023, 6, 30), 100, 10),
+        income((2023, 3, 31), 100, 10),
+        income((2022, 12, 31), 100, 10),
+    ]);
+    assert!(period::trailing_sum(&gapped).is_none());
+}
+
+#[test]
+fn test_report_period_ranges() {
+    let (_, start, end) = report_period(vec![2023, 2022], ReportPeriod::Annual);
+    assert_eq!(start, NaiveDate::from_ymd_opt(2022, 12, 31).unwrap());
+    assert_eq!(end, NaiveDate::from_ymd_opt(2023, 12, 31).unwrap());
+
+    // 季度/TTM 多取一年用于推算
+    let (_, start, end) = report_period(vec![2023, 2022], ReportPeriod::Ttm);
+    assert_eq!(start, NaiveDate::from_ymd_opt(2021, 1, 1).unwrap());
+    assert_eq!(end, NaiveDate::from_ymd_opt(2023, 12, 31).unwrap());
+}
+
+#[tokio::test]
+async fn test_quarterly_analysis_uses_single_quarters() {
+    let analyzer = FinancialAnalyzer::new().with_period(ReportPeriod::Quarterly);
+    let result = analyzer
+        .analyze("600519.SH", vec![2023], &MockDataSource::new())
+        .await
+        .unwrap();
+
+    assert_eq!(result.period, ReportPeriod::Quarterly);
+    assert_eq!(result.period_labels, vec!["2023Q4", "2023Q3", "2023Q2", "2023Q1"]);
+    assert_eq!(result.period_label(0), "2023Q4");
+
+    // Mock累计数据均匀分布，每个单季营收为全年的1/4
+    let revenues: Vec<Decimal> = result
+        .statements
+        .iter()
+        .filter(|s| s.report_type == ReportType::IncomeStatement)
+        .map(|s| s.items["营业收入"])
+        .collect();
+    assert!(revenues.iter().all(|r| *r == Decimal::new(1_250_000, 0)));
+}
+
+#[tokio::test]
+async fn test_quarterly_valuation_requires_four_quarters() {
+    let analyzer = FinancialAnalyzer::new().with_period(ReportPeriod::Quarterly);
+    let mut result = analyzer
+        .analyze("600519.SH", vec![2023], &MockDataSource::new())
+        .await
+        .unwrap();
+
+    // 缺少一季度时不能把单季数据当作全年估值
+    let q1 = NaiveDate::from_ymd_opt(2023, 3, 31).unwrap();
+    result
+        .statements
+        .retain(|s| s.report_type == ReportType::BalanceSheet || s.report_date != q1);
+    let err = analyzer
+        .calculate_sensitivity(&mut result, SensitivityParams::default())
+        .unwrap_err();
+    assert!(err.to_string().contains("四个连续季度"), "{}", err);
+    assert!(result.sensitivity.is_none());
+}
+
+#[tokio::test]
+async fn test_ttm_valuation_matches_annual_for_mock() {
+    let annual = FinancialAnalyzer::new()
+        .analyze("600519.SH", vec![2023, 2022], &MockDataSource::new())
+        .await
+        .unwrap();
+    let ttm = FinancialAnalyzer::new()
+        .with_period(ReportPeriod::Ttm)
+        .analyze("600519.SH", vec![2023, 2022], &MockDataSource::new())
+        .await
+        .unwrap();
+    let quarterly = FinancialAnalyzer::new()
+        .with_period(ReportPeriod::Quarterly)
+        .analyze("600519.SH", vec![2023, 2022], &MockDataSource::new())
+        .await
+        .unwrap();
+
+    assert_eq!(annual.period_labels, vec!["2023", "2022"]);
+    assert_eq!(ttm.period_labels, vec!["TTM 2023Q4", "2022"]);
+
+    // Mock每年数据相同，TTM与最近四季合计应等于年报估值
+    let annual_price = annual.valuation.unwrap().dcf.price_per_share;
+    assert_eq!(ttm.valuation.unwrap().dcf.price_per_share, annual_price);
+    assert_eq!(quarterly.valuation.unwrap().dcf.price_per_share, annual_price);
+}
+
+#[test]
+fn test_comparison_rank() {
+    let values = [Some(0.3), None, Some(0.5), Some(0.3)];
+    assert_eq!(rank(&values, Direction::Higher), vec![Some(2), None, Some(1), Some(2)]);
+    assert_eq!(rank(&values, Direction::Lower), vec![Some(1), None, Some(3), Some(1)]);
+}
+
+#[tokio::test]
+async fn test_comparison_aligns_periods() {
+    let analyzer = FinancialAnalyzer::new();
+    let moutai = analyzer
+        .analyze("600519.SH", vec![2023, 2022, 2021], &MockDataSource::new())
+        .await
+        .unwrap();
+    let mut wuliangye = analyzer
+        .analyze("000858.SZ", vec![2023, 2022], &MockDataSource::new())
+        .await
+        .unwrap();
+    // 第二家公司净利润减半：净利润率、ROE 排名靠后
+    for statement in wuliangye.statements.iter_mut().filter(|s| s.report_type == ReportType::IncomeStatement) {
+        statement.items.insert("净利润".to_string(), Decimal::new(500_000, 0));
+    }
+    wuliangye.strength = RatioCalculator::new().calculate_strength(&wuliangye.statements);
+
+    let results = vec![moutai, wuliangye];
+    let comparison = Comparison::new(&results).unwrap();
+    assert_eq!(comparison.stock_codes(), vec!["600519.SH", "000858.SZ"]);
+    let labels: Vec<&str> = comparison.periods.iter().map(|p| p.label.as_str()).collect();
+    assert_eq!(labels, vec!["2023年", "2022年"]);
+    assert_eq!(comparison.periods[1].indices, vec![1, 1]);
+
+    let metrics = comparison.metrics();
+    let roe = metrics.iter().find(|m| m.name.starts_with("ROE")).unwrap();
+    assert_eq!(roe.values[0], vec![Some(0.4), Some(0.2)]);
+    assert_eq!(roe.ranks[0], vec![Some(1), Some(2)]);
+    let debt = metrics.iter().find(|m| m.name == "资产负债率").unwrap();
+    assert_eq!(debt.direction, Direction::Lower);
+    assert_eq!(debt.ranks[0], vec![Some(1), Some(1)]);
+
+    let common_size = comparison.common_size();
+    assert_eq!(common_size[0].rows[0].item, "货币资金");
+    assert_eq!(common_size[0].rows[0].values[0], vec![Some(0.25), Some(0.25)]);
+    let net_profit = common_size[1].rows.iter().find(|r| r.item == "净利润").unwrap();
+    assert_eq!(net_profit.values[0], vec![Some(0.2), Some(0.1)]);
+
+    assert!(Comparison::new(&results[..1]).is_err());
+}
+
+fn cashflow(year: i32, free_cashflow: i64) -> CashflowStatement {
+    CashflowStatement {
+        statement: FinancialStatement {
+            stock_code: "600519.SH".to_string(),
+            report_date: NaiveDate::from_ymd_opt(year, 12, 31).unwrap(),
+            report_type: ReportType::CashflowStatement,
+            items: HashMap::new(),
+            sources: HashMap::new(),
+        },
+        operating_cashflow: Decimal::new(free_cashflow, 0),
+        investing_cashflow: Decimal::ZERO,
+        financing_cashflow: Decimal::ZERO,
+        free_cashflow: Decimal::new(free_cashflow, 0),
+    }
+}
+
+#[test]
+fn test_forecast_stage_parse() {
+    let stage: ForecastStage = "5:15%".parse().unwrap();
+    assert_eq!(stage, ForecastStage { years: 5, growth_rate: 0.15, fade: false });
+    let stage: ForecastStage = " 5 : ~0.03 ".parse().unwrap();
+    assert_eq!(stage, ForecastStage { years: 5, growth_rate: 0.03, fade: true });
+
+    assert!("5".parse::<ForecastStage>().is_err());
+    assert!("0:10%".parse::<ForecastStage>().is_err());
+    assert!("5:abc".parse::<ForecastStage>().is_err());
+}
+
+#[test]
+fn test_default_dcf_projects_three_years() {
+    let params = ValuationParams {
+        total_shares: Decimal::new(100, 0),
+        ..Default::default()
+    };
+    let dcf = Valuator::new(params).calculate(None, &[], &[cashflow(2023, 1000)]).unwrap().dcf;
+
+    assert_eq!(dcf.projections.len(), 3);
+    assert_eq!(dcf.base_fcf, Decimal::new(1000, 0));
+    assert!((dcf.projections[2].fcf.to_f64().unwrap() - 1331.0).abs() < 1e-6);
+    assert!((dcf.projections[0].discount_factor.to_f64().unwrap() - 1.0 / 1.08).abs() < 1e-9);
+
+    // 与原3年公式一致：Σ FCF·1.1^t/1.08^t + FCF·1.1^3·1.03/(0.08-0.03)/1.08^3
+    let expected: f64 = (1..=3).map(|t| 1000.0 * 1.1f64.powi(t) / 1.08f64.powi(t)).sum::<f64>()
+        + 1331.0 * 1.03 / 0.05 / 1.08f64.powi(3);
+    assert!((dcf.enterprise_value.to_f64().unwrap() - expected).abs() < 1e-6);
+    let pv_sum: Decimal = dcf.projections.iter().map(|p| p.present_value).sum();
+    assert_eq!(pv_sum + dcf.terminal_present_value, dcf.enterprise_value);
+}
+
+#[test]
+fn test_multi_stage_dcf_with_fade_and_normalized_base() {
+    let mut params = ValuationParams::default();
+    params.dcf.stages = vec!["5:15%".parse().unwrap(), "5:~3%".parse().unwrap()];
+    params.dcf.base_fcf_years = 3;
+    let rates = params.dcf.yearly_growth_rates();
+    assert_eq!(rates.len(), 10);
+    assert_eq!(rates[4], 0.15);
+    assert!((rates[5] - 0.126).abs() < 1e-12);
+    assert!((rates[9] - 0.03).abs() < 1e-12);
+
+    let cashflows = [cashflow(2023, 1200), cashflow(2022, 900), cashflow(2021, 900)];
+    let dcf = Valuator::new(params.clone()).calculate(None, &[], &cashflows).unwrap().dcf;
+    assert_eq!(dcf.base_fcf, Decimal::new(1000, 0));
+    assert_eq!(dcf.projections.len(), 10);
+    assert_eq!(dcf.projections.last().unwrap().year, 10);
+
+    let growth: f64 = rates.iter().map(|g| 1.0 + g).product();
+    let last_fcf = 1000.0 * growth;
+    assert!((dcf.projections[9].fcf.to_f64().unwrap() - last_fcf).abs() < 1e-6);
+    let terminal = last_fcf * 1.03 / 0.05;
+    assert!((dcf.terminal_value.to_f64().unwrap() - terminal).abs() < 1e-4);
+    assert!((dcf.terminal_present_value.to_f64().unwrap() - terminal / 1.08f64.powi(10)).abs() < 1e-4);
+
+    // 平均年数超过可用数据时使用全部年份
+    params.dcf.base_fcf_years = 5;
+    let dcf = Valuator::new(params.clone()).calculate(None, &[], &cashflows[..2]).unwrap().dcf;
+    assert_eq!(dcf.base_fcf, Decimal::new(1050, 0));
+
+    params.dcf.stages.push(ForecastStage { years: 0, growth_rate: 0.1, fade: false });
+    assert!(Valuator::new(params).calculate(None, &[], &cashflows).is_err());
+}
+
+#[test]
+fn test_equity_bridge_adjusts_dcf_per_share_value() {
+    let params = ValuationParams {
+        total_shares: Decimal::new(100, 0),
+        ..Default::default()
+    };
+    let items: HashMap<String, Decimal> = [("货币资金", 5000), ("交易性金融资产", 0), ("短期借款", 1200), ("应付债券", 800), ("少数股东权益", 500)]
+        .into_iter()
+        .map(|(item, amount)| (item.to_string(), Decimal::new(amount, 0)))
+        .collect();
+    let balance_sheet = FinancialStatement {
+        stock_code: "600519.SH".to_string(),
+        report_date: NaiveDate::from_ymd_opt(2023, 12, 31).unwrap(),
+        report_type: ReportType::BalanceSheet,
+        items,
+        sources: HashMap::new(),
+    };
+    let valuator = Valuator::new(params);
+    let cashflows = [cashflow(2023, 1000)];
+
+    let dcf = valuator.calculate(Some(&balance_sheet), &[], &cashflows).unwrap().dcf;
+    let bridge = dcf.bridge.as_ref().unwrap();
+    // 为0或报表中没有的科目不列出，减项为负
+    let adjustments: Vec<(&str, Decimal)> = bridge.adjustments.iter().map(|a| (a.item.as_str(), a.amount)).collect();
+    assert_eq!(
+        adjustments,
+        vec![("货币资金", Decimal::new(5000, 0)), ("短期借款", Decimal::new(-1200, 0)), ("应付债券", Decimal::new(-800, 0)), ("少数股东权益", Decimal::new(-500, 0))]
+    );
+    assert_eq!(bridge.equity_value, dcf.enterprise_value + Decimal::new(2500, 0));
+    assert_eq!(dcf.price_per_share, bridge.equity_value / Decimal::new(100, 0));
+
+    // 没有资产负债表时按企业价值计算
+    let dcf = valuator.calculate(None, &[], &cashflows).unwrap().dcf;
+    assert!(dcf.bridge.is_none());
+    assert_eq!(dcf.price_per_share, dcf.enterprise_value / Decimal::new(100, 0));
+}
+
+#[tokio::test]
+async fn test_wacc_from_statements() {
+    let analyzer = FinancialAnalyzer::new();
+    let mut result = analyzer
+        .analyze("600519.SH", vec![2023, 2022], &MockDataSource::new())
+        .await
+        .unwrap();
+    let params = WaccParams {
+        weights: CapitalWeights::Book,
+        ..Default::default()
+    };
+    let wacc = analyzer.calculate_wacc(&result, params.clone()).unwrap();
+
+    // 模拟数据：所有者权益250万、短期借款60万、财务费用5万、利润总额125万、所得税25万
+    assert!((wacc.cost_of_equity - 0.085).abs() < 1e-12);
+    assert!((wacc.cost_of_debt - 50_000.0 / 600_000.0).abs() < 1e-12);
+    assert!((wacc.tax_rate - 0.2).abs() < 1e-12);
+    assert!((wacc.equity_weight - 2.5 / 3.1).abs() < 1e-12);
+    let expected = (2.5 * 0.085 + 0.6 * (50_000.0 / 600_000.0) * 0.8) / 3.1;
+    assert!((wacc.wacc - expected).abs() < 1e-12);
+    assert!(wacc.notes.is_empty());
+
+    // 季度模式按最近四季合计的利润表计算，与年报一致
+    let quarterly = FinancialAnalyzer::new().with_period(ReportPeriod::Quarterly);
+    let quarterly_result = quarterly.analyze("600519.SH", vec![2023], &MockDataSource::new()).await.unwrap();
+    let quarterly_wacc = quarterly.calculate_wacc(&quarterly_result, params).unwrap();
+    assert!((quarterly_wacc.wacc - wacc.wacc).abs() < 1e-12);
+
+    // 模拟数据没有股本，市值权重无法计算
+    let market = WaccParams { price: Some(10.0), ..Default::default() };
+    assert!(analyzer.calculate_wacc(&result, market).is_err());
+    assert!(analyzer.calculate_wacc(&result, WaccParams::default()).is_err());
+
+    analyzer
+        .calculate_sensitivity(&mut result, SensitivityParams::default().with_wacc(wacc))
+        .unwrap();
+    let sensitivity = result.sensitivity.unwrap();
+    assert!((sensitivity.params.discount_rate - expected).abs() < 1e-12);
+    assert!(sensitivity.params.wacc.is_some());
+}
+
+#[test]
+fn test_wacc_reads_mapped_items_and_notes_fallbacks() {
+    let classifier = crate::data_source::AccountClassifier::default();
+    let date = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+    let items = |pairs: &[(&str, i64)]| -> HashMap<String, Decimal> {
+        pairs.iter().map(|(item, amount)| (item.to_string(), Decimal::new(*amount, 0))).collect()
+    };
+    let balance = classifier
+        .build_balance_sheet("600519.SH", date, items(&[("total_hldr_eqy_inc_min_int", 2_500_000), ("st_borr", 600_000)]))
+        .statement;
+    let income = classifier
+        .build_income_statement("600519.SH", date, items(&[("fin_exp", 50_000), ("total_profit", 1_250_000), ("income_tax", 250_000)]))
+        .statement;
+    let params = WaccParams {
+        weights: CapitalWeights::Book,
+        ..Default::default()
+    };
+
+    // Tushare 字段名经科目映射归一后与标准科目名的结果一致
+    let wacc = Wacc::calculate(params.clone(), &balance, &income).unwrap();
+    assert!((wacc.cost_of_debt - 50_000.0 / 600_000.0).abs() < 1e-12);
+    assert!((wacc.tax_rate - 0.2).abs() < 1e-12);
+    assert!(wacc.notes.is_empty());
+
+    // 缺失的科目不按0计算，回退值在说明中列出
+    let mut missing = income.clone();
+    missing.items.remove("财务费用");
+    missing.items.remove("所得税费用");
+    let wacc = Wacc::calculate(params, &balance, &missing).unwrap();
+    assert_eq!(wacc.cost_of_debt, wacc.params.risk_free_rate);
+    assert_eq!(wacc.tax_rate, 0.25);
+    assert_eq!(
+        wacc.notes,
+        vec!["利润表缺少财务费用，债务成本按无风险收益率计", "利润表缺少所得税费用，按法定税率25%计"]
+    );
+}
+
+#[test]
+fn test_beta_regression() {
+    let date = |day: u32| NaiveDate::from_ymd_opt(2023, 1, 1).unwrap() + chrono::Days::new(day as u64);
+    let index_returns = [0.01, -0.02, 0.015, 0.003, -0.007, 0.02, -0.01, 0.004, 0.012, -0.015, 0.008, -0.003, 0.006, 0.011];
+
+    // 个股收益率恒为指数的1.5倍，个股多出的交易日不参与回归
+    let mut index = BTreeMap::from([(date(0), 100.0)]);
+    let mut stock = BTreeMap::from([(date(0), 20.0)]);
+    for (i, r) in index_returns.iter().enumerate() {
+        let day = i as u32 + 1;
+        index.insert(date(day), index[&date(day - 1)] * (1.0 + r));
+        stock.insert(date(day), stock[&date(day - 1)] * (1.0 + 1.5 * r));
     }
-    
-    #[test]
-    fn test_analyzer_creation() {
-        let analyzer = FinancialAnalyzer::new();
-        // 验证创建成功
-        assert!(true);
+    stock.insert(date(30), 25.0);
+
+    let estimate = BetaEstimate::regress(&stock, &index).unwrap();
+    assert!((estimate.beta - 1.5).abs() < 1e-9);
+    assert_eq!(estimate.observations, index_returns.len());
+    assert_eq!((estimate.start_date, estimate.end_date), (date(0), date(14)));
+
+    let params = WaccParams::default().with_beta_estimate(estimate);
+    assert!((params.beta - 1.5).abs() < 1e-9);
+
+    let short: BTreeMap<NaiveDate, f64> = index.iter().take(5).map(|(d, c)| (*d, *c)).collect();
+    assert!(BetaEstimate::regress(&stock, &short).is_err());
+
+    assert_eq!("Book".parse::<CapitalWeights>(), Ok(CapitalWeights::Book));
+    assert!("fair".parse::<CapitalWeights>().is_err());
+}
+
+#[test]
+fn test_reverse_dcf_recovers_growth_assumptions() {
+    let params = ValuationParams {
+        total_shares: Decimal::new(100, 0),
+        ..Default::default()
+    };
+    let incomes = [income((2023, 12, 31), 5000, 1000)];
+    let cashflows = [cashflow(2023, 1000)];
+    let valuator = Valuator::new(params.clone());
+    let valuation = valuator.calculate(None, &incomes, &cashflows).unwrap();
+
+    // 以估值结果作为股价，反推出的增长率应等于估值假设（FCF增长10%、净利润增长10%）
+    let dcf_price = valuation.dcf.price_per_share.to_f64().unwrap();
+    let implied = ImpliedGrowth::solve(&valuator, dcf_price, None, &incomes, &cashflows);
+    assert!((implied.fcf_growth_rate.unwrap() - 0.10).abs() < 1e-6);
+    let tangchao_price = valuation.tangchao.low_estimate.to_f64().unwrap();
+    let implied = ImpliedGrowth::solve(&valuator, tangchao_price, None, &incomes, &cashflows);
+    assert!((implied.net_profit_growth_rate.unwrap() - 0.10).abs() < 1e-6);
+
+    // 多阶段预测按相同年数的统一增长率求解
+    let mut staged = params.clone();
+    staged.dcf.stages = vec!["5:15%".parse().unwrap(), "5:~3%".parse().unwrap()];
+    let staged = Valuator::new(staged);
+    let implied = ImpliedGrowth::solve(&staged, dcf_price, None, &incomes, &cashflows).fcf_growth_rate.unwrap();
+    let mut uniform = params.clone();
+    uniform.dcf.stages = vec![ForecastStage { years: 10, growth_rate: implied, fade: false }];
+    let price = Valuator::new(uniform).calculate(None, &incomes, &cashflows).unwrap().dcf.price_per_share;
+    assert!((price.to_f64().unwrap() - dcf_price).abs() < 1e-4);
+
+    // 股价超出可求解范围、或FCF和净利润不为正时无解
+    let implied = ImpliedGrowth::solve(&valuator, 1e9, None, &incomes, &cashflows);
+    assert_eq!((implied.fcf_growth_rate, implied.net_profit_growth_rate), (None, None));
+    let implied = ImpliedGrowth::solve(&valuator, dcf_price, None, &[income((2023, 12, 31), 5000, -10)], &[cashflow(2023, -10)]);
+    assert_eq!((implied.fcf_growth_rate, implied.net_profit_growth_rate), (None, None));
+}
+
+#[tokio::test]
+async fn test_sensitivity_reports_implied_growth() {
+    let analyzer = FinancialAnalyzer::new();
+    let mut result = analyzer
+        .analyze("600519.SH", vec![2023, 2022], &MockDataSource::new())
+        .await
+        .unwrap();
+    analyzer.calculate_sensitivity(&mut result, SensitivityParams::default()).unwrap();
+    let sensitivity = result.sensitivity.as_ref().unwrap();
+    assert!(sensitivity.implied_growth.is_none());
+
+    let params = SensitivityParams {
+        market_price: Some(sensitivity.dcf_price_per_share.to_f64().unwrap()),
+        ..Default::default()
+    };
+    analyzer.calculate_sensitivity(&mut result, params.clone()).unwrap();
+    let sensitivity = result.sensitivity.as_ref().unwrap();
+    let implied = sensitivity.implied_growth.as_ref().unwrap();
+    assert!((implied.fcf_growth_rate.unwrap() - SensitivityParams::default().fcf_growth_rate).abs() < 1e-6);
+    assert!(implied.net_profit_growth_rate.is_some());
+    assert!(sensitivity.implied_fcf_growth_note().contains("预测期3年"));
+
+    // 多阶段预测以单一增长率求解，说明中注明
+    let params = SensitivityParams {
+        forecast_stages: vec!["5:15%".parse().unwrap(), "5:~5%".parse().unwrap()],
+        ..params
+    };
+    analyzer.calculate_sensitivity(&mut result, params).unwrap();
+    let note = result.sensitivity.unwrap().implied_fcf_growth_note();
+    assert!(note.contains("2个预测阶段"), "{}", note);
+    assert!(note.contains("10年"), "{}", note);
+}
+
+#[test]
+fn test_valuation_model_parse() {
+    assert_eq!("ddm".parse::<ValuationModel>().unwrap(), ValuationModel::Ddm);
+    assert_eq!(" FCFE ".parse::<ValuationModel>().unwrap(), ValuationModel::Fcfe);
+    assert!("dcf".parse::<ValuationModel>().is_err());
+}
+
+#[test]
+fn test_ddm_and_fcfe_valuation() {
+    let mut latest = cashflow(2023, 1000);
+    for (item, amount) in [
+        ("分配股利、利润或偿付利息支付的现金", 500),
+        ("购建固定资产、无形资产和其他长期资产支付的现金", 200),
+        ("取得借款收到的现金", 300),
+        ("偿还债务支付的现金", 100),
+    ] {
+        latest.statement.items.insert(item.to_string(), Decimal::new(amount, 0));
     }
-    
-    #[test]
-    fn test_analyzer_with_params() {
-        let params = ValuationParams::default();
-        let analyzer = FinancialAnalyzer::new()
-            .with_valuation_params(params);
-        // 验证创建成功
-        assert!(true);
+    // FCFE 不使用分类器的自由现金流（经营+投资现金流）
+    latest.free_cashflow = Decimal::ZERO;
+    let cashflows = [latest];
+    let incomes = [income((2023, 12, 31), 5000, 1000)];
+    let mut params = ValuationParams {
+        total_shares: Decimal::new(100, 0),
+        models: vec![ValuationModel::Ddm, ValuationModel::Fcfe],
+        ..Default::default()
+    };
+    params.dcf.perpetual_growth_rate = 0.04;
+    params.equity.cost_of_equity = 0.09;
+
+    // 高速增长期为0年时即 Gordon 模型：D0 × (1+g) / (ke-g)
+    params.equity.high_growth_years = 0;
+    let valuation = Valuator::new(params.clone()).calculate(None, &incomes, &cashflows).unwrap();
+    let ddm = valuation.ddm.unwrap();
+    assert!((ddm.price_per_share.to_f64().unwrap() - 500.0 * 1.04 / 0.05 / 100.0).abs() < 1e-6);
+    assert_eq!(ddm.high_growth_present_value, Decimal::ZERO);
+    // FCFE = 经营现金流 - 资本支出 + 取得借款 - 偿还债务
+    let fcfe = valuation.fcfe.unwrap();
+    assert_eq!(fcfe.base_cashflow, Decimal::new(1000, 0));
+    assert!((fcfe.price_per_share.to_f64().unwrap() - 1000.0 * 1.04 / 0.05 / 100.0).abs() < 1e-6);
+
+    // 股利扣除同期利润表的利息费用；没有利息费用时用为正的财务费用近似
+    let mut with_interest = incomes.clone();
+    with_interest[0].statement.items.insert("利息费用".to_string(), Decimal::new(120, 0));
+    with_interest[0].statement.items.insert("财务费用".to_string(), Decimal::new(80, 0));
+    let ddm = Valuator::new(params.clone()).calculate(None, &with_interest, &cashflows).unwrap().ddm.unwrap();
+    assert_eq!(ddm.base_cashflow, Decimal::new(380, 0));
+    with_interest[0].statement.items.remove("利息费用");
+    let ddm = Valuator::new(params.clone()).calculate(None, &with_interest, &cashflows).unwrap().ddm.unwrap();
+    assert_eq!(ddm.base_cashflow, Decimal::new(420, 0));
+    with_interest[0].statement.items.insert("财务费用".to_string(), Decimal::new(-80, 0));
+    let ddm = Valuator::new(params.clone()).calculate(None, &with_interest, &cashflows).unwrap().ddm.unwrap();
+    assert_eq!(ddm.base_cashflow, Decimal::new(500, 0));
+
+    // 非有限的参数报错而不是 panic
+    let mut invalid = params.clone();
+    invalid.equity.cost_of_equity = f64::NAN;
+    assert!(Valuator::new(invalid).calculate(None, &incomes, &cashflows).is_err());
+    let mut invalid = params.clone();
+    invalid.dcf.fcf_growth_rate = f64::INFINITY;
+    assert!(Valuator::new(invalid).calculate(None, &incomes, &cashflows).is_err());
+
+    // 两阶段：前5年按8%增长逐年折现，期末按永续增长计算终值
+    params.equity.high_growth_years = 5;
+    params.equity.high_growth_rate = 0.08;
+    let ddm = Valuator::new(params.clone()).calculate(None, &incomes, &cashflows).unwrap().ddm.unwrap();
+    let high_growth: f64 = (1..=5).map(|t| 500.0 * 1.08f64.powi(t) / 1.09f64.powi(t)).sum();
+    let terminal = 500.0 * 1.08f64.powi(5) * 1.04 / 0.05 / 1.09f64.powi(5);
+    assert!((ddm.high_growth_present_value.to_f64().unwrap() - high_growth).abs() < 1e-6);
+    assert!((ddm.terminal_present_value.to_f64().unwrap() - terminal).abs() < 1e-6);
+    assert!((ddm.price_per_share.to_f64().unwrap() - (high_growth + terminal) / 100.0).abs() < 1e-6);
+
+    // 未选择的模型不计算；股权成本不高于永续增长率时报错
+    params.models = vec![ValuationModel::Fcfe];
+    let valuation = Valuator::new(params.clone()).calculate(None, &incomes, &cashflows).unwrap();
+    assert!(valuation.ddm.is_none() && valuation.fcfe.is_some());
+    params.equity.cost_of_equity = 0.04;
+    assert!(Valuator::new(params).calculate(None, &incomes, &cashflows).is_err());
+}
+
+#[tokio::test]
+async fn test_sensitivity_equity_models_use_wacc_cost_of_equity() {
+    let analyzer = FinancialAnalyzer::new();
+    let mut result = analyzer
+        .analyze("600519.SH", vec![2023, 2022], &MockDataSource::new())
+        .await
+        .unwrap();
+    analyzer.calculate_sensitivity(&mut result, SensitivityParams::default()).unwrap();
+    assert!(result.sensitivity.as_ref().unwrap().equity_models().is_empty());
+
+    let wacc_params = WaccParams { weights: CapitalWeights::Book, ..Default::default() };
+    let wacc = analyzer.calculate_wacc(&result, wacc_params).unwrap();
+    let params = SensitivityParams {
+        models: vec![ValuationModel::Fcfe, ValuationModel::Ddm],
+        ..Default::default()
     }
+    .with_wacc(wacc.clone());
+    assert_eq!(params.cost_of_equity, wacc.cost_of_equity);
+    analyzer.calculate_sensitivity(&mut result, params).unwrap();
+    let sensitivity = result.sensitivity.unwrap();
+    let models: Vec<ValuationModel> = sensitivity.equity_models().into_iter().map(|(model, _)| model).collect();
+    assert_eq!(models, vec![ValuationModel::Ddm, ValuationModel::Fcfe]);
+    assert!(sensitivity.ddm.unwrap().price_per_share > Decimal::ZERO);
 }
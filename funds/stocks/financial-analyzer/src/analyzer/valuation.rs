@@ -1,8 +1,11 @@
 use crate::domain::*;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use rust_decimal::Decimal;
-use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// 有息负债：DCF股权价值调整中扣除，WACC中计算债务成本和债务权重
+pub const INTEREST_BEARING_DEBT: [&str; 3] = ["短期借款", "长期借款", "应付债券"];
 
 /// 估值参数配置
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -10,6 +13,80 @@ pub struct ValuationParams {
     pub dcf: DCFParams,
     pub tangchao: TangchaoParams,
     pub total_shares: Decimal,
+    /// 在DCF和唐朝估值之外另行计算的模型
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<ValuationModel>,
+    /// 股利折现和FCFE模型的参数
+    #[serde(default)]
+    pub equity: EquityModelParams,
+}
+
+/// 可选的估值模型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValuationModel {
+    /// 股利折现模型
+    Ddm,
+    /// 股权自由现金流模型
+    Fcfe,
+}
+
+impl ValuationModel {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Ddm => "股利折现(DDM)",
+            Self::Fcfe => "股权自由现金流(FCFE)",
+        }
+    }
+
+    /// 估值结果表中的行名
+    pub fn price_label(&self) -> &'static str {
+        match self {
+            Self::Ddm => "DDM每股价值",
+            Self::Fcfe => "FCFE每股价值",
+        }
+    }
+
+    /// 折现的股权现金流
+    pub fn cashflow_label(&self) -> &'static str {
+        match self {
+            Self::Ddm => "分配股利、利润或偿付利息支付的现金-利息费用",
+            Self::Fcfe => "经营现金流-资本支出+取得借款-偿还债务",
+        }
+    }
+}
+
+impl FromStr for ValuationModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "ddm" => Ok(Self::Ddm),
+            "fcfe" => Ok(Self::Fcfe),
+            _ => Err(format!("无效的估值模型: {}（可选 ddm, fcfe）", s)),
+        }
+    }
+}
+
+/// 股权估值模型（DDM、FCFE）参数：两阶段增长，永续增长率沿用DCF的永续增长率
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EquityModelParams {
+    /// 股权成本，作为折现率
+    pub cost_of_equity: f64,
+    /// 高速增长期年数，0 为 Gordon 单阶段模型
+    pub high_growth_years: u32,
+    /// 高速增长期的增长率
+    pub high_growth_rate: f64,
+}
+
+impl Default for EquityModelParams {
+    fn default() -> Self {
+        Self {
+            cost_of_equity: 0.09,
+            high_growth_years: 5,
+            high_growth_rate: 0.08,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -17,6 +94,89 @@ pub struct DCFParams {
     pub discount_rate: f64,
     pub perpetual_growth_rate: f64,
     pub fcf_growth_rate: f64,
+    /// 显式预测期的各阶段；为空时按 fcf_growth_rate 预测3年
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stages: Vec<ForecastStage>,
+    /// 基准FCF取最近几年的平均值（1 为只用最近一年）
+    #[serde(default = "default_base_fcf_years")]
+    pub base_fcf_years: usize,
+}
+
+fn default_base_fcf_years() -> usize {
+    1
+}
+
+impl DCFParams {
+    /// 实际使用的预测阶段
+    pub fn forecast_stages(&self) -> Vec<ForecastStage> {
+        if self.stages.is_empty() {
+            vec![ForecastStage {
+                years: 3,
+                growth_rate: self.fcf_growth_rate,
+                fade: false,
+            }]
+        } else {
+            self.stages.clone()
+        }
+    }
+
+    /// 预测期内逐年的FCF增长率
+    ///
+    /// 渐变阶段从上一阶段末的增长率（首个阶段为 fcf_growth_rate）逐年线性过渡到本阶段的增长率。
+    pub fn yearly_growth_rates(&self) -> Vec<f64> {
+        let mut rates = Vec::new();
+        let mut previous = self.fcf_growth_rate;
+        for stage in self.forecast_stages() {
+            for year in 1..=stage.years {
+                let rate = if stage.fade {
+                    previous + (stage.growth_rate - previous) * year as f64 / stage.years as f64
+                } else {
+                    stage.growth_rate
+                };
+                rates.push(rate);
+            }
+            previous = stage.growth_rate;
+        }
+        rates
+    }
+}
+
+/// DCF预测阶段
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ForecastStage {
+    pub years: u32,
+    pub growth_rate: f64,
+    /// 增长率是否从上一阶段逐年线性过渡到 growth_rate
+    #[serde(default)]
+    pub fade: bool,
+}
+
+impl FromStr for ForecastStage {
+    type Err = String;
+
+    /// `年数:增长率`，增长率前加 `~` 表示渐变，如 `5:15%`、`5:~0.03`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (years, rate) = s
+            .split_once(':')
+            .ok_or_else(|| format!("预测阶段格式应为 年数:增长率，如 5:15% 或 5:~3%: {}", s))?;
+        let years: u32 = years
+            .trim()
+            .parse()
+            .ok()
+            .filter(|years| *years > 0)
+            .ok_or_else(|| format!("预测阶段年数必须是正整数: {}", s))?;
+        let rate = rate.trim();
+        let (fade, rate) = match rate.strip_prefix('~') {
+            Some(rate) => (true, rate.trim()),
+            None => (false, rate),
+        };
+        let growth_rate = match rate.strip_suffix('%') {
+            Some(percent) => percent.trim().parse::<f64>().map(|v| v / 100.0),
+            None => rate.parse::<f64>(),
+        }
+        .map_err(|_| format!("无效的预测阶段增长率: {}", s))?;
+        Ok(Self { years, growth_rate, fade })
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -34,6 +194,8 @@ impl Default for ValuationParams {
                 discount_rate: 0.08,
                 perpetual_growth_rate: 0.03,
                 fcf_growth_rate: 0.10,
+                stages: Vec::new(),
+                base_fcf_years: 1,
             },
             tangchao: TangchaoParams {
                 net_profit_growth_rate: 0.10,
@@ -42,6 +204,8 @@ impl Default for ValuationParams {
                 safety_margin: 0.7,
             },
             total_shares: Decimal::new(100_000_000, 0),
+            models: Vec::new(),
+            equity: EquityModelParams::default(),
         }
     }
 }
@@ -51,12 +215,109 @@ impl Default for ValuationParams {
 pub struct ValuationResult {
     pub dcf: DCFValuation,
     pub tangchao: TangchaoValuation,
+    /// 股利折现估值，未选择该模型时为空
+    #[serde(default)]
+    pub ddm: Option<EquityModelValuation>,
+    /// 股权自由现金流估值，未选择该模型时为空
+    #[serde(default)]
+    pub fcfe: Option<EquityModelValuation>,
 }
 
+/// 股权现金流（股利或FCFE）两阶段折现的结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquityModelValuation {
+    /// 最近一年的股权现金流
+    pub base_cashflow: Decimal,
+    /// 每股股权现金流
+    pub per_share_cashflow: Decimal,
+    /// 高速增长期现值合计
+    pub high_growth_present_value: Decimal,
+    /// 高速增长期末永续价值的现值
+    pub terminal_present_value: Decimal,
+    pub equity_value: Decimal,
+    pub price_per_share: Decimal,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DCFValuation {
     pub enterprise_value: Decimal,
+    /// 股权价值 / 总股本（没有资产负债表时为企业价值 / 总股本）
     pub price_per_share: Decimal,
+    /// 预测起点的FCF
+    #[serde(default)]
+    pub base_fcf: Decimal,
+    /// 显式预测期逐年明细
+    #[serde(default)]
+    pub projections: Vec<DCFProjection>,
+    /// 预测期末的永续价值及其现值
+    #[serde(default)]
+    pub terminal_value: Decimal,
+    #[serde(default)]
+    pub terminal_present_value: Decimal,
+    /// 企业价值到股权价值的调整；没有资产负债表时为空，每股价值按企业价值计算
+    #[serde(default)]
+    pub bridge: Option<EquityBridge>,
+}
+
+impl DCFValuation {
+    /// 每股价值按股权价值计算：企业价值加非经营性资产，减有息负债和少数股东权益
+    pub fn bridge_to_equity(&mut self, balance_sheet: &FinancialStatement, total_shares: Decimal) {
+        let bridge = EquityBridge::new(self.enterprise_value, balance_sheet);
+        self.price_per_share = bridge.equity_value / total_shares;
+        self.bridge = Some(bridge);
+    }
+}
+
+/// 企业价值到股权价值的调整，金额取自最新一期资产负债表
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EquityBridge {
+    /// 调整项，加项为正、减项为负；报表中没有或为0的科目不列出
+    pub adjustments: Vec<BridgeAdjustment>,
+    pub equity_value: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeAdjustment {
+    pub item: String,
+    pub amount: Decimal,
+}
+
+impl EquityBridge {
+    /// 加回的非经营性资产（其收益不在经营FCF中）
+    pub const ADDITIONS: [&'static str; 3] = ["货币资金", "交易性金融资产", "长期股权投资"];
+    /// 有息负债（`INTEREST_BEARING_DEBT`）之外另行扣除的少数股东权益
+    pub const MINORITY_INTEREST: &'static str = "少数股东权益";
+
+    pub fn new(enterprise_value: Decimal, balance_sheet: &FinancialStatement) -> Self {
+        let additions = Self::ADDITIONS.iter().map(|item| (*item, Decimal::ONE));
+        let deductions = INTEREST_BEARING_DEBT
+            .iter()
+            .chain([&Self::MINORITY_INTEREST])
+            .map(|item| (*item, -Decimal::ONE));
+        let adjustments: Vec<BridgeAdjustment> = additions
+            .chain(deductions)
+            .filter_map(|(item, sign)| {
+                let amount = balance_sheet.items.get(item).copied().filter(|amount| !amount.is_zero())?;
+                Some(BridgeAdjustment {
+                    item: item.to_string(),
+                    amount: amount * sign,
+                })
+            })
+            .collect();
+        let equity_value = enterprise_value + adjustments.iter().map(|a| a.amount).sum::<Decimal>();
+        Self { adjustments, equity_value }
+    }
+}
+
+/// 预测期某一年的FCF及折现
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DCFProjection {
+    pub year: u32,
+    pub growth_rate: f64,
+    pub fcf: Decimal,
+    /// 折现系数 1/(1+r)^year
+    pub discount_factor: Decimal,
+    pub present_value: Decimal,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,42 +341,125 @@ impl Valuator {
         Self::new(ValuationParams::default())
     }
 
-    /// 计算估值
+    /// 计算估值，balance_sheet 为最新一期资产负债表（用于股权价值调整）
     pub fn calculate(
         &self,
+        balance_sheet: Option<&FinancialStatement>,
         income_statements: &[IncomeStatement],
         cashflow_statements: &[CashflowStatement],
     ) -> Result<ValuationResult> {
-        let dcf = self.calculate_dcf(cashflow_statements)?;
+        let dcf = self.calculate_dcf(balance_sheet, cashflow_statements)?;
         let tangchao = self.calculate_tangchao(income_statements)?;
+        let ddm = if self.params.models.contains(&ValuationModel::Ddm) {
+            self.calculate_ddm(income_statements, cashflow_statements)?
+        } else {
+            None
+        };
+        let fcfe = if self.params.models.contains(&ValuationModel::Fcfe) {
+            self.calculate_fcfe(cashflow_statements)?
+        } else {
+            None
+        };
+
+        Ok(ValuationResult { dcf, tangchao, ddm, fcfe })
+    }
+
+    /// 股利折现模型：以最近一年分配股利、利润或偿付利息支付的现金扣除利息为基准股利
+    ///
+    /// 该现金流出同时包含偿付的利息，按同期利润表的利息费用扣除（没有利息费用科目时用为正的财务费用近似）。
+    fn calculate_ddm(&self, incomes: &[IncomeStatement], cashflows: &[CashflowStatement]) -> Result<Option<EquityModelValuation>> {
+        let Some(latest) = cashflows.first() else {
+            return Ok(None);
+        };
+        let paid = latest.statement.items.get("分配股利、利润或偿付利息支付的现金").copied().unwrap_or(Decimal::ZERO);
+        let interest = incomes
+            .iter()
+            .find(|is| is.statement.report_date == latest.statement.report_date)
+            .map_or(Decimal::ZERO, |is| interest_expense(&is.statement));
+        let dividends = (paid - interest).max(Decimal::ZERO);
+        self.discount_equity_cashflow(dividends).map(Some)
+    }
 
-        Ok(ValuationResult { dcf, tangchao })
+    /// FCFE模型：FCFE = 经营现金流 - 资本支出 + 取得借款收到的现金 - 偿还债务支付的现金
+    fn calculate_fcfe(&self, cashflows: &[CashflowStatement]) -> Result<Option<EquityModelValuation>> {
+        let Some(latest) = cashflows.first() else {
+            return Ok(None);
+        };
+        let item = |name: &str| latest.statement.items.get(name).copied().unwrap_or(Decimal::ZERO);
+        let fcfe = latest.operating_cashflow - item("购建固定资产、无形资产和其他长期资产支付的现金")
+            + item("取得借款收到的现金")
+            - item("偿还债务支付的现金");
+        self.discount_equity_cashflow(fcfe).map(Some)
     }
 
-    /// DCF估值
-    fn calculate_dcf(&self, cashflows: &[CashflowStatement]) -> Result<DCFValuation> {
+    /// 两阶段折现：高速增长期逐年折现，期末按永续增长计算终值；高速增长期为0年时即 Gordon 模型
+    fn discount_equity_cashflow(&self, base_cashflow: Decimal) -> Result<EquityModelValuation> {
+        let params = &self.params.equity;
+        let cost_of_equity = to_decimal("股权成本", params.cost_of_equity)?;
+        let high_growth = to_decimal("高速增长率", params.high_growth_rate)?;
+        let terminal_growth = to_decimal("永续增长率", self.params.dcf.perpetual_growth_rate)?;
+        if cost_of_equity <= terminal_growth {
+            return Err(anyhow::anyhow!(
+                "股权估值错误：股权成本({:.2}%)必须大于永续增长率({:.2}%)",
+                params.cost_of_equity * 100.0,
+                self.params.dcf.perpetual_growth_rate * 100.0
+            ));
+        }
+        if base_cashflow <= Decimal::ZERO {
+            tracing::warn!("股权现金流为负或零({})，股权估值可能不准确", base_cashflow);
+        }
+
+        let mut cashflow = base_cashflow;
+        let mut compound_discount = Decimal::ONE;
+        let mut high_growth_present_value = Decimal::ZERO;
+        for _ in 0..params.high_growth_years {
+            cashflow *= Decimal::ONE + high_growth;
+            compound_discount *= Decimal::ONE + cost_of_equity;
+            high_growth_present_value += cashflow / compound_discount;
+        }
+        let terminal_value = cashflow * (Decimal::ONE + terminal_growth) / (cost_of_equity - terminal_growth);
+        let terminal_present_value = terminal_value / compound_discount;
+        let equity_value = high_growth_present_value + terminal_present_value;
+
+        Ok(EquityModelValuation {
+            base_cashflow,
+            per_share_cashflow: base_cashflow / self.params.total_shares,
+            high_growth_present_value,
+            terminal_present_value,
+            equity_value,
+            price_per_share: equity_value / self.params.total_shares,
+        })
+    }
+
+    /// DCF估值：分阶段预测FCF并折现，预测期末按永续增长计算终值，再调整为股权价值
+    pub(super) fn calculate_dcf(&self, balance_sheet: Option<&FinancialStatement>, cashflows: &[CashflowStatement]) -> Result<DCFValuation> {
         if cashflows.is_empty() {
-            return Ok(DCFValuation {
-                enterprise_value: Decimal::ZERO,
-                price_per_share: Decimal::ZERO,
-            });
+            return Ok(DCFValuation::default());
         }
 
-        let discount_rate = Decimal::from_f64_retain(self.params.dcf.discount_rate).unwrap();
-        let fcf_growth = Decimal::from_f64_retain(self.params.dcf.fcf_growth_rate).unwrap();
-        let perpetual_growth = Decimal::from_f64_retain(self.params.dcf.perpetual_growth_rate).unwrap();
+        let params = &self.params.dcf;
+        let discount_rate = to_decimal("折现率", params.discount_rate)?;
+        let perpetual_growth = to_decimal("永续增长率", params.perpetual_growth_rate)?;
 
         // 验证：折现率必须大于永续增长率
         if discount_rate <= perpetual_growth {
             return Err(anyhow::anyhow!(
                 "DCF估值错误：折现率({:.2}%)必须大于永续增长率({:.2}%)",
-                self.params.dcf.discount_rate * 100.0,
-                self.params.dcf.perpetual_growth_rate * 100.0
+                params.discount_rate * 100.0,
+                params.perpetual_growth_rate * 100.0
             ));
         }
+        if params.stages.iter().any(|stage| stage.years == 0) {
+            return Err(anyhow::anyhow!("DCF估值错误：预测阶段的年数必须大于0"));
+        }
 
-        // 使用最新年份的FCF（假设数据按时间倒序排列）
-        let base_fcf = cashflows[0].free_cashflow;
+        // 基准FCF：最近N年的平均值（数据按时间倒序排列）
+        let base_years = params.base_fcf_years.clamp(1, cashflows.len());
+        let base_fcf = if base_years == 1 {
+            cashflows[0].free_cashflow
+        } else {
+            cashflows[..base_years].iter().map(|c| c.free_cashflow).sum::<Decimal>() / Decimal::from(base_years)
+        };
 
         // 警告：负FCF
         if base_fcf <= Decimal::ZERO {
@@ -125,48 +469,50 @@ impl Valuator {
             );
         }
 
-        // 计算前3年现值
+        // 逐年预测并折现
+        let mut projections = Vec::new();
+        let mut growth_factor = Decimal::ONE;
+        let mut compound_discount = Decimal::ONE;
         let mut pv_sum = Decimal::ZERO;
-        for year in 1..=3 {
-            let mut growth_factor = Decimal::ONE;
-            let mut discount_factor = Decimal::ONE;
-            
-            for _ in 0..year {
-                growth_factor *= Decimal::ONE + fcf_growth;
-                discount_factor *= Decimal::ONE + discount_rate;
-            }
-            
+        for (i, rate) in params.yearly_growth_rates().into_iter().enumerate() {
+            growth_factor *= Decimal::ONE + to_decimal("FCF增长率", rate)?;
+            compound_discount *= Decimal::ONE + discount_rate;
+
             let fcf = base_fcf * growth_factor;
-            let pv = fcf / discount_factor;
-            pv_sum += pv;
+            let present_value = fcf / compound_discount;
+            pv_sum += present_value;
+            projections.push(DCFProjection {
+                year: i as u32 + 1,
+                growth_rate: rate,
+                fcf,
+                discount_factor: Decimal::ONE / compound_discount,
+                present_value,
+            });
         }
 
         // 计算永续价值
-        let mut terminal_growth_factor = Decimal::ONE;
-        for _ in 0..3 {
-            terminal_growth_factor *= Decimal::ONE + fcf_growth;
-        }
-        let terminal_fcf = base_fcf * terminal_growth_factor;
-        
+        let terminal_fcf = base_fcf * growth_factor;
         let terminal_value = terminal_fcf * (Decimal::ONE + perpetual_growth) / (discount_rate - perpetual_growth);
-        
-        let mut terminal_discount = Decimal::ONE;
-        for _ in 0..3 {
-            terminal_discount *= Decimal::ONE + discount_rate;
-        }
-        let pv_terminal = terminal_value / terminal_discount;
-
-        let enterprise_value = pv_sum + pv_terminal;
-        let price_per_share = enterprise_value / self.params.total_shares;
+        let terminal_present_value = terminal_value / compound_discount;
 
-        Ok(DCFValuation {
+        let enterprise_value = pv_sum + terminal_present_value;
+        let mut dcf = DCFValuation {
             enterprise_value,
-            price_per_share,
-        })
+            price_per_share: enterprise_value / self.params.total_shares,
+            base_fcf,
+            projections,
+            terminal_value,
+            terminal_present_value,
+            bridge: None,
+        };
+        if let Some(balance_sheet) = balance_sheet {
+            dcf.bridge_to_equity(balance_sheet, self.params.total_shares);
+        }
+        Ok(dcf)
     }
 
     /// 唐朝估值法
-    fn calculate_tangchao(&self, income_statements: &[IncomeStatement]) -> Result<TangchaoValuation> {
+    pub(super) fn calculate_tangchao(&self, income_statements: &[IncomeStatement]) -> Result<TangchaoValuation> {
         if income_statements.is_empty() {
             return Ok(TangchaoValuation {
                 low_estimate: Decimal::ZERO,
@@ -176,13 +522,13 @@ impl Valuator {
         }
 
         let latest_net_profit = income_statements[0].net_profit;
-        let growth_rate = Decimal::from_f64_retain(self.params.tangchao.net_profit_growth_rate).unwrap();
+        let growth_rate = to_decimal("净利润增长率", self.params.tangchao.net_profit_growth_rate)?;
 
         // 计算PE倍数
         // 低估区域：高收益率(0.04) → 低PE(25)
-        let low_pe = Decimal::ONE / Decimal::from_f64_retain(self.params.tangchao.low_risk_free_rate).unwrap();
+        let low_pe = Decimal::ONE / to_decimal("低估区域无风险收益率", self.params.tangchao.low_risk_free_rate)?;
         // 高估区域：低收益率(0.02) → 高PE(50)
-        let high_pe = Decimal::ONE / Decimal::from_f64_retain(self.params.tangchao.high_risk_free_rate).unwrap();
+        let high_pe = Decimal::ONE / to_decimal("高估区域无风险收益率", self.params.tangchao.high_risk_free_rate)?;
 
         // 3年后净利润
         let mut future_profit = latest_net_profit;
@@ -193,7 +539,7 @@ impl Valuator {
         // 估值：低估区域用低PE，高估区域用高PE
         let low_estimate = (future_profit * low_pe) / self.params.total_shares;
         let high_estimate = (future_profit * high_pe) / self.params.total_shares;
-        let safety_margin_price = low_estimate * Decimal::from_f64_retain(self.params.tangchao.safety_margin).unwrap();
+        let safety_margin_price = low_estimate * to_decimal("安全边际", self.params.tangchao.safety_margin)?;
 
         Ok(TangchaoValuation {
             low_estimate,
@@ -202,3 +548,21 @@ impl Valuator {
         })
     }
 }
+
+/// 估值参数转为 Decimal，NaN 和无穷大报错
+fn to_decimal(name: &str, value: f64) -> Result<Decimal> {
+    Decimal::from_f64_retain(value)
+        .filter(|_| value.is_finite())
+        .ok_or_else(|| anyhow!("估值参数{}无效: {}", name, value))
+}
+
+/// 利润表的利息费用；没有该科目时用为正的财务费用近似
+fn interest_expense(statement: &FinancialStatement) -> Decimal {
+    statement
+        .items
+        .get("利息费用")
+        .or_else(|| statement.items.get("财务费用"))
+        .copied()
+        .unwrap_or(Decimal::ZERO)
+        .max(Decimal::ZERO)
+}
@@ -0,0 +1,283 @@
+//! 加权平均资本成本（WACC）
+//!
+//! 股权成本按 CAPM 计算：无风险收益率 + β × 股权风险溢价；
+//! 债务成本 = 财务费用 / 有息负债，按利润表的实际所得税率抵税；
+//! 资本权重取市值（股价 × 总股本）或账面值（所有者权益合计），债务均按账面值计。
+
+use super::valuation::INTEREST_BEARING_DEBT;
+use crate::domain::FinancialStatement;
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDate;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+
+/// 无法从利润表得到实际税率时使用的法定税率
+pub const STATUTORY_TAX_RATE: f64 = 0.25;
+
+/// 估计β至少需要的收益率样本数
+const MIN_BETA_OBSERVATIONS: usize = 12;
+
+/// 资本权重的计量方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CapitalWeights {
+    /// 股权按市值（股价 × 总股本）
+    #[default]
+    Market,
+    /// 股权按账面值（所有者权益合计）
+    Book,
+}
+
+impl CapitalWeights {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Market => "市值",
+            Self::Book => "账面值",
+        }
+    }
+}
+
+impl FromStr for CapitalWeights {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "market" | "市值" => Ok(Self::Market),
+            "book" | "账面" | "账面值" => Ok(Self::Book),
+            _ => Err(format!("无效的资本权重: {}（可选 market, book）", s)),
+        }
+    }
+}
+
+/// WACC 输入参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaccParams {
+    pub risk_free_rate: f64,
+    pub equity_risk_premium: f64,
+    pub beta: f64,
+    /// β由股价回归得到时的样本信息；直接输入β时为空
+    #[serde(default)]
+    pub beta_estimate: Option<BetaEstimate>,
+    pub weights: CapitalWeights,
+    /// 当前股价，市值权重时必填
+    #[serde(default)]
+    pub price: Option<f64>,
+}
+
+impl Default for WaccParams {
+    fn default() -> Self {
+        Self {
+            risk_free_rate: 0.025,
+            equity_risk_premium: 0.06,
+            beta: 1.0,
+            beta_estimate: None,
+            weights: CapitalWeights::Market,
+            price: None,
+        }
+    }
+}
+
+impl WaccParams {
+    /// 使用回归估计的β
+    pub fn with_beta_estimate(mut self, estimate: BetaEstimate) -> Self {
+        self.beta = estimate.beta;
+        self.beta_estimate = Some(estimate);
+        self
+    }
+}
+
+/// 由股价与指数收盘价回归得到的β
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BetaEstimate {
+    pub beta: f64,
+    /// 收益率样本数
+    pub observations: usize,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+impl BetaEstimate {
+    /// β = Cov(个股收益率, 指数收益率) / Var(指数收益率)
+    ///
+    /// 只使用两份价格都有的交易日，收益率按相邻共同交易日计算。
+    pub fn regress(stock: &BTreeMap<NaiveDate, f64>, index: &BTreeMap<NaiveDate, f64>) -> Result<Self> {
+        let common: Vec<(NaiveDate, f64, f64)> = stock
+            .iter()
+            .filter_map(|(date, close)| index.get(date).map(|index_close| (*date, *close, *index_close)))
+            .collect();
+        let returns: Vec<(f64, f64)> = common
+            .windows(2)
+            .map(|pair| (pair[1].1 / pair[0].1 - 1.0, pair[1].2 / pair[0].2 - 1.0))
+            .collect();
+        if returns.len() < MIN_BETA_OBSERVATIONS {
+            return Err(anyhow!(
+                "估计β的样本不足：共同交易日的收益率只有 {} 个，至少需要 {} 个",
+                returns.len(),
+                MIN_BETA_OBSERVATIONS
+            ));
+        }
+
+        let n = returns.len() as f64;
+        let stock_mean = returns.iter().map(|r| r.0).sum::<f64>() / n;
+        let index_mean = returns.iter().map(|r| r.1).sum::<f64>() / n;
+        let covariance: f64 = returns.iter().map(|r| (r.0 - stock_mean) * (r.1 - index_mean)).sum();
+        let variance: f64 = returns.iter().map(|r| (r.1 - index_mean).powi(2)).sum();
+        if variance <= 0.0 {
+            return Err(anyhow!("指数收益率没有波动，无法估计β"));
+        }
+
+        Ok(Self {
+            beta: covariance / variance,
+            observations: returns.len(),
+            start_date: common[0].0,
+            end_date: common[common.len() - 1].0,
+        })
+    }
+}
+
+/// 读取收盘价 CSV（date,close 两列，日期为 YYYY-MM-DD 或 YYYYMMDD）
+pub fn load_closes(path: &Path) -> Result<BTreeMap<NaiveDate, f64>> {
+    #[derive(Deserialize)]
+    struct CloseRow {
+        date: String,
+        close: f64,
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .with_context(|| format!("无法读取价格文件 {}", path.display()))?;
+    let mut closes = BTreeMap::new();
+    for (i, row) in reader.deserialize().enumerate() {
+        let row: CloseRow = row.with_context(|| format!("价格文件 {} 第 {} 行", path.display(), i + 2))?;
+        let date = NaiveDate::parse_from_str(&row.date, "%Y-%m-%d")
+            .or_else(|_| NaiveDate::parse_from_str(&row.date, "%Y%m%d"))
+            .with_context(|| format!("价格文件 {} 第 {} 行：无效的日期 {}", path.display(), i + 2, row.date))?;
+        if row.close <= 0.0 {
+            return Err(anyhow!("价格文件 {} 第 {} 行：收盘价必须为正", path.display(), i + 2));
+        }
+        closes.insert(date, row.close);
+    }
+    Ok(closes)
+}
+
+/// WACC 计算结果，保留各项输入以便在报告中说明折现率的来源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Wacc {
+    pub params: WaccParams,
+    /// 股权成本 = 无风险收益率 + β × 股权风险溢价
+    pub cost_of_equity: f64,
+    pub interest_expense: Decimal,
+    pub interest_bearing_debt: Decimal,
+    /// 税前债务成本
+    pub cost_of_debt: f64,
+    pub tax_rate: f64,
+    pub equity_value: Decimal,
+    pub equity_weight: f64,
+    pub debt_weight: f64,
+    pub wacc: f64,
+    /// 取值说明（如税率或债务成本的回退）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+impl Wacc {
+    /// 按最新一期资产负债表和利润表（季度模式为最近四季合计）计算
+    ///
+    /// 科目按标准名称读取，各数据源的原始科目名（如 Tushare 的 `fin_exp`）在构建报表时已按科目映射归一；
+    /// 缺少财务费用、利润总额或所得税费用时按回退值计算，并在 `notes` 中说明。
+    pub fn calculate(params: WaccParams, balance_sheet: &FinancialStatement, income: &FinancialStatement) -> Result<Self> {
+        let balance = |item: &str| balance_sheet.items.get(item).copied().unwrap_or(Decimal::ZERO);
+        let mut notes = Vec::new();
+
+        let cost_of_equity = params.risk_free_rate + params.beta * params.equity_risk_premium;
+
+        let equity_value = match params.weights {
+            CapitalWeights::Market => {
+                let price = params.price.ok_or_else(|| anyhow!("市值权重需要当前股价，请提供股价或改用账面值权重"))?;
+                let shares = balance("股本");
+                if shares <= Decimal::ZERO {
+                    return Err(anyhow!("资产负债表缺少股本，无法计算股权市值"));
+                }
+                Decimal::from_f64_retain(price).unwrap_or(Decimal::ZERO) * shares
+            }
+            CapitalWeights::Book => balance("所有者权益合计"),
+        };
+        if equity_value <= Decimal::ZERO {
+            return Err(anyhow!("股权{}不为正，无法计算WACC", params.weights.display_name()));
+        }
+
+        let interest_bearing_debt: Decimal = INTEREST_BEARING_DEBT.iter().map(|item| balance(item)).sum();
+        let income_item = |item: &str| income.items.get(item).copied();
+        let interest_expense = income_item("财务费用");
+
+        // 财务费用已扣除利息收入，可能偏低甚至为负，债务成本不低于无风险收益率
+        let cost_of_debt = match interest_expense {
+            _ if interest_bearing_debt <= Decimal::ZERO => {
+                notes.push("没有有息负债，债务权重为0".to_string());
+                params.risk_free_rate
+            }
+            None => {
+                notes.push("利润表缺少财务费用，债务成本按无风险收益率计".to_string());
+                params.risk_free_rate
+            }
+            Some(interest_expense) => {
+                let implied = (interest_expense / interest_bearing_debt).to_f64().unwrap_or(0.0);
+                if implied < params.risk_free_rate {
+                    notes.push(format!(
+                        "财务费用/有息负债为{:.2}%，低于无风险收益率，债务成本按无风险收益率计",
+                        implied * 100.0
+                    ));
+                    params.risk_free_rate
+                } else {
+                    implied
+                }
+            }
+        };
+
+        let tax_rate = match (income_item("利润总额"), income_item("所得税费用")) {
+            (None, _) => Err("利润表缺少利润总额".to_string()),
+            (_, None) => Err("利润表缺少所得税费用".to_string()),
+            (Some(pretax_profit), _) if pretax_profit <= Decimal::ZERO => Err("利润总额不为正".to_string()),
+            (Some(pretax_profit), Some(tax)) => match (tax / pretax_profit).to_f64() {
+                Some(rate) if (0.0..1.0).contains(&rate) => Ok(rate),
+                _ => Err("实际税率不在0~100%之间".to_string()),
+            },
+        };
+        let tax_rate = tax_rate.unwrap_or_else(|reason| {
+            notes.push(format!("{}，按法定税率{:.0}%计", reason, STATUTORY_TAX_RATE * 100.0));
+            STATUTORY_TAX_RATE
+        });
+        for note in &notes {
+            tracing::warn!("WACC: {}", note);
+        }
+
+        let total_capital = equity_value + interest_bearing_debt;
+        let equity_weight = (equity_value / total_capital).to_f64().unwrap_or(1.0);
+        let debt_weight = 1.0 - equity_weight;
+        let wacc = equity_weight * cost_of_equity + debt_weight * cost_of_debt * (1.0 - tax_rate);
+
+        Ok(Self {
+            params,
+            cost_of_equity,
+            interest_expense: interest_expense.unwrap_or(Decimal::ZERO),
+            interest_bearing_debt,
+            cost_of_debt,
+            tax_rate,
+            equity_value,
+            equity_weight,
+            debt_weight,
+            wacc,
+            notes,
+        })
+    }
+
+    /// 税后债务成本
+    pub fn after_tax_cost_of_debt(&self) -> f64 {
+        self.cost_of_debt * (1.0 - self.tax_rate)
+    }
+}
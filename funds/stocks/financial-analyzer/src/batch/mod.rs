@@ -0,0 +1,122 @@
+//! 批量分析：按自选股清单并发分析，单只股票失败不影响其他股票
+//!
+//! 所有任务共用一个数据源和分析器，同时运行的任务数由 `with_concurrency` 限制；
+//! 结果按清单顺序返回，失败的股票连同原因单独列出。
+
+mod watchlist;
+#[cfg(test)]
+mod tests;
+
+pub use watchlist::Watchlist;
+
+use crate::analyzer::FinancialAnalyzer;
+use crate::data_source::{factory, DataSource};
+use crate::domain::AnalysisResult;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// 缺省的并发任务数
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// 分析失败的股票
+#[derive(Debug, Clone)]
+pub struct BatchFailure {
+    pub code: String,
+    pub reason: String,
+}
+
+/// 批量分析结果（均按清单顺序）
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    pub results: Vec<AnalysisResult>,
+    pub failures: Vec<BatchFailure>,
+}
+
+impl BatchReport {
+    /// 记录失败原因（含完整的错误链）
+    pub fn fail(&mut self, code: &str, error: &anyhow::Error) {
+        self.failures.push(BatchFailure {
+            code: code.to_string(),
+            reason: format!("{:#}", error),
+        });
+    }
+}
+
+/// 批量分析执行器
+pub struct BatchRunner {
+    analyzer: Arc<FinancialAnalyzer>,
+    data_source: Arc<dyn DataSource>,
+    concurrency: usize,
+}
+
+impl BatchRunner {
+    pub fn new(analyzer: FinancialAnalyzer, data_source: Arc<dyn DataSource>) -> Self {
+        Self {
+            analyzer: Arc::new(analyzer),
+            data_source,
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// 同时运行的分析任务数（至少为1）
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub async fn run(&self, watchlist: &Watchlist) -> BatchReport {
+        let permits = Arc::new(Semaphore::new(self.concurrency));
+        let mut tasks = JoinSet::new();
+        let mut spawned = HashMap::new();
+
+        for (index, entry) in watchlist.stocks.iter().enumerate() {
+            let analyzer = Arc::clone(&self.analyzer);
+            let data_source = Arc::clone(&self.data_source);
+            let permits = Arc::clone(&permits);
+            let years = watchlist.years_for(entry);
+            let entry = entry.clone();
+            let code = entry.code.clone();
+
+            let handle = tasks.spawn(async move {
+                let outcome = async {
+                    let _permit = permits.acquire_owned().await?;
+                    factory::validate_stock_code(data_source.name(), &entry.code)?;
+                    tracing::info!("批量分析: {}", entry.code);
+                    let params = entry.valuation_params(analyzer.valuation_params());
+                    let mut result = analyzer
+                        .analyze_with_params(&entry.code, years, data_source.as_ref(), params)
+                        .await?;
+                    analyzer.calculate_sensitivity(&mut result, entry.sensitivity_params())?;
+                    Ok::<_, anyhow::Error>(result)
+                }
+                .await;
+                (index, entry.code, outcome)
+            });
+            spawned.insert(handle.id(), (index, code));
+        }
+
+        let mut outcomes: Vec<(usize, String, Result<AnalysisResult>)> = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok(outcome) => outcomes.push(outcome),
+                Err(e) => {
+                    let (index, code) = spawned.remove(&e.id()).unwrap_or((usize::MAX, "?".to_string()));
+                    outcomes.push((index, code, Err(anyhow!("分析任务异常终止: {}", e))));
+                }
+            }
+        }
+        outcomes.sort_by_key(|(index, _, _)| *index);
+
+        let mut report = BatchReport::default();
+        for (_, code, outcome) in outcomes {
+            match outcome {
+                Ok(result) => report.results.push(result),
+                Err(e) => report.fail(&code, &e),
+            }
+        }
+        report
+    }
+}
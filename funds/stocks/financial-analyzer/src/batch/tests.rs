@@ -0,0 +1,84 @@
+//! 批量分析测试
+
+use super::watchlist::Watchlist;
+use super::BatchRunner;
+use crate::analyzer::{FinancialAnalyzer, ValuationParams};
+use crate::data_source::MockDataSource;
+use std::sync::Arc;
+
+const WATCHLIST_TOML: &str = r#"
+years = [2023, 2022]
+
+[[stocks]]
+code = "600519.SH"
+discount_rate = 0.09
+
+[[stocks]]
+code = "sz000858"
+years = "2023;2022;2021"
+
+[[stocks]]
+code = "不存在"
+"#;
+
+#[test]
+fn test_watchlist_toml() {
+    let watchlist: Watchlist = toml::from_str(WATCHLIST_TOML).unwrap();
+    assert_eq!(watchlist.stocks.len(), 3);
+    assert_eq!(watchlist.years_for(&watchlist.stocks[0]), vec![2023, 2022]);
+    assert_eq!(watchlist.years_for(&watchlist.stocks[1]), vec![2023, 2022, 2021]);
+
+    let params = watchlist.stocks[0].sensitivity_params();
+    assert_eq!(params.discount_rate, 0.09);
+    assert_eq!(params.perpetual_growth_rate, 0.04);
+
+    // 主估值在分析器参数上应用同样的覆盖，未指定的参数保持不变
+    let base = ValuationParams::default();
+    let valuation = watchlist.stocks[0].valuation_params(&base);
+    assert_eq!(valuation.dcf.discount_rate, 0.09);
+    assert_eq!(valuation.dcf.perpetual_growth_rate, base.dcf.perpetual_growth_rate);
+    assert_eq!(watchlist.stocks[1].valuation_params(&base).dcf.discount_rate, base.dcf.discount_rate);
+}
+
+#[test]
+fn test_watchlist_csv() {
+    let csv = "code,years,discount_rate,fcf_growth_rate\n\
+               # 注释行\n\
+               600519.SH,2023;2022,0.09,\n\
+               000858.SZ,2023,,0.05\n\
+               000568.SZ,,,\n";
+    let watchlist = Watchlist::from_csv(csv).unwrap();
+    let codes: Vec<&str> = watchlist.stocks.iter().map(|s| s.code.as_str()).collect();
+    assert_eq!(codes, vec!["600519.SH", "000858.SZ", "000568.SZ"]);
+    assert_eq!(watchlist.stocks[0].years, vec![2023, 2022]);
+    assert_eq!(watchlist.stocks[1].years, vec![2023]);
+    assert!(watchlist.stocks[2].years.is_empty());
+    assert_eq!(watchlist.stocks[0].discount_rate, Some(0.09));
+    assert_eq!(watchlist.stocks[0].fcf_growth_rate, None);
+    assert_eq!(watchlist.stocks[1].sensitivity_params().fcf_growth_rate, 0.05);
+
+    let error = Watchlist::from_csv("code,years\n600519.SH,abc\n").unwrap_err();
+    assert!(format!("{:#}", error).contains("第 2 行"));
+}
+
+#[tokio::test]
+async fn test_batch_runner_collects_failures() {
+    let watchlist: Watchlist = toml::from_str(WATCHLIST_TOML).unwrap();
+    let report = BatchRunner::new(FinancialAnalyzer::new(), Arc::new(MockDataSource::new()))
+        .with_concurrency(2)
+        .run(&watchlist)
+        .await;
+
+    // 成功的结果保持清单顺序和代码写法，无效代码记入失败列表而不中断
+    let codes: Vec<&str> = report.results.iter().map(|r| r.stock_code.as_str()).collect();
+    assert_eq!(codes, vec!["600519.SH", "sz000858"]);
+    assert_eq!(report.results[0].period_labels, vec!["2023", "2022"]);
+    assert_eq!(report.results[1].period_labels.len(), 3);
+    assert_eq!(report.results[0].sensitivity.as_ref().unwrap().params.discount_rate, 0.09);
+    assert_eq!(report.results[0].valuation_params.as_ref().unwrap().dcf.discount_rate, 0.09);
+    assert_eq!(report.results[1].valuation_params.as_ref().unwrap().dcf.discount_rate, 0.08);
+
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].code, "不存在");
+    assert!(!report.failures[0].reason.is_empty());
+}
@@ -0,0 +1,171 @@
+//! 自选股清单：TOML 或 CSV，每只股票可单独指定年份和估值参数
+//!
+//! TOML:
+//! ```toml
+//! years = [2023, 2022, 2021]      # 缺省年份
+//!
+//! [[stocks]]
+//! code = "600519.SH"
+//! discount_rate = 0.09
+//! ```
+//!
+//! CSV 首行为列名，`code` 必填，年份用分号或空格分隔：
+//! ```text
+//! code,years,discount_rate
+//! 600519.SH,2023;2022,0.09
+//! ```
+
+use crate::analyzer::{SensitivityParams, ValuationParams};
+use anyhow::{bail, Context, Result};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::path::Path;
+
+/// 清单中的一只股票
+#[derive(Debug, Clone, Deserialize)]
+pub struct WatchlistEntry {
+    /// 股票代码，写法同命令行（600519、600519.SH、sh600519 等）
+    pub code: String,
+    /// 分析年份，为空时使用清单的缺省年份
+    #[serde(default, deserialize_with = "deserialize_years")]
+    pub years: Vec<i32>,
+    pub discount_rate: Option<f64>,
+    pub perpetual_growth_rate: Option<f64>,
+    pub fcf_growth_rate: Option<f64>,
+    pub net_profit_growth_rate: Option<f64>,
+    pub low_risk_free_rate: Option<f64>,
+    pub high_risk_free_rate: Option<f64>,
+}
+
+impl WatchlistEntry {
+    /// 在默认敏感性参数上应用该股票的估值参数
+    pub fn sensitivity_params(&self) -> SensitivityParams {
+        let mut params = SensitivityParams::default();
+        let overrides = [
+            (&mut params.discount_rate, self.discount_rate),
+            (&mut params.perpetual_growth_rate, self.perpetual_growth_rate),
+            (&mut params.fcf_growth_rate, self.fcf_growth_rate),
+            (&mut params.net_profit_growth_rate, self.net_profit_growth_rate),
+            (&mut params.low_risk_free_rate, self.low_risk_free_rate),
+            (&mut params.high_risk_free_rate, self.high_risk_free_rate),
+        ];
+        for (field, value) in overrides {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+        params
+    }
+
+    /// 在分析器的估值参数上应用该股票的估值参数，使主估值与敏感性分析采用同一组参数
+    pub fn valuation_params(&self, base: &ValuationParams) -> ValuationParams {
+        let mut params = base.clone();
+        let overrides = [
+            (&mut params.dcf.discount_rate, self.discount_rate),
+            (&mut params.dcf.perpetual_growth_rate, self.perpetual_growth_rate),
+            (&mut params.dcf.fcf_growth_rate, self.fcf_growth_rate),
+            (&mut params.tangchao.net_profit_growth_rate, self.net_profit_growth_rate),
+            (&mut params.tangchao.low_risk_free_rate, self.low_risk_free_rate),
+            (&mut params.tangchao.high_risk_free_rate, self.high_risk_free_rate),
+        ];
+        for (field, value) in overrides {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+        params
+    }
+}
+
+/// 自选股清单
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Watchlist {
+    /// 缺省年份（股票未指定年份时使用），为空时取最近三年
+    #[serde(default, deserialize_with = "deserialize_years")]
+    pub years: Vec<i32>,
+    #[serde(default)]
+    pub stocks: Vec<WatchlistEntry>,
+}
+
+impl Watchlist {
+    /// 按扩展名读取 TOML 或 CSV 清单
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("无法读取自选股清单 {}", path.display()))?;
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+        let watchlist = match extension.as_str() {
+            "toml" => toml::from_str(&content).with_context(|| format!("自选股清单格式错误: {}", path.display()))?,
+            "csv" => Self::from_csv(&content).with_context(|| format!("自选股清单格式错误: {}", path.display()))?,
+            _ => bail!("不支持的清单格式: {}（支持 .toml / .csv）", path.display()),
+        };
+        Ok(watchlist)
+    }
+
+    pub fn from_csv(content: &str) -> Result<Self> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .comment(Some(b'#'))
+            .from_reader(content.as_bytes());
+        let stocks = reader
+            .deserialize()
+            .enumerate()
+            .map(|(i, row)| row.with_context(|| format!("第 {} 行", i + 2)))
+            .collect::<Result<Vec<WatchlistEntry>>>()?;
+        Ok(Self { years: Vec::new(), stocks })
+    }
+
+    /// 股票实际使用的年份
+    pub fn years_for(&self, entry: &WatchlistEntry) -> Vec<i32> {
+        if entry.years.is_empty() {
+            self.years.clone()
+        } else {
+            entry.years.clone()
+        }
+    }
+}
+
+/// 年份可以是整数数组、单个整数，或用逗号/分号/空格分隔的字符串
+fn deserialize_years<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<i32>, D::Error> {
+    struct YearsVisitor;
+
+    impl<'de> Visitor<'de> for YearsVisitor {
+        type Value = Vec<i32>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("年份列表，如 [2023, 2022] 或 \"2023;2022\"")
+        }
+
+        fn visit_i64<E: de::Error>(self, year: i64) -> std::result::Result<Self::Value, E> {
+            i32::try_from(year).map(|year| vec![year]).map_err(|_| E::custom(format!("无效的年份: {}", year)))
+        }
+
+        fn visit_u64<E: de::Error>(self, year: u64) -> std::result::Result<Self::Value, E> {
+            i32::try_from(year).map(|year| vec![year]).map_err(|_| E::custom(format!("无效的年份: {}", year)))
+        }
+
+        fn visit_str<E: de::Error>(self, text: &str) -> std::result::Result<Self::Value, E> {
+            text.split([',', ';', ' '])
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse().map_err(|_| E::custom(format!("无效的年份: {}", s))))
+                .collect()
+        }
+
+        fn visit_unit<E: de::Error>(self) -> std::result::Result<Self::Value, E> {
+            Ok(Vec::new())
+        }
+
+        fn visit_none<E: de::Error>(self) -> std::result::Result<Self::Value, E> {
+            Ok(Vec::new())
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error> {
+            let mut years = Vec::new();
+            while let Some(year) = seq.next_element()? {
+                years.push(year);
+            }
+            Ok(years)
+        }
+    }
+
+    deserializer.deserialize_any(YearsVisitor)
+}
@@ -1,6 +1,12 @@
+use crate::analyzer::{CapitalWeights, ForecastStage, ValuationModel};
+use crate::domain::{ReportPeriod, StockId};
+use crate::report::OutputFormat;
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
+#[cfg(test)]
+mod tests;
+
 #[derive(Parser)]
 #[command(name = "financial-analyzer")]
 #[command(about = "财务报表分析系统", version)]
@@ -10,29 +16,59 @@ pub struct Cli {
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 pub enum Commands {
     /// 分析单只股票
     Analyze {
-        /// 股票代码
+        /// 股票代码（600519、600519.SH、sh600519、00700.HK 等）；file/store 数据源可用任意标识
         #[arg(short, long)]
-        stock: String,
+        stock: StockId,
 
         /// 年份列表（逗号分隔）
         #[arg(short, long, value_delimiter = ',')]
         years: Vec<i32>,
 
-        /// 数据源 (mock, tushare, akshare)
+        /// 数据源 (mock, tushare, akshare, file, store)；
+        /// 多个数据源用逗号分隔按顺序回退，用加号连接逐科目合并，如 akshare+tushare
         #[arg(long, default_value = "mock")]
         source: String,
 
+        /// 本地报表目录（--source file 时必填）
+        #[arg(long)]
+        data_dir: Option<PathBuf>,
+
+        /// 分析期间 (annual, quarterly, ttm)
+        #[arg(long, default_value = "annual")]
+        period: ReportPeriod,
+
         /// 输出路径（默认为：股票代码_财务分析.xlsx）
         #[arg(short, long)]
         output: Option<PathBuf>,
 
+        /// 输出格式 (json, xlsx, txt, html, md, all)，可用逗号组合；json/txt/html/md 文件与 xlsx 同名、扩展名不同
+        #[arg(long, value_delimiter = ',', default_value = "xlsx,txt")]
+        format: Vec<OutputFormat>,
+
+        /// Excel报告模板（TOML/YAML），替换内置模板 templates/report.toml 描述的全部工作表（看板和数据来源除外）
+        #[arg(long)]
+        template: Option<PathBuf>,
+
         /// 启用数据验证
         #[arg(long, default_value = "false")]
         enable_validation: bool,
 
+        /// 跳过本地缓存，直接请求数据源
+        #[arg(long, default_value = "false")]
+        no_cache: bool,
+
+        /// 把 AKShare/Tushare 的原始响应录制到该目录
+        #[arg(long, conflicts_with = "replay")]
+        record: Option<PathBuf>,
+
+        /// 从录制目录回放原始响应，不访问 Python 和网络
+        #[arg(long)]
+        replay: Option<PathBuf>,
+
         /// 敏感性分析 - 折现率
         #[arg(long)]
         discount_rate: Option<f64>,
@@ -56,5 +92,223 @@ pub enum Commands {
         /// 敏感性分析 - 无风险收益率(高估)
         #[arg(long)]
         high_risk_free_rate: Option<f64>,
+
+        /// 敏感性分析 - DCF预测阶段（年数:增长率，增长率前加 ~ 表示从上一阶段线性渐变），
+        /// 如 "5:15%,5:~3%"；缺省按FCF增长率预测3年
+        #[arg(long, value_delimiter = ',')]
+        forecast_stages: Vec<ForecastStage>,
+
+        /// 敏感性分析 - 基准FCF取最近几年的平均值
+        #[arg(long)]
+        base_fcf_years: Option<usize>,
+
+        /// 用 WACC 作为DCF折现率（CAPM股权成本 + 财务费用/有息负债推算的债务成本）
+        #[arg(long, default_value = "false", conflicts_with = "discount_rate")]
+        wacc: bool,
+
+        /// WACC - 无风险收益率（默认2.5%）
+        #[arg(long, requires = "wacc")]
+        risk_free_rate: Option<f64>,
+
+        /// WACC - 股权风险溢价（默认6%）
+        #[arg(long, requires = "wacc")]
+        equity_risk_premium: Option<f64>,
+
+        /// WACC - β系数（默认1.0），也可用 --stock-prices 和 --index-prices 回归估计
+        #[arg(long, requires = "wacc", conflicts_with = "stock_prices")]
+        beta: Option<f64>,
+
+        /// WACC - 个股收盘价 CSV（date,close 两列），与指数收盘价回归估计β
+        #[arg(long, requires_all = ["wacc", "index_prices"])]
+        stock_prices: Option<PathBuf>,
+
+        /// WACC - 指数收盘价 CSV（date,close 两列）
+        #[arg(long, requires = "stock_prices")]
+        index_prices: Option<PathBuf>,
+
+        /// 当前股价：反推市场隐含的FCF增长率和净利润增长率（反向DCF），也用于WACC市值权重；
+        /// 缺省取 --stock-prices 的最新收盘价
+        #[arg(long)]
+        price: Option<f64>,
+
+        /// WACC - 资本权重 (market, book)
+        #[arg(long, default_value = "market", requires = "wacc")]
+        capital_weights: CapitalWeights,
+
+        /// 另行计算的估值模型 (ddm, fcfe)，逗号分隔；与DCF、唐朝估值并列输出
+        #[arg(long, value_delimiter = ',')]
+        models: Vec<ValuationModel>,
+
+        /// DDM/FCFE - 股权成本（默认9%；启用 --wacc 时取CAPM股权成本）
+        #[arg(long, conflicts_with = "wacc")]
+        cost_of_equity: Option<f64>,
+
+        /// DDM/FCFE - 高速增长期年数（默认5，0 为 Gordon 单阶段模型），之后按永续增长率增长
+        #[arg(long)]
+        high_growth_years: Option<u32>,
+
+        /// DDM/FCFE - 高速增长期的增长率（默认8%）
+        #[arg(long)]
+        high_growth_rate: Option<f64>,
+    },
+
+    /// 对比多家公司：并列比率、指标排名、共同比报表和估值对比
+    Compare {
+        /// 股票代码列表（逗号分隔，至少两只）
+        #[arg(short, long, value_delimiter = ',', required = true, num_args = 1..)]
+        stocks: Vec<StockId>,
+
+        /// 年份列表（逗号分隔），只对比各公司都有数据的报告期
+        #[arg(short, long, value_delimiter = ',')]
+        years: Vec<i32>,
+
+        /// 数据源 (mock, tushare, akshare, file, store)，写法同 analyze
+        #[arg(long, default_value = "mock")]
+        source: String,
+
+        /// 本地报表目录（--source file 时必填）
+        #[arg(long)]
+        data_dir: Option<PathBuf>,
+
+        /// 分析期间 (annual, quarterly, ttm)
+        #[arg(long, default_value = "annual")]
+        period: ReportPeriod,
+
+        /// 输出路径（默认为：股票代码_..._对比分析.xlsx）
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// 跳过本地缓存，直接请求数据源
+        #[arg(long, default_value = "false")]
+        no_cache: bool,
+    },
+
+    /// 按自选股清单批量分析（并发执行，单只失败不中断）
+    Batch {
+        /// 自选股清单（TOML 或 CSV），可为每只股票指定年份和估值参数
+        #[arg(short, long)]
+        watchlist: PathBuf,
+
+        /// 数据源 (mock, tushare, akshare, file, store)，写法同 analyze
+        #[arg(long, default_value = "mock")]
+        source: String,
+
+        /// 本地报表目录（--source file 时必填）
+        #[arg(long)]
+        data_dir: Option<PathBuf>,
+
+        /// 分析期间 (annual, quarterly, ttm)
+        #[arg(long, default_value = "annual")]
+        period: ReportPeriod,
+
+        /// 输出目录：每只股票一份报告，另有 批量汇总.xlsx
+        #[arg(short, long, default_value = "../analyzer-report/batch")]
+        output_dir: PathBuf,
+
+        /// 每只股票的报告格式 (json, xlsx, txt, html, md, all)，可用逗号组合
+        #[arg(long, value_delimiter = ',', default_value = "xlsx")]
+        format: Vec<OutputFormat>,
+
+        /// 同时分析的股票数
+        #[arg(short, long, default_value_t = crate::batch::DEFAULT_CONCURRENCY)]
+        jobs: usize,
+
+        /// 启用数据验证
+        #[arg(long, default_value = "false")]
+        enable_validation: bool,
+
+        /// 跳过本地缓存，直接请求数据源
+        #[arg(long, default_value = "false")]
+        no_cache: bool,
+    },
+
+    /// 在已保存的 JSON 分析报告上按条件筛选股票并排名
+    Screen {
+        /// 筛选条件，如 "roe > 15% and debt_ratio < 60% and min(ocf_to_net_profit, 3) >= 1"
+        #[arg(short, long)]
+        filter: String,
+
+        /// JSON 报告所在目录（含子目录）
+        #[arg(short, long, default_value = "../analyzer-report")]
+        input: PathBuf,
+
+        /// 当前股价 CSV（code,price 两列），供 price 字段使用
+        #[arg(long)]
+        prices: Option<PathBuf>,
+
+        /// 排序依据（数值表达式），缺省从高到低
+        #[arg(long, default_value = "roe")]
+        sort: String,
+
+        /// 从低到高排序
+        #[arg(long, default_value = "false")]
+        ascending: bool,
+
+        /// 只显示前 N 名
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// 同时把筛选结果写入 Excel 文件
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// 管理本地数据缓存
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+
+    /// 增量同步报表到本地报表库（只拉取缺失的报告期）
+    Sync {
+        /// 股票代码列表（逗号分隔）
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        stocks: Vec<StockId>,
+
+        /// 年份列表（逗号分隔）
+        #[arg(short, long, value_delimiter = ',')]
+        years: Vec<i32>,
+
+        /// 数据源 (akshare, tushare)
+        #[arg(long, default_value = "akshare")]
+        source: String,
+
+        /// 分析期间 (annual, quarterly, ttm)，决定需要同步的报告期
+        #[arg(long, default_value = "annual")]
+        period: ReportPeriod,
+    },
+    /// 以 MCP 服务方式运行（通过标准输入输出收发 JSON-RPC 消息）
+    Mcp,
+}
+
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// 列出缓存条目
+    List,
+
+    /// 清除缓存
+    Clear {
+        /// 只清除指定股票的缓存
+        #[arg(short, long)]
+        stock: Option<StockId>,
+    },
+
+    /// 预先拉取数据写入缓存
+    Warm {
+        /// 股票代码列表（逗号分隔）
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        stocks: Vec<StockId>,
+
+        /// 年份列表（逗号分隔）
+        #[arg(short, long, value_delimiter = ',')]
+        years: Vec<i32>,
+
+        /// 数据源 (mock, tushare, akshare)
+        #[arg(long, default_value = "mock")]
+        source: String,
+
+        /// 分析期间 (annual, quarterly, ttm)
+        #[arg(long, default_value = "annual")]
+        period: ReportPeriod,
     },
 }
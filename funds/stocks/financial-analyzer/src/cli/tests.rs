@@ -0,0 +1,33 @@
+//! 命令行参数解析测试
+
+use super::{Cli, Commands};
+use clap::Parser;
+
+#[test]
+fn test_analyze_accepts_unlisted_company_code() {
+    // 非交易所代码在参数解析阶段不报错，由数据源决定是否可用，且保留原写法
+    let cli = Cli::try_parse_from(["financial-analyzer", "analyze", "--stock", "MYCO", "--source", "file", "--data-dir", "./data"]).unwrap();
+    match cli.command {
+        Commands::Analyze { stock, source, .. } => {
+            assert_eq!(stock.as_str(), "MYCO");
+            assert!(stock.code().is_none());
+            assert_eq!(source, "file");
+        }
+        _ => panic!("应解析为 analyze 命令"),
+    }
+
+    let cli = Cli::try_parse_from(["financial-analyzer", "analyze", "--stock", "600519"]).unwrap();
+    assert!(matches!(cli.command, Commands::Analyze { stock, .. } if stock.as_str() == "600519" && stock.key() == "600519.SH"));
+}
+
+#[test]
+fn test_malformed_exchange_code_rejected_at_parse_time() {
+    for args in [
+        vec!["financial-analyzer", "analyze", "--stock", "600519.SZ"],
+        vec!["financial-analyzer", "compare", "--stocks", "600519,6005"],
+        vec!["financial-analyzer", "cache", "clear", "--stock", "sh00700"],
+    ] {
+        let err = Cli::try_parse_from(&args).err().unwrap_or_else(|| panic!("{:?} 应在参数解析阶段报错", args));
+        assert_eq!(err.kind(), clap::error::ErrorKind::ValueValidation, "{:?}", args);
+    }
+}
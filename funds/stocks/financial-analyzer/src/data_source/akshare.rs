@@ -1,16 +1,22 @@
+use super::akshare_worker::PythonWorker;
+use super::classifier::AccountClassifier;
+use super::recorder::Recorder;
 use super::traits::DataSource;
 use crate::domain::*;
+use crate::utils::AkshareConfig;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use chrono::{NaiveDate, Datelike};
+use chrono::NaiveDate;
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::process::Command;
+use std::time::Duration;
 
-/// AKShare数据源（通过Python脚本调用）
+/// AKShare数据源（通过常驻Python进程调用）
 pub struct AkshareClient {
-    python_path: String,
+    worker: PythonWorker,
+    classifier: AccountClassifier,
+    recorder: Option<Recorder>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -101,6 +107,8 @@ struct AkshareBalanceSheet {
     current_assets: Option<f64>,
     #[serde(rename = "NON_CURRENT_ASSETS")]
     non_current_assets: Option<f64>,
+    #[serde(rename = "MINORITY_INTEREST")]
+    minority_interest: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -115,6 +123,10 @@ struct AkshareIncomeStatement {
     main_operate_cost: Option<f64>,
     #[serde(rename = "OPERATE_PROFIT")]
     operate_profit: Option<f64>,
+    #[serde(rename = "TOTAL_PROFIT")]
+    total_profit: Option<f64>,
+    #[serde(rename = "INCOME_TAX")]
+    income_tax: Option<f64>,
     #[serde(rename = "NETPROFIT")]
     net_profit: Option<f64>,
     #[serde(rename = "TAX")]
@@ -174,95 +186,44 @@ struct AkshareCashflow {
 }
 
 impl AkshareClient {
-    pub fn new() -> Self {
-        // On Windows, use "python", on Unix use "python3"
-        let python_path = if cfg!(windows) {
-            "python".to_string()
-        } else {
-            "python3".to_string()
-        };
-        
-        Self { python_path }
+    pub fn from_config(config: &AkshareConfig) -> Self {
+        Self {
+            worker: PythonWorker::new(
+                config.python_path.clone(),
+                Duration::from_secs(config.startup_timeout_secs),
+                Duration::from_secs(config.timeout_secs),
+            ),
+            classifier: AccountClassifier::default(),
+            recorder: None,
+        }
     }
 
-    /// 调用Python脚本获取数据
-    fn call_python_script(&self, script: &str) -> Result<String> {
-        use std::fs;
-        use std::env;
-        
-        // On Windows, write script to temp file to avoid encoding issues
-        if cfg!(windows) {
-            let temp_dir = env::temp_dir();
-            let script_path = temp_dir.join("akshare_script.py");
-            
-            // Write script with UTF-8 BOM to ensure proper encoding
-            let script_with_bom = format!("\u{FEFF}# -*- coding: utf-8 -*-\n{}", script);
-            fs::write(&script_path, script_with_bom)
-                .map_err(|e| anyhow!("写入临时脚本失败: {}", e))?;
-            
-            let output = Command::new(&self.python_path)
-                .arg(&script_path)
-                .output()
-                .map_err(|e| anyhow!("执行Python失败: {}. 请确保已安装Python和akshare库 (pip install akshare)", e))?;
-            
-            // Clean up temp file
-            let _ = fs::remove_file(&script_path);
-            
-            if !output.status.success() {
-                let error = String::from_utf8_lossy(&output.stderr);
-                
-                // 检查是否是lxml解析器问题
-                if error.contains("lxml") || error.contains("FeatureNotFound") {
-                    return Err(anyhow!(
-                        "AKShare依赖错误: 请安装完整依赖\n\
-                        解决方案:\n\
-                        1. pip install beautifulsoup4 lxml html5lib\n\
-                        2. 或使用: pip install --upgrade akshare beautifulsoup4 lxml\n\
-                        \n原始错误: {}", error
-                    ));
-                }
-                
-                return Err(anyhow!("Python脚本执行错误: {}", error));
-            }
-            
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
-        } else {
-            let output = Command::new(&self.python_path)
-                .arg("-c")
-                .arg(script)
-                .output()
-                .map_err(|e| anyhow!("执行Python失败: {}. 请确保已安装Python3和akshare库 (pip3 install akshare)", e))?;
-
-            if !output.status.success() {
-                let error = String::from_utf8_lossy(&output.stderr);
-                
-                // 检查是否是lxml解析器问题
-                if error.contains("lxml") || error.contains("FeatureNotFound") {
-                    return Err(anyhow!(
-                        "AKShare依赖错误: 请安装完整依赖\n\
-                        解决方案:\n\
-                        1. pip3 install beautifulsoup4 lxml html5lib\n\
-                        2. 或使用: pip3 install --upgrade akshare beautifulsoup4 lxml\n\
-                        \n原始错误: {}", error
-                    ));
-                }
-                
-                return Err(anyhow!("Python脚本执行错误: {}", error));
-            }
+    pub fn with_classifier(mut self, classifier: AccountClassifier) -> Self {
+        self.classifier = classifier;
+        self
+    }
 
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    /// 录制或回放脚本输出
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// 执行脚本并返回输出；回放模式下直接读取录制的输出
+    async fn run_script(&self, key: &str, script: &str) -> Result<String> {
+        if let Some(replayed) = self.recorder.as_ref().and_then(|r| r.replay_response("akshare", key)) {
+            return replayed;
+        }
+
+        let output = self.worker.run(script).await?;
+        if let Some(recorder) = &self.recorder {
+            recorder.save("akshare", key, &output)?;
         }
+        Ok(output)
     }
 
     /// 获取资产负债表
-    fn fetch_balance_sheet_data(&self, stock_code: &str) -> Result<Vec<AkshareBalanceSheet>> {
-        // 转换股票代码格式: 600519 -> sh600519
-        let sina_code = if stock_code.starts_with('6') {
-            format!("sh{}", stock_code)
-        } else {
-            format!("sz{}", stock_code)
-        };
-        
+    async fn fetch_balance_sheet_data(&self, sina_code: &str) -> Result<Vec<AkshareBalanceSheet>> {
         let script = format!(
             r#"
 import akshare as ak
@@ -270,24 +231,18 @@ import json
 import math
 df = ak.stock_financial_report_sina(stock='{}', symbol='资产负债表')
 result = []
+{helpers}
 for _, row in df.iterrows():
-    def safe_float(val):
-        try:
-            f = float(val or 0)
-            return 0.0 if (math.isnan(f) or math.isinf(f)) else f
-        except:
-            return 0.0
-    
     result.append({{
         'REPORT_DATE': str(row['报告日']),
         'TOTAL_ASSETS': safe_float(row.get('资产总计')),
         'TOTAL_LIABILITIES': safe_float(row.get('负债合计')),
-        'TOTAL_EQUITY': safe_float(row.get('所有者权益(或股东权益)合计')) or safe_float(row.get('所有者权益合计')),
+        'TOTAL_EQUITY': first_float(row.get('所有者权益(或股东权益)合计'), row.get('所有者权益合计')),
         'MONETARYFUNDS': safe_float(row.get('货币资金')),
-        'FIXED_ASSETS': safe_float(row.get('固定资产净额')) or safe_float(row.get('固定资产及清理合计')),
+        'FIXED_ASSETS': first_float(row.get('固定资产净额'), row.get('固定资产及清理合计')),
         'ACCOUNTS_RECE': safe_float(row.get('应收账款')),
         'INVENTORY': safe_float(row.get('存货')),
-        'SHARE_CAPITAL': safe_float(row.get('实收资本(或股本)')) or safe_float(row.get('股本')),
+        'SHARE_CAPITAL': first_float(row.get('实收资本(或股本)'), row.get('股本')),
         'NOTES_RECEIVABLE': safe_float(row.get('应收票据')),
         'PREPAYMENTS': safe_float(row.get('预付款项')),
         'INTANGIBLE_ASSETS': safe_float(row.get('无形资产')),
@@ -298,20 +253,20 @@ for _, row in df.iterrows():
         'LONG_TERM_RECEIVABLES': safe_float(row.get('长期应收款')),
         'INTEREST_RECEIVABLE': safe_float(row.get('应收利息')),
         'DIVIDEND_RECEIVABLE': safe_float(row.get('应收股利')),
-        'DEFERRED_TAX_ASSETS': safe_float(row.get('递延所得税资产')) or safe_float(row.get('递延税款借项')),
+        'DEFERRED_TAX_ASSETS': first_float(row.get('递延所得税资产'), row.get('递延税款借项')),
         'NON_CURRENT_ASSETS_DUE_WITHIN_ONE_YEAR': safe_float(row.get('一年内到期的非流动资产')),
         'OTHER_NON_CURRENT_ASSETS': safe_float(row.get('其他非流动资产')),
-        'NOTES_PAYABLE': safe_float(row.get('应付票据')) or safe_float(row.get('应付票据及应付账款')),
+        'NOTES_PAYABLE': first_float(row.get('应付票据'), row.get('应付票据及应付账款')),
         'ACCOUNTS_PAYABLE': safe_float(row.get('应付账款')),
         'ADVANCE_RECEIPTS': safe_float(row.get('预收款项')),
         'EMPLOYEE_PAYABLE': safe_float(row.get('应付职工薪酬')),
         'TAX_PAYABLE': safe_float(row.get('应交税费')),
         'CONTRACT_LIABILITIES': safe_float(row.get('合同负债')),
-        'DEFERRED_TAX_LIABILITIES': safe_float(row.get('递延所得税负债')) or safe_float(row.get('递延税款贷项')),
-        'DEFERRED_REVENUE': safe_float(row.get('长期递延收益')) or safe_float(row.get('递延收益')),
+        'DEFERRED_TAX_LIABILITIES': first_float(row.get('递延所得税负债'), row.get('递延税款贷项')),
+        'DEFERRED_REVENUE': first_float(row.get('长期递延收益'), row.get('递延收益')),
         'INTEREST_PAYABLE': safe_float(row.get('应付利息')),
         'DIVIDEND_PAYABLE': safe_float(row.get('应付股利')),
-        'BONDS_PAYABLE': safe_float(row.get('应付债券')) or safe_float(row.get('应付债券款')),
+        'BONDS_PAYABLE': first_float(row.get('应付债券'), row.get('应付债券款')),
         'TRADING_FINANCIAL_LIABILITIES': safe_float(row.get('交易性金融负债')),
         'LONG_TERM_PAYABLE': safe_float(row.get('长期应付款')),
         'LONG_TERM_LOAN': safe_float(row.get('长期借款')),
@@ -321,25 +276,22 @@ for _, row in df.iterrows():
         'NON_CURRENT_LIABILITIES': safe_float(row.get('非流动负债合计')),
         'CURRENT_ASSETS': safe_float(row.get('流动资产合计')),
         'NON_CURRENT_ASSETS': safe_float(row.get('非流动资产合计')),
+        'MINORITY_INTEREST': safe_float(row.get('少数股东权益')),
     }})
 print(json.dumps(result))
 "#,
-            sina_code
+            sina_code,
+            helpers = PYTHON_HELPERS
         );
 
-        let json_str = self.call_python_script(&script)?;
+        let key = format!("{}_{}", sina_code, ReportType::BalanceSheet.as_str());
+        let json_str = self.run_script(&key, &script).await?;
         serde_json::from_str(&json_str)
             .map_err(|e| anyhow!("解析资产负债表JSON失败: {}", e))
     }
 
     /// 获取利润表
-    fn fetch_income_statement_data(&self, stock_code: &str) -> Result<Vec<AkshareIncomeStatement>> {
-        let sina_code = if stock_code.starts_with('6') {
-            format!("sh{}", stock_code)
-        } else {
-            format!("sz{}", stock_code)
-        };
-        
+    async fn fetch_income_statement_data(&self, sina_code: &str) -> Result<Vec<AkshareIncomeStatement>> {
         let script = format!(
             r#"
 import akshare as ak
@@ -347,25 +299,21 @@ import json
 import math
 df = ak.stock_financial_report_sina(stock='{}', symbol='利润表')
 result = []
-def safe_float(val):
-    try:
-        f = float(val or 0)
-        return 0.0 if (math.isnan(f) or math.isinf(f)) else f
-    except:
-        return 0.0
-
+{helpers}
 for _, row in df.iterrows():
     result.append({{
         'REPORT_DATE': str(row['报告日']),
-        'TOTAL_OPERATE_INCOME': safe_float(row.get('营业总收入')) or safe_float(row.get('营业收入')),
-        'OPERATE_COST': safe_float(row.get('营业总成本')) or safe_float(row.get('营业支出')),
+        'TOTAL_OPERATE_INCOME': first_float(row.get('营业总收入'), row.get('营业收入')),
+        'OPERATE_COST': first_float(row.get('营业总成本'), row.get('营业支出')),
         'MAIN_OPERATE_COST': safe_float(row.get('营业成本')),
         'OPERATE_PROFIT': safe_float(row.get('营业利润')),
+        'TOTAL_PROFIT': first_float(row.get('利润总额'), row.get('四、利润总额')),
+        'INCOME_TAX': first_float(row.get('减：所得税费用'), row.get('所得税费用')),
         'NETPROFIT': safe_float(row.get('净利润')),
         'TAX': safe_float(row.get('营业税金及附加')),
         'FINANCE_EXPENSE': safe_float(row.get('财务费用')),
         'SALES_EXPENSE': safe_float(row.get('销售费用')) or 0,
-        'ADMIN_EXPENSE': safe_float(row.get('管理费用')) or safe_float(row.get('业务及管理费')),
+        'ADMIN_EXPENSE': first_float(row.get('管理费用'), row.get('业务及管理费')),
         'BIZ_ADMIN_EXPENSE': safe_float(row.get('业务及管理费')),
         'RD_EXPENSE': safe_float(row.get('研发费用')),
         'OTHER_INCOME': safe_float(row.get('其他收益')),
@@ -374,27 +322,23 @@ for _, row in df.iterrows():
         'ASSET_DISPOSAL_INCOME': safe_float(row.get('资产处置收益')),
         'ASSET_IMPAIRMENT_LOSS': safe_float(row.get('资产减值损失')),
         'CREDIT_IMPAIRMENT_LOSS': safe_float(row.get('信用减值损失')),
-        'NON_OPERATING_INCOME': safe_float(row.get('营业外收入')) or safe_float(row.get('加:营业外收入')),
-        'NON_OPERATING_EXPENSE': safe_float(row.get('营业外支出')) or safe_float(row.get('减:营业外支出')),
+        'NON_OPERATING_INCOME': first_float(row.get('营业外收入'), row.get('加:营业外收入')),
+        'NON_OPERATING_EXPENSE': first_float(row.get('营业外支出'), row.get('减:营业外支出')),
     }})
 print(json.dumps(result))
 "#,
-            sina_code
+            sina_code,
+            helpers = PYTHON_HELPERS
         );
 
-        let json_str = self.call_python_script(&script)?;
+        let key = format!("{}_{}", sina_code, ReportType::IncomeStatement.as_str());
+        let json_str = self.run_script(&key, &script).await?;
         serde_json::from_str(&json_str)
             .map_err(|e| anyhow!("解析利润表JSON失败: {}", e))
     }
 
     /// 获取现金流量表
-    fn fetch_cashflow_statement_data(&self, stock_code: &str) -> Result<Vec<AkshareCashflow>> {
-        let sina_code = if stock_code.starts_with('6') {
-            format!("sh{}", stock_code)
-        } else {
-            format!("sz{}", stock_code)
-        };
-        
+    async fn fetch_cashflow_statement_data(&self, sina_code: &str) -> Result<Vec<AkshareCashflow>> {
         let script = format!(
             r#"
 import akshare as ak
@@ -402,20 +346,14 @@ import json
 import math
 df = ak.stock_financial_report_sina(stock='{}', symbol='现金流量表')
 result = []
-def safe_float(val):
-    try:
-        f = float(val or 0)
-        return 0.0 if (math.isnan(f) or math.isinf(f)) else f
-    except:
-        return 0.0
-
+{helpers}
 for _, row in df.iterrows():
     result.append({{
         'REPORT_DATE': str(row['报告日']),
         'OPERATE_CASH_FLOW': safe_float(row.get('经营活动产生的现金流量净额')),
         'INVEST_CASH_FLOW': safe_float(row.get('投资活动产生的现金流量净额')),
         'FINANCE_CASH_FLOW': safe_float(row.get('筹资活动产生的现金流量净额')),
-        'CAPEX': safe_float(row.get('购建固定资产、无形资产和其他长期资产所支付的现金')) or safe_float(row.get('购建固定资产、无形资产和其他长期资产支付的现金')),
+        'CAPEX': first_float(row.get('购建固定资产、无形资产和其他长期资产所支付的现金'), row.get('购建固定资产、无形资产和其他长期资产支付的现金')),
         'INVEST_PAY_CASH': safe_float(row.get('投资所支付的现金')),
         'RECEIVE_INVEST_CASH': safe_float(row.get('吸收投资收到的现金')),
         'RECEIVE_LOAN_CASH': safe_float(row.get('取得借款收到的现金')),
@@ -425,17 +363,47 @@ for _, row in df.iterrows():
     }})
 print(json.dumps(result))
 "#,
-            sina_code
+            sina_code,
+            helpers = PYTHON_HELPERS
         );
 
-        let json_str = self.call_python_script(&script)?;
+        let key = format!("{}_{}", sina_code, ReportType::CashflowStatement.as_str());
+        let json_str = self.run_script(&key, &script).await?;
         serde_json::from_str(&json_str)
             .map_err(|e| anyhow!("解析现金流量表JSON失败: {}", e))
     }
 
-    /// 转换股票代码格式 (600519.SH -> 600519)
-    fn convert_stock_code(&self, code: &str) -> String {
-        code.split('.').next().unwrap_or(code).to_string()
+    /// 转换为新浪代码格式 (600519.SH -> sh600519)
+    fn sina_code(&self, stock_code: &str) -> Result<String> {
+        let code: StockCode = stock_code.parse().map_err(|e: String| anyhow!(e))?;
+        if code.exchange() == Exchange::HongKong {
+            return Err(anyhow!("AKShare数据源暂不支持港股: {}", code));
+        }
+        Ok(code.sina())
+    }
+}
+
+/// 脚本共用的取值函数：缺失、NaN 和无法解析的值返回 None，不记为0
+const PYTHON_HELPERS: &str = r#"
+def safe_float(val):
+    try:
+        f = float(val)
+    except (TypeError, ValueError):
+        return None
+    return None if (math.isnan(f) or math.isinf(f)) else f
+
+def first_float(*vals):
+    for val in vals:
+        f = safe_float(val)
+        if f is not None:
+            return f
+    return None
+"#;
+
+/// 写入有值的科目；缺失值不记为0，以便多源合并时由其他数据源补齐
+fn insert_value(items: &mut HashMap<String, Decimal>, name: &str, value: Option<f64>) {
+    if let Some(decimal) = value.and_then(Decimal::from_f64_retain) {
+        items.insert(name.to_string(), decimal);
     }
 }
 
@@ -447,119 +415,69 @@ impl DataSource for AkshareClient {
         start_date: NaiveDate,
         end_date: NaiveDate,
     ) -> Result<Vec<BalanceSheet>> {
-        let code = self.convert_stock_code(stock_code);
-        let data = self.fetch_balance_sheet_data(&code)?;
+        let code = self.sina_code(stock_code)?;
+        let data = self.fetch_balance_sheet_data(&code).await?;
 
         let mut sheets = Vec::new();
         for item in data {
             let report_date = NaiveDate::parse_from_str(&item.report_date, "%Y-%m-%d")
                 .or_else(|_| NaiveDate::parse_from_str(&item.report_date, "%Y%m%d"))?;
 
-            // 只保留年报数据（12月31日）且在日期范围内
-            if report_date.month() != 12 || report_date.day() != 31 {
-                continue;
-            }
+            // 保留日期范围内的全部报告（含中期报告，由分析器按期间模式筛选）
             if report_date < start_date || report_date > end_date {
                 continue;
             }
 
             let mut items_map = HashMap::new();
-            items_map.insert("资产总计".to_string(), Decimal::from_f64_retain(item.total_assets.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("负债合计".to_string(), Decimal::from_f64_retain(item.total_liabilities.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("所有者权益合计".to_string(), Decimal::from_f64_retain(item.total_equity.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("货币资金".to_string(), Decimal::from_f64_retain(item.monetary_funds.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("固定资产".to_string(), Decimal::from_f64_retain(item.fixed_assets.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("应收账款".to_string(), Decimal::from_f64_retain(item.accounts_receivable.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("存货".to_string(), Decimal::from_f64_retain(item.inventory.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("股本".to_string(), Decimal::from_f64_retain(item.share_capital.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("实收资本(或股本)".to_string(), Decimal::from_f64_retain(item.share_capital.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
+            insert_value(&mut items_map, "资产总计", item.total_assets);
+            insert_value(&mut items_map, "负债合计", item.total_liabilities);
+            insert_value(&mut items_map, "所有者权益合计", item.total_equity);
+            insert_value(&mut items_map, "货币资金", item.monetary_funds);
+            insert_value(&mut items_map, "固定资产", item.fixed_assets);
+            insert_value(&mut items_map, "应收账款", item.accounts_receivable);
+            insert_value(&mut items_map, "存货", item.inventory);
+            insert_value(&mut items_map, "股本", item.share_capital);
+            insert_value(&mut items_map, "实收资本(或股本)", item.share_capital);
             
             // 资产科目
-            items_map.insert("应收票据".to_string(), Decimal::from_f64_retain(item.notes_receivable.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("预付款项".to_string(), Decimal::from_f64_retain(item.prepayments.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("无形资产".to_string(), Decimal::from_f64_retain(item.intangible_assets.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("交易性金融资产".to_string(), Decimal::from_f64_retain(item.trading_financial_assets.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("长期股权投资".to_string(), Decimal::from_f64_retain(item.long_term_equity_investment.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("持有至到期投资".to_string(), Decimal::from_f64_retain(item.held_to_maturity_investments.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("投资性房地产".to_string(), Decimal::from_f64_retain(item.investment_property.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("长期应收款".to_string(), Decimal::from_f64_retain(item.long_term_receivables.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("应收利息".to_string(), Decimal::from_f64_retain(item.interest_receivable.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("应收股利".to_string(), Decimal::from_f64_retain(item.dividend_receivable.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("递延所得税资产".to_string(), Decimal::from_f64_retain(item.deferred_tax_assets.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("一年内到期的非流动资产".to_string(), Decimal::from_f64_retain(item.non_current_assets_due_within_one_year.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("其他非流动资产".to_string(), Decimal::from_f64_retain(item.other_non_current_assets.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
+            insert_value(&mut items_map, "应收票据", item.notes_receivable);
+            insert_value(&mut items_map, "预付款项", item.prepayments);
+            insert_value(&mut items_map, "无形资产", item.intangible_assets);
+            insert_value(&mut items_map, "交易性金融资产", item.trading_financial_assets);
+            insert_value(&mut items_map, "长期股权投资", item.long_term_equity_investment);
+            insert_value(&mut items_map, "持有至到期投资", item.held_to_maturity_investments);
+            insert_value(&mut items_map, "投资性房地产", item.investment_property);
+            insert_value(&mut items_map, "长期应收款", item.long_term_receivables);
+            insert_value(&mut items_map, "应收利息", item.interest_receivable);
+            insert_value(&mut items_map, "应收股利", item.dividend_receivable);
+            insert_value(&mut items_map, "递延所得税资产", item.deferred_tax_assets);
+            insert_value(&mut items_map, "一年内到期的非流动资产", item.non_current_assets_due_within_one_year);
+            insert_value(&mut items_map, "其他非流动资产", item.other_non_current_assets);
             
             // 负债科目
-            items_map.insert("应付票据".to_string(), Decimal::from_f64_retain(item.notes_payable.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("应付账款".to_string(), Decimal::from_f64_retain(item.accounts_payable.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("预收款项".to_string(), Decimal::from_f64_retain(item.advance_receipts.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("应付职工薪酬".to_string(), Decimal::from_f64_retain(item.employee_payable.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("应交税费".to_string(), Decimal::from_f64_retain(item.tax_payable.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("合同负债".to_string(), Decimal::from_f64_retain(item.contract_liabilities.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("递延所得税负债".to_string(), Decimal::from_f64_retain(item.deferred_tax_liabilities.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("递延收益".to_string(), Decimal::from_f64_retain(item.deferred_revenue.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("应付利息".to_string(), Decimal::from_f64_retain(item.interest_payable.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("应付股利".to_string(), Decimal::from_f64_retain(item.dividend_payable.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("应付债券".to_string(), Decimal::from_f64_retain(item.bonds_payable.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("交易性金融负债".to_string(), Decimal::from_f64_retain(item.trading_financial_liabilities.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("长期应付款".to_string(), Decimal::from_f64_retain(item.long_term_payable.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("长期借款".to_string(), Decimal::from_f64_retain(item.long_term_loan.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("短期借款".to_string(), Decimal::from_f64_retain(item.short_term_loan.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("一年内到期的非流动负债".to_string(), Decimal::from_f64_retain(item.non_current_liabilities_due_within_one_year.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("流动负债合计".to_string(), Decimal::from_f64_retain(item.current_liabilities.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("非流动负债合计".to_string(), Decimal::from_f64_retain(item.non_current_liabilities.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("流动资产合计".to_string(), Decimal::from_f64_retain(item.current_assets.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("非流动资产合计".to_string(), Decimal::from_f64_retain(item.non_current_assets.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-
-            // 填充经营性资产
-            let mut operating_assets = AssetGroup::new();
-            if let Some(v) = items_map.get("货币资金") { operating_assets.add("货币资金".to_string(), *v); }
-            if let Some(v) = items_map.get("固定资产") { operating_assets.add("固定资产".to_string(), *v); }
-            if let Some(v) = items_map.get("应收票据") { operating_assets.add("应收票据".to_string(), *v); }
-            if let Some(v) = items_map.get("应收账款") { operating_assets.add("应收账款".to_string(), *v); }
-            if let Some(v) = items_map.get("预付款项") { operating_assets.add("预付款项".to_string(), *v); }
-            if let Some(v) = items_map.get("存货") { operating_assets.add("存货".to_string(), *v); }
-            if let Some(v) = items_map.get("无形资产") { operating_assets.add("无形资产".to_string(), *v); }
-
-            // 填充金融性资产
-            let mut financial_assets = AssetGroup::new();
-            if let Some(v) = items_map.get("交易性金融资产") { financial_assets.add("交易性金融资产".to_string(), *v); }
-            if let Some(v) = items_map.get("长期股权投资") { financial_assets.add("长期股权投资".to_string(), *v); }
-            if let Some(v) = items_map.get("投资性房地产") { financial_assets.add("投资性房地产".to_string(), *v); }
-            if let Some(v) = items_map.get("递延所得税资产") { financial_assets.add("递延所得税资产".to_string(), *v); }
-
-            // 填充经营性负债
-            let mut operating_liabilities = LiabilityGroup::new();
-            if let Some(v) = items_map.get("应付票据") { operating_liabilities.add("应付票据".to_string(), *v); }
-            if let Some(v) = items_map.get("应付账款") { operating_liabilities.add("应付账款".to_string(), *v); }
-            if let Some(v) = items_map.get("预收款项") { operating_liabilities.add("预收款项".to_string(), *v); }
-            if let Some(v) = items_map.get("应付职工薪酬") { operating_liabilities.add("应付职工薪酬".to_string(), *v); }
-            if let Some(v) = items_map.get("应交税费") { operating_liabilities.add("应交税费".to_string(), *v); }
-            if let Some(v) = items_map.get("合同负债") { operating_liabilities.add("合同负债".to_string(), *v); }
-            if let Some(v) = items_map.get("递延所得税负债") { operating_liabilities.add("递延所得税负债".to_string(), *v); }
-
-            // 填充金融性负债
-            let mut financial_liabilities = LiabilityGroup::new();
-            if let Some(v) = items_map.get("短期借款") { financial_liabilities.add("短期借款".to_string(), *v); }
-            if let Some(v) = items_map.get("长期借款") { financial_liabilities.add("长期借款".to_string(), *v); }
-            if let Some(v) = items_map.get("应付债券") { financial_liabilities.add("应付债券".to_string(), *v); }
-            if let Some(v) = items_map.get("交易性金融负债") { financial_liabilities.add("交易性金融负债".to_string(), *v); }
-            if let Some(v) = items_map.get("一年内到期的非流动负债") { financial_liabilities.add("一年内到期的非流动负债".to_string(), *v); }
-
-            let statement = FinancialStatement {
-                stock_code: stock_code.to_string(),
-                report_date,
-                report_type: ReportType::BalanceSheet,
-                items: items_map,
-            };
-
-            sheets.push(BalanceSheet {
-                statement,
-                operating_assets,
-                financial_assets,
-                operating_liabilities,
-                financial_liabilities,
-            });
+            insert_value(&mut items_map, "应付票据", item.notes_payable);
+            insert_value(&mut items_map, "应付账款", item.accounts_payable);
+            insert_value(&mut items_map, "预收款项", item.advance_receipts);
+            insert_value(&mut items_map, "应付职工薪酬", item.employee_payable);
+            insert_value(&mut items_map, "应交税费", item.tax_payable);
+            insert_value(&mut items_map, "合同负债", item.contract_liabilities);
+            insert_value(&mut items_map, "递延所得税负债", item.deferred_tax_liabilities);
+            insert_value(&mut items_map, "递延收益", item.deferred_revenue);
+            insert_value(&mut items_map, "应付利息", item.interest_payable);
+            insert_value(&mut items_map, "应付股利", item.dividend_payable);
+            insert_value(&mut items_map, "应付债券", item.bonds_payable);
+            insert_value(&mut items_map, "交易性金融负债", item.trading_financial_liabilities);
+            insert_value(&mut items_map, "长期应付款", item.long_term_payable);
+            insert_value(&mut items_map, "长期借款", item.long_term_loan);
+            insert_value(&mut items_map, "短期借款", item.short_term_loan);
+            insert_value(&mut items_map, "一年内到期的非流动负债", item.non_current_liabilities_due_within_one_year);
+            insert_value(&mut items_map, "流动负债合计", item.current_liabilities);
+            insert_value(&mut items_map, "非流动负债合计", item.non_current_liabilities);
+            insert_value(&mut items_map, "流动资产合计", item.current_assets);
+            insert_value(&mut items_map, "非流动资产合计", item.non_current_assets);
+            insert_value(&mut items_map, "少数股东权益", item.minority_interest);
+
+            sheets.push(self.classifier.build_balance_sheet(stock_code, report_date, items_map));
         }
 
         Ok(sheets)
@@ -571,70 +489,43 @@ impl DataSource for AkshareClient {
         start_date: NaiveDate,
         end_date: NaiveDate,
     ) -> Result<Vec<IncomeStatement>> {
-        let code = self.convert_stock_code(stock_code);
-        let data = self.fetch_income_statement_data(&code)?;
+        let code = self.sina_code(stock_code)?;
+        let data = self.fetch_income_statement_data(&code).await?;
 
         let mut statements = Vec::new();
         for item in data {
             let report_date = NaiveDate::parse_from_str(&item.report_date, "%Y-%m-%d")
                 .or_else(|_| NaiveDate::parse_from_str(&item.report_date, "%Y%m%d"))?;
 
-            // 只保留年报数据（12月31日）且在日期范围内
-            if report_date.month() != 12 || report_date.day() != 31 {
-                continue;
-            }
+            // 保留日期范围内的全部报告（含中期报告，由分析器按期间模式筛选）
             if report_date < start_date || report_date > end_date {
                 continue;
             }
 
-            let revenue = Decimal::from_f64_retain(item.revenue.unwrap_or(0.0)).unwrap_or(Decimal::ZERO);
-            let operating_cost = Decimal::from_f64_retain(item.operating_cost.unwrap_or(0.0)).unwrap_or(Decimal::ZERO);
-            let net_profit = Decimal::from_f64_retain(item.net_profit.unwrap_or(0.0)).unwrap_or(Decimal::ZERO);
-            let tax = Decimal::from_f64_retain(item.tax.unwrap_or(0.0)).unwrap_or(Decimal::ZERO);
-            let finance_expense = Decimal::from_f64_retain(item.finance_expense.unwrap_or(0.0)).unwrap_or(Decimal::ZERO);
-            let sales_expense = Decimal::from_f64_retain(item.sales_expense.unwrap_or(0.0)).unwrap_or(Decimal::ZERO);
-            let admin_expense = Decimal::from_f64_retain(item.admin_expense.unwrap_or(0.0)).unwrap_or(Decimal::ZERO);
-            let biz_admin_expense = Decimal::from_f64_retain(item.biz_admin_expense.unwrap_or(0.0)).unwrap_or(Decimal::ZERO);
-            let rd_expense = Decimal::from_f64_retain(item.rd_expense.unwrap_or(0.0)).unwrap_or(Decimal::ZERO);
-            let main_cost = Decimal::from_f64_retain(item.main_operate_cost.unwrap_or(0.0)).unwrap_or(Decimal::ZERO);
-            let operate_profit = Decimal::from_f64_retain(item.operate_profit.unwrap_or(0.0)).unwrap_or(Decimal::ZERO);
-
             let mut items_map = HashMap::new();
-            items_map.insert("营业总收入".to_string(), revenue);
-            items_map.insert("营业总成本".to_string(), operating_cost);
-            items_map.insert("营业成本".to_string(), main_cost);
-            items_map.insert("营业利润".to_string(), operate_profit);
-            items_map.insert("净利润".to_string(), net_profit);
-            items_map.insert("税金及附加".to_string(), tax);
-            items_map.insert("财务费用".to_string(), finance_expense);
-            items_map.insert("销售费用".to_string(), sales_expense);
-            items_map.insert("管理费用".to_string(), admin_expense);
-            items_map.insert("业务及管理费".to_string(), biz_admin_expense);
-            items_map.insert("研发费用".to_string(), rd_expense);
-            items_map.insert("其他收益".to_string(), Decimal::from_f64_retain(item.other_income.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("投资收益".to_string(), Decimal::from_f64_retain(item.invest_income.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("公允价值变动收益".to_string(), Decimal::from_f64_retain(item.fair_value_change.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("资产处置收益".to_string(), Decimal::from_f64_retain(item.asset_disposal_income.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("资产减值损失".to_string(), Decimal::from_f64_retain(item.asset_impairment_loss.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("信用减值损失".to_string(), Decimal::from_f64_retain(item.credit_impairment_loss.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("营业外收入".to_string(), Decimal::from_f64_retain(item.non_operating_income.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("营业外支出".to_string(), Decimal::from_f64_retain(item.non_operating_expense.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-
-            let statement = FinancialStatement {
-                stock_code: stock_code.to_string(),
-                report_date,
-                report_type: ReportType::IncomeStatement,
-                items: items_map,
-            };
-
-            statements.push(IncomeStatement {
-                statement,
-                revenue,
-                operating_cost,
-                gross_profit: revenue - main_cost,  // 毛利 = 营业总收入 - 营业成本
-                core_profit: net_profit,
-                net_profit,
-            });
+            insert_value(&mut items_map, "营业总收入", item.revenue);
+            insert_value(&mut items_map, "营业总成本", item.operating_cost);
+            insert_value(&mut items_map, "营业成本", item.main_operate_cost);
+            insert_value(&mut items_map, "营业利润", item.operate_profit);
+            insert_value(&mut items_map, "净利润", item.net_profit);
+            insert_value(&mut items_map, "利润总额", item.total_profit);
+            insert_value(&mut items_map, "所得税费用", item.income_tax);
+            insert_value(&mut items_map, "税金及附加", item.tax);
+            insert_value(&mut items_map, "财务费用", item.finance_expense);
+            insert_value(&mut items_map, "销售费用", item.sales_expense);
+            insert_value(&mut items_map, "管理费用", item.admin_expense);
+            insert_value(&mut items_map, "业务及管理费", item.biz_admin_expense);
+            insert_value(&mut items_map, "研发费用", item.rd_expense);
+            insert_value(&mut items_map, "其他收益", item.other_income);
+            insert_value(&mut items_map, "投资收益", item.invest_income);
+            insert_value(&mut items_map, "公允价值变动收益", item.fair_value_change);
+            insert_value(&mut items_map, "资产处置收益", item.asset_disposal_income);
+            insert_value(&mut items_map, "资产减值损失", item.asset_impairment_loss);
+            insert_value(&mut items_map, "信用减值损失", item.credit_impairment_loss);
+            insert_value(&mut items_map, "营业外收入", item.non_operating_income);
+            insert_value(&mut items_map, "营业外支出", item.non_operating_expense);
+
+            statements.push(self.classifier.build_income_statement(stock_code, report_date, items_map));
         }
 
         Ok(statements)
@@ -646,52 +537,32 @@ impl DataSource for AkshareClient {
         start_date: NaiveDate,
         end_date: NaiveDate,
     ) -> Result<Vec<CashflowStatement>> {
-        let code = self.convert_stock_code(stock_code);
-        let data = self.fetch_cashflow_statement_data(&code)?;
+        let code = self.sina_code(stock_code)?;
+        let data = self.fetch_cashflow_statement_data(&code).await?;
 
         let mut statements = Vec::new();
         for item in data {
             let report_date = NaiveDate::parse_from_str(&item.report_date, "%Y-%m-%d")
                 .or_else(|_| NaiveDate::parse_from_str(&item.report_date, "%Y%m%d"))?;
 
-            // 只保留年报数据（12月31日）且在日期范围内
-            if report_date.month() != 12 || report_date.day() != 31 {
-                continue;
-            }
+            // 保留日期范围内的全部报告（含中期报告，由分析器按期间模式筛选）
             if report_date < start_date || report_date > end_date {
                 continue;
             }
 
-            let operating_cashflow = Decimal::from_f64_retain(item.operating_cashflow.unwrap_or(0.0)).unwrap_or(Decimal::ZERO);
-            let investing_cashflow = Decimal::from_f64_retain(item.investing_cashflow.unwrap_or(0.0)).unwrap_or(Decimal::ZERO);
-            let financing_cashflow = Decimal::from_f64_retain(item.financing_cashflow.unwrap_or(0.0)).unwrap_or(Decimal::ZERO);
-
             let mut items_map = HashMap::new();
-            items_map.insert("经营活动产生的现金流量净额".to_string(), operating_cashflow);
-            items_map.insert("投资活动产生的现金流量净额".to_string(), investing_cashflow);
-            items_map.insert("筹资活动产生的现金流量净额".to_string(), financing_cashflow);
-            items_map.insert("购建固定资产、无形资产和其他长期资产支付的现金".to_string(), Decimal::from_f64_retain(item.capex.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("投资支付的现金".to_string(), Decimal::from_f64_retain(item.invest_pay_cash.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("吸收投资收到的现金".to_string(), Decimal::from_f64_retain(item.receive_invest_cash.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("取得借款收到的现金".to_string(), Decimal::from_f64_retain(item.receive_loan_cash.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("偿还债务支付的现金".to_string(), Decimal::from_f64_retain(item.repay_debt_cash.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("分配股利、利润或偿付利息支付的现金".to_string(), Decimal::from_f64_retain(item.distribute_dividend_cash.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-            items_map.insert("支付其他与筹资活动有关的现金".to_string(), Decimal::from_f64_retain(item.pay_other_finance_cash.unwrap_or(0.0)).unwrap_or(Decimal::ZERO));
-
-            let statement = FinancialStatement {
-                stock_code: stock_code.to_string(),
-                report_date,
-                report_type: ReportType::CashflowStatement,
-                items: items_map,
-            };
-
-            statements.push(CashflowStatement {
-                statement,
-                operating_cashflow,
-                investing_cashflow,
-                financing_cashflow,
-                free_cashflow: operating_cashflow + investing_cashflow,
-            });
+            insert_value(&mut items_map, "经营活动产生的现金流量净额", item.operating_cashflow);
+            insert_value(&mut items_map, "投资活动产生的现金流量净额", item.investing_cashflow);
+            insert_value(&mut items_map, "筹资活动产生的现金流量净额", item.financing_cashflow);
+            insert_value(&mut items_map, "购建固定资产、无形资产和其他长期资产支付的现金", item.capex);
+            insert_value(&mut items_map, "投资支付的现金", item.invest_pay_cash);
+            insert_value(&mut items_map, "吸收投资收到的现金", item.receive_invest_cash);
+            insert_value(&mut items_map, "取得借款收到的现金", item.receive_loan_cash);
+            insert_value(&mut items_map, "偿还债务支付的现金", item.repay_debt_cash);
+            insert_value(&mut items_map, "分配股利、利润或偿付利息支付的现金", item.distribute_dividend_cash);
+            insert_value(&mut items_map, "支付其他与筹资活动有关的现金", item.pay_other_finance_cash);
+
+            statements.push(self.classifier.build_cashflow_statement(stock_code, report_date, items_map));
         }
 
         Ok(statements)
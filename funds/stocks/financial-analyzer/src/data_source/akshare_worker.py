@@ -0,0 +1,66 @@
+# AKShare worker: executes scripts sent by the Rust client in a long-lived interpreter.
+#
+# Protocol (one JSON object per line):
+#   request:  {"id": 1, "method": "run", "script": "..."} | {"id": 2, "method": "ping"}
+#   response: {"id": 1, "ok": true, "output": "..."}      | {"id": 1, "ok": false, "error": "..."}
+#
+# Scripts print their result; stdout is captured per request. Anything else written to
+# stdout (e.g. by libraries) is redirected to stderr so it cannot corrupt the protocol.
+
+import contextlib
+import io
+import json
+import sys
+import traceback
+
+for _stream in (sys.stdin, sys.stdout, sys.stderr):
+    if hasattr(_stream, "reconfigure"):
+        _stream.reconfigure(encoding="utf-8")
+
+_protocol = sys.stdout
+sys.stdout = sys.stderr
+
+try:
+    import akshare
+
+    _akshare_version = getattr(akshare, "__version__", "unknown")
+    _akshare_error = None
+except Exception:
+    _akshare_version = None
+    _akshare_error = traceback.format_exc()
+
+
+def _reply(**message):
+    _protocol.write(json.dumps(message) + "\n")
+    _protocol.flush()
+
+
+for _line in sys.stdin:
+    _line = _line.strip()
+    if not _line:
+        continue
+
+    try:
+        _request = json.loads(_line)
+    except ValueError as e:
+        _reply(id=None, ok=False, error="invalid request: %s" % e)
+        continue
+
+    _id = _request.get("id")
+    _method = _request.get("method")
+
+    if _method == "ping":
+        if _akshare_error is None:
+            _reply(id=_id, ok=True, output=_akshare_version)
+        else:
+            _reply(id=_id, ok=False, error=_akshare_error)
+    elif _method == "run":
+        _buffer = io.StringIO()
+        try:
+            with contextlib.redirect_stdout(_buffer):
+                exec(_request["script"], {"__name__": "__akshare_job__"})
+            _reply(id=_id, ok=True, output=_buffer.getvalue())
+        except BaseException:
+            _reply(id=_id, ok=False, error=traceback.format_exc())
+    else:
+        _reply(id=_id, ok=False, error="unknown method: %s" % _method)
@@ -0,0 +1,332 @@
+//! AKShare 常驻 Python 进程
+//!
+//! 每个 `AkshareClient` 首次请求时启动一个 Python 进程（预先导入 akshare），之后所有脚本
+//! 都通过 stdin/stdout 上按行分隔的 JSON 发送给该进程执行，避免每张报表都重新启动解释器。
+//! 请求带递增 ID；超时后终止进程，进程意外退出时自动重启并重试一次。
+//! 管道读写是阻塞的，在 `spawn_blocking` 线程上执行，不占用异步运行时的工作线程。
+//! 标准错误输出由后台线程持续读取，只保留最后若干行，进程退出、超时或健康检查失败时附在错误信息中。
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Python 端脚本
+const WORKER_SCRIPT: &str = include_str!("akshare_worker.py");
+
+/// 保留的标准错误输出行数
+const STDERR_TAIL_LINES: usize = 20;
+/// 单行保留的最大字符数
+const STDERR_LINE_CHARS: usize = 500;
+
+#[derive(Debug, Serialize)]
+struct WorkerRequest<'a> {
+    id: u64,
+    method: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    script: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkerResponse {
+    id: Option<u64>,
+    ok: bool,
+    #[serde(default)]
+    output: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// 单次请求失败的类型
+enum WorkerFailure {
+    /// 进程已退出或管道断开，可重启后重试
+    Crashed(anyhow::Error),
+    /// 等待响应超时
+    Timeout,
+}
+
+/// 常驻 Python 进程（按需启动）
+pub struct PythonWorker {
+    state: Arc<WorkerState>,
+}
+
+impl PythonWorker {
+    pub fn new(python_path: String, startup_timeout: Duration, request_timeout: Duration) -> Self {
+        Self {
+            state: Arc::new(WorkerState {
+                python_path,
+                startup_timeout,
+                request_timeout,
+                process: Mutex::new(None),
+                next_id: AtomicU64::new(1),
+            }),
+        }
+    }
+
+    /// 执行脚本并返回其标准输出
+    pub async fn run(&self, script: &str) -> Result<String> {
+        let state = Arc::clone(&self.state);
+        let script = script.to_string();
+        tokio::task::spawn_blocking(move || state.call("run", Some(&script), state.request_timeout))
+            .await
+            .context("AKShare 请求线程异常退出")?
+    }
+}
+
+/// 进程状态，由阻塞线程共享
+struct WorkerState {
+    python_path: String,
+    startup_timeout: Duration,
+    request_timeout: Duration,
+    process: Mutex<Option<WorkerProcess>>,
+    next_id: AtomicU64,
+}
+
+impl WorkerState {
+    fn call(&self, method: &str, script: Option<&str>, timeout: Duration) -> Result<String> {
+        let mut guard = self.process.lock().unwrap_or_else(|e| e.into_inner());
+
+        for attempt in 0..2 {
+            let alive = guard.as_mut().is_some_and(|process| process.is_alive());
+            if !alive {
+                if guard.is_some() {
+                    tracing::warn!("AKShare Python 进程已退出，正在重启");
+                }
+                *guard = Some(self.spawn()?);
+            }
+            let process = guard.as_mut().expect("进程已启动");
+
+            let request = WorkerRequest {
+                id: self.next_id.fetch_add(1, Ordering::Relaxed),
+                method,
+                script,
+            };
+
+            match process.request(&request, timeout) {
+                Ok(response) if response.ok => return Ok(response.output.unwrap_or_default()),
+                Ok(response) => return Err(script_error(&response.error.unwrap_or_default())),
+                Err(WorkerFailure::Timeout) => {
+                    let error = process.exit_error(anyhow!(
+                        "AKShare 请求超时（{}秒），已终止 Python 进程，下次请求时重新启动",
+                        timeout.as_secs()
+                    ));
+                    *guard = None;
+                    return Err(error);
+                }
+                Err(WorkerFailure::Crashed(e)) => {
+                    let e = process.exit_error(e);
+                    *guard = None;
+                    if attempt > 0 {
+                        return Err(e.context("AKShare Python 进程重启后仍然退出"));
+                    }
+                    tracing::warn!("AKShare Python 进程异常退出，重启后重试: {}", e);
+                }
+            }
+        }
+
+        unreachable!("重试次数已用尽")
+    }
+
+    /// 启动进程并等待其就绪
+    fn spawn(&self) -> Result<WorkerProcess> {
+        let started = Instant::now();
+        let mut child = Command::new(&self.python_path)
+            .arg("-u")
+            .arg("-c")
+            .arg(WORKER_SCRIPT)
+            .env("PYTHONIOENCODING", "utf-8")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("执行Python失败，请确保已安装Python3和akshare库 ({} install akshare)", pip()))?;
+
+        let stdin = child.stdin.take().context("无法获取 Python 进程的标准输入")?;
+        let stdout = child.stdout.take().context("无法获取 Python 进程的标准输出")?;
+        let stderr = StderrTail::capture(child.stderr.take().context("无法获取 Python 进程的标准错误输出")?);
+
+        let (sender, responses) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                match serde_json::from_str::<WorkerResponse>(&line) {
+                    Ok(response) => {
+                        if sender.send(response).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => tracing::debug!("忽略 AKShare 进程输出: {}", line),
+                }
+            }
+        });
+
+        let mut process = WorkerProcess { child, stdin, responses, stderr };
+
+        // 就绪握手：导入 akshare 完成后才会响应，akshare 是否可用不影响握手结果
+        let ping = WorkerRequest {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            method: "ping",
+            script: None,
+        };
+        match process.request(&ping, self.startup_timeout) {
+            Ok(response) => {
+                tracing::debug!("AKShare Python 进程已启动，耗时 {:?}", started.elapsed());
+                if response.ok {
+                    tracing::info!("AKShare 已就绪 (akshare {})", response.output.unwrap_or_default());
+                } else {
+                    let error = process.with_stderr(script_error(&response.error.unwrap_or_default()));
+                    tracing::warn!("AKShare健康检查失败: {:#}", error);
+                }
+                Ok(process)
+            }
+            Err(WorkerFailure::Timeout) => Err(process.exit_error(anyhow!(
+                "AKShare Python 进程启动超时（{}秒）",
+                self.startup_timeout.as_secs()
+            ))),
+            Err(WorkerFailure::Crashed(e)) => Err(process.exit_error(e).context("AKShare Python 进程启动失败")),
+        }
+    }
+}
+
+/// 运行中的 Python 进程
+struct WorkerProcess {
+    child: Child,
+    stdin: ChildStdin,
+    responses: Receiver<WorkerResponse>,
+    stderr: Arc<StderrTail>,
+}
+
+impl WorkerProcess {
+    fn is_alive(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// 终止进程，等标准错误输出读完后附在错误信息中
+    fn exit_error(&mut self, error: anyhow::Error) -> anyhow::Error {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        self.stderr.wait_closed(Duration::from_millis(500));
+        self.with_stderr(error)
+    }
+
+    /// 在错误信息后附上最近的标准错误输出
+    fn with_stderr(&self, error: anyhow::Error) -> anyhow::Error {
+        match self.stderr.tail() {
+            Some(tail) => anyhow!("{:#}\nPython 标准错误输出（最后 {} 行）:\n{}", error, tail.lines().count(), tail),
+            None => error,
+        }
+    }
+
+    /// 发送请求并等待 ID 相同的响应
+    fn request(&mut self, request: &WorkerRequest, timeout: Duration) -> std::result::Result<WorkerResponse, WorkerFailure> {
+        let mut line = serde_json::to_string(request).map_err(|e| WorkerFailure::Crashed(e.into()))?;
+        line.push('\n');
+        self.stdin
+            .write_all(line.as_bytes())
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| WorkerFailure::Crashed(anyhow!("写入 Python 进程失败: {}", e)))?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.responses.recv_timeout(remaining) {
+                Ok(response) if response.id == Some(request.id) => return Ok(response),
+                Ok(response) => tracing::debug!("丢弃过期的 AKShare 响应: {:?}", response.id),
+                Err(RecvTimeoutError::Timeout) => return Err(WorkerFailure::Timeout),
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(WorkerFailure::Crashed(anyhow!("Python 进程意外退出")))
+                }
+            }
+        }
+    }
+}
+
+impl Drop for WorkerProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// 标准错误输出的最后若干行
+struct StderrTail {
+    lines: Mutex<VecDeque<String>>,
+    closed: AtomicBool,
+}
+
+impl StderrTail {
+    /// 启动后台线程持续读取，避免管道写满后阻塞 Python 进程
+    fn capture(stderr: impl Read + Send + 'static) -> Arc<Self> {
+        let tail = Arc::new(Self {
+            lines: Mutex::new(VecDeque::with_capacity(STDERR_TAIL_LINES)),
+            closed: AtomicBool::new(false),
+        });
+        let writer = Arc::clone(&tail);
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(stderr);
+            let mut buffer = Vec::new();
+            while matches!(reader.read_until(b'\n', &mut buffer), Ok(n) if n > 0) {
+                let line = String::from_utf8_lossy(&buffer);
+                let line: String = line.trim_end().chars().take(STDERR_LINE_CHARS).collect();
+                buffer.clear();
+                if line.is_empty() {
+                    continue;
+                }
+                tracing::debug!("AKShare 进程: {}", line);
+                let mut lines = writer.lines.lock().unwrap_or_else(|e| e.into_inner());
+                if lines.len() == STDERR_TAIL_LINES {
+                    lines.pop_front();
+                }
+                lines.push_back(line);
+            }
+            writer.closed.store(true, Ordering::Release);
+        });
+        tail
+    }
+
+    /// 等待输出读完（进程退出后管道随即关闭），最多等待 `timeout`
+    fn wait_closed(&self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        while !self.closed.load(Ordering::Acquire) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn tail(&self) -> Option<String> {
+        let lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        (!lines.is_empty()).then(|| lines.iter().cloned().collect::<Vec<_>>().join("\n"))
+    }
+}
+
+fn pip() -> &'static str {
+    if cfg!(windows) {
+        "pip"
+    } else {
+        "pip3"
+    }
+}
+
+/// 将 Python 端的异常转换为错误，依赖缺失时附带解决方案
+fn script_error(error: &str) -> anyhow::Error {
+    if error.contains("lxml") || error.contains("FeatureNotFound") {
+        return anyhow!(
+            "AKShare依赖错误: 请安装完整依赖\n\
+            解决方案:\n\
+            1. {pip} install beautifulsoup4 lxml html5lib\n\
+            2. 或使用: {pip} install --upgrade akshare beautifulsoup4 lxml\n\
+            \n原始错误: {error}",
+            pip = pip(),
+            error = error
+        );
+    }
+    if error.contains("No module named 'akshare'") {
+        return anyhow!("未安装akshare库 ({} install akshare)", pip());
+    }
+
+    anyhow!("Python脚本执行错误: {}", error)
+}
@@ -0,0 +1,282 @@
+//! 本地磁盘缓存：按 数据源/股票代码/报表类型/日期区间 缓存报表数据
+//!
+//! 股票代码按 `stock_key` 统一，`600519`、`sh600519` 和 `600519.SH` 共用同一份缓存。
+
+use super::traits::DataSource;
+use crate::domain::*;
+use crate::utils::CacheConfig;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 缓存文件内容
+#[derive(Serialize, Deserialize)]
+struct CacheFile<T> {
+    #[serde(flatten)]
+    entry: CacheEntry,
+    data: T,
+}
+
+/// 缓存条目元信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub source: String,
+    pub stock_code: String,
+    pub report_type: ReportType,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub cached_at: DateTime<Utc>,
+}
+
+/// 磁盘缓存存储
+#[derive(Debug, Clone)]
+pub struct CacheStore {
+    dir: PathBuf,
+    ttl_secs: u64,
+}
+
+impl CacheStore {
+    pub fn new(dir: impl Into<PathBuf>, ttl_secs: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl_secs,
+        }
+    }
+
+    pub fn from_config(config: &CacheConfig) -> Self {
+        Self::new(&config.dir, config.ttl_secs)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path_for(
+        &self,
+        source: &str,
+        stock_code: &str,
+        report_type: ReportType,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> PathBuf {
+        self.dir.join(source).join(stock_key(stock_code)).join(format!(
+            "{}_{}_{}.json",
+            report_type.as_str(),
+            start_date.format("%Y%m%d"),
+            end_date.format("%Y%m%d")
+        ))
+    }
+
+    /// 条目是否已过期
+    pub fn is_expired(&self, entry: &CacheEntry) -> bool {
+        let age = Utc::now().signed_duration_since(entry.cached_at);
+        age.num_seconds() < 0 || age.num_seconds() as u64 >= self.ttl_secs
+    }
+
+    /// 读取未过期的缓存，不存在、已过期或无法解析时返回 None
+    pub fn get<T: DeserializeOwned>(
+        &self,
+        source: &str,
+        stock_code: &str,
+        report_type: ReportType,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Option<T> {
+        let path = self.path_for(source, stock_code, report_type, start_date, end_date);
+        let content = std::fs::read_to_string(&path).ok()?;
+
+        match serde_json::from_str::<CacheFile<T>>(&content) {
+            Ok(file) if !self.is_expired(&file.entry) => Some(file.data),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!("缓存文件损坏，忽略: {} ({})", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// 写入缓存
+    pub fn put<T: Serialize>(
+        &self,
+        source: &str,
+        stock_code: &str,
+        report_type: ReportType,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        data: &T,
+    ) -> Result<()> {
+        let path = self.path_for(source, stock_code, report_type, start_date, end_date);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = CacheFile {
+            entry: CacheEntry {
+                source: source.to_string(),
+                stock_code: stock_key(stock_code),
+                report_type,
+                start_date,
+                end_date,
+                cached_at: Utc::now(),
+            },
+            data,
+        };
+        std::fs::write(&path, serde_json::to_string(&file)?)?;
+
+        Ok(())
+    }
+
+    /// 列出全部缓存条目
+    pub fn list(&self) -> Result<Vec<CacheEntry>> {
+        let mut entries = Vec::new();
+        for path in self.files()? {
+            let content = std::fs::read_to_string(&path)?;
+            match serde_json::from_str::<CacheFile<serde::de::IgnoredAny>>(&content) {
+                Ok(file) => entries.push(file.entry),
+                Err(e) => tracing::warn!("缓存文件损坏，跳过: {} ({})", path.display(), e),
+            }
+        }
+
+        entries.sort_by(|a, b| {
+            (&a.source, &a.stock_code, a.report_type.as_str(), a.start_date)
+                .cmp(&(&b.source, &b.stock_code, b.report_type.as_str(), b.start_date))
+        });
+        Ok(entries)
+    }
+
+    /// 清除缓存，可按股票代码过滤，返回删除的文件数
+    pub fn clear(&self, stock_code: Option<&str>) -> Result<usize> {
+        let key = stock_code.map(stock_key);
+        let mut removed = 0;
+        for path in self.files()? {
+            let matches = match &key {
+                Some(code) => path
+                    .parent()
+                    .and_then(|p| p.file_name())
+                    .is_some_and(|name| name == code.as_str()),
+                None => true,
+            };
+            if matches {
+                std::fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// 缓存目录下的所有缓存文件（source/stock_code/*.json）
+    fn files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        if !self.dir.exists() {
+            return Ok(files);
+        }
+
+        for source_dir in std::fs::read_dir(&self.dir)? {
+            let source_dir = source_dir?.path();
+            if !source_dir.is_dir() {
+                continue;
+            }
+            for stock_dir in std::fs::read_dir(&source_dir)? {
+                let stock_dir = stock_dir?.path();
+                if !stock_dir.is_dir() {
+                    continue;
+                }
+                for file in std::fs::read_dir(&stock_dir)? {
+                    let file = file?.path();
+                    if file.extension().is_some_and(|ext| ext == "json") {
+                        files.push(file);
+                    }
+                }
+            }
+        }
+
+        Ok(files)
+    }
+}
+
+/// 带磁盘缓存的数据源包装器
+pub struct CachedDataSource {
+    inner: Box<dyn DataSource>,
+    store: CacheStore,
+}
+
+impl CachedDataSource {
+    pub fn new(inner: Box<dyn DataSource>, store: CacheStore) -> Self {
+        Self { inner, store }
+    }
+
+    fn store_result<T: Serialize>(
+        &self,
+        stock_code: &str,
+        report_type: ReportType,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        data: &T,
+    ) {
+        // 缓存写入失败不影响分析流程
+        if let Err(e) = self.store.put(self.inner.name(), stock_code, report_type, start_date, end_date, data) {
+            tracing::warn!("写入缓存失败: {}", e);
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for CachedDataSource {
+    async fn fetch_balance_sheet(
+        &self,
+        stock_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<BalanceSheet>> {
+        let report_type = ReportType::BalanceSheet;
+        if let Some(cached) = self.store.get(self.inner.name(), stock_code, report_type, start_date, end_date) {
+            tracing::info!("命中缓存: {} {}", stock_code, report_type.as_str());
+            return Ok(cached);
+        }
+
+        let data = self.inner.fetch_balance_sheet(stock_code, start_date, end_date).await?;
+        self.store_result(stock_code, report_type, start_date, end_date, &data);
+        Ok(data)
+    }
+
+    async fn fetch_income_statement(
+        &self,
+        stock_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<IncomeStatement>> {
+        let report_type = ReportType::IncomeStatement;
+        if let Some(cached) = self.store.get(self.inner.name(), stock_code, report_type, start_date, end_date) {
+            tracing::info!("命中缓存: {} {}", stock_code, report_type.as_str());
+            return Ok(cached);
+        }
+
+        let data = self.inner.fetch_income_statement(stock_code, start_date, end_date).await?;
+        self.store_result(stock_code, report_type, start_date, end_date, &data);
+        Ok(data)
+    }
+
+    async fn fetch_cashflow_statement(
+        &self,
+        stock_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CashflowStatement>> {
+        let report_type = ReportType::CashflowStatement;
+        if let Some(cached) = self.store.get(self.inner.name(), stock_code, report_type, start_date, end_date) {
+            tracing::info!("命中缓存: {} {}", stock_code, report_type.as_str());
+            return Ok(cached);
+        }
+
+        let data = self.inner.fetch_cashflow_statement(stock_code, start_date, end_date).await?;
+        self.store_result(stock_code, report_type, start_date, end_date, &data);
+        Ok(data)
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
@@ -0,0 +1,270 @@
+//! 科目分类引擎：按 account_mapping.toml 把各数据源的原始科目归入经营性/金融性资产与负债，
+//! 并把利润表、现金流量表的科目归一为标准科目名后计算营业收入、自由现金流等字段
+
+use crate::domain::*;
+use crate::utils::AccountMapping;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+
+/// 分类结果
+#[derive(Debug, Clone)]
+pub struct Classification {
+    pub operating_assets: AssetGroup,
+    pub financial_assets: AssetGroup,
+    pub operating_liabilities: LiabilityGroup,
+    pub financial_liabilities: LiabilityGroup,
+    /// 金额非零但未在映射中配置的科目
+    pub unmapped: Vec<String>,
+}
+
+/// 科目分类器
+#[derive(Debug, Clone)]
+pub struct AccountClassifier {
+    mapping: AccountMapping,
+    /// 所有已配置的别名（含标准科目名）
+    known: HashSet<String>,
+}
+
+impl AccountClassifier {
+    pub fn new(mapping: AccountMapping) -> Self {
+        let known = Self::sections(&mapping)
+            .into_iter()
+            .flat_map(|section| section.iter())
+            .flat_map(|(canonical, aliases)| {
+                std::iter::once(canonical.clone()).chain(aliases.iter().cloned())
+            })
+            .collect();
+
+        Self { mapping, known }
+    }
+
+    fn sections(mapping: &AccountMapping) -> [&HashMap<String, Vec<String>>; 5] {
+        [
+            &mapping.operating_assets,
+            &mapping.financial_assets,
+            &mapping.operating_liabilities,
+            &mapping.financial_liabilities,
+            &mapping.other_accounts,
+        ]
+    }
+
+    /// 按优先级查找标准科目的取值：先标准名，再依次尝试别名
+    ///
+    /// 命中合并列示的别名时，扣除报表中单独列示的其他组成科目，避免重复计数。
+    fn resolve(&self, items: &HashMap<String, Decimal>, canonical: &str, aliases: &[String]) -> Option<Decimal> {
+        let (name, value) = Self::first_match(items, canonical, aliases)?;
+        let Some(components) = self.mapping.combined_accounts.get(name) else {
+            return Some(value);
+        };
+        let separate: Decimal = components
+            .iter()
+            .filter(|component| component.as_str() != canonical)
+            .filter_map(|component| self.resolve_separate(items, component))
+            .sum();
+        Some(value - separate)
+    }
+
+    fn first_match<'a>(
+        items: &HashMap<String, Decimal>,
+        canonical: &'a str,
+        aliases: &'a [String],
+    ) -> Option<(&'a str, Decimal)> {
+        std::iter::once(canonical)
+            .chain(aliases.iter().map(String::as_str))
+            .find_map(|name| items.get(name).map(|value| (name, *value)))
+    }
+
+    /// 组成科目单独列示的金额（不含合并列示的别名）
+    fn resolve_separate(&self, items: &HashMap<String, Decimal>, canonical: &str) -> Option<Decimal> {
+        let aliases = Self::sections(&self.mapping)
+            .into_iter()
+            .find_map(|section| section.get(canonical))?;
+        std::iter::once(canonical)
+            .chain(aliases.iter().map(String::as_str))
+            .filter(|name| !self.mapping.combined_accounts.contains_key(*name))
+            .find_map(|name| items.get(name).copied())
+    }
+
+    /// 补齐标准科目名，原始科目保留不动
+    pub fn normalize(&self, items: &HashMap<String, Decimal>) -> HashMap<String, Decimal> {
+        self.normalize_with(items, Self::sections(&self.mapping))
+    }
+
+    fn normalize_with<'a>(
+        &self,
+        items: &HashMap<String, Decimal>,
+        sections: impl IntoIterator<Item = &'a HashMap<String, Vec<String>>>,
+    ) -> HashMap<String, Decimal> {
+        let mut normalized = items.clone();
+
+        for section in sections {
+            for (canonical, aliases) in section {
+                if normalized.contains_key(canonical) {
+                    continue;
+                }
+                if let Some(value) = self.resolve(items, canonical, aliases) {
+                    normalized.insert(canonical.clone(), value);
+                }
+            }
+        }
+
+        normalized
+    }
+
+    /// 按映射把科目归入四个分组
+    pub fn classify(&self, items: &HashMap<String, Decimal>) -> Classification {
+        let collect = |section: &HashMap<String, Vec<String>>| {
+            let mut found: Vec<(String, Decimal)> = section
+                .iter()
+                .filter_map(|(canonical, aliases)| {
+                    self.resolve(items, canonical, aliases).map(|v| (canonical.clone(), v))
+                })
+                .collect();
+            found.sort_by(|a, b| a.0.cmp(&b.0));
+            found
+        };
+
+        let mut operating_assets = AssetGroup::new();
+        for (name, value) in collect(&self.mapping.operating_assets) {
+            operating_assets.add(name, value);
+        }
+
+        let mut financial_assets = AssetGroup::new();
+        for (name, value) in collect(&self.mapping.financial_assets) {
+            financial_assets.add(name, value);
+        }
+
+        let mut operating_liabilities = LiabilityGroup::new();
+        for (name, value) in collect(&self.mapping.operating_liabilities) {
+            operating_liabilities.add(name, value);
+        }
+
+        let mut financial_liabilities = LiabilityGroup::new();
+        for (name, value) in collect(&self.mapping.financial_liabilities) {
+            financial_liabilities.add(name, value);
+        }
+
+        let mut unmapped: Vec<String> = items
+            .iter()
+            .filter(|(name, value)| !value.is_zero() && !self.known.contains(*name))
+            .map(|(name, _)| name.clone())
+            .collect();
+        unmapped.sort();
+
+        Classification {
+            operating_assets,
+            financial_assets,
+            operating_liabilities,
+            financial_liabilities,
+            unmapped,
+        }
+    }
+
+    /// 由原始科目构建资产负债表，并报告未映射科目
+    pub fn build_balance_sheet(
+        &self,
+        stock_code: &str,
+        report_date: NaiveDate,
+        items: HashMap<String, Decimal>,
+    ) -> BalanceSheet {
+        let classification = self.classify(&items);
+
+        if !classification.unmapped.is_empty() {
+            tracing::warn!(
+                "{} {} 有 {} 个科目未在科目映射中配置: {}",
+                stock_code,
+                report_date,
+                classification.unmapped.len(),
+                classification.unmapped.join(", ")
+            );
+        }
+
+        let statement = FinancialStatement {
+            stock_code: stock_code.to_string(),
+            report_date,
+            report_type: ReportType::BalanceSheet,
+            items: self.normalize(&items),
+            sources: HashMap::new(),
+        };
+
+        BalanceSheet {
+            statement,
+            operating_assets: classification.operating_assets,
+            financial_assets: classification.financial_assets,
+            operating_liabilities: classification.operating_liabilities,
+            financial_liabilities: classification.financial_liabilities,
+        }
+    }
+
+    /// 由原始科目构建利润表：补齐标准科目名后计算营业收入、毛利和净利润
+    pub fn build_income_statement(
+        &self,
+        stock_code: &str,
+        report_date: NaiveDate,
+        items: HashMap<String, Decimal>,
+    ) -> IncomeStatement {
+        let items = self.normalize_with(&items, [&self.mapping.income_statement]);
+        let revenue = first_of(&items, &["营业总收入", "营业收入"]);
+        let operating_cost = first_of(&items, &["营业总成本", "营业成本"]);
+        let main_cost = first_of(&items, &["营业成本", "营业总成本"]);
+        let net_profit = first_of(&items, &["净利润", "归属于母公司所有者的净利润"]);
+
+        IncomeStatement {
+            statement: FinancialStatement {
+                stock_code: stock_code.to_string(),
+                report_date,
+                report_type: ReportType::IncomeStatement,
+                items,
+                sources: HashMap::new(),
+            },
+            revenue,
+            operating_cost,
+            gross_profit: revenue - main_cost,
+            core_profit: net_profit,
+            net_profit,
+        }
+    }
+
+    /// 由原始科目构建现金流量表：自由现金流 = 经营活动现金流 + 投资活动现金流
+    pub fn build_cashflow_statement(
+        &self,
+        stock_code: &str,
+        report_date: NaiveDate,
+        items: HashMap<String, Decimal>,
+    ) -> CashflowStatement {
+        let items = self.normalize_with(&items, [&self.mapping.cashflow_statement]);
+        let operating_cashflow = first_of(&items, &["经营活动产生的现金流量净额"]);
+        let investing_cashflow = first_of(&items, &["投资活动产生的现金流量净额"]);
+        let financing_cashflow = first_of(&items, &["筹资活动产生的现金流量净额"]);
+
+        CashflowStatement {
+            statement: FinancialStatement {
+                stock_code: stock_code.to_string(),
+                report_date,
+                report_type: ReportType::CashflowStatement,
+                items,
+                sources: HashMap::new(),
+            },
+            operating_cashflow,
+            investing_cashflow,
+            financing_cashflow,
+            free_cashflow: operating_cashflow + investing_cashflow,
+        }
+    }
+}
+
+/// 依次取第一个存在的科目
+fn first_of(items: &HashMap<String, Decimal>, names: &[&str]) -> Decimal {
+    names
+        .iter()
+        .find_map(|name| items.get(*name))
+        .copied()
+        .unwrap_or(Decimal::ZERO)
+}
+
+impl Default for AccountClassifier {
+    fn default() -> Self {
+        Self::new(AccountMapping::builtin())
+    }
+}
@@ -0,0 +1,244 @@
+//! 组合数据源：按优先级依次查询多个数据源
+//!
+//! - 回退模式：取第一个成功返回数据的数据源，出错或无数据时尝试下一个；
+//! - 合并模式：查询全部数据源，以优先级最高的报表为基础，逐科目补充其他数据源独有的科目。
+//!
+//! 两种模式都会在 `FinancialStatement.sources` 中记录每个科目的来源。
+
+use super::classifier::AccountClassifier;
+use super::traits::DataSource;
+use crate::domain::*;
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+/// 组合数据源
+pub struct CompositeDataSource {
+    sources: Vec<Box<dyn DataSource>>,
+    merge: bool,
+    classifier: AccountClassifier,
+    name: String,
+}
+
+impl CompositeDataSource {
+    /// 按优先级（从高到低）组合数据源，默认为回退模式
+    pub fn new(sources: Vec<Box<dyn DataSource>>) -> Self {
+        let name = sources.iter().map(|s| s.name()).collect::<Vec<_>>().join(",");
+        Self {
+            sources,
+            merge: false,
+            classifier: AccountClassifier::default(),
+            name,
+        }
+    }
+
+    /// 启用逐科目合并
+    pub fn with_merge(mut self, merge: bool) -> Self {
+        self.merge = merge;
+        self.name = self
+            .sources
+            .iter()
+            .map(|s| s.name())
+            .collect::<Vec<_>>()
+            .join(if merge { "+" } else { "," });
+        self
+    }
+
+    /// 合并后重新分类资产负债表所用的科目映射
+    pub fn with_classifier(mut self, classifier: AccountClassifier) -> Self {
+        self.classifier = classifier;
+        self
+    }
+
+    /// 按优先级查询，回退模式下遇到第一个有数据的数据源即停止
+    async fn fetch<T: SourcedStatement>(
+        &self,
+        stock_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<T>> {
+        let mut results = Vec::new();
+        let mut errors = Vec::new();
+
+        for source in &self.sources {
+            match T::fetch(source.as_ref(), stock_code, start_date, end_date).await {
+                Ok(data) if data.is_empty() => {
+                    tracing::warn!("{} 未返回{}数据", source.name(), T::REPORT_TYPE.display_name());
+                    errors.push(format!("{}: 无数据", source.name()));
+                }
+                Ok(data) => {
+                    results.push((source.name().to_string(), data));
+                    if !self.merge {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("{} 获取{}失败，尝试下一个数据源: {}", source.name(), T::REPORT_TYPE.display_name(), e);
+                    errors.push(format!("{}: {}", source.name(), e));
+                }
+            }
+        }
+
+        if results.is_empty() {
+            bail!("所有数据源均未能提供{}: {}", T::REPORT_TYPE.display_name(), errors.join("; "));
+        }
+
+        Ok(merge_statements(results, &self.classifier))
+    }
+}
+
+/// 按报告日期合并各数据源的报表，结果按日期倒序
+///
+/// 每个报告期以优先级最高的数据源为基础，其他数据源只补充基础报表中缺失的科目。
+fn merge_statements<T: SourcedStatement>(results: Vec<(String, Vec<T>)>, classifier: &AccountClassifier) -> Vec<T> {
+    let mut merged: BTreeMap<NaiveDate, T> = BTreeMap::new();
+
+    for (source, statements) in results {
+        for mut incoming in statements {
+            let date = incoming.statement().report_date;
+            match merged.get_mut(&date) {
+                None => {
+                    let statement = incoming.statement_mut();
+                    statement.sources = statement.items.keys().map(|name| (name.clone(), source.clone())).collect();
+                    merged.insert(date, incoming);
+                }
+                Some(base) => {
+                    let base = base.statement_mut();
+                    for (name, value) in &incoming.statement().items {
+                        if !base.items.contains_key(name) {
+                            base.items.insert(name.clone(), *value);
+                            base.sources.insert(name.clone(), source.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    merged.into_values().rev().map(|s| s.finish(classifier)).collect()
+}
+
+/// 可被组合数据源查询和合并的报表
+#[async_trait]
+trait SourcedStatement: Sized + Send {
+    const REPORT_TYPE: ReportType;
+
+    async fn fetch(source: &dyn DataSource, stock_code: &str, start_date: NaiveDate, end_date: NaiveDate) -> Result<Vec<Self>>;
+
+    fn statement(&self) -> &FinancialStatement;
+
+    fn statement_mut(&mut self) -> &mut FinancialStatement;
+
+    /// 合并完成后按补齐的科目重新计算派生字段
+    fn finish(self, classifier: &AccountClassifier) -> Self;
+}
+
+#[async_trait]
+impl SourcedStatement for BalanceSheet {
+    const REPORT_TYPE: ReportType = ReportType::BalanceSheet;
+
+    async fn fetch(source: &dyn DataSource, stock_code: &str, start_date: NaiveDate, end_date: NaiveDate) -> Result<Vec<Self>> {
+        source.fetch_balance_sheet(stock_code, start_date, end_date).await
+    }
+
+    fn statement(&self) -> &FinancialStatement {
+        &self.statement
+    }
+
+    fn statement_mut(&mut self) -> &mut FinancialStatement {
+        &mut self.statement
+    }
+
+    /// 补充科目后需要重新划分经营性/金融性资产负债
+    fn finish(self, classifier: &AccountClassifier) -> Self {
+        let FinancialStatement { stock_code, report_date, items, sources, .. } = self.statement;
+        let mut sheet = classifier.build_balance_sheet(&stock_code, report_date, items);
+        sheet.statement.sources = sources;
+        sheet
+    }
+}
+
+#[async_trait]
+impl SourcedStatement for IncomeStatement {
+    const REPORT_TYPE: ReportType = ReportType::IncomeStatement;
+
+    async fn fetch(source: &dyn DataSource, stock_code: &str, start_date: NaiveDate, end_date: NaiveDate) -> Result<Vec<Self>> {
+        source.fetch_income_statement(stock_code, start_date, end_date).await
+    }
+
+    fn statement(&self) -> &FinancialStatement {
+        &self.statement
+    }
+
+    fn statement_mut(&mut self) -> &mut FinancialStatement {
+        &mut self.statement
+    }
+
+    /// 补充科目后需要重新计算收入、成本和利润
+    fn finish(self, classifier: &AccountClassifier) -> Self {
+        let FinancialStatement { stock_code, report_date, items, sources, .. } = self.statement;
+        let mut statement = classifier.build_income_statement(&stock_code, report_date, items);
+        statement.statement.sources = sources;
+        statement
+    }
+}
+
+#[async_trait]
+impl SourcedStatement for CashflowStatement {
+    const REPORT_TYPE: ReportType = ReportType::CashflowStatement;
+
+    async fn fetch(source: &dyn DataSource, stock_code: &str, start_date: NaiveDate, end_date: NaiveDate) -> Result<Vec<Self>> {
+        source.fetch_cashflow_statement(stock_code, start_date, end_date).await
+    }
+
+    fn statement(&self) -> &FinancialStatement {
+        &self.statement
+    }
+
+    fn statement_mut(&mut self) -> &mut FinancialStatement {
+        &mut self.statement
+    }
+
+    /// 补充科目后需要重新计算三类现金流和自由现金流
+    fn finish(self, classifier: &AccountClassifier) -> Self {
+        let FinancialStatement { stock_code, report_date, items, sources, .. } = self.statement;
+        let mut statement = classifier.build_cashflow_statement(&stock_code, report_date, items);
+        statement.statement.sources = sources;
+        statement
+    }
+}
+
+#[async_trait]
+impl DataSource for CompositeDataSource {
+    async fn fetch_balance_sheet(
+        &self,
+        stock_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<BalanceSheet>> {
+        self.fetch(stock_code, start_date, end_date).await
+    }
+
+    async fn fetch_income_statement(
+        &self,
+        stock_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<IncomeStatement>> {
+        self.fetch(stock_code, start_date, end_date).await
+    }
+
+    async fn fetch_cashflow_statement(
+        &self,
+        stock_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CashflowStatement>> {
+        self.fetch(stock_code, start_date, end_date).await
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
@@ -0,0 +1,120 @@
+//! 按名称创建数据源（命令行和 MCP 服务共用）
+//!
+//! 创建过程不向标准输出打印，出错时返回错误而不退出进程，由调用方决定如何提示。
+
+use super::recorder::{RecordMode, Recorder};
+use super::{
+    AccountClassifier, AkshareClient, CacheStore, CachedDataSource, CompositeDataSource, DataSource, FileDataSource,
+    MockDataSource, StatementStore, StoreDataSource, TushareClient,
+};
+use crate::domain::StockCode;
+use crate::utils::{CacheConfig, DataSourcesConfig};
+use anyhow::{anyhow, bail, Context, Result};
+use std::path::Path;
+
+/// 创建数据源时的附加选项
+#[derive(Default)]
+pub struct SourceOptions<'a> {
+    /// 本地文件数据源的目录
+    pub data_dir: Option<&'a Path>,
+    /// AKShare/Tushare 原始响应的录制/回放
+    pub recorder: Option<Recorder>,
+}
+
+/// 根据名称创建数据源
+///
+/// `akshare,tushare` 按顺序回退，`akshare+tushare` 逐科目合并。
+pub fn create_data_source(
+    source: &str,
+    options: &SourceOptions,
+    classifier: AccountClassifier,
+    config: &DataSourcesConfig,
+) -> Result<Box<dyn DataSource>> {
+    let merge = source.contains('+');
+    if merge || source.contains(',') {
+        let sources = source
+            .split(['+', ','])
+            .map(|name| create_data_source(name.trim(), options, classifier.clone(), config))
+            .collect::<Result<Vec<_>>>()?;
+        let composite = CompositeDataSource::new(sources)
+            .with_merge(merge)
+            .with_classifier(classifier);
+        return Ok(Box::new(composite));
+    }
+
+    let replay = options.recorder.as_ref().is_some_and(|r| r.mode() == RecordMode::Replay);
+    let data_source: Box<dyn DataSource> = match source {
+        "mock" => Box::new(MockDataSource::new().with_classifier(classifier)),
+        "tushare" => {
+            // 回放时不需要真实Token
+            let client = if replay {
+                TushareClient::new(String::new(), &config.tushare)
+            } else {
+                TushareClient::from_env(&config.tushare)
+            }
+            .context("Tushare初始化失败（请设置环境变量: export TUSHARE_TOKEN=your_token）")?
+            .with_classifier(classifier);
+            match &options.recorder {
+                Some(recorder) => Box::new(client.with_recorder(recorder.clone())),
+                None => Box::new(client),
+            }
+        }
+        "akshare" => {
+            // 常驻Python进程在首次未命中缓存的请求时才启动
+            let client = AkshareClient::from_config(&config.akshare).with_classifier(classifier);
+            match &options.recorder {
+                Some(recorder) => Box::new(client.with_recorder(recorder.clone())),
+                None => Box::new(client),
+            }
+        }
+        "store" => {
+            let store = StatementStore::from_config(&config.store)
+                .with_context(|| format!("无法打开本地报表库 {}", config.store.path))?;
+            Box::new(StoreDataSource::new(store))
+        }
+        "file" => {
+            let dir = options.data_dir.ok_or_else(|| anyhow!("使用本地文件数据源需要指定 --data-dir"))?;
+            Box::new(FileDataSource::new(dir).with_classifier(classifier))
+        }
+        _ => bail!("不支持的数据源: {}（当前支持: mock, tushare, akshare, file, store）", source),
+    };
+
+    Ok(data_source)
+}
+
+/// 检查股票代码能否由指定数据源查询，返回时不改写代码
+///
+/// Tushare、AKShare 和 Mock 只处理上市公司，需要可识别的交易所代码；本地文件和报表库
+/// 按目录名/键名查找，接受任意非空标识（如未上市公司），并保留原写法。
+/// 组合数据源只要有一个成员可以处理即可。
+pub fn validate_stock_code(source: &str, stock: &str) -> Result<()> {
+    if stock.trim().is_empty() {
+        bail!("股票代码不能为空");
+    }
+
+    let mut errors = Vec::new();
+    for name in source.split(['+', ',']).map(str::trim) {
+        match name {
+            "tushare" | "akshare" | "mock" => match stock.parse::<StockCode>() {
+                Ok(_) => return Ok(()),
+                Err(e) => errors.push(format!("{}: {}", name, e)),
+            },
+            _ => return Ok(()),
+        }
+    }
+    bail!("{}", errors.join("；"))
+}
+
+/// 是否适合加缓存：本地文件和报表库本身就是离线数据，缓存只会掩盖对它们的修改；
+/// 录制/回放需要每次都经过真实的数据源
+pub fn cacheable(source: &str, options: &SourceOptions) -> bool {
+    options.recorder.is_none() && !matches!(source, "file" | "store")
+}
+
+/// 按配置为数据源加上磁盘缓存
+pub fn with_cache(data_source: Box<dyn DataSource>, cache_config: &CacheConfig) -> Box<dyn DataSource> {
+    if !cache_config.enabled {
+        return data_source;
+    }
+    Box::new(CachedDataSource::new(data_source, CacheStore::from_config(cache_config)))
+}
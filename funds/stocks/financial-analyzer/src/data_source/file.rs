@@ -0,0 +1,228 @@
+//! 本地文件数据源：从目录读取 CSV / JSON 格式的报表
+//!
+//! 每种报表一个文件，文件名为报表类型（balance_sheet / income_statement / cashflow_statement），
+//! 优先读取 `数据目录/股票代码/` 子目录，不存在时直接读取数据目录。
+//!
+//! CSV：首行为表头，第一列为科目名称，其余各列为报告日期；
+//! ```text
+//! 项目,2023-12-31,2022-12-31
+//! 营业收入,150560000000,127554000000
+//! ```
+//! JSON：报告日期到科目数值的映射；
+//! ```text
+//! {"2023-12-31": {"营业收入": 150560000000, "净利润": 74734000000}}
+//! ```
+//! 报告日期支持 `2023-12-31`、`20231231` 和 `2023`（视为年报），空值和 `--` 视为缺失。
+
+use super::classifier::AccountClassifier;
+use super::traits::DataSource;
+use crate::domain::*;
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// 按报告日期组织的科目数值
+type DatedItems = BTreeMap<NaiveDate, HashMap<String, Decimal>>;
+
+/// 本地文件数据源
+pub struct FileDataSource {
+    data_dir: PathBuf,
+    classifier: AccountClassifier,
+}
+
+impl FileDataSource {
+    pub fn new(data_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            data_dir: data_dir.into(),
+            classifier: AccountClassifier::default(),
+        }
+    }
+
+    pub fn with_classifier(mut self, classifier: AccountClassifier) -> Self {
+        self.classifier = classifier;
+        self
+    }
+
+    /// 查找报表文件：股票子目录优先，CSV 优先于 JSON
+    fn statement_file(&self, stock_code: &str, report_type: ReportType) -> Result<PathBuf> {
+        let stock_dir = self.data_dir.join(stock_code);
+        let dirs = [stock_dir.as_path(), self.data_dir.as_path()];
+
+        for dir in dirs {
+            for ext in ["csv", "json"] {
+                let path = dir.join(format!("{}.{}", report_type.as_str(), ext));
+                if path.is_file() {
+                    return Ok(path);
+                }
+            }
+        }
+
+        bail!(
+            "未找到{}文件: {}/{}.csv|json",
+            report_type.display_name(),
+            self.data_dir.display(),
+            report_type.as_str()
+        )
+    }
+
+    /// 读取报表文件并按日期区间过滤
+    fn load(
+        &self,
+        stock_code: &str,
+        report_type: ReportType,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<DatedItems> {
+        let path = self.statement_file(stock_code, report_type)?;
+        tracing::info!("读取{}: {}", report_type.display_name(), path.display());
+
+        let items = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => parse_json(&path),
+            _ => parse_csv(&path),
+        }
+        .with_context(|| format!("解析文件失败: {}", path.display()))?;
+
+        Ok(items
+            .into_iter()
+            .filter(|(date, _)| *date >= start_date && *date <= end_date)
+            .collect())
+    }
+}
+
+/// 解析报告日期
+fn parse_date(text: &str) -> Result<NaiveDate> {
+    let text = text.trim();
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(text, "%Y%m%d"))
+        .ok()
+        .or_else(|| {
+            text.parse::<i32>()
+                .ok()
+                .and_then(|year| NaiveDate::from_ymd_opt(year, 12, 31))
+        })
+        .ok_or_else(|| anyhow!("无法识别的报告日期: {}", text))
+}
+
+/// 解析数值，允许千分位分隔符和科学计数法；空值返回 None
+fn parse_value(text: &str) -> Result<Option<Decimal>> {
+    let text = text.trim().replace(',', "");
+    if text.is_empty() || text == "--" || text == "-" {
+        return Ok(None);
+    }
+
+    Decimal::from_str(&text)
+        .or_else(|_| Decimal::from_scientific(&text))
+        .map(Some)
+        .map_err(|_| anyhow!("无法识别的数值: {}", text))
+}
+
+fn parse_csv(path: &Path) -> Result<DatedItems> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_path(path)?;
+
+    let dates = reader
+        .headers()?
+        .iter()
+        .skip(1)
+        .map(parse_date)
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut result: DatedItems = dates.iter().map(|date| (*date, HashMap::new())).collect();
+    for record in reader.records() {
+        let record = record?;
+        let Some(name) = record.get(0).filter(|name| !name.is_empty()) else {
+            continue;
+        };
+
+        for (date, cell) in dates.iter().zip(record.iter().skip(1)) {
+            let value = parse_value(cell).with_context(|| format!("科目: {}", name))?;
+            if let Some(value) = value {
+                result.entry(*date).or_default().insert(name.to_string(), value);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+fn parse_json(path: &Path) -> Result<DatedItems> {
+    let content = std::fs::read_to_string(path)?;
+    let raw: BTreeMap<String, HashMap<String, serde_json::Value>> = serde_json::from_str(&content)?;
+
+    let mut result = DatedItems::new();
+    for (date, accounts) in raw {
+        let date = parse_date(&date)?;
+        let items = result.entry(date).or_default();
+        for (name, value) in accounts {
+            let value = match value {
+                serde_json::Value::Null => None,
+                serde_json::Value::Number(n) => parse_value(&n.to_string())?,
+                serde_json::Value::String(s) => parse_value(&s)?,
+                other => bail!("科目 {} 的数值格式不正确: {}", name, other),
+            };
+            if let Some(value) = value {
+                items.insert(name, value);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+#[async_trait]
+impl DataSource for FileDataSource {
+    async fn fetch_balance_sheet(
+        &self,
+        stock_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<BalanceSheet>> {
+        let data = self.load(stock_code, ReportType::BalanceSheet, start_date, end_date)?;
+
+        Ok(data
+            .into_iter()
+            .rev()
+            .map(|(date, items)| self.classifier.build_balance_sheet(stock_code, date, items))
+            .collect())
+    }
+
+    async fn fetch_income_statement(
+        &self,
+        stock_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<IncomeStatement>> {
+        let data = self.load(stock_code, ReportType::IncomeStatement, start_date, end_date)?;
+
+        Ok(data
+            .into_iter()
+            .rev()
+            .map(|(date, items)| self.classifier.build_income_statement(stock_code, date, items))
+            .collect())
+    }
+
+    async fn fetch_cashflow_statement(
+        &self,
+        stock_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CashflowStatement>> {
+        let data = self.load(stock_code, ReportType::CashflowStatement, start_date, end_date)?;
+
+        Ok(data
+            .into_iter()
+            .rev()
+            .map(|(date, items)| self.classifier.build_cashflow_statement(stock_code, date, items))
+            .collect())
+    }
+
+    fn name(&self) -> &str {
+        "file"
+    }
+}
@@ -1,3 +1,4 @@
+use super::classifier::AccountClassifier;
 use super::traits::DataSource;
 use crate::domain::*;
 use anyhow::Result;
@@ -7,11 +8,20 @@ use rust_decimal::Decimal;
 use std::collections::HashMap;
 
 /// Mock数据源（用于测试）
-pub struct MockDataSource;
+pub struct MockDataSource {
+    classifier: AccountClassifier,
+}
 
 impl MockDataSource {
     pub fn new() -> Self {
-        Self
+        Self {
+            classifier: AccountClassifier::default(),
+        }
+    }
+
+    pub fn with_classifier(mut self, classifier: AccountClassifier) -> Self {
+        self.classifier = classifier;
+        self
     }
 
     fn create_mock_balance_sheet(&self, stock_code: &str, date: NaiveDate) -> BalanceSheet {
@@ -26,82 +36,81 @@ impl MockDataSource {
         items.insert("负债合计".to_string(), Decimal::new(1500000, 0));
         items.insert("所有者权益合计".to_string(), Decimal::new(2500000, 0));
 
-        let statement = FinancialStatement {
-            stock_code: stock_code.to_string(),
-            report_date: date,
-            report_type: ReportType::BalanceSheet,
-            items,
-        };
-
-        let mut operating_assets = AssetGroup::new();
-        operating_assets.add("货币资金".to_string(), Decimal::new(1000000, 0));
-        operating_assets.add("应收账款".to_string(), Decimal::new(500000, 0));
-        operating_assets.add("存货".to_string(), Decimal::new(300000, 0));
-        operating_assets.add("固定资产".to_string(), Decimal::new(2000000, 0));
-
-        let financial_assets = AssetGroup::new();
-
-        let mut operating_liabilities = LiabilityGroup::new();
-        operating_liabilities.add("应付账款".to_string(), Decimal::new(400000, 0));
+        self.classifier.build_balance_sheet(stock_code, date, items)
+    }
 
-        let mut financial_liabilities = LiabilityGroup::new();
-        financial_liabilities.add("短期借款".to_string(), Decimal::new(600000, 0));
+    /// 累计报表的比例：Q1 为全年的1/4，年报为全年
+    fn ytd_scale(date: NaiveDate) -> Decimal {
+        Decimal::from(date.month().div_ceil(3)) / Decimal::from(4)
+    }
 
-        BalanceSheet {
-            statement,
-            operating_assets,
-            financial_assets,
-            operating_liabilities,
-            financial_liabilities,
-        }
+    /// 日期区间内的全部报告期（季末）
+    fn report_dates(start_date: NaiveDate, end_date: NaiveDate) -> Vec<NaiveDate> {
+        (start_date.year()..=end_date.year())
+            .flat_map(|year| {
+                [(3, 31), (6, 30), (9, 30), (12, 31)]
+                    .into_iter()
+                    .filter_map(move |(month, day)| NaiveDate::from_ymd_opt(year, month, day))
+            })
+            .filter(|date| *date >= start_date && *date <= end_date)
+            .collect()
     }
 
     fn create_mock_income_statement(&self, stock_code: &str, date: NaiveDate) -> IncomeStatement {
+        let scale = Self::ytd_scale(date);
         let mut items = HashMap::new();
-        items.insert("营业收入".to_string(), Decimal::new(5000000, 0));
-        items.insert("营业成本".to_string(), Decimal::new(3000000, 0));
-        items.insert("税金及附加".to_string(), Decimal::new(50000, 0));
-        items.insert("销售费用".to_string(), Decimal::new(300000, 0));
-        items.insert("管理费用".to_string(), Decimal::new(200000, 0));
-        items.insert("研发费用".to_string(), Decimal::new(150000, 0));
-        items.insert("财务费用".to_string(), Decimal::new(50000, 0));
-        items.insert("营业利润".to_string(), Decimal::new(1250000, 0));
-        items.insert("净利润".to_string(), Decimal::new(1000000, 0));
+        items.insert("营业收入".to_string(), Decimal::new(5000000, 0) * scale);
+        items.insert("营业成本".to_string(), Decimal::new(3000000, 0) * scale);
+        items.insert("税金及附加".to_string(), Decimal::new(50000, 0) * scale);
+        items.insert("销售费用".to_string(), Decimal::new(300000, 0) * scale);
+        items.insert("管理费用".to_string(), Decimal::new(200000, 0) * scale);
+        items.insert("研发费用".to_string(), Decimal::new(150000, 0) * scale);
+        items.insert("财务费用".to_string(), Decimal::new(50000, 0) * scale);
+        items.insert("营业利润".to_string(), Decimal::new(1250000, 0) * scale);
+        items.insert("利润总额".to_string(), Decimal::new(1250000, 0) * scale);
+        items.insert("所得税费用".to_string(), Decimal::new(250000, 0) * scale);
+        items.insert("净利润".to_string(), Decimal::new(1000000, 0) * scale);
 
         let statement = FinancialStatement {
             stock_code: stock_code.to_string(),
             report_date: date,
             report_type: ReportType::IncomeStatement,
             items,
+            sources: HashMap::new(),
         };
 
         IncomeStatement {
             statement,
-            revenue: Decimal::new(5000000, 0),
-            operating_cost: Decimal::new(3000000, 0),
-            gross_profit: Decimal::new(2000000, 0),
-            core_profit: Decimal::new(1250000, 0),
-            net_profit: Decimal::new(1000000, 0),
+            revenue: Decimal::new(5000000, 0) * scale,
+            operating_cost: Decimal::new(3000000, 0) * scale,
+            gross_profit: Decimal::new(2000000, 0) * scale,
+            core_profit: Decimal::new(1250000, 0) * scale,
+            net_profit: Decimal::new(1000000, 0) * scale,
         }
     }
 
     fn create_mock_cashflow_statement(&self, stock_code: &str, date: NaiveDate) -> CashflowStatement {
+        let scale = Self::ytd_scale(date);
         let mut items = HashMap::new();
-        items.insert("经营活动产生的现金流量净额".to_string(), Decimal::new(900000, 0));
+        items.insert("经营活动产生的现金流量净额".to_string(), Decimal::new(900000, 0) * scale);
+        items.insert("取得借款收到的现金".to_string(), Decimal::new(200000, 0) * scale);
+        items.insert("偿还债务支付的现金".to_string(), Decimal::new(150000, 0) * scale);
+        items.insert("分配股利、利润或偿付利息支付的现金".to_string(), Decimal::new(150000, 0) * scale);
 
         let statement = FinancialStatement {
             stock_code: stock_code.to_string(),
             report_date: date,
             report_type: ReportType::CashflowStatement,
             items,
+            sources: HashMap::new(),
         };
 
         CashflowStatement {
             statement,
-            operating_cashflow: Decimal::new(900000, 0),
-            investing_cashflow: Decimal::new(-200000, 0),
-            financing_cashflow: Decimal::new(-100000, 0),
-            free_cashflow: Decimal::new(700000, 0),
+            operating_cashflow: Decimal::new(900000, 0) * scale,
+            investing_cashflow: Decimal::new(-200000, 0) * scale,
+            financing_cashflow: Decimal::new(-100000, 0) * scale,
+            free_cashflow: Decimal::new(700000, 0) * scale,
         }
     }
 }
@@ -114,15 +123,10 @@ impl DataSource for MockDataSource {
         start_date: NaiveDate,
         end_date: NaiveDate,
     ) -> Result<Vec<BalanceSheet>> {
-        let mut sheets = Vec::new();
-        let mut current = start_date;
-
-        while current <= end_date {
-            sheets.push(self.create_mock_balance_sheet(stock_code, current));
-            current = NaiveDate::from_ymd_opt(current.year() + 1, 12, 31).unwrap();
-        }
-
-        Ok(sheets)
+        Ok(Self::report_dates(start_date, end_date)
+            .into_iter()
+            .map(|date| self.create_mock_balance_sheet(stock_code, date))
+            .collect())
     }
 
     async fn fetch_income_statement(
@@ -131,15 +135,10 @@ impl DataSource for MockDataSource {
         start_date: NaiveDate,
         end_date: NaiveDate,
     ) -> Result<Vec<IncomeStatement>> {
-        let mut statements = Vec::new();
-        let mut current = start_date;
-
-        while current <= end_date {
-            statements.push(self.create_mock_income_statement(stock_code, current));
-            current = NaiveDate::from_ymd_opt(current.year() + 1, 12, 31).unwrap();
-        }
-
-        Ok(statements)
+        Ok(Self::report_dates(start_date, end_date)
+            .into_iter()
+            .map(|date| self.create_mock_income_statement(stock_code, date))
+            .collect())
     }
 
     async fn fetch_cashflow_statement(
@@ -148,15 +147,10 @@ impl DataSource for MockDataSource {
         start_date: NaiveDate,
         end_date: NaiveDate,
     ) -> Result<Vec<CashflowStatement>> {
-        let mut statements = Vec::new();
-        let mut current = start_date;
-
-        while current <= end_date {
-            statements.push(self.create_mock_cashflow_statement(stock_code, current));
-            current = NaiveDate::from_ymd_opt(current.year() + 1, 12, 31).unwrap();
-        }
-
-        Ok(statements)
+        Ok(Self::report_dates(start_date, end_date)
+            .into_iter()
+            .map(|date| self.create_mock_cashflow_statement(stock_code, date))
+            .collect())
     }
 
     fn name(&self) -> &str {
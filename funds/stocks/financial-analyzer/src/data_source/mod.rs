@@ -1,9 +1,26 @@
 pub mod traits;
+pub mod classifier;
+pub mod cache;
+pub mod rate_limit;
+pub mod recorder;
 pub mod mock;
+pub mod file;
+pub mod store;
+pub mod composite;
+pub mod factory;
 pub mod tushare;
 pub mod akshare;
+mod akshare_worker;
+#[cfg(test)]
+mod tests;
 
 pub use traits::DataSource;
+pub use classifier::AccountClassifier;
+pub use cache::{CacheStore, CachedDataSource};
 pub use mock::MockDataSource;
+pub use file::FileDataSource;
+pub use store::{StatementStore, StoreDataSource};
+pub use composite::CompositeDataSource;
+pub use recorder::Recorder;
 pub use tushare::TushareClient;
 pub use akshare::AkshareClient;
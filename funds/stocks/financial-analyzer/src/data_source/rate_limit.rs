@@ -0,0 +1,57 @@
+//! 令牌桶限流器，克隆后共享同一个桶，可在并发请求间共用
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// 令牌桶限流器
+#[derive(Clone)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<Bucket>>,
+    capacity: f64,
+    /// 每秒补充的令牌数
+    refill_per_sec: f64,
+}
+
+impl RateLimiter {
+    /// 按每分钟请求数创建，桶容量等于每分钟配额
+    pub fn per_minute(requests_per_minute: u32) -> Self {
+        let capacity = requests_per_minute.max(1) as f64;
+        Self {
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: capacity,
+                last_refill: Instant::now(),
+            })),
+            capacity,
+            refill_per_sec: capacity / 60.0,
+        }
+    }
+
+    /// 获取一个令牌，桶空时等待补充
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().await;
+                let now = Instant::now();
+                let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+                bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+                bucket.last_refill = now;
+
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - bucket.tokens) / self.refill_per_sec)
+            };
+
+            tracing::debug!("触发限流，等待 {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! 录制/回放：保存数据源的原始响应（AKShare 脚本输出、Tushare 响应 JSON），
+//! 回放时跳过 Python 和网络，原样交给同一套解析代码，用于离线的确定性测试。
+//!
+//! 夹具文件位于 `目录/数据源名称/键.json`，键由调用方根据请求参数生成。
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+/// 录制或回放
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordMode {
+    /// 正常请求，并把原始响应写入夹具目录
+    Record,
+    /// 只从夹具目录读取，不发起真实请求
+    Replay,
+}
+
+/// 原始响应录制器
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    mode: RecordMode,
+    dir: PathBuf,
+}
+
+impl Recorder {
+    pub fn record(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: RecordMode::Record,
+            dir: dir.into(),
+        }
+    }
+
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: RecordMode::Replay,
+            dir: dir.into(),
+        }
+    }
+
+    pub fn mode(&self) -> RecordMode {
+        self.mode
+    }
+
+    fn path(&self, source: &str, key: &str) -> PathBuf {
+        let file_name: String = key
+            .chars()
+            .map(|c| if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+            .collect();
+        self.dir.join(source).join(format!("{}.json", file_name))
+    }
+
+    /// 回放模式下读取夹具；录制模式返回 None，由调用方发起真实请求
+    pub fn replay_response(&self, source: &str, key: &str) -> Option<Result<String>> {
+        if self.mode != RecordMode::Replay {
+            return None;
+        }
+
+        let path = self.path(source, key);
+        tracing::debug!("回放: {}", path.display());
+        Some(
+            std::fs::read_to_string(&path)
+                .with_context(|| format!("回放夹具不存在: {}（请先用 --record 录制）", path.display())),
+        )
+    }
+
+    /// 录制模式下保存原始响应，回放模式下不做任何事
+    pub fn save(&self, source: &str, key: &str, body: &str) -> Result<()> {
+        if self.mode != RecordMode::Record {
+            return Ok(());
+        }
+
+        let path = self.path(source, key);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, body).with_context(|| format!("写入夹具失败: {}", path.display()))?;
+        tracing::info!("已录制: {}", path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,379 @@
+//! 本地报表库：用 SQLite 保存各数据源返回的报表，支持增量同步和完全离线的分析
+//!
+//! 每行一份报表（股票代码、报告日期、报表类型、来源、获取时间、科目明细及其来源），
+//! 同一报告期在不同来源下各保留一份，读取时取最近获取的一份。
+//! 股票代码列按 `stock_key` 统一，同一只股票的不同写法读写同一批报表。
+
+use super::traits::DataSource;
+use crate::analyzer::PeriodStatement;
+use crate::domain::*;
+use crate::utils::StoreConfig;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::{Datelike, NaiveDate, Utc};
+use rusqlite::{params, Connection};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS statements (
+    stock_code  TEXT NOT NULL,
+    report_date TEXT NOT NULL,
+    report_type TEXT NOT NULL,
+    source      TEXT NOT NULL,
+    fetched_at  TEXT NOT NULL,
+    items       TEXT NOT NULL,
+    metrics     TEXT NOT NULL,
+    sources     TEXT NOT NULL DEFAULT '{}',
+    PRIMARY KEY (stock_code, report_date, report_type, source)
+)";
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 库中某只股票的报表概况
+#[derive(Debug, Clone)]
+pub struct StoreSummary {
+    pub stock_code: String,
+    pub source: String,
+    pub report_type: String,
+    pub count: usize,
+    pub earliest: NaiveDate,
+    pub latest: NaiveDate,
+}
+
+/// 单只股票的同步结果
+#[derive(Debug, Clone, Default)]
+pub struct SyncReport {
+    /// 新写入的报表数
+    pub saved: usize,
+    /// 同步后仍缺失的报告期数（通常是尚未披露的报告）
+    pub missing: usize,
+}
+
+/// SQLite 报表库
+pub struct StatementStore {
+    conn: Mutex<Connection>,
+}
+
+impl StatementStore {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn from_config(config: &StoreConfig) -> Result<Self> {
+        Self::open(Path::new(&config.path))
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+
+        // 早期版本的库没有 sources 列
+        let has_sources: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('statements') WHERE name = 'sources'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_sources {
+            conn.execute_batch("ALTER TABLE statements ADD COLUMN sources TEXT NOT NULL DEFAULT '{}'")?;
+        }
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> Result<MutexGuard<'_, Connection>> {
+        self.conn.lock().map_err(|_| anyhow!("报表库连接不可用"))
+    }
+
+    /// 写入报表（同一来源的同一报告期覆盖旧记录），返回写入条数
+    ///
+    /// 科目明细单独存为 `items`，报表类型特有的汇总字段（营业收入、资产分组等）存为 `metrics`。
+    pub fn save<T: Serialize>(&self, source: &str, statements: &[T]) -> Result<usize> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        let fetched_at = Utc::now().to_rfc3339();
+
+        for statement in statements {
+            let mut metrics = serde_json::to_value(statement)?;
+            let base: FinancialStatement = metrics
+                .as_object_mut()
+                .and_then(|fields| fields.remove("statement"))
+                .map(serde_json::from_value)
+                .transpose()?
+                .ok_or_else(|| anyhow!("报表缺少基础信息"))?;
+
+            tx.execute(
+                "INSERT OR REPLACE INTO statements
+                 (stock_code, report_date, report_type, source, fetched_at, items, metrics, sources)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    stock_key(&base.stock_code),
+                    base.report_date.format(DATE_FORMAT).to_string(),
+                    base.report_type.as_str(),
+                    source,
+                    fetched_at,
+                    serde_json::to_string(&base.items)?,
+                    metrics.to_string(),
+                    serde_json::to_string(&base.sources)?,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(statements.len())
+    }
+
+    /// 读取日期区间内的报表，按报告日期倒序；同一报告期有多个来源时取最近获取的一份
+    pub fn load<T: DeserializeOwned>(
+        &self,
+        stock_code: &str,
+        report_type: ReportType,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<T>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT report_date, items, metrics, sources FROM statements
+             WHERE stock_code = ?1 AND report_type = ?2 AND report_date BETWEEN ?3 AND ?4
+             ORDER BY report_date DESC, fetched_at DESC",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                stock_key(stock_code),
+                report_type.as_str(),
+                start_date.format(DATE_FORMAT).to_string(),
+                end_date.format(DATE_FORMAT).to_string(),
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            },
+        )?;
+
+        let mut statements = Vec::new();
+        let mut last_date = None;
+        for row in rows {
+            let (date, items, metrics, sources) = row?;
+            if last_date.as_ref() == Some(&date) {
+                continue;
+            }
+
+            let statement = FinancialStatement {
+                stock_code: stock_code.to_string(),
+                report_date: NaiveDate::parse_from_str(&date, DATE_FORMAT)?,
+                report_type,
+                items: serde_json::from_str::<HashMap<String, rust_decimal::Decimal>>(&items)?,
+                sources: serde_json::from_str(&sources)?,
+            };
+            let mut value: serde_json::Value = serde_json::from_str(&metrics)?;
+            if let Some(fields) = value.as_object_mut() {
+                fields.insert("statement".to_string(), serde_json::to_value(statement)?);
+            }
+            statements.push(serde_json::from_value(value)?);
+            last_date = Some(date);
+        }
+
+        Ok(statements)
+    }
+
+    /// 某来源已入库的报告日期
+    pub fn report_dates(&self, stock_code: &str, report_type: ReportType, source: &str) -> Result<Vec<NaiveDate>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT report_date FROM statements
+             WHERE stock_code = ?1 AND report_type = ?2 AND source = ?3
+             ORDER BY report_date DESC",
+        )?;
+        let dates = stmt
+            .query_map(params![stock_key(stock_code), report_type.as_str(), source], |row| row.get::<_, String>(0))?
+            .map(|date| Ok(NaiveDate::parse_from_str(&date?, DATE_FORMAT)?))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(dates)
+    }
+
+    /// 按 股票/来源/报表类型 汇总库中数据
+    pub fn summary(&self) -> Result<Vec<StoreSummary>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT stock_code, source, report_type, COUNT(*), MIN(report_date), MAX(report_date)
+             FROM statements
+             GROUP BY stock_code, source, report_type
+             ORDER BY stock_code, source, report_type",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })?;
+
+        let mut summary = Vec::new();
+        for row in rows {
+            let (stock_code, source, report_type, count, earliest, latest) = row?;
+            summary.push(StoreSummary {
+                stock_code,
+                source,
+                report_type,
+                count: count as usize,
+                earliest: NaiveDate::parse_from_str(&earliest, DATE_FORMAT)?,
+                latest: NaiveDate::parse_from_str(&latest, DATE_FORMAT)?,
+            });
+        }
+        Ok(summary)
+    }
+
+    /// 从数据源增量同步一只股票：只请求库中缺失的报告期
+    pub async fn sync(&self, source: &dyn DataSource, stock_code: &str, expected: &[NaiveDate]) -> Result<SyncReport> {
+        let mut report = SyncReport::default();
+
+        if let Some((start, end, missing)) = self.missing_range(stock_code, ReportType::BalanceSheet, source.name(), expected)? {
+            let fetched = source.fetch_balance_sheet(stock_code, start, end).await?;
+            report.add(self.save_missing(source.name(), fetched, &missing)?, missing.len());
+        }
+        if let Some((start, end, missing)) = self.missing_range(stock_code, ReportType::IncomeStatement, source.name(), expected)? {
+            let fetched = source.fetch_income_statement(stock_code, start, end).await?;
+            report.add(self.save_missing(source.name(), fetched, &missing)?, missing.len());
+        }
+        if let Some((start, end, missing)) = self.missing_range(stock_code, ReportType::CashflowStatement, source.name(), expected)? {
+            let fetched = source.fetch_cashflow_statement(stock_code, start, end).await?;
+            report.add(self.save_missing(source.name(), fetched, &missing)?, missing.len());
+        }
+
+        Ok(report)
+    }
+
+    /// 缺失的报告期及覆盖它们的请求区间，无缺失时返回 None
+    fn missing_range(
+        &self,
+        stock_code: &str,
+        report_type: ReportType,
+        source: &str,
+        expected: &[NaiveDate],
+    ) -> Result<Option<(NaiveDate, NaiveDate, Vec<NaiveDate>)>> {
+        let stored = self.report_dates(stock_code, report_type, source)?;
+        let missing: Vec<NaiveDate> = expected.iter().filter(|date| !stored.contains(date)).copied().collect();
+
+        Ok(match (missing.iter().min(), missing.iter().max()) {
+            (Some(start), Some(end)) => Some((*start, *end, missing.clone())),
+            _ => None,
+        })
+    }
+
+    fn save_missing<T: Serialize + PeriodStatement>(&self, source: &str, fetched: Vec<T>, missing: &[NaiveDate]) -> Result<usize> {
+        let new: Vec<T> = fetched
+            .into_iter()
+            .filter(|s| missing.contains(&s.report_date()))
+            .collect();
+        self.save(source, &new)
+    }
+}
+
+impl SyncReport {
+    fn add(&mut self, saved: usize, requested: usize) {
+        self.saved += saved;
+        self.missing += requested.saturating_sub(saved);
+    }
+}
+
+/// 日期区间内应当存在的报告期：年报模式只含年末，其余模式含各季末；不含尚未到来的日期
+pub fn expected_report_dates(start_date: NaiveDate, end_date: NaiveDate, period: ReportPeriod, today: NaiveDate) -> Vec<NaiveDate> {
+    let quarter_ends: &[(u32, u32)] = match period {
+        ReportPeriod::Annual => &[(12, 31)],
+        _ => &[(3, 31), (6, 30), (9, 30), (12, 31)],
+    };
+
+    (start_date.year()..=end_date.year())
+        .flat_map(|year| {
+            quarter_ends
+                .iter()
+                .filter_map(move |(month, day)| NaiveDate::from_ymd_opt(year, *month, *day))
+        })
+        .filter(|date| *date >= start_date && *date <= end_date && *date <= today)
+        .rev()
+        .collect()
+}
+
+/// 从本地报表库读取数据的数据源，不访问网络
+pub struct StoreDataSource {
+    store: StatementStore,
+}
+
+impl StoreDataSource {
+    pub fn new(store: StatementStore) -> Self {
+        Self { store }
+    }
+
+    fn load<T: DeserializeOwned>(
+        &self,
+        stock_code: &str,
+        report_type: ReportType,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<T>> {
+        let statements = self.store.load(stock_code, report_type, start_date, end_date)?;
+        if statements.is_empty() {
+            bail!(
+                "本地报表库中没有 {} 在 {} ~ {} 的{}数据，请先运行 sync",
+                stock_code,
+                start_date,
+                end_date,
+                report_type.display_name()
+            );
+        }
+        Ok(statements)
+    }
+}
+
+#[async_trait]
+impl DataSource for StoreDataSource {
+    async fn fetch_balance_sheet(
+        &self,
+        stock_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<BalanceSheet>> {
+        self.load(stock_code, ReportType::BalanceSheet, start_date, end_date)
+    }
+
+    async fn fetch_income_statement(
+        &self,
+        stock_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<IncomeStatement>> {
+        self.load(stock_code, ReportType::IncomeStatement, start_date, end_date)
+    }
+
+    async fn fetch_cashflow_statement(
+        &self,
+        stock_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<CashflowStatement>> {
+        self.load(stock_code, ReportType::CashflowStatement, start_date, end_date)
+    }
+
+    fn name(&self) -> &str {
+        "store"
+    }
+}
@@ -0,0 +1,808 @@
+//! 数据源模块单元测试
+
+use crate::data_source::rate_limit::RateLimiter;
+use crate::data_source::store::expected_report_dates;
+use crate::data_source::tushare::backoff_delay;
+use crate::data_source::{
+    AccountClassifier, CacheStore, CachedDataSource, CompositeDataSource, DataSource, FileDataSource, MockDataSource,
+    Recorder, StatementStore,
+    StoreDataSource, TushareClient,
+};
+use crate::domain::*;
+use crate::utils::{AccountMapping, TushareConfig};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn items(pairs: &[(&str, i64)]) -> HashMap<String, Decimal> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), Decimal::new(*value, 0)))
+        .collect()
+}
+
+#[test]
+fn test_builtin_mapping_matches_config_file() {
+    let path = Path::new(AccountMapping::default_path());
+    let from_file = AccountMapping::load(path).unwrap();
+    let builtin = AccountMapping::builtin();
+    assert_eq!(from_file.operating_assets, builtin.operating_assets);
+    assert_eq!(from_file.other_accounts, builtin.other_accounts);
+    assert_eq!(from_file.combined_accounts, builtin.combined_accounts);
+}
+
+#[test]
+fn test_classify_tushare_fields() {
+    let classifier = AccountClassifier::default();
+    let classification = classifier.classify(&items(&[
+        ("money_cap", 100),
+        ("inventories", 50),
+        ("lt_eqt_invest", 30),
+        ("acct_payable", 40),
+        ("st_borr", 20),
+        ("lt_borr", 10),
+        ("total_assets", 500),
+    ]));
+
+    assert_eq!(classification.operating_assets.total, Decimal::new(150, 0));
+    assert_eq!(classification.financial_assets.total, Decimal::new(30, 0));
+    assert_eq!(classification.operating_liabilities.total, Decimal::new(40, 0));
+    assert_eq!(classification.financial_liabilities.total, Decimal::new(30, 0));
+    assert!(classification.operating_assets.items.contains_key("货币资金"));
+    assert!(classification.unmapped.is_empty());
+}
+
+#[test]
+fn test_alias_priority_avoids_double_counting() {
+    let classifier = AccountClassifier::default();
+    // 应收账款与“应收票据及应收账款”同时存在时只取应收账款
+    let classification = classifier.classify(&items(&[
+        ("应收账款", 80),
+        ("应收票据及应收账款", 120),
+    ]));
+
+    assert_eq!(classification.operating_assets.total, Decimal::new(80, 0));
+    assert_eq!(classification.operating_assets.items.len(), 1);
+}
+
+#[test]
+fn test_combined_receivables_not_double_counted() {
+    let classifier = AccountClassifier::default();
+    // 合并列示的应收票据及应收账款与单独列示的应收票据同时存在时，应收账款扣除应收票据
+    let classification = classifier.classify(&items(&[
+        ("应收票据及应收账款", 120),
+        ("应收票据", 30),
+        ("应付票据及应付账款", 90),
+    ]));
+    assert_eq!(classification.operating_assets.total, Decimal::new(120, 0));
+    assert_eq!(classification.operating_assets.items.get("应收账款"), Some(&Decimal::new(90, 0)));
+    assert_eq!(classification.operating_liabilities.items.get("应付账款"), Some(&Decimal::new(90, 0)));
+
+    let tushare = classifier.normalize(&items(&[("accounts_receiv_bill", 120), ("notes_receiv", 30)]));
+    assert_eq!(tushare.get("应收账款"), Some(&Decimal::new(90, 0)));
+    assert_eq!(tushare.get("应收票据"), Some(&Decimal::new(30, 0)));
+}
+
+#[test]
+fn test_unmapped_accounts_reported() {
+    let classifier = AccountClassifier::default();
+    let classification = classifier.classify(&items(&[
+        ("货币资金", 100),
+        ("长期待摊费用", 5),
+        ("商誉", 0),
+    ]));
+
+    assert_eq!(classification.unmapped, vec!["长期待摊费用".to_string()]);
+}
+
+#[test]
+fn test_build_balance_sheet_adds_canonical_names() {
+    let classifier = AccountClassifier::default();
+    let bs = classifier.build_balance_sheet(
+        "000001.SZ",
+        NaiveDate::from_ymd_opt(2023, 12, 31).unwrap(),
+        items(&[("total_share", 1_000), ("money_cap", 100)]),
+    );
+
+    assert_eq!(bs.statement.items.get("股本"), Some(&Decimal::new(1_000, 0)));
+    assert_eq!(bs.statement.items.get("货币资金"), Some(&Decimal::new(100, 0)));
+    assert_eq!(bs.statement.items.get("money_cap"), Some(&Decimal::new(100, 0)));
+}
+
+/// 统计调用次数的数据源
+struct CountingSource {
+    inner: MockDataSource,
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl DataSource for CountingSource {
+    async fn fetch_balance_sheet(&self, stock_code: &str, start_date: NaiveDate, end_date: NaiveDate) -> anyhow::Result<Vec<BalanceSheet>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        self.inner.fetch_balance_sheet(stock_code, start_date, end_date).await
+    }
+
+    async fn fetch_income_statement(&self, stock_code: &str, start_date: NaiveDate, end_date: NaiveDate) -> anyhow::Result<Vec<IncomeStatement>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        self.inner.fetch_income_statement(stock_code, start_date, end_date).await
+    }
+
+    async fn fetch_cashflow_statement(&self, stock_code: &str, start_date: NaiveDate, end_date: NaiveDate) -> anyhow::Result<Vec<CashflowStatement>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        self.inner.fetch_cashflow_statement(stock_code, start_date, end_date).await
+    }
+
+    fn name(&self) -> &str {
+        "counting"
+    }
+}
+
+fn temp_test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("financial-analyzer-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn counting_cache(dir: &Path, ttl_secs: u64) -> (CachedDataSource, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let source = CountingSource {
+        inner: MockDataSource::new(),
+        calls: calls.clone(),
+    };
+    (CachedDataSource::new(Box::new(source), CacheStore::new(dir, ttl_secs)), calls)
+}
+
+#[tokio::test]
+async fn test_cache_hit_skips_inner_source() {
+    let dir = temp_test_dir("hit");
+    let (cached, calls) = counting_cache(&dir, 3600);
+    let start = NaiveDate::from_ymd_opt(2021, 12, 31).unwrap();
+    let end = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+
+    let first = cached.fetch_balance_sheet("600519.SH", start, end).await.unwrap();
+    let second = cached.fetch_balance_sheet("600519.SH", start, end).await.unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(first.len(), second.len());
+    assert_eq!(first[0].operating_assets.total, second[0].operating_assets.total);
+
+    // 不同日期区间使用不同的缓存键
+    cached.fetch_balance_sheet("600519.SH", end, end).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_cache_expired_entry_is_refetched() {
+    let dir = temp_test_dir("expired");
+    let (cached, calls) = counting_cache(&dir, 0);
+    let date = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+
+    cached.fetch_income_statement("000001.SZ", date, date).await.unwrap();
+    cached.fetch_income_statement("000001.SZ", date, date).await.unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_cache_list_and_clear() {
+    let dir = temp_test_dir("list");
+    let (cached, _) = counting_cache(&dir, 3600);
+    let date = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+
+    cached.fetch_balance_sheet("600519.SH", date, date).await.unwrap();
+    cached.fetch_cashflow_statement("600519.SH", date, date).await.unwrap();
+    cached.fetch_balance_sheet("000001.SZ", date, date).await.unwrap();
+
+    let store = CacheStore::new(&dir, 3600);
+    let entries = store.list().unwrap();
+    assert_eq!(entries.len(), 3);
+    assert!(entries.iter().all(|e| e.source == "counting"));
+
+    assert_eq!(store.clear(Some("600519.SH")).unwrap(), 2);
+    assert_eq!(store.list().unwrap().len(), 1);
+    assert_eq!(store.clear(None).unwrap(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_cache_keys_by_canonical_stock_code() {
+    let dir = temp_test_dir("canonical");
+    let (cached, calls) = counting_cache(&dir, 3600);
+    let date = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+
+    // 同一只股票的不同写法共用一份缓存
+    for code in ["600519", "600519.SH", "sh600519"] {
+        cached.fetch_balance_sheet(code, date, date).await.unwrap();
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let store = CacheStore::new(&dir, 3600);
+    let entries = store.list().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].stock_code, "600519.SH");
+    assert_eq!(store.clear(Some("SH600519")).unwrap(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+fn tushare_client(server: &mockito::Server, retry_times: u32) -> TushareClient {
+    let config = TushareConfig {
+        api_url: server.url(),
+        timeout_secs: 5,
+        retry_times,
+        retry_delay_ms: 1,
+        requests_per_minute: 6000,
+    };
+    TushareClient::new("test-token".to_string(), &config).unwrap()
+}
+
+const BALANCE_SHEET_BODY: &str = r#"{"code":0,"msg":"","data":{"fields":["ts_code","end_date","money_cap","st_borr"],"items":[["600519.SH","20231231",100.0,20.0]]}}"#;
+
+async fn fetch_tushare_balance_sheet(client: &TushareClient) -> anyhow::Result<Vec<BalanceSheet>> {
+    let date = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+    client.fetch_balance_sheet("600519.SH", date, date).await
+}
+
+#[tokio::test]
+async fn test_tushare_retries_rate_limit_error() {
+    let mut server = mockito::Server::new_async().await;
+    let limited = server
+        .mock("POST", "/")
+        .with_body(r#"{"code":40203,"msg":"抱歉，您每分钟最多访问该接口200次","data":null}"#)
+        .expect(2)
+        .create_async()
+        .await;
+    let ok = server
+        .mock("POST", "/")
+        .with_body(BALANCE_SHEET_BODY)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let sheets = fetch_tushare_balance_sheet(&tushare_client(&server, 3)).await.unwrap();
+
+    limited.assert_async().await;
+    ok.assert_async().await;
+    assert_eq!(sheets[0].operating_assets.total, Decimal::new(100, 0));
+    assert_eq!(sheets[0].financial_liabilities.total, Decimal::new(20, 0));
+}
+
+#[tokio::test]
+async fn test_tushare_retries_server_error() {
+    let mut server = mockito::Server::new_async().await;
+    let failed = server.mock("POST", "/").with_status(502).expect(1).create_async().await;
+    let ok = server
+        .mock("POST", "/")
+        .with_body(BALANCE_SHEET_BODY)
+        .expect(1)
+        .create_async()
+        .await;
+
+    assert!(fetch_tushare_balance_sheet(&tushare_client(&server, 3)).await.is_ok());
+
+    failed.assert_async().await;
+    ok.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_tushare_permanent_error_not_retried() {
+    let mut server = mockito::Server::new_async().await;
+    let invalid_token = server
+        .mock("POST", "/")
+        .with_body(r#"{"code":40101,"msg":"您的token不对，请确认。","data":null}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let err = fetch_tushare_balance_sheet(&tushare_client(&server, 3)).await.unwrap_err();
+
+    invalid_token.assert_async().await;
+    assert!(err.to_string().contains("40101"));
+}
+
+#[tokio::test]
+async fn test_tushare_gives_up_after_retry_times() {
+    let mut server = mockito::Server::new_async().await;
+    let failed = server.mock("POST", "/").with_status(500).expect(3).create_async().await;
+
+    assert!(fetch_tushare_balance_sheet(&tushare_client(&server, 2)).await.is_err());
+
+    failed.assert_async().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limiter_waits_when_bucket_empty() {
+    // 每分钟600次：桶容量600，每秒补充10个令牌；时钟暂停，只在等待时自动推进
+    let limiter = RateLimiter::per_minute(600);
+    let started = tokio::time::Instant::now();
+    for _ in 0..600 {
+        limiter.acquire().await;
+    }
+    assert_eq!(started.elapsed(), Duration::ZERO);
+
+    let started = tokio::time::Instant::now();
+    let shared = limiter.clone();
+    shared.acquire().await;
+    assert!(started.elapsed() >= Duration::from_millis(100));
+    assert!(started.elapsed() < Duration::from_millis(110));
+}
+
+#[test]
+fn test_backoff_delay_is_capped() {
+    let base = Duration::from_millis(500);
+    assert_eq!(backoff_delay(base, 0), base);
+    assert_eq!(backoff_delay(base, 2), Duration::from_secs(2));
+    assert_eq!(backoff_delay(base, 40), Duration::from_secs(60));
+    assert_eq!(backoff_delay(Duration::from_secs(u64::MAX), 3), Duration::from_secs(60));
+}
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+#[tokio::test]
+async fn test_file_source_reads_csv() {
+    let dir = temp_test_dir("file-csv");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("income_statement.csv"),
+        "项目,2023-12-31,2022-12-31,2021\n\
+         营业收入,\"1,500\",1200,1000\n\
+         营业成本,500,400,--\n\
+         净利润,600,480,400\n",
+    )
+    .unwrap();
+
+    let source = FileDataSource::new(&dir);
+    let statements = source
+        .fetch_income_statement("600519.SH", date(2022, 1, 1), date(2023, 12, 31))
+        .await
+        .unwrap();
+
+    // 区间外的2021年被过滤，结果按日期倒序
+    assert_eq!(statements.len(), 2);
+    assert_eq!(statements[0].statement.report_date, date(2023, 12, 31));
+    assert_eq!(statements[0].revenue, Decimal::new(1500, 0));
+    assert_eq!(statements[0].gross_profit, Decimal::new(1000, 0));
+    assert_eq!(statements[1].net_profit, Decimal::new(480, 0));
+}
+
+#[tokio::test]
+async fn test_file_source_reads_json_and_classifies_balance_sheet() {
+    let dir = temp_test_dir("file-json");
+    let stock_dir = dir.join("600519.SH");
+    std::fs::create_dir_all(&stock_dir).unwrap();
+    std::fs::write(
+        stock_dir.join("balance_sheet.json"),
+        r#"{"20231231": {"货币资金": 100, "应收账款": "50", "短期借款": 1.5e2, "商誉": null}}"#,
+    )
+    .unwrap();
+
+    let source = FileDataSource::new(&dir);
+    let sheets = source
+        .fetch_balance_sheet("600519.SH", date(2023, 1, 1), date(2023, 12, 31))
+        .await
+        .unwrap();
+
+    assert_eq!(sheets.len(), 1);
+    assert_eq!(sheets[0].operating_assets.total, Decimal::new(150, 0));
+    assert_eq!(sheets[0].financial_liabilities.total, Decimal::new(150, 0));
+    assert!(!sheets[0].statement.items.contains_key("商誉"));
+}
+
+#[tokio::test]
+async fn test_file_source_reports_missing_file_and_bad_values() {
+    let dir = temp_test_dir("file-errors");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("cashflow_statement.csv"), "项目,2023-12-31\n经营活动产生的现金流量净额,abc\n").unwrap();
+
+    let source = FileDataSource::new(&dir);
+    let missing = source
+        .fetch_balance_sheet("600519.SH", date(2023, 1, 1), date(2023, 12, 31))
+        .await
+        .unwrap_err();
+    assert!(missing.to_string().contains("balance_sheet"));
+
+    let invalid = source
+        .fetch_cashflow_statement("600519.SH", date(2023, 1, 1), date(2023, 12, 31))
+        .await
+        .unwrap_err();
+    assert!(format!("{:#}", invalid).contains("abc"));
+}
+
+#[test]
+fn test_expected_report_dates() {
+    let annual = expected_report_dates(date(2021, 12, 31), date(2023, 12, 31), ReportPeriod::Annual, date(2024, 5, 1));
+    assert_eq!(annual, vec![date(2023, 12, 31), date(2022, 12, 31), date(2021, 12, 31)]);
+
+    // 尚未到来的报告期不计入
+    let quarterly = expected_report_dates(date(2024, 1, 1), date(2024, 12, 31), ReportPeriod::Quarterly, date(2024, 8, 1));
+    assert_eq!(quarterly, vec![date(2024, 6, 30), date(2024, 3, 31)]);
+}
+
+#[tokio::test]
+async fn test_store_round_trip_keeps_typed_fields() {
+    let store = StatementStore::open_in_memory().unwrap();
+    let mock = MockDataSource::new();
+    let (start, end) = (date(2023, 12, 31), date(2023, 12, 31));
+    let sheets = mock.fetch_balance_sheet("600519.SH", start, end).await.unwrap();
+    let incomes = mock.fetch_income_statement("600519.SH", start, end).await.unwrap();
+
+    assert_eq!(store.save("mock", &sheets).unwrap(), 1);
+    store.save("mock", &incomes).unwrap();
+
+    let loaded: Vec<BalanceSheet> = store.load("600519.SH", ReportType::BalanceSheet, start, end).unwrap();
+    assert_eq!(loaded[0].operating_assets.total, sheets[0].operating_assets.total);
+    assert_eq!(loaded[0].statement.items, sheets[0].statement.items);
+
+    let loaded: Vec<IncomeStatement> = store.load("600519.SH", ReportType::IncomeStatement, start, end).unwrap();
+    assert_eq!(loaded[0].net_profit, incomes[0].net_profit);
+    assert_eq!(loaded[0].statement.report_type, ReportType::IncomeStatement);
+}
+
+#[tokio::test]
+async fn test_store_sync_fetches_only_missing_periods() {
+    let store = StatementStore::open_in_memory().unwrap();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let source = CountingSource {
+        inner: MockDataSource::new(),
+        calls: calls.clone(),
+    };
+    let expected = expected_report_dates(date(2022, 12, 31), date(2023, 12, 31), ReportPeriod::Annual, date(2024, 5, 1));
+
+    let first = store.sync(&source, "600519.SH", &expected).await.unwrap();
+    assert_eq!(first.saved, 6);
+    assert_eq!(first.missing, 0);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    // 已入库的报告期不再请求
+    let second = store.sync(&source, "600519.SH", &expected).await.unwrap();
+    assert_eq!(second.saved, 0);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    // 扩大区间后只补新的年份
+    let wider = expected_report_dates(date(2021, 12, 31), date(2023, 12, 31), ReportPeriod::Annual, date(2024, 5, 1));
+    let third = store.sync(&source, "600519.SH", &wider).await.unwrap();
+    assert_eq!(third.saved, 3);
+    assert_eq!(store.report_dates("600519.SH", ReportType::IncomeStatement, "counting").unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_store_keys_by_canonical_stock_code() {
+    let store = StatementStore::open_in_memory().unwrap();
+    let source = CountingSource {
+        inner: MockDataSource::new(),
+        calls: Arc::new(AtomicUsize::new(0)),
+    };
+    let expected = expected_report_dates(date(2023, 12, 31), date(2023, 12, 31), ReportPeriod::Annual, date(2024, 5, 1));
+
+    assert_eq!(store.sync(&source, "600519", &expected).await.unwrap().saved, 3);
+    // 换一种写法不会重复入库
+    assert_eq!(store.sync(&source, "sh600519", &expected).await.unwrap().saved, 0);
+
+    let summary = store.summary().unwrap();
+    assert!(summary.iter().all(|entry| entry.stock_code == "600519.SH"));
+    let loaded: Vec<BalanceSheet> = store.load("600519.SH", ReportType::BalanceSheet, date(2023, 12, 31), date(2023, 12, 31)).unwrap();
+    assert_eq!(loaded.len(), 1);
+}
+
+#[tokio::test]
+async fn test_store_data_source_serves_analysis() {
+    let store = StatementStore::open_in_memory().unwrap();
+    let expected = expected_report_dates(date(2022, 12, 31), date(2023, 12, 31), ReportPeriod::Annual, date(2024, 5, 1));
+    store.sync(&MockDataSource::new(), "600519.SH", &expected).await.unwrap();
+
+    let source = StoreDataSource::new(store);
+    let result = crate::analyzer::FinancialAnalyzer::new()
+        .analyze("600519.SH", vec![2023, 2022], &source)
+        .await
+        .unwrap();
+    assert_eq!(result.period_labels, vec!["2023", "2022"]);
+
+    let missing = source
+        .fetch_balance_sheet("000001.SZ", date(2023, 1, 1), date(2023, 12, 31))
+        .await
+        .unwrap_err();
+    assert!(missing.to_string().contains("sync"));
+}
+
+/// 总是失败的数据源
+struct FailingSource;
+
+#[async_trait::async_trait]
+impl DataSource for FailingSource {
+    async fn fetch_balance_sheet(&self, _: &str, _: NaiveDate, _: NaiveDate) -> anyhow::Result<Vec<BalanceSheet>> {
+        anyhow::bail!("接口不可用")
+    }
+
+    async fn fetch_income_statement(&self, _: &str, _: NaiveDate, _: NaiveDate) -> anyhow::Result<Vec<IncomeStatement>> {
+        anyhow::bail!("接口不可用")
+    }
+
+    async fn fetch_cashflow_statement(&self, _: &str, _: NaiveDate, _: NaiveDate) -> anyhow::Result<Vec<CashflowStatement>> {
+        anyhow::bail!("接口不可用")
+    }
+
+    fn name(&self) -> &str {
+        "failing"
+    }
+}
+
+/// 以指定名称读取本地文件的数据源，用于模拟不同来源
+struct NamedFileSource {
+    name: &'static str,
+    dir: PathBuf,
+    inner: FileDataSource,
+}
+
+impl NamedFileSource {
+    fn new(name: &'static str, balance_sheet_csv: &str) -> Self {
+        let dir = temp_test_dir(&format!("composite-{}", name));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("balance_sheet.csv"), balance_sheet_csv).unwrap();
+        Self {
+            name,
+            inner: FileDataSource::new(&dir),
+            dir,
+        }
+    }
+
+    fn with_statement(self, report_type: ReportType, csv: &str) -> Self {
+        std::fs::write(self.dir.join(format!("{}.csv", report_type.as_str())), csv).unwrap();
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl DataSource for NamedFileSource {
+    async fn fetch_balance_sheet(&self, stock_code: &str, start_date: NaiveDate, end_date: NaiveDate) -> anyhow::Result<Vec<BalanceSheet>> {
+        self.inner.fetch_balance_sheet(stock_code, start_date, end_date).await
+    }
+
+    async fn fetch_income_statement(&self, stock_code: &str, start_date: NaiveDate, end_date: NaiveDate) -> anyhow::Result<Vec<IncomeStatement>> {
+        self.inner.fetch_income_statement(stock_code, start_date, end_date).await
+    }
+
+    async fn fetch_cashflow_statement(&self, stock_code: &str, start_date: NaiveDate, end_date: NaiveDate) -> anyhow::Result<Vec<CashflowStatement>> {
+        self.inner.fetch_cashflow_statement(stock_code, start_date, end_date).await
+    }
+
+    fn name(&self) -> &str {
+        self.name
+    }
+}
+
+#[test]
+fn test_validate_stock_code_per_source() {
+    use crate::data_source::factory::validate_stock_code;
+
+    // 本地文件和报表库接受任意标识
+    assert!(validate_stock_code("file", "MYCO").is_ok());
+    assert!(validate_stock_code("store", "未上市公司").is_ok());
+    assert!(validate_stock_code("file", " ").is_err());
+
+    // 交易所数据源需要可识别的代码
+    assert!(validate_stock_code("tushare", "600519").is_ok());
+    assert!(validate_stock_code("akshare", "MYCO").is_err());
+    assert!(validate_stock_code("mock", "600519.SZ").unwrap_err().to_string().contains("深交所"));
+
+    // 组合数据源只要有一个成员可以处理
+    assert!(validate_stock_code("tushare,file", "MYCO").is_ok());
+    assert!(validate_stock_code("akshare+tushare", "MYCO").is_err());
+}
+
+#[tokio::test]
+async fn test_composite_falls_back_on_error() {
+    let composite = CompositeDataSource::new(vec![Box::new(FailingSource), Box::new(MockDataSource::new())]);
+    assert_eq!(composite.name(), "failing,mock");
+
+    let incomes = composite
+        .fetch_income_statement("600519.SH", date(2023, 12, 31), date(2023, 12, 31))
+        .await
+        .unwrap();
+    assert_eq!(incomes.len(), 1);
+    assert!(incomes[0].statement.sources.values().all(|source| source == "mock"));
+    assert_eq!(incomes[0].statement.primary_source().as_deref(), Some("mock"));
+
+    let all_failing = CompositeDataSource::new(vec![Box::new(FailingSource)]);
+    let err = all_failing
+        .fetch_cashflow_statement("600519.SH", date(2023, 12, 31), date(2023, 12, 31))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("接口不可用"));
+}
+
+#[tokio::test]
+async fn test_composite_merges_items_and_records_provenance() {
+    let primary = NamedFileSource::new("primary", "项目,2023-12-31\n货币资金,100\n短期借款,10\n");
+    let secondary = NamedFileSource::new(
+        "secondary",
+        "项目,2023-12-31,2022-12-31\n货币资金,999,80\n应付债券,50,40\n",
+    );
+    let composite = CompositeDataSource::new(vec![Box::new(primary), Box::new(secondary)]).with_merge(true);
+    assert_eq!(composite.name(), "primary+secondary");
+
+    let sheets = composite
+        .fetch_balance_sheet("600519.SH", date(2022, 1, 1), date(2023, 12, 31))
+        .await
+        .unwrap();
+    assert_eq!(sheets.len(), 2);
+
+    // 同一科目以优先级高的数据源为准，缺失科目由其他数据源补充并重新分类
+    let latest = &sheets[0];
+    assert_eq!(latest.statement.report_date, date(2023, 12, 31));
+    assert_eq!(latest.statement.items["货币资金"], Decimal::new(100, 0));
+    assert_eq!(latest.statement.sources["货币资金"], "primary");
+    assert_eq!(latest.statement.sources["应付债券"], "secondary");
+    assert_eq!(latest.financial_liabilities.total, Decimal::new(60, 0));
+    assert_eq!(
+        latest.statement.source_counts(),
+        vec![("primary".to_string(), 2), ("secondary".to_string(), 1)]
+    );
+
+    // 只有次要数据源提供的报告期也会保留
+    assert_eq!(sheets[1].statement.sources["货币资金"], "secondary");
+}
+
+#[tokio::test]
+async fn test_composite_merge_recomputes_income_and_cashflow() {
+    let primary = NamedFileSource::new("gap-primary", "项目,2023-12-31\n货币资金,100\n")
+        .with_statement(ReportType::IncomeStatement, "项目,2023-12-31\n营业总收入,1000\n净利润,100\n")
+        .with_statement(ReportType::CashflowStatement, "项目,2023-12-31\n经营活动产生的现金流量净额,900\n");
+    // 次要数据源使用Tushare字段名，合并前按科目映射归一
+    let secondary = NamedFileSource::new("gap-secondary", "项目,2023-12-31\n货币资金,100\n")
+        .with_statement(ReportType::IncomeStatement, "项目,2023-12-31\ntotal_revenue,999\noper_cost,600\n")
+        .with_statement(ReportType::CashflowStatement, "项目,2023-12-31\nn_cashflow_act,1\nn_cashflow_inv_act,-300\n");
+    let composite = CompositeDataSource::new(vec![Box::new(primary), Box::new(secondary)]).with_merge(true);
+
+    let incomes = composite
+        .fetch_income_statement("600519.SH", date(2023, 12, 31), date(2023, 12, 31))
+        .await
+        .unwrap();
+    assert_eq!(incomes[0].revenue, Decimal::new(1000, 0));
+    assert_eq!(incomes[0].statement.sources["营业成本"], "gap-secondary");
+    assert_eq!(incomes[0].gross_profit, Decimal::new(400, 0));
+
+    // 补齐的营业成本改变毛利率
+    let profit = crate::analyzer::RatioCalculator::new()
+        .calculate_profit_ratios(&incomes)
+        .unwrap();
+    assert_eq!(profit.gross_margin[0], Decimal::new(4, 1));
+
+    let cashflows = composite
+        .fetch_cashflow_statement("600519.SH", date(2023, 12, 31), date(2023, 12, 31))
+        .await
+        .unwrap();
+    assert_eq!(cashflows[0].operating_cashflow, Decimal::new(900, 0));
+    assert_eq!(cashflows[0].investing_cashflow, Decimal::new(-300, 0));
+    assert_eq!(cashflows[0].free_cashflow, Decimal::new(600, 0));
+}
+
+#[tokio::test]
+async fn test_tushare_record_then_replay() {
+    let dir = temp_test_dir("record-tushare");
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("POST", "/")
+        .with_status(200)
+        .with_body(BALANCE_SHEET_BODY)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let recorded = fetch_tushare_balance_sheet(&tushare_client(&server, 0).with_recorder(Recorder::record(&dir)))
+        .await
+        .unwrap();
+    mock.assert_async().await;
+    let fixture = dir.join("tushare").join("balancesheet_20231231_20231231_600519.SH.json");
+    assert_eq!(std::fs::read_to_string(&fixture).unwrap(), BALANCE_SHEET_BODY);
+
+    // 回放不再访问服务端
+    drop(server);
+    let config = TushareConfig {
+        api_url: "http://127.0.0.1:9".to_string(),
+        ..TushareConfig::default()
+    };
+    let replay_client = TushareClient::new(String::new(), &config)
+        .unwrap()
+        .with_recorder(Recorder::replay(&dir));
+    let replayed = fetch_tushare_balance_sheet(&replay_client).await.unwrap();
+    assert_eq!(replayed[0].statement.items, recorded[0].statement.items);
+    assert_eq!(replayed[0].financial_liabilities.total, Decimal::new(20, 0));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_replay_missing_fixture_is_an_error() {
+    let dir = temp_test_dir("replay-missing");
+    let client = crate::data_source::AkshareClient::from_config(&crate::utils::AkshareConfig::default()).with_recorder(Recorder::replay(&dir));
+    let err = client
+        .fetch_balance_sheet("600519.SH", date(2023, 1, 1), date(2023, 12, 31))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("--record"));
+}
+
+fn python_worker(request_timeout: Duration) -> super::akshare_worker::PythonWorker {
+    let python = crate::utils::AkshareConfig::default().python_path;
+    super::akshare_worker::PythonWorker::new(python, Duration::from_secs(30), request_timeout)
+}
+
+#[tokio::test]
+async fn test_python_worker_reuses_process() {
+    let worker = python_worker(Duration::from_secs(30));
+
+    let first = worker.run("import os\nprint(os.getpid())").await.unwrap();
+    let second = worker.run("import os\nprint(os.getpid())").await.unwrap();
+    assert_eq!(first, second, "多次请求应由同一个Python进程处理");
+
+    // 脚本之间不共享全局变量，报错不影响进程
+    let err = worker.run("print(undefined_name)").await.unwrap_err();
+    assert!(err.to_string().contains("NameError"));
+    assert_eq!(worker.run("print('中文', end='')").await.unwrap(), "中文");
+    assert_eq!(worker.run("import os\nprint(os.getpid())").await.unwrap(), first);
+}
+
+#[tokio::test]
+async fn test_python_worker_restarts_after_crash() {
+    let dir = temp_test_dir("python-worker-crash");
+    std::fs::create_dir_all(&dir).unwrap();
+    let marker = dir.join("crashed");
+    let worker = python_worker(Duration::from_secs(30));
+
+    // 首次执行时进程退出，自动重启后重试成功
+    let script = format!(
+        "import os\nif not os.path.exists({0:?}):\n    open({0:?}, 'w').close()\n    os._exit(1)\nprint('recovered', end='')",
+        marker.to_str().unwrap()
+    );
+    assert_eq!(worker.run(&script).await.unwrap(), "recovered");
+    assert!(marker.exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_python_worker_crash_reports_stderr() {
+    let worker = python_worker(Duration::from_secs(30));
+
+    // 重启后仍然退出时，错误信息带上 Python 的标准错误输出
+    let script = "import os, sys\nsys.stderr.write('ModuleNotFoundError: fake-missing-module\\n')\nsys.stderr.flush()\nos._exit(3)";
+    let err = worker.run(script).await.unwrap_err();
+    let message = format!("{:#}", err);
+    assert!(message.contains("重启后仍然退出"), "{}", message);
+    assert!(message.contains("fake-missing-module"), "{}", message);
+}
+
+#[tokio::test]
+async fn test_python_worker_timeout_kills_process() {
+    let worker = python_worker(Duration::from_millis(500));
+    let pid = worker.run("import os\nprint(os.getpid())").await.unwrap();
+
+    let err = worker.run("import time\ntime.sleep(10)").await.unwrap_err();
+    assert!(err.to_string().contains("超时"));
+
+    // 超时后重新启动新进程
+    let new_pid = worker.run("import os\nprint(os.getpid())").await.unwrap();
+    assert_ne!(pid, new_pid);
+}
+
+#[tokio::test]
+async fn test_python_worker_without_akshare() {
+    let worker = python_worker(Duration::from_secs(30));
+    // 未安装akshare时导入失败并给出安装提示，但进程仍可执行其他脚本
+    match worker.run("import akshare\nprint(akshare.__version__, end='')").await {
+        Ok(version) => assert!(!version.is_empty()),
+        Err(e) => assert!(e.to_string().contains("install akshare"), "{}", e),
+    }
+    assert_eq!(worker.run("print(1 + 1, end='')").await.unwrap(), "2");
+}
@@ -1,10 +1,14 @@
+use super::classifier::AccountClassifier;
+use super::rate_limit::RateLimiter;
+use super::recorder::Recorder;
 use super::traits::DataSource;
 use crate::domain::*;
+use crate::utils::TushareConfig;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use chrono::{Datelike, NaiveDate};
+use chrono::NaiveDate;
 use rust_decimal::Decimal;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use std::collections::HashMap;
 use std::time::Duration;
 
@@ -22,34 +26,131 @@ struct TushareData {
     items: Vec<Vec<serde_json::Value>>,
 }
 
+/// Tushare 频率超限时返回的错误码（权限不足也使用该错误码，需结合错误信息判断）
+const RATE_LIMIT_CODE: i32 = 40203;
+
+/// 单次请求失败的类型
+enum ApiFailure {
+    /// 网络错误、服务端错误或频率超限，可重试
+    Retryable(anyhow::Error),
+    /// Token无效、参数错误等，重试无意义
+    Permanent(anyhow::Error),
+}
+
 /// Tushare客户端
 pub struct TushareClient {
     api_url: String,
     token: String,
     client: reqwest::Client,
+    classifier: AccountClassifier,
+    limiter: RateLimiter,
+    retry_times: u32,
+    retry_delay: Duration,
+    recorder: Option<Recorder>,
+}
+
+/// 重试等待的上限，避免重试次数配置较大时等待过久
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// 第 attempt 次重试（从0开始）前的等待：首次等待 base，之后逐次翻倍，不超过 `MAX_RETRY_DELAY`
+pub(super) fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    let factor = 2u32.checked_pow(attempt).unwrap_or(u32::MAX);
+    base.saturating_mul(factor).min(MAX_RETRY_DELAY)
 }
 
 impl TushareClient {
-    pub fn new(token: String) -> Result<Self> {
+    pub fn new(token: String, config: &TushareConfig) -> Result<Self> {
         let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(30))
+            .timeout(Duration::from_secs(config.timeout_secs))
             .build()?;
 
         Ok(Self {
-            api_url: "http://api.tushare.pro".to_string(),
+            api_url: config.api_url.clone(),
             token,
             client,
+            classifier: AccountClassifier::default(),
+            limiter: RateLimiter::per_minute(config.requests_per_minute),
+            retry_times: config.retry_times,
+            retry_delay: Duration::from_millis(config.retry_delay_ms),
+            recorder: None,
         })
     }
 
-    pub fn from_env() -> Result<Self> {
+    pub fn with_classifier(mut self, classifier: AccountClassifier) -> Self {
+        self.classifier = classifier;
+        self
+    }
+
+    /// 录制或回放API响应
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    pub fn from_env(config: &TushareConfig) -> Result<Self> {
         let token = std::env::var("TUSHARE_TOKEN")
             .map_err(|_| anyhow!("TUSHARE_TOKEN环境变量未设置"))?;
-        Self::new(token)
+        Self::new(token, config)
     }
 
-    /// 调用Tushare API
+    /// 调用Tushare API（限流 + 指数退避重试）
     async fn call_api(&self, api_name: &str, params: serde_json::Value) -> Result<TushareData> {
+        let key = Self::fixture_key(api_name, &params);
+        if let Some(replayed) = self.recorder.as_ref().and_then(|r| r.replay_response("tushare", &key)) {
+            return Self::parse_response(&replayed?).map_err(|failure| match failure {
+                ApiFailure::Retryable(e) | ApiFailure::Permanent(e) => e,
+            });
+        }
+
+        let mut attempt = 0;
+
+        loop {
+            self.limiter.acquire().await;
+
+            match self.send_request(api_name, &params).await {
+                Ok((data, body)) => {
+                    if let Some(recorder) = &self.recorder {
+                        recorder.save("tushare", &key, &body)?;
+                    }
+                    return Ok(data);
+                }
+                Err(ApiFailure::Permanent(e)) => return Err(e),
+                Err(ApiFailure::Retryable(e)) if attempt < self.retry_times => {
+                    let delay = backoff_delay(self.retry_delay, attempt);
+                    attempt += 1;
+                    tracing::warn!(
+                        "Tushare {} 请求失败（第{}次重试，{:?}后）: {}",
+                        api_name, attempt, delay, e
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(ApiFailure::Retryable(e)) => {
+                    return Err(e.context(format!("Tushare {} 重试{}次后仍失败", api_name, self.retry_times)));
+                }
+            }
+        }
+    }
+
+    /// 夹具文件名：接口名加按键名排序的参数值
+    fn fixture_key(api_name: &str, params: &serde_json::Value) -> String {
+        let mut parts = vec![api_name.to_string()];
+        if let Some(map) = params.as_object() {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            parts.extend(keys.into_iter().map(|k| match &map[k] {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            }));
+        }
+        parts.join("_")
+    }
+
+    /// 发送单次请求，成功时同时返回原始响应体
+    async fn send_request(
+        &self,
+        api_name: &str,
+        params: &serde_json::Value,
+    ) -> std::result::Result<(TushareData, String), ApiFailure> {
         let request_body = serde_json::json!({
             "api_name": api_name,
             "token": self.token,
@@ -62,111 +163,100 @@ impl TushareClient {
             .post(&self.api_url)
             .json(&request_body)
             .send()
-            .await?;
+            .await
+            .map_err(|e| ApiFailure::Retryable(e.into()))?;
 
-        let result: TushareResponse = response.json().await?;
+        let status = response.status();
+        if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(ApiFailure::Retryable(anyhow!("Tushare HTTP错误: {}", status)));
+        }
+        if !status.is_success() {
+            return Err(ApiFailure::Permanent(anyhow!("Tushare HTTP错误: {}", status)));
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| ApiFailure::Retryable(e.into()))?;
+        let data = Self::parse_response(&body)?;
+        Ok((data, body))
+    }
+
+    /// 解析响应体（真实请求与回放共用）
+    fn parse_response(body: &str) -> std::result::Result<TushareData, ApiFailure> {
+        let result: TushareResponse = serde_json::from_str(body)
+            .map_err(|e| ApiFailure::Permanent(anyhow!("Tushare响应解析失败: {}", e)))?;
 
         if result.code != 0 {
-            return Err(anyhow!(
-                "Tushare API错误: {}",
-                result.msg.unwrap_or_else(|| "未知错误".to_string())
-            ));
+            let msg = result.msg.unwrap_or_else(|| "未知错误".to_string());
+            let error = anyhow!("Tushare API错误({}): {}", result.code, msg);
+            return Err(if Self::is_rate_limited(result.code, &msg) {
+                ApiFailure::Retryable(error)
+            } else {
+                ApiFailure::Permanent(error)
+            });
         }
 
-        result.data.ok_or_else(|| anyhow!("API返回数据为空"))
+        result.data.ok_or_else(|| ApiFailure::Permanent(anyhow!("API返回数据为空")))
     }
 
-    /// 解析资产负债表数据
-    fn parse_balance_sheet(&self, data: TushareData, stock_code: &str) -> Result<Vec<BalanceSheet>> {
-        let mut sheets = Vec::new();
+    /// 规范化为Tushare代码格式（sh600519 -> 600519.SH）
+    fn ts_code(stock_code: &str) -> Result<String> {
+        let code: StockCode = stock_code.parse().map_err(|e: String| anyhow!(e))?;
+        Ok(code.tushare())
+    }
+
+    /// 是否为频率超限错误
+    fn is_rate_limited(code: i32, msg: &str) -> bool {
+        code == RATE_LIMIT_CODE && (msg.contains("最多访问") || msg.contains("频率"))
+    }
+
+    /// 按字段名解析各行的数值科目和报告日期
+    fn parse_rows(data: TushareData) -> Result<Vec<(NaiveDate, HashMap<String, Decimal>)>> {
+        let date_index = data.fields.iter().position(|f| f == "end_date").unwrap_or(0);
+        let mut rows = Vec::new();
 
         for item in data.items {
             let mut items_map = HashMap::new();
-            
-            // 解析字段
             for (i, field) in data.fields.iter().enumerate() {
-                if let Some(value) = item.get(i) {
-                    if let Some(num) = value.as_f64() {
-                        items_map.insert(field.clone(), Decimal::from_f64_retain(num).unwrap_or(Decimal::ZERO));
-                    }
+                if let Some(num) = item.get(i).and_then(|value| value.as_f64()) {
+                    items_map.insert(field.clone(), Decimal::from_f64_retain(num).unwrap_or(Decimal::ZERO));
                 }
             }
 
-            // 获取报告日期
-            let end_date_str = item.get(data.fields.iter().position(|f| f == "end_date").unwrap_or(0))
+            let end_date_str = item
+                .get(date_index)
                 .and_then(|v| v.as_str())
                 .ok_or_else(|| anyhow!("缺少报告日期"))?;
-
             let report_date = NaiveDate::parse_from_str(end_date_str, "%Y%m%d")?;
-
-            let statement = FinancialStatement {
-                stock_code: stock_code.to_string(),
-                report_date,
-                report_type: ReportType::BalanceSheet,
-                items: items_map,
-            };
-
-            // 简化分类（实际应使用配置文件）
-            let mut operating_assets = AssetGroup::new();
-            let mut financial_assets = AssetGroup::new();
-            let mut operating_liabilities = LiabilityGroup::new();
-            let mut financial_liabilities = LiabilityGroup::new();
-
-            sheets.push(BalanceSheet {
-                statement,
-                operating_assets,
-                financial_assets,
-                operating_liabilities,
-                financial_liabilities,
-            });
+            rows.push((report_date, items_map));
         }
 
-        Ok(sheets)
+        Ok(rows)
+    }
+
+    /// 解析资产负债表数据
+    fn parse_balance_sheet(&self, data: TushareData, stock_code: &str) -> Result<Vec<BalanceSheet>> {
+        Ok(Self::parse_rows(data)?
+            .into_iter()
+            .map(|(date, items)| self.classifier.build_balance_sheet(stock_code, date, items))
+            .collect())
     }
 
     /// 解析利润表数据
     fn parse_income_statement(&self, data: TushareData, stock_code: &str) -> Result<Vec<IncomeStatement>> {
-        let mut statements = Vec::new();
-
-        for item in data.items {
-            let mut items_map = HashMap::new();
-            
-            for (i, field) in data.fields.iter().enumerate() {
-                if let Some(value) = item.get(i) {
-                    if let Some(num) = value.as_f64() {
-                        items_map.insert(field.clone(), Decimal::from_f64_retain(num).unwrap_or(Decimal::ZERO));
-                    }
-                }
-            }
-
-            let end_date_str = item.get(data.fields.iter().position(|f| f == "end_date").unwrap_or(0))
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| anyhow!("缺少报告日期"))?;
-
-            let report_date = NaiveDate::parse_from_str(end_date_str, "%Y%m%d")?;
-
-            let revenue = items_map.get("revenue").copied().unwrap_or(Decimal::ZERO);
-            let operating_cost = items_map.get("oper_cost").copied().unwrap_or(Decimal::ZERO);
-            let net_profit = items_map.get("n_income").copied().unwrap_or(Decimal::ZERO);
-
-            let statement = FinancialStatement {
-                stock_code: stock_code.to_string(),
-                report_date,
-                report_type: ReportType::IncomeStatement,
-                items: items_map,
-            };
-
-            statements.push(IncomeStatement {
-                statement,
-                revenue,
-                operating_cost,
-                gross_profit: revenue - operating_cost,
-                core_profit: net_profit,
-                net_profit,
-            });
-        }
+        Ok(Self::parse_rows(data)?
+            .into_iter()
+            .map(|(date, items)| self.classifier.build_income_statement(stock_code, date, items))
+            .collect())
+    }
 
-        Ok(statements)
+    /// 解析现金流量表数据
+    fn parse_cashflow_statement(&self, data: TushareData, stock_code: &str) -> Result<Vec<CashflowStatement>> {
+        Ok(Self::parse_rows(data)?
+            .into_iter()
+            .map(|(date, items)| self.classifier.build_cashflow_statement(stock_code, date, items))
+            .collect())
     }
 }
 
@@ -179,7 +269,7 @@ impl DataSource for TushareClient {
         end_date: NaiveDate,
     ) -> Result<Vec<BalanceSheet>> {
         let params = serde_json::json!({
-            "ts_code": stock_code,
+            "ts_code": Self::ts_code(stock_code)?,
             "start_date": start_date.format("%Y%m%d").to_string(),
             "end_date": end_date.format("%Y%m%d").to_string(),
         });
@@ -195,7 +285,7 @@ impl DataSource for TushareClient {
         end_date: NaiveDate,
     ) -> Result<Vec<IncomeStatement>> {
         let params = serde_json::json!({
-            "ts_code": stock_code,
+            "ts_code": Self::ts_code(stock_code)?,
             "start_date": start_date.format("%Y%m%d").to_string(),
             "end_date": end_date.format("%Y%m%d").to_string(),
         });
@@ -211,39 +301,13 @@ impl DataSource for TushareClient {
         end_date: NaiveDate,
     ) -> Result<Vec<CashflowStatement>> {
         let params = serde_json::json!({
-            "ts_code": stock_code,
+            "ts_code": Self::ts_code(stock_code)?,
             "start_date": start_date.format("%Y%m%d").to_string(),
             "end_date": end_date.format("%Y%m%d").to_string(),
         });
 
         let data = self.call_api("cashflow", params).await?;
-        
-        // 简化实现
-        let mut statements = Vec::new();
-        for item in data.items {
-            let end_date_str = item.get(data.fields.iter().position(|f| f == "end_date").unwrap_or(0))
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| anyhow!("缺少报告日期"))?;
-
-            let report_date = NaiveDate::parse_from_str(end_date_str, "%Y%m%d")?;
-
-            let statement = FinancialStatement {
-                stock_code: stock_code.to_string(),
-                report_date,
-                report_type: ReportType::CashflowStatement,
-                items: HashMap::new(),
-            };
-
-            statements.push(CashflowStatement {
-                statement,
-                operating_cashflow: Decimal::ZERO,
-                investing_cashflow: Decimal::ZERO,
-                financing_cashflow: Decimal::ZERO,
-                free_cashflow: Decimal::ZERO,
-            });
-        }
-
-        Ok(statements)
+        self.parse_cashflow_statement(data, stock_code)
     }
 
     fn name(&self) -> &str {
@@ -1,3 +1,7 @@
 pub mod models;
+pub mod stock_code;
+#[cfg(test)]
+mod tests;
 
 pub use models::*;
+pub use stock_code::{stock_key, Exchange, StockCode, StockId};
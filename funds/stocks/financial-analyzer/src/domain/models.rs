@@ -19,6 +19,67 @@ impl ReportType {
             Self::CashflowStatement => "cashflow_statement",
         }
     }
+
+    /// 中文名称，用于提示信息
+    pub fn display_name(&self) -> &str {
+        match self {
+            Self::BalanceSheet => "资产负债表",
+            Self::IncomeStatement => "利润表",
+            Self::CashflowStatement => "现金流量表",
+        }
+    }
+}
+
+impl std::str::FromStr for ReportType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "balance_sheet" => Ok(Self::BalanceSheet),
+            "income_statement" => Ok(Self::IncomeStatement),
+            "cashflow_statement" => Ok(Self::CashflowStatement),
+            _ => Err(format!(
+                "不支持的报表类型: {}（可选 balance_sheet, income_statement, cashflow_statement）",
+                s
+            )),
+        }
+    }
+}
+
+/// 分析期间模式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportPeriod {
+    /// 年报
+    #[default]
+    Annual,
+    /// 单季度（由累计报表推算）
+    Quarterly,
+    /// 滚动十二个月：最新一期为TTM，其余为年报
+    Ttm,
+}
+
+impl ReportPeriod {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Annual => "annual",
+            Self::Quarterly => "quarterly",
+            Self::Ttm => "ttm",
+        }
+    }
+}
+
+impl std::str::FromStr for ReportPeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "annual" => Ok(Self::Annual),
+            "quarterly" => Ok(Self::Quarterly),
+            "ttm" => Ok(Self::Ttm),
+            _ => Err(format!("不支持的期间模式: {}（可选 annual, quarterly, ttm）", s)),
+        }
+    }
 }
 
 /// 财务报表基础结构
@@ -28,6 +89,31 @@ pub struct FinancialStatement {
     pub report_date: NaiveDate,
     pub report_type: ReportType,
     pub items: HashMap<String, Decimal>,
+    /// 科目 → 提供该数值的数据源，仅组合数据源填写
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sources: HashMap<String, String>,
+}
+
+impl FinancialStatement {
+    /// 各数据源提供的科目数，按数量从多到少排列；未记录来源时为空
+    pub fn source_counts(&self) -> Vec<(String, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for source in self.sources.values() {
+            *counts.entry(source.as_str()).or_default() += 1;
+        }
+
+        let mut counts: Vec<(String, usize)> = counts
+            .into_iter()
+            .map(|(source, count)| (source.to_string(), count))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
+    }
+
+    /// 提供科目最多的数据源
+    pub fn primary_source(&self) -> Option<String> {
+        self.source_counts().into_iter().next().map(|(source, _)| source)
+    }
 }
 
 /// 资产组
@@ -110,10 +196,40 @@ pub struct AnalysisResult {
     pub years: Vec<i32>,
     pub asset_structure: AssetStructureAnalysis,
     pub profit_analysis: ProfitAnalysis,
+    /// ROE、ROA、资产负债率
+    #[serde(default)]
+    pub strength: StrengthAnalysis,
     pub leverage_analysis: Option<LeverageAnalysis>,
     pub valuation: Option<crate::analyzer::ValuationResult>,
     pub statements: Vec<FinancialStatement>,  // 添加原始报表数据
     pub sensitivity: Option<crate::analyzer::SensitivityResult>,  // 敏感性分析结果
+    #[serde(default)]
+    pub period: ReportPeriod,
+    /// 各期标签（与报表顺序一致），如 2023、2024Q3、TTM 2024Q3
+    #[serde(default)]
+    pub period_labels: Vec<String>,
+    /// 估值使用的参数（含从报表读取的总股本）
+    #[serde(default)]
+    pub valuation_params: Option<crate::analyzer::ValuationParams>,
+    /// 数据验证结果（未启用验证时为空）
+    #[serde(default)]
+    pub validation: Vec<crate::validation::StatementValidation>,
+}
+
+impl AnalysisResult {
+    /// 第 idx 期的表头文字：年报显示为“2023年”，季度/TTM显示期间标签
+    pub fn period_label(&self, idx: usize) -> String {
+        match self.period_labels.get(idx) {
+            Some(label) if label.chars().all(|c| c.is_ascii_digit()) => format!("{}年", label),
+            Some(label) => label.clone(),
+            None => self
+                .asset_structure
+                .years
+                .get(idx)
+                .map(|year| format!("{}年", year))
+                .unwrap_or_default(),
+        }
+    }
 }
 
 /// 资产结构分析
@@ -133,6 +249,15 @@ pub struct ProfitAnalysis {
     pub net_profit_margin: Vec<Decimal>,
 }
 
+/// 盈利与偿债能力，缺少科目或分母不为正的期间为 None
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StrengthAnalysis {
+    pub years: Vec<i32>,
+    pub roe: Vec<Option<Decimal>>,         // 净利润 / 所有者权益合计
+    pub roa: Vec<Option<Decimal>>,         // 净利润 / 资产总计
+    pub debt_ratio: Vec<Option<Decimal>>,  // 负债合计 / 资产总计
+}
+
 /// 杠杆分析
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LeverageAnalysis {
@@ -0,0 +1,266 @@
+//! 股票代码：识别交易所和板块，并按各数据源的格式输出
+//!
+//! 支持的输入格式（不区分大小写）：
+//! - 纯代码：`600519`（A股按代码段推断交易所）、`00700`（5位视为港股）
+//! - 后缀：`600519.SH`、`600519.SS`、`000858.SZ`、`430047.BJ`、`00700.HK`
+//! - 前缀：`sh600519`、`sz000858`、`bj430047`、`hk00700`
+//!
+//! 命令行使用 `StockId`：形如交易所代码的输入必须能识别，其余非空标识（如未上市公司）原样保留，
+//! 由数据源决定能否查询。缓存和报表库以 `stock_key` 为键，同一只股票的不同写法共用一份数据。
+
+use std::fmt;
+use std::str::FromStr;
+
+/// 交易所
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Exchange {
+    /// 上海证券交易所
+    Shanghai,
+    /// 深圳证券交易所
+    Shenzhen,
+    /// 北京证券交易所
+    Beijing,
+    /// 香港联合交易所
+    HongKong,
+}
+
+impl Exchange {
+    /// Tushare 后缀（SH / SZ / BJ / HK）
+    pub fn suffix(&self) -> &str {
+        match self {
+            Self::Shanghai => "SH",
+            Self::Shenzhen => "SZ",
+            Self::Beijing => "BJ",
+            Self::HongKong => "HK",
+        }
+    }
+
+    /// 新浪前缀（sh / sz / bj / hk）
+    pub fn prefix(&self) -> &str {
+        match self {
+            Self::Shanghai => "sh",
+            Self::Shenzhen => "sz",
+            Self::Beijing => "bj",
+            Self::HongKong => "hk",
+        }
+    }
+
+    pub fn display_name(&self) -> &str {
+        match self {
+            Self::Shanghai => "上交所",
+            Self::Shenzhen => "深交所",
+            Self::Beijing => "北交所",
+            Self::HongKong => "港交所",
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        match text.to_ascii_uppercase().as_str() {
+            "SH" | "SS" => Some(Self::Shanghai),
+            "SZ" => Some(Self::Shenzhen),
+            "BJ" => Some(Self::Beijing),
+            "HK" => Some(Self::HongKong),
+            _ => None,
+        }
+    }
+}
+
+/// 板块
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Board {
+    /// 沪深主板
+    Main,
+    /// 科创板（688/689）
+    Star,
+    /// 创业板（300/301）
+    ChiNext,
+    /// 北交所
+    Beijing,
+    /// B股（沪市900、深市200）
+    BShare,
+    /// 港股
+    HongKong,
+}
+
+impl Board {
+    pub fn display_name(&self) -> &str {
+        match self {
+            Self::Main => "主板",
+            Self::Star => "科创板",
+            Self::ChiNext => "创业板",
+            Self::Beijing => "北交所",
+            Self::BShare => "B股",
+            Self::HongKong => "港股",
+        }
+    }
+}
+
+/// 股票代码
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StockCode {
+    symbol: String,
+    exchange: Exchange,
+}
+
+impl StockCode {
+    pub fn exchange(&self) -> Exchange {
+        self.exchange
+    }
+
+    pub fn board(&self) -> Board {
+        let symbol = self.symbol.as_str();
+        match self.exchange {
+            Exchange::HongKong => Board::HongKong,
+            Exchange::Beijing => Board::Beijing,
+            Exchange::Shanghai if symbol.starts_with("688") || symbol.starts_with("689") => Board::Star,
+            Exchange::Shanghai if symbol.starts_with("900") => Board::BShare,
+            Exchange::Shenzhen if symbol.starts_with("300") || symbol.starts_with("301") => Board::ChiNext,
+            Exchange::Shenzhen if symbol.starts_with("200") => Board::BShare,
+            Exchange::Shanghai | Exchange::Shenzhen => Board::Main,
+        }
+    }
+
+    /// Tushare 格式：600519.SH
+    pub fn tushare(&self) -> String {
+        format!("{}.{}", self.symbol, self.exchange.suffix())
+    }
+
+    /// 新浪 / AKShare 格式：sh600519
+    pub fn sina(&self) -> String {
+        format!("{}{}", self.exchange.prefix(), self.symbol)
+    }
+
+    /// 用于文件名：600519_SH
+    pub fn file_stem(&self) -> String {
+        format!("{}_{}", self.symbol, self.exchange.suffix())
+    }
+
+    /// 根据代码段推断交易所（5位为港股）
+    fn infer_exchange(symbol: &str) -> Option<Exchange> {
+        match symbol.len() {
+            5 => Some(Exchange::HongKong),
+            6 => match symbol.as_bytes()[0] {
+                b'6' => Some(Exchange::Shanghai),
+                // 北交所新代码段 920xxx，其余 9 开头为沪市B股
+                b'9' if symbol.starts_with("92") => Some(Exchange::Beijing),
+                b'9' => Some(Exchange::Shanghai),
+                b'0' | b'2' | b'3' => Some(Exchange::Shenzhen),
+                b'4' | b'8' => Some(Exchange::Beijing),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for StockCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        let (symbol, explicit) = if let Some((symbol, suffix)) = text.split_once('.') {
+            let exchange = Exchange::parse(suffix).ok_or_else(|| format!("无法识别的交易所后缀: {}", s))?;
+            (symbol, Some(exchange))
+        } else if text.len() > 2 && text.is_char_boundary(2) && Exchange::parse(&text[..2]).is_some() {
+            (&text[2..], Exchange::parse(&text[..2]))
+        } else {
+            (text, None)
+        };
+
+        if symbol.is_empty() || !symbol.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("无效的股票代码: {}", s));
+        }
+
+        // 港股代码统一补齐为5位
+        let symbol = match explicit {
+            Some(Exchange::HongKong) if symbol.len() < 5 => format!("{:0>5}", symbol),
+            _ => symbol.to_string(),
+        };
+
+        let inferred = Self::infer_exchange(&symbol);
+        let exchange = match (explicit, inferred) {
+            (Some(explicit), Some(inferred)) if explicit == inferred => explicit,
+            (Some(explicit), Some(_)) => {
+                return Err(format!("股票代码 {} 不属于{}", symbol, explicit.display_name()));
+            }
+            (None, Some(inferred)) => inferred,
+            (_, None) => {
+                return Err(format!("无效的股票代码: {}（A股为6位数字，港股为5位数字）", s));
+            }
+        };
+
+        Ok(Self { symbol, exchange })
+    }
+}
+
+impl fmt::Display for StockCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.tushare())
+    }
+}
+
+/// 股票标识：保留输入的写法，可识别为交易所代码时附带解析结果
+///
+/// 纯数字或带交易所前缀/后缀的输入按 `StockCode` 校验，无法识别时报错；
+/// 其余非空标识（如未上市公司 `MYCO`）不校验，供本地文件和报表库使用。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StockId {
+    raw: String,
+    code: Option<StockCode>,
+}
+
+impl StockId {
+    /// 输入的写法（本地文件按此查找子目录）
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    pub fn code(&self) -> Option<&StockCode> {
+        self.code.as_ref()
+    }
+
+    /// 缓存、报表库和匹配用的键，见 `stock_key`
+    pub fn key(&self) -> String {
+        self.code.as_ref().map_or_else(|| self.raw.clone(), StockCode::to_string)
+    }
+
+    /// 去掉交易所前缀/后缀后是否为纯数字，即是否应当是交易所代码
+    fn looks_like_code(text: &str) -> bool {
+        let symbol = match text.split_once('.') {
+            Some((symbol, _)) => symbol,
+            None if text.len() > 2 && text.is_char_boundary(2) && Exchange::parse(&text[..2]).is_some() => &text[2..],
+            None => text,
+        };
+        !symbol.is_empty() && symbol.chars().all(|c| c.is_ascii_digit())
+    }
+}
+
+impl FromStr for StockId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let raw = s.trim();
+        if raw.is_empty() {
+            return Err("股票代码不能为空".to_string());
+        }
+        let code = match raw.parse::<StockCode>() {
+            Ok(code) => Some(code),
+            Err(e) if Self::looks_like_code(raw) => return Err(e),
+            Err(_) => None,
+        };
+        Ok(Self { raw: raw.to_string(), code })
+    }
+}
+
+impl fmt::Display for StockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+/// 股票标识的统一键：可识别的代码统一为 600519.SH，其余标识去掉首尾空白后保留原写法
+pub fn stock_key(text: &str) -> String {
+    text.parse::<StockCode>()
+        .map(|code| code.to_string())
+        .unwrap_or_else(|_| text.trim().to_string())
+}
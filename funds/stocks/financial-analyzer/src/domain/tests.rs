@@ -0,0 +1,80 @@
+use super::stock_code::Board;
+use super::*;
+
+fn parse(text: &str) -> StockCode {
+    text.parse().unwrap_or_else(|e| panic!("{}: {}", text, e))
+}
+
+#[test]
+fn test_stock_code_formats() {
+    for text in ["600519", "600519.SH", "600519.ss", "sh600519", "SH600519", " 600519.sh "] {
+        let code = parse(text);
+        assert_eq!(code.exchange(), Exchange::Shanghai, "{}", text);
+        assert_eq!(code.tushare(), "600519.SH");
+        assert_eq!(code.sina(), "sh600519");
+        assert_eq!(code.file_stem(), "600519_SH");
+        assert_eq!(code.to_string(), "600519.SH");
+    }
+
+    let hk = parse("00700.HK");
+    assert_eq!(hk.exchange(), Exchange::HongKong);
+    assert_eq!(hk.sina(), "hk00700");
+    assert_eq!(parse("700.hk"), hk);
+    assert_eq!(parse("hk00700"), hk);
+    assert_eq!(parse("00700"), hk);
+}
+
+#[test]
+fn test_stock_code_exchange_and_board() {
+    let cases = [
+        ("600519", Exchange::Shanghai, Board::Main),
+        ("688981", Exchange::Shanghai, Board::Star),
+        ("900901", Exchange::Shanghai, Board::BShare),
+        ("000858", Exchange::Shenzhen, Board::Main),
+        ("002594", Exchange::Shenzhen, Board::Main),
+        ("300750", Exchange::Shenzhen, Board::ChiNext),
+        ("200002", Exchange::Shenzhen, Board::BShare),
+        ("430047", Exchange::Beijing, Board::Beijing),
+        ("830799", Exchange::Beijing, Board::Beijing),
+        ("920002", Exchange::Beijing, Board::Beijing),
+    ];
+
+    for (text, exchange, board) in cases {
+        let code = parse(text);
+        assert_eq!(code.exchange(), exchange, "{}", text);
+        assert_eq!(code.board(), board, "{}", text);
+    }
+    assert_eq!(parse("430047").sina(), "bj430047");
+    assert_eq!(parse("830799").tushare(), "830799.BJ");
+}
+
+#[test]
+fn test_stock_code_rejects_invalid() {
+    for text in ["", "abc", "6005", "6005190", "600519.XX", "600519.SZ", "000858.SH", "123456", "sh00700", "600519.HK"] {
+        assert!(text.parse::<StockCode>().is_err(), "{} 应解析失败", text);
+    }
+
+    let err = "600519.SZ".parse::<StockCode>().unwrap_err();
+    assert!(err.contains("深交所"), "{}", err);
+}
+
+#[test]
+fn test_stock_id_keeps_spelling_and_rejects_malformed_codes() {
+    let id: StockId = " sh600519 ".parse().unwrap();
+    assert_eq!(id.as_str(), "sh600519");
+    assert_eq!(id.key(), "600519.SH");
+    assert_eq!(id.code().map(StockCode::exchange), Some(Exchange::Shanghai));
+
+    // 非交易所代码的标识原样保留
+    let id: StockId = "MYCO".parse().unwrap();
+    assert!(id.code().is_none());
+    assert_eq!(id.key(), "MYCO");
+
+    // 形如交易所代码但无法识别的输入报错
+    for text in ["", " ", "6005", "600519.XX", "600519.SZ", "sh00700", "123456"] {
+        assert!(text.parse::<StockId>().is_err(), "{} 应解析失败", text);
+    }
+
+    assert_eq!(stock_key("600519"), stock_key("sh600519"));
+    assert_eq!(stock_key(" MYCO "), "MYCO");
+}
@@ -0,0 +1,136 @@
+//! 批量分析汇总工作簿
+//! 汇总表：每只股票最新一期的关键比率和估值；失败列表：未能完成分析的股票及原因
+
+use super::common::ExcelFormats;
+use super::SheetBuilder;
+use crate::batch::BatchReport;
+use crate::domain::*;
+use anyhow::Result;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_xlsxwriter::*;
+use std::path::Path;
+
+pub const SHEET_SUMMARY: &str = "批量汇总";
+pub const SHEET_FAILURES: &str = "失败列表";
+
+/// 列标题及格式（true 为百分比）
+const COLUMNS: [(&str, bool); 10] = [
+    ("毛利率", true),
+    ("核心利润率", true),
+    ("净利润率", true),
+    ("ROE", true),
+    ("资产负债率", true),
+    ("DCF每股价值", false),
+    ("唐朝低估价", false),
+    ("唐朝高估价", false),
+    ("唐朝安全边际价", false),
+    ("折现率", true),
+];
+
+/// 批量分析汇总报告生成器
+pub struct BatchSummaryWriter;
+
+impl BatchSummaryWriter {
+    pub fn generate(report: &BatchReport, output_path: &Path) -> Result<()> {
+        let mut workbook = Self::build(report)?;
+        workbook.save(output_path)?;
+        Ok(())
+    }
+
+    pub(super) fn build(report: &BatchReport) -> Result<Workbook> {
+        let builder = SheetBuilder::new(&format!("批量分析 {} 只", report.results.len() + report.failures.len()));
+        let formats = ExcelFormats::new();
+        let mut workbook = Workbook::new();
+        Self::write_summary(workbook.add_worksheet(), report, &builder, &formats)?;
+        Self::write_failures(workbook.add_worksheet(), report, &builder, &formats)?;
+        Ok(workbook)
+    }
+
+    fn write_summary(worksheet: &mut Worksheet, report: &BatchReport, builder: &SheetBuilder, formats: &ExcelFormats) -> Result<()> {
+        worksheet.set_name(SHEET_SUMMARY)?;
+        let mut row = builder.write_header(worksheet, SHEET_SUMMARY)?;
+
+        worksheet.write_string_with_format(row, 0, "股票代码", &formats.header)?;
+        worksheet.write_string_with_format(row, 1, "最新报告期", &formats.header)?;
+        for (i, (title, _)) in COLUMNS.iter().enumerate() {
+            worksheet.write_string_with_format(row, 2 + i as u16, *title, &formats.header)?;
+        }
+        worksheet.set_freeze_panes(row + 1, 1)?;
+        row += 1;
+
+        for result in &report.results {
+            worksheet.write_string(row, 0, &result.stock_code)?;
+            worksheet.write_string(row, 1, result.period_label(0))?;
+            for (i, value) in Self::latest_values(result).iter().enumerate() {
+                if let Some(value) = value {
+                    let format = if COLUMNS[i].1 { &formats.percent } else { &formats.number };
+                    worksheet.write_number_with_format(row, 2 + i as u16, *value, format)?;
+                }
+            }
+            row += 1;
+        }
+
+        worksheet.set_column_width(0, 14)?;
+        worksheet.set_column_width(1, 14)?;
+        for col in 2..2 + COLUMNS.len() as u16 {
+            worksheet.set_column_width(col, 16)?;
+        }
+        Ok(())
+    }
+
+    /// 与 `COLUMNS` 对应的最新一期数值
+    fn latest_values(result: &AnalysisResult) -> [Option<f64>; 10] {
+        let first = |values: &[Decimal]| values.first().and_then(|v| v.to_f64());
+        let first_opt = |values: &[Option<Decimal>]| values.first().copied().flatten().and_then(|v| v.to_f64());
+
+        // 敏感性分析使用清单中的估值参数，优先于默认参数的估值
+        let (prices, discount_rate) = match (&result.sensitivity, &result.valuation) {
+            (Some(s), _) => (
+                [s.dcf_price_per_share, s.tangchao_low_estimate, s.tangchao_high_estimate, s.tangchao_safety_margin_price].map(|v| v.to_f64()),
+                Some(s.params.discount_rate),
+            ),
+            (None, Some(v)) => (
+                [v.dcf.price_per_share, v.tangchao.low_estimate, v.tangchao.high_estimate, v.tangchao.safety_margin_price].map(|v| v.to_f64()),
+                result.valuation_params.as_ref().map(|p| p.dcf.discount_rate),
+            ),
+            (None, None) => ([None; 4], None),
+        };
+
+        let profit = &result.profit_analysis;
+        [
+            first(&profit.gross_margin),
+            first(&profit.core_profit_margin),
+            first(&profit.net_profit_margin),
+            first_opt(&result.strength.roe),
+            first_opt(&result.strength.debt_ratio),
+            prices[0],
+            prices[1],
+            prices[2],
+            prices[3],
+            discount_rate,
+        ]
+    }
+
+    fn write_failures(worksheet: &mut Worksheet, report: &BatchReport, builder: &SheetBuilder, formats: &ExcelFormats) -> Result<()> {
+        worksheet.set_name(SHEET_FAILURES)?;
+        let mut row = builder.write_header(worksheet, SHEET_FAILURES)?;
+
+        worksheet.write_string_with_format(row, 0, "股票代码", &formats.header)?;
+        worksheet.write_string_with_format(row, 1, "失败原因", &formats.header)?;
+        row += 1;
+
+        if report.failures.is_empty() {
+            worksheet.write_string(row, 0, "全部分析成功")?;
+        }
+        for failure in &report.failures {
+            worksheet.write_string(row, 0, &failure.code)?;
+            worksheet.write_string(row, 1, &failure.reason)?;
+            row += 1;
+        }
+
+        worksheet.set_column_width(0, 14)?;
+        worksheet.set_column_width(1, 100)?;
+        Ok(())
+    }
+}
@@ -2,4 +2,4 @@
 
 pub mod formats;
 
-pub use formats::{ExcelFormats, set_standard_column_widths, set_standard_row_heights};
+pub use formats::ExcelFormats;
@@ -0,0 +1,219 @@
+//! 多公司对比工作簿
+//! 指标对比、指标排名、共同比报表、估值对比四个工作表
+//!
+//! 前三个工作表按报告期分组、每组内各公司并列（最新一期在左），便于横向比较同行。
+
+use super::common::ExcelFormats;
+use super::SheetBuilder;
+use crate::analyzer::{Comparison, Direction};
+use anyhow::Result;
+use rust_decimal::prelude::ToPrimitive;
+use rust_xlsxwriter::*;
+use std::path::Path;
+
+pub const SHEET_METRICS: &str = "指标对比";
+pub const SHEET_RANKS: &str = "指标排名";
+pub const SHEET_COMMON_SIZE: &str = "共同比报表";
+pub const SHEET_VALUATION: &str = "估值对比";
+
+/// 多公司对比报告生成器
+pub struct ComparisonWriter;
+
+impl ComparisonWriter {
+    pub fn generate(comparison: &Comparison, output_path: &Path) -> Result<()> {
+        let mut workbook = Self::build(comparison)?;
+        workbook.save(output_path)?;
+        Ok(())
+    }
+
+    pub(super) fn build(comparison: &Comparison) -> Result<Workbook> {
+        let title = comparison.stock_codes().join(" vs ");
+        let builder = SheetBuilder::new(&title);
+        let formats = ExcelFormats::new();
+        let mut workbook = Workbook::new();
+
+        Self::write_metrics(workbook.add_worksheet(), comparison, &builder, &formats)?;
+        Self::write_ranks(workbook.add_worksheet(), comparison, &builder, &formats)?;
+        Self::write_common_size(workbook.add_worksheet(), comparison, &builder, &formats)?;
+        Self::write_valuation(workbook.add_worksheet(), comparison, &builder, &formats)?;
+        Ok(workbook)
+    }
+
+    /// 报告期分组表头（两行），返回数据起始行
+    fn write_period_header(worksheet: &mut Worksheet, row: u32, first: &str, comparison: &Comparison, formats: &ExcelFormats) -> Result<u32> {
+        let codes = comparison.stock_codes();
+        let width = codes.len() as u16;
+        worksheet.merge_range(row, 0, row + 1, 0, first, &formats.header)?;
+        for (p, period) in comparison.periods.iter().enumerate() {
+            let col = 1 + p as u16 * width;
+            if width > 1 {
+                worksheet.merge_range(row, col, row, col + width - 1, &period.label, &formats.header)?;
+            } else {
+                worksheet.write_string_with_format(row, col, &period.label, &formats.header)?;
+            }
+            for (c, code) in codes.iter().enumerate() {
+                worksheet.write_string_with_format(row + 1, col + c as u16, *code, &formats.subheader)?;
+            }
+        }
+
+        worksheet.set_column_width(0, 28)?;
+        for col in 1..=(comparison.periods.len() as u16 * width) {
+            worksheet.set_column_width(col, 16)?;
+        }
+        worksheet.set_freeze_panes(row + 2, 1)?;
+        Ok(row + 2)
+    }
+
+    /// 第 p 期第 c 家公司的数据列
+    fn column(comparison: &Comparison, p: usize, c: usize) -> u16 {
+        1 + (p * comparison.results.len() + c) as u16
+    }
+
+    fn write_metrics(worksheet: &mut Worksheet, comparison: &Comparison, builder: &SheetBuilder, formats: &ExcelFormats) -> Result<()> {
+        worksheet.set_name(SHEET_METRICS)?;
+        let row = builder.write_header(worksheet, SHEET_METRICS)?;
+        let mut row = Self::write_period_header(worksheet, row, "指标（黄色为同期最优）", comparison, formats)?;
+
+        let times_fmt = Format::new().set_num_format("0.00").set_border(FormatBorder::Thin);
+        for metric in comparison.metrics() {
+            worksheet.write_string(row, 0, metric.name)?;
+            for (p, (values, ranks)) in metric.values.iter().zip(&metric.ranks).enumerate() {
+                for (c, value) in values.iter().enumerate() {
+                    let Some(value) = value else { continue };
+                    let best = ranks[c] == Some(1);
+                    let format = match (metric.percent, best) {
+                        (true, true) => &formats.highlight,
+                        (true, false) => &formats.percent,
+                        (false, true) => &formats.highlight_number,
+                        (false, false) => &times_fmt,
+                    };
+                    worksheet.write_number_with_format(row, Self::column(comparison, p, c), *value, format)?;
+                }
+            }
+            row += 1;
+        }
+        Ok(())
+    }
+
+    fn write_ranks(worksheet: &mut Worksheet, comparison: &Comparison, builder: &SheetBuilder, formats: &ExcelFormats) -> Result<()> {
+        worksheet.set_name(SHEET_RANKS)?;
+        let row = builder.write_header(worksheet, SHEET_RANKS)?;
+        let mut row = Self::write_period_header(worksheet, row, "指标（1为最优）", comparison, formats)?;
+
+        let rank_fmt = Format::new().set_align(FormatAlign::Center).set_border(FormatBorder::Thin);
+        let average_fmt = Format::new()
+            .set_num_format("0.00")
+            .set_align(FormatAlign::Center)
+            .set_bold()
+            .set_background_color(Color::RGB(0xD9E1F2))
+            .set_border(FormatBorder::Thin);
+
+        let metrics = comparison.metrics();
+        let first_row = row;
+        for metric in &metrics {
+            let direction = match metric.direction {
+                Direction::Higher => "越高越好",
+                Direction::Lower => "越低越好",
+            };
+            worksheet.write_string(row, 0, format!("{}（{}）", metric.name, direction))?;
+            for (p, ranks) in metric.ranks.iter().enumerate() {
+                for (c, rank) in ranks.iter().enumerate() {
+                    if let Some(rank) = rank {
+                        worksheet.write_number_with_format(row, Self::column(comparison, p, c), *rank as f64, &rank_fmt)?;
+                    }
+                }
+            }
+            row += 1;
+        }
+
+        // 平均名次：对上方排名取平均，缺失的排名不计入
+        worksheet.write_string_with_format(row, 0, "平均名次", &formats.subheader)?;
+        for p in 0..comparison.periods.len() {
+            for c in 0..comparison.results.len() {
+                let col = Self::column(comparison, p, c);
+                let range = utility::cell_range(first_row, col, row - 1, col);
+                worksheet.write_formula_with_format(row, col, format!("=IFERROR(AVERAGE({}),\"\")", range).as_str(), &average_fmt)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_common_size(worksheet: &mut Worksheet, comparison: &Comparison, builder: &SheetBuilder, formats: &ExcelFormats) -> Result<()> {
+        worksheet.set_name(SHEET_COMMON_SIZE)?;
+        let row = builder.write_header(worksheet, SHEET_COMMON_SIZE)?;
+        let mut row = Self::write_period_header(worksheet, row, "科目", comparison, formats)?;
+
+        for section in comparison.common_size() {
+            worksheet.write_string_with_format(row, 0, format!("{}（占{}）", section.title, section.base), &formats.subheader)?;
+            row += 1;
+            for item in &section.rows {
+                worksheet.write_string(row, 0, item.item)?;
+                for (p, values) in item.values.iter().enumerate() {
+                    for (c, value) in values.iter().enumerate() {
+                        if let Some(value) = value {
+                            worksheet.write_number_with_format(row, Self::column(comparison, p, c), *value, &formats.percent)?;
+                        }
+                    }
+                }
+                row += 1;
+            }
+            row += 1;
+        }
+        Ok(())
+    }
+
+    fn write_valuation(worksheet: &mut Worksheet, comparison: &Comparison, builder: &SheetBuilder, formats: &ExcelFormats) -> Result<()> {
+        worksheet.set_name(SHEET_VALUATION)?;
+        let mut row = builder.write_header(worksheet, SHEET_VALUATION)?;
+
+        let headers = ["股票代码", "DCF企业价值", "DCF每股价值", "唐朝低估价", "唐朝高估价", "唐朝安全边际价", "折现率", "总股本"];
+        for (col, header) in headers.iter().enumerate() {
+            worksheet.write_string_with_format(row, col as u16, *header, &formats.header)?;
+        }
+        row += 1;
+
+        for result in comparison.results {
+            worksheet.write_string(row, 0, &result.stock_code)?;
+            // 敏感性分析使用命令行调整后的参数，优先于默认参数的估值
+            let prices = match (&result.sensitivity, &result.valuation) {
+                (Some(s), _) => Some((
+                    [
+                        s.dcf_enterprise_value,
+                        s.dcf_price_per_share,
+                        s.tangchao_low_estimate,
+                        s.tangchao_high_estimate,
+                        s.tangchao_safety_margin_price,
+                    ],
+                    s.params.discount_rate,
+                )),
+                (None, Some(v)) => Some((
+                    [
+                        v.dcf.enterprise_value,
+                        v.dcf.price_per_share,
+                        v.tangchao.low_estimate,
+                        v.tangchao.high_estimate,
+                        v.tangchao.safety_margin_price,
+                    ],
+                    result.valuation_params.as_ref().map_or(0.0, |p| p.dcf.discount_rate),
+                )),
+                (None, None) => None,
+            };
+            if let Some((values, discount_rate)) = prices {
+                for (offset, value) in values.iter().enumerate() {
+                    worksheet.write_number_with_format(row, 1 + offset as u16, value.to_f64().unwrap_or(0.0), &formats.number)?;
+                }
+                worksheet.write_number_with_format(row, 6, discount_rate, &formats.percent)?;
+            }
+            if let Some(params) = &result.valuation_params {
+                worksheet.write_number_with_format(row, 7, params.total_shares.to_f64().unwrap_or(0.0), &formats.number)?;
+            }
+            row += 1;
+        }
+
+        worksheet.set_column_width(0, 16)?;
+        for col in 1..headers.len() as u16 {
+            worksheet.set_column_width(col, 20)?;
+        }
+        Ok(())
+    }
+}
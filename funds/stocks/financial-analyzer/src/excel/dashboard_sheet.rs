@@ -0,0 +1,189 @@
+//! 图表看板Sheet
+//! 资产结构堆积柱形图、利润率折线图、经营现金流与净利润对比柱形图、估值区间条形图
+//!
+//! 图表数据写在看板上方的数据区（按时间从早到晚排列），图表放在数据区下方。
+
+use crate::domain::*;
+use crate::excel::{DataHelper, SheetBuilder};
+use anyhow::Result;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_xlsxwriter::*;
+
+pub const SHEET_DASHBOARD: &str = "图表看板";
+
+/// 每行图表占用的行数
+const CHART_ROWS: u32 = 16;
+/// 第二列图表的起始列
+const CHART_RIGHT_COL: u16 = 6;
+
+pub fn write_dashboard_sheet(worksheet: &mut Worksheet, result: &AnalysisResult, stock_code: &str) -> Result<()> {
+    worksheet.set_name(SHEET_DASHBOARD)?;
+
+    let builder = SheetBuilder::new(stock_code);
+    let mut row = builder.write_header(worksheet, SHEET_DASHBOARD)?;
+
+    let subheader_fmt = Format::new()
+        .set_bold()
+        .set_background_color(Color::RGB(0xD9E1F2))
+        .set_border(FormatBorder::Thin);
+    let number_fmt = Format::new().set_num_format("#,##0.00");
+    let percent_fmt = Format::new().set_num_format("0.00%");
+
+    // 分析结果最新一期在前，图表按时间顺序从左到右
+    let num_years = result.asset_structure.years.len();
+    let periods: Vec<usize> = (0..num_years).rev().collect();
+    let last_col = num_years as u16;
+
+    // ========== 数据区 ==========
+    let category_row = row;
+    worksheet.write_string_with_format(row, 0, "期间", &subheader_fmt)?;
+    for (col, &i) in periods.iter().enumerate() {
+        worksheet.write_string_with_format(row, 1 + col as u16, result.period_label(i), &subheader_fmt)?;
+    }
+    row += 1;
+
+    let asset = &result.asset_structure;
+    let profit = &result.profit_analysis;
+    let ratio_rows: [(&str, &[Decimal]); 5] = [
+        ("经营性资产占比", &asset.operating_asset_ratio),
+        ("金融性资产占比", &asset.financial_asset_ratio),
+        ("毛利率", &profit.gross_margin),
+        ("核心利润率", &profit.core_profit_margin),
+        ("净利润率", &profit.net_profit_margin),
+    ];
+    let ratio_start = row;
+    for (label, values) in ratio_rows {
+        worksheet.write_string(row, 0, label)?;
+        for (col, &i) in periods.iter().enumerate() {
+            if let Some(value) = values.get(i).and_then(|v| v.to_f64()) {
+                worksheet.write_number_with_format(row, 1 + col as u16, value, &percent_fmt)?;
+            }
+        }
+        row += 1;
+    }
+
+    let data = DataHelper::new(&result.statements);
+    let cashflow_start = row;
+    worksheet.write_string(row, 0, "经营活动现金流净额")?;
+    worksheet.write_string(row + 1, 0, "净利润")?;
+    for (col, &i) in periods.iter().enumerate() {
+        let col = 1 + col as u16;
+        if let Some(value) = data.get_cashflow_opt(i, "经营活动产生的现金流量净额") {
+            worksheet.write_number_with_format(row, col, value, &number_fmt)?;
+        }
+        if let Some(value) = data.get_income_opt(i, "净利润") {
+            worksheet.write_number_with_format(row + 1, col, value, &number_fmt)?;
+        }
+    }
+    row += 3;
+
+    let valuation = valuation_prices(result);
+    let valuation_start = row;
+    if let Some(prices) = &valuation {
+        worksheet.write_string_with_format(row, 0, "估值（元/股）", &subheader_fmt)?;
+        worksheet.write_string_with_format(row, 1, "价格", &subheader_fmt)?;
+        for (offset, (label, price)) in prices.iter().enumerate() {
+            worksheet.write_string(row + 1 + offset as u32, 0, *label)?;
+            worksheet.write_number_with_format(row + 1 + offset as u32, 1, *price, &number_fmt)?;
+        }
+        row += 1 + prices.len() as u32 + 1;
+    }
+
+    // ========== 图表 ==========
+    let categories = (SHEET_DASHBOARD, category_row, 1, category_row, last_col);
+    let series_row = |row: u32| (SHEET_DASHBOARD, row, 1, row, last_col);
+    let series_name = |row: u32| (SHEET_DASHBOARD, row, 0);
+
+    // 资产结构
+    let mut asset_chart = Chart::new(ChartType::ColumnStacked);
+    asset_chart.title().set_name("资产结构");
+    for offset in 0..2 {
+        asset_chart
+            .add_series()
+            .set_name(series_name(ratio_start + offset))
+            .set_categories(categories)
+            .set_values(series_row(ratio_start + offset));
+    }
+    asset_chart.y_axis().set_num_format("0%");
+    asset_chart.legend().set_position(ChartLegendPosition::Bottom);
+    worksheet.insert_chart(row, 0, &asset_chart)?;
+
+    // 利润率趋势
+    let mut margin_chart = Chart::new(ChartType::Line);
+    margin_chart.title().set_name("利润率趋势");
+    for offset in 2..5 {
+        margin_chart
+            .add_series()
+            .set_name(series_name(ratio_start + offset))
+            .set_categories(categories)
+            .set_values(series_row(ratio_start + offset))
+            .set_marker(ChartMarker::new().set_automatic());
+    }
+    margin_chart.y_axis().set_num_format("0%");
+    margin_chart.legend().set_position(ChartLegendPosition::Bottom);
+    worksheet.insert_chart(row, CHART_RIGHT_COL, &margin_chart)?;
+
+    // 经营现金流与净利润
+    let mut cashflow_chart = Chart::new(ChartType::Column);
+    cashflow_chart.title().set_name("经营现金流 vs 净利润");
+    for offset in 0..2 {
+        cashflow_chart
+            .add_series()
+            .set_name(series_name(cashflow_start + offset))
+            .set_categories(categories)
+            .set_values(series_row(cashflow_start + offset));
+    }
+    cashflow_chart.y_axis().set_num_format("#,##0");
+    cashflow_chart.legend().set_position(ChartLegendPosition::Bottom);
+    worksheet.insert_chart(row + CHART_ROWS, 0, &cashflow_chart)?;
+
+    // 估值区间
+    if let Some(prices) = &valuation {
+        let last_row = valuation_start + prices.len() as u32;
+        let mut valuation_chart = Chart::new(ChartType::Bar);
+        valuation_chart.title().set_name("估值区间（元/股）");
+        valuation_chart
+            .add_series()
+            .set_name((SHEET_DASHBOARD, valuation_start, 1))
+            .set_categories((SHEET_DASHBOARD, valuation_start + 1, 0, last_row, 0))
+            .set_values((SHEET_DASHBOARD, valuation_start + 1, 1, last_row, 1))
+            .set_data_label(ChartDataLabel::new().show_value().set_num_format("#,##0.00"));
+        valuation_chart.legend().set_hidden();
+        worksheet.insert_chart(row + CHART_ROWS, CHART_RIGHT_COL, &valuation_chart)?;
+    }
+
+    // 设置列宽
+    worksheet.set_column_width(0, 20)?;
+    for col in 1..=last_col.max(1) {
+        worksheet.set_column_width(col, 16)?;
+    }
+
+    Ok(())
+}
+
+/// 估值价格：优先使用敏感性分析（命令行参数调整后）的结果
+fn valuation_prices(result: &AnalysisResult) -> Option<[(&'static str, f64); 4]> {
+    let (dcf, low, high, safety) = match (&result.sensitivity, &result.valuation) {
+        (Some(s), _) => (
+            s.dcf_price_per_share,
+            s.tangchao_low_estimate,
+            s.tangchao_high_estimate,
+            s.tangchao_safety_margin_price,
+        ),
+        (None, Some(v)) => (
+            v.dcf.price_per_share,
+            v.tangchao.low_estimate,
+            v.tangchao.high_estimate,
+            v.tangchao.safety_margin_price,
+        ),
+        (None, None) => return None,
+    };
+    let price = |value: Decimal| value.to_f64().unwrap_or(0.0);
+    Some([
+        ("安全边际价", price(safety)),
+        ("唐朝低估价", price(low)),
+        ("DCF每股价值", price(dcf)),
+        ("唐朝高估价", price(high)),
+    ])
+}
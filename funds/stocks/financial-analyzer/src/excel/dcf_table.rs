@@ -0,0 +1,163 @@
+//! DCF逐年预测表：增长率为可编辑输入，预测FCF、折现系数和现值为公式，与 `Valuator::calculate_dcf` 的口径一致
+
+use crate::analyzer::{DCFValuation, SensitivityResult};
+use crate::domain::AnalysisResult;
+use anyhow::Result;
+use rust_decimal::prelude::ToPrimitive;
+use rust_xlsxwriter::{utility, Format, Worksheet};
+
+/// 工作簿展示的DCF：有敏感性分析时取其参数和结果，否则取默认参数的估值
+pub(crate) struct DisplayedDcf<'a> {
+    pub dcf: &'a DCFValuation,
+    pub discount_rate: f64,
+    pub perpetual_growth_rate: f64,
+    pub base_fcf_years: usize,
+    /// 是否按设定的预测阶段逐年增长（否则各年都按FCF增长率）
+    pub explicit_stages: bool,
+}
+
+impl<'a> DisplayedDcf<'a> {
+    pub fn from_result(result: &'a AnalysisResult) -> Option<Self> {
+        match (&result.sensitivity, &result.valuation, &result.valuation_params) {
+            (Some(SensitivityResult { dcf_detail: Some(dcf), params, .. }), _, _) => Some(Self {
+                dcf,
+                discount_rate: params.discount_rate,
+                perpetual_growth_rate: params.perpetual_growth_rate,
+                base_fcf_years: params.base_fcf_years,
+                explicit_stages: !params.forecast_stages.is_empty(),
+            }),
+            (_, Some(valuation), Some(params)) => Some(Self {
+                dcf: &valuation.dcf,
+                discount_rate: params.dcf.discount_rate,
+                perpetual_growth_rate: params.dcf.perpetual_growth_rate,
+                base_fcf_years: params.dcf.base_fcf_years,
+                explicit_stages: !params.dcf.stages.is_empty(),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn base_label(&self) -> String {
+        base_fcf_label(self.base_fcf_years)
+    }
+}
+
+pub(crate) fn base_fcf_label(base_fcf_years: usize) -> String {
+    format!("基准FCF({})", base_fcf_period(base_fcf_years))
+}
+
+/// 基准FCF的取值区间：“最近一年”或“近N年平均”
+pub(crate) fn base_fcf_period(base_fcf_years: usize) -> String {
+    if base_fcf_years > 1 {
+        format!("近{}年平均", base_fcf_years)
+    } else {
+        "最近一年".to_string()
+    }
+}
+
+/// 可编辑的逐年增长率：未设定预测阶段时各年引用 fcf_growth 单元格，否则为各阶段展开后的逐年增长率；
+/// 没有DCF结果时按3年预测
+pub(crate) fn growth_inputs(displayed: Option<&DisplayedDcf>, fcf_growth: &str) -> Vec<(u32, GrowthInput)> {
+    match displayed {
+        Some(displayed) if displayed.explicit_stages => {
+            displayed.dcf.projections.iter().map(|p| (p.year, GrowthInput::Rate(p.growth_rate))).collect()
+        }
+        Some(displayed) => displayed.dcf.projections.iter().map(|p| (p.year, GrowthInput::Cell(fcf_growth.to_string()))).collect(),
+        None => (1..=3).map(|year| (year, GrowthInput::Cell(fcf_growth.to_string()))).collect(),
+    }
+}
+
+/// 估值使用的总股本：优先取估值参数中的股本，否则从最新资产负债表读取，都没有时按1亿股
+pub(crate) fn total_shares(result: &AnalysisResult) -> f64 {
+    if let Some(shares) = result.valuation_params.as_ref().and_then(|params| params.total_shares.to_f64()) {
+        return shares;
+    }
+    let data = super::DataHelper::new(&result.statements);
+    [data.get_balance(0, "股本"), data.get_balance(0, "实收资本(或股本)")]
+        .into_iter()
+        .find(|value| *value > 0.0)
+        .unwrap_or(100_000_000.0)
+}
+
+/// 某一预测年的FCF增长率：直接写入数值，或引用参数单元格
+pub(crate) enum GrowthInput {
+    Rate(f64),
+    Cell(String),
+}
+
+/// 预测表引用的参数单元格
+pub(crate) struct DcfTableInputs {
+    pub base_fcf: String,
+    pub discount_rate: String,
+    pub perpetual_growth: String,
+}
+
+/// 已写入的预测表位置，value_col 为现值列
+pub(crate) struct DcfTable {
+    pub first_row: u32,
+    pub terminal_row: u32,
+    pub value_col: u16,
+}
+
+impl DcfTable {
+    /// 显式预测期现值之和；没有预测年时为0
+    pub fn forecast_sum(&self) -> String {
+        if self.terminal_row == self.first_row {
+            "=0".to_string()
+        } else {
+            format!("=SUM({})", utility::cell_range(self.first_row, self.value_col, self.terminal_row - 1, self.value_col))
+        }
+    }
+
+    pub fn terminal_pv(&self) -> String {
+        utility::row_col_to_cell(self.terminal_row, self.value_col)
+    }
+}
+
+/// 自 header_row 起写入表头、逐年预测和永续价值，列依次为预测年、FCF增长率、预测FCF、折现系数、现值
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn write_dcf_table(
+    worksheet: &mut Worksheet,
+    header_row: u32,
+    col: u16,
+    growth: &[(u32, GrowthInput)],
+    inputs: &DcfTableInputs,
+    number_fmt: &Format,
+    percent_fmt: &Format,
+    subheader_fmt: &Format,
+) -> Result<DcfTable> {
+    for (i, title) in ["预测年", "FCF增长率", "预测FCF", "折现系数", "现值"].iter().enumerate() {
+        worksheet.write_string_with_format(header_row, col + i as u16, *title, subheader_fmt)?;
+    }
+    let (growth_col, fcf_col, factor_col, value_col) = (col + 1, col + 2, col + 3, col + 4);
+    let DcfTableInputs { base_fcf, discount_rate: r, perpetual_growth: g } = inputs;
+
+    let first_row = header_row + 1;
+    let mut row = first_row;
+    let mut previous_fcf = base_fcf.clone();
+    let mut previous_factor = "1".to_string();
+    for (year, input) in growth {
+        let cell = |col: u16| utility::row_col_to_cell(row, col);
+        worksheet.write_string(row, col, format!("第{}年", year))?;
+        match input {
+            GrowthInput::Rate(rate) => worksheet.write_number_with_format(row, growth_col, *rate, percent_fmt)?,
+            GrowthInput::Cell(reference) => worksheet.write_formula_with_format(row, growth_col, format!("={}", reference).as_str(), percent_fmt)?,
+        };
+        let fcf = format!("={}*(1+{})", previous_fcf, cell(growth_col));
+        worksheet.write_formula_with_format(row, fcf_col, fcf.as_str(), number_fmt)?;
+        worksheet.write_formula_with_format(row, factor_col, format!("=1/POWER(1+{},{})", r, year).as_str(), number_fmt)?;
+        worksheet.write_formula_with_format(row, value_col, format!("={}*{}", cell(fcf_col), cell(factor_col)).as_str(), number_fmt)?;
+        previous_fcf = cell(fcf_col);
+        previous_factor = cell(factor_col);
+        row += 1;
+    }
+
+    // 永续价值 = 预测期末FCF × (1+g) / (r-g)，按预测期末的折现系数折现
+    worksheet.write_string(row, col, "永续价值")?;
+    worksheet.write_formula_with_format(row, fcf_col, format!("={}*(1+{})/({}-{})", previous_fcf, g, r, g).as_str(), number_fmt)?;
+    worksheet.write_formula_with_format(row, factor_col, format!("={}", previous_factor).as_str(), number_fmt)?;
+    let terminal = format!("={}*{}", utility::row_col_to_cell(row, fcf_col), utility::row_col_to_cell(row, factor_col));
+    worksheet.write_formula_with_format(row, value_col, terminal.as_str(), number_fmt)?;
+
+    Ok(DcfTable { first_row, terminal_row: row, value_col })
+}
@@ -46,9 +46,14 @@ impl IndicatorDescriptions {
         
         // 估值指标
         descriptions.insert("DCF企业价值".to_string(), "现金流折现价值".to_string());
-        descriptions.insert("DCF每股价值".to_string(), "企业价值/总股本".to_string());
+        descriptions.insert("DCF每股价值".to_string(), "股权价值/总股本".to_string());
         descriptions.insert("唐朝低估价".to_string(), "保守买入价".to_string());
         descriptions.insert("唐朝高估价".to_string(), "乐观卖出价".to_string());
+        descriptions.insert("股权成本(CAPM)".to_string(), "无风险收益率+β×股权风险溢价".to_string());
+        descriptions.insert("税后债务成本".to_string(), "财务费用/有息负债×(1-税率)".to_string());
+        descriptions.insert("WACC".to_string(), "按资本权重加权的资本成本".to_string());
+        descriptions.insert("DDM每股价值".to_string(), "股利两阶段折现/总股本".to_string());
+        descriptions.insert("FCFE每股价值".to_string(), "股权自由现金流折现/总股本".to_string());
         
         Self { descriptions }
     }
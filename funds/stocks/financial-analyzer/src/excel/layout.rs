@@ -0,0 +1,150 @@
+//! 工作表布局登记：各Sheet按键登记数据所在的行，跨表公式由键生成
+//!
+//! 每个键对应一行按期排列的数据（最新一期在前，从 `first_col` 起向右），
+//! 工作簿为每个键定义同名的命名区域，例如
+//! `bs_total_assets` → `'(经营性&金融性)资产&负债结构分析'!$C$21:$G$21`。
+//! 其他Sheet通过 `INDEX(bs_total_assets,1)` 引用第一期，年份数量变化或在Excel中插入行都不会错位。
+
+use anyhow::{anyhow, bail, Result};
+use rust_xlsxwriter::{cell_range_absolute, row_col_to_cell, Workbook};
+use std::collections::BTreeMap;
+
+/// 登记的位置：一行按期数据或单个单元格
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slot {
+    pub sheet: String,
+    pub row: u32,
+    pub first_col: u16,
+    /// 占用的列数（按期数据为期数，单个参数为1）
+    pub width: u16,
+}
+
+impl Slot {
+    /// 第 period 期所在列
+    pub fn col(&self, period: usize) -> u16 {
+        self.first_col + period as u16
+    }
+
+    /// 本表内第 period 期的单元格地址，如 `C21`
+    pub fn cell(&self, period: usize) -> String {
+        row_col_to_cell(self.row, self.col(period))
+    }
+
+    /// 命名区域的引用公式，如 `='工作表'!$C$21:$E$21`
+    pub fn name_formula(&self) -> String {
+        let range = cell_range_absolute(self.row, self.first_col, self.row, self.first_col + self.width - 1);
+        format!("='{}'!{}", self.sheet.replace('\'', "''"), range)
+    }
+}
+
+/// 工作簿布局
+#[derive(Debug, Default)]
+pub struct Layout {
+    periods: usize,
+    slots: BTreeMap<String, Slot>,
+}
+
+impl Layout {
+    pub fn new(periods: usize) -> Self {
+        Self {
+            periods,
+            slots: BTreeMap::new(),
+        }
+    }
+
+    /// 数据期数
+    pub fn periods(&self) -> usize {
+        self.periods
+    }
+
+    /// 登记按期排列的一行
+    pub fn register_row(&mut self, key: &str, sheet: &str, row: u32, first_col: u16) -> Result<()> {
+        let width = self.periods.max(1) as u16;
+        self.register(key, sheet, row, first_col, width)
+    }
+
+    /// 登记单个单元格（估值参数等）
+    pub fn register_cell(&mut self, key: &str, sheet: &str, row: u32, col: u16) -> Result<()> {
+        self.register(key, sheet, row, col, 1)
+    }
+
+    fn register(&mut self, key: &str, sheet: &str, row: u32, first_col: u16, width: u16) -> Result<()> {
+        validate_key(key)?;
+        if let Some(existing) = self.slots.get(key) {
+            bail!("布局键 {} 重复登记（已在 {} 第{}行）", key, existing.sheet, existing.row + 1);
+        }
+        self.slots.insert(
+            key.to_string(),
+            Slot {
+                sheet: sheet.to_string(),
+                row,
+                first_col,
+                width,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn slot(&self, key: &str) -> Result<&Slot> {
+        self.slots.get(key).ok_or_else(|| anyhow!("布局键 {} 未登记", key))
+    }
+
+    /// 同一Sheet内引用第 period 期的单元格，如 `C21`
+    pub fn local(&self, key: &str, period: usize) -> Result<String> {
+        let slot = self.slot(key)?;
+        self.check_period(key, slot, period)?;
+        Ok(slot.cell(period))
+    }
+
+    /// 同一Sheet内从 first_key 到 last_key 的连续区域，如 `C4:C10`
+    pub fn local_span(&self, first_key: &str, last_key: &str, period: usize) -> Result<String> {
+        Ok(format!("{}:{}", self.local(first_key, period)?, self.local(last_key, period)?))
+    }
+
+    /// 跨表引用第 period 期：按期数据为 `INDEX(key,n)`，单个单元格直接用名称
+    pub fn reference(&self, key: &str, period: usize) -> Result<String> {
+        let slot = self.slot(key)?;
+        self.check_period(key, slot, period)?;
+        Ok(if slot.width == 1 && period == 0 {
+            key.to_string()
+        } else {
+            format!("INDEX({},{})", key, period + 1)
+        })
+    }
+
+    /// 从 sheet 引用第 period 期：同一Sheet内用单元格地址，跨表用命名区域
+    pub fn reference_from(&self, sheet: &str, key: &str, period: usize) -> Result<String> {
+        if self.slot(key)?.sheet == sheet {
+            self.local(key, period)
+        } else {
+            self.reference(key, period)
+        }
+    }
+
+    fn check_period(&self, key: &str, slot: &Slot, period: usize) -> Result<()> {
+        if period >= slot.width as usize {
+            bail!("布局键 {} 只有{}期数据，无法引用第{}期", key, slot.width, period + 1);
+        }
+        Ok(())
+    }
+
+    /// 为所有登记的键定义工作簿级命名区域
+    pub fn define_names(&self, workbook: &mut Workbook) -> Result<()> {
+        for (key, slot) in &self.slots {
+            workbook.define_name(key.as_str(), &slot.name_formula())?;
+        }
+        Ok(())
+    }
+}
+
+/// 键同时作为Excel名称：小写字母开头，只含小写字母、数字和下划线，
+/// 且必须包含下划线，避免与 `fcf1` 这类单元格地址冲突
+fn validate_key(key: &str) -> Result<()> {
+    let valid = key.starts_with(|c: char| c.is_ascii_lowercase())
+        && key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && key.contains('_');
+    if !valid {
+        bail!("无效的布局键: {}（应为小写字母、数字和下划线组成，且包含下划线）", key);
+    }
+    Ok(())
+}
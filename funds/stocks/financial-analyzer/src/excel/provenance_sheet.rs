@@ -0,0 +1,70 @@
+//! 数据来源Sheet
+//! 列出组合数据源下每个科目的来源，不是来自主要来源（提供科目最多）的科目以底色标出
+
+use crate::domain::*;
+use crate::excel::SheetBuilder;
+use anyhow::Result;
+use rust_decimal::prelude::ToPrimitive;
+use rust_xlsxwriter::*;
+
+pub const SHEET_PROVENANCE: &str = "数据来源";
+
+pub fn write_provenance_sheet(
+    workbook: &mut Workbook,
+    result: &AnalysisResult,
+    stock_code: &str,
+) -> Result<()> {
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(SHEET_PROVENANCE)?;
+
+    let builder = SheetBuilder::new(stock_code);
+    let mut row = builder.write_header(worksheet, SHEET_PROVENANCE)?;
+
+    let subheader_fmt = Format::new()
+        .set_bold()
+        .set_background_color(Color::RGB(0xD9E1F2))
+        .set_border(FormatBorder::Thin);
+    let number_fmt = Format::new().set_num_format("#,##0.00");
+    let supplement_fmt = Format::new().set_background_color(Color::RGB(0xFFF2CC));
+    let supplement_number_fmt = Format::new()
+        .set_num_format("#,##0.00")
+        .set_background_color(Color::RGB(0xFFF2CC));
+
+    for (col, title) in ["报告期", "报表", "科目", "数值", "来源"].iter().enumerate() {
+        worksheet.write_string_with_format(row, col as u16, *title, &subheader_fmt)?;
+    }
+    row += 1;
+
+    for statement in result.statements.iter().filter(|s| !s.sources.is_empty()) {
+        let primary = statement.primary_source();
+        let mut names: Vec<&String> = statement.sources.keys().collect();
+        names.sort();
+
+        for name in names {
+            let source = &statement.sources[name];
+            let supplemented = primary.as_ref() != Some(source);
+            let (text_fmt, value_fmt) = if supplemented {
+                (&supplement_fmt, &supplement_number_fmt)
+            } else {
+                (&Format::new(), &number_fmt)
+            };
+
+            worksheet.write_string_with_format(row, 0, statement.report_date.to_string(), text_fmt)?;
+            worksheet.write_string_with_format(row, 1, statement.report_type.display_name(), text_fmt)?;
+            worksheet.write_string_with_format(row, 2, name, text_fmt)?;
+            if let Some(value) = statement.items.get(name).and_then(|v| v.to_f64()) {
+                worksheet.write_number_with_format(row, 3, value, value_fmt)?;
+            }
+            worksheet.write_string_with_format(row, 4, source, text_fmt)?;
+            row += 1;
+        }
+    }
+
+    worksheet.set_column_width(0, 12)?;
+    worksheet.set_column_width(1, 12)?;
+    worksheet.set_column_width(2, 40)?;
+    worksheet.set_column_width(3, 22)?;
+    worksheet.set_column_width(4, 12)?;
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+//! 选股筛选结果工作簿
+//! 筛选结果：入选股票按排序依据排名，并列出条件中引用的各项取值
+
+use super::common::ExcelFormats;
+use super::SheetBuilder;
+use crate::report::Unit;
+use crate::screen::ScreenResult;
+use anyhow::Result;
+use rust_xlsxwriter::*;
+use std::path::Path;
+
+pub const SHEET_SCREEN: &str = "筛选结果";
+
+/// 选股筛选报告生成器
+pub struct ScreenWriter;
+
+impl ScreenWriter {
+    pub fn generate(result: &ScreenResult, output_path: &Path) -> Result<()> {
+        let mut workbook = Self::build(result)?;
+        workbook.save(output_path)?;
+        Ok(())
+    }
+
+    pub(super) fn build(result: &ScreenResult) -> Result<Workbook> {
+        let builder = SheetBuilder::new(&format!("筛选 {} 只，入选 {} 只", result.screened, result.rows.len()));
+        let formats = ExcelFormats::new();
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(SHEET_SCREEN)?;
+        let mut row = builder.write_header(worksheet, SHEET_SCREEN)?;
+
+        worksheet.write_string(row, 0, "筛选条件")?;
+        worksheet.write_string(row, 1, &result.condition)?;
+        row += 2;
+
+        let headers = ["排名", "股票代码", "报告期"];
+        for (col, title) in headers.iter().enumerate() {
+            worksheet.write_string_with_format(row, col as u16, *title, &formats.header)?;
+        }
+        let first_term = headers.len() as u16 + 1;
+        worksheet.write_string_with_format(row, first_term - 1, format!("排序: {}", result.sort), &formats.header)?;
+        for (i, term) in result.terms.iter().enumerate() {
+            worksheet.write_string_with_format(row, first_term + i as u16, term.label(), &formats.header)?;
+        }
+        worksheet.set_freeze_panes(row + 1, 2)?;
+        row += 1;
+
+        for (rank, screened) in result.rows.iter().enumerate() {
+            worksheet.write_number(row, 0, (rank + 1) as f64)?;
+            worksheet.write_string(row, 1, &screened.stock_code)?;
+            worksheet.write_string(row, 2, &screened.period)?;
+            if let Some(score) = screened.score {
+                worksheet.write_number_with_format(row, first_term - 1, score, &formats.number)?;
+            }
+            for (i, (term, value)) in result.terms.iter().zip(&screened.values).enumerate() {
+                if let Some(value) = value {
+                    let format = if term.unit() == Unit::Percent { &formats.percent } else { &formats.number };
+                    worksheet.write_number_with_format(row, first_term + i as u16, *value, format)?;
+                }
+            }
+            row += 1;
+        }
+
+        worksheet.set_column_width(0, 10)?;
+        worksheet.set_column_width(1, 14)?;
+        worksheet.set_column_width(2, 14)?;
+        for col in first_term - 1..first_term + result.terms.len() as u16 {
+            worksheet.set_column_width(col, 18)?;
+        }
+        Ok(workbook)
+    }
+}
@@ -0,0 +1,210 @@
+//! 敏感性分析的分区：可编辑的DCF逐年预测（`dcf_table`）、折现率WACC（`wacc`）、
+//! 股权估值模型（`equity_models`）和市场隐含增长率（`implied_growth`）
+//!
+//! 各分区左起三列依次为名称、数值和说明（DCF逐年预测为五列），标题行横跨全部列。
+
+use super::common::ExcelFormats;
+use super::dcf_table::{growth_inputs, write_dcf_table, DcfTableInputs, DisplayedDcf};
+use super::layout::Layout;
+use super::template::DcfInputs;
+use crate::domain::*;
+use anyhow::{bail, Result};
+use rust_decimal::prelude::ToPrimitive;
+use rust_xlsxwriter::*;
+
+/// 名称、数值、说明三列
+pub(crate) const SECTION_COLS: u16 = 3;
+/// 预测年、FCF增长率、预测FCF、折现系数、现值五列
+pub(crate) const DCF_TABLE_COLS: u16 = 5;
+
+/// DCF逐年预测的行数：标题、表头、预测年和永续价值
+pub(crate) fn dcf_table_rows(result: &AnalysisResult) -> u32 {
+    let years = DisplayedDcf::from_result(result).map_or(3, |displayed| displayed.dcf.projections.len());
+    3 + years as u32
+}
+
+/// 登记第一个预测年和永续价值的现值单元格 `<key>_first_pv` / `<key>_terminal_pv`
+pub(crate) fn register_dcf_table(layout: &mut Layout, result: &AnalysisResult, key: &str, sheet: &str, row: u32, col: u16) -> Result<()> {
+    let value_col = col + DCF_TABLE_COLS - 1;
+    let terminal_row = row + dcf_table_rows(result) - 1;
+    layout.register_cell(&format!("{key}_first_pv"), sheet, row + 2, value_col)?;
+    layout.register_cell(&format!("{key}_terminal_pv"), sheet, terminal_row, value_col)
+}
+
+/// 写入可编辑的DCF逐年预测：未设定预测阶段时各年增长率引用 `fcf_growth` 单元格
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn write_dcf_table_section(
+    worksheet: &mut Worksheet,
+    result: &AnalysisResult,
+    layout: &Layout,
+    sheet: &str,
+    row: u32,
+    col: u16,
+    title: &str,
+    inputs: &DcfInputs,
+    formats: &ExcelFormats,
+) -> Result<()> {
+    worksheet.merge_range(row, col, row, col + DCF_TABLE_COLS - 1, title, &formats.header)?;
+    let cell = |key: &str| layout.reference_from(sheet, key, 0);
+    let growth = growth_inputs(DisplayedDcf::from_result(result).as_ref(), &cell(&inputs.fcf_growth)?);
+    let inputs = DcfTableInputs {
+        base_fcf: cell(&inputs.base_fcf)?,
+        discount_rate: cell(&inputs.discount_rate)?,
+        perpetual_growth: cell(&inputs.perpetual_growth)?,
+    };
+    write_dcf_table(worksheet, row + 1, col, &growth, &inputs, &formats.number, &formats.percent, &formats.subheader)?;
+    Ok(())
+}
+
+/// WACC分区的行数：标题、表头、7项输入、股权成本、WACC和注释；没有WACC时不输出
+pub(crate) fn wacc_rows(result: &AnalysisResult) -> Option<u32> {
+    let wacc = result.sensitivity.as_ref()?.params.wacc.as_ref()?;
+    Some(11 + wacc.notes.len() as u32)
+}
+
+/// 写入WACC计算过程；指定 discount_rate 时该折现率单元格改为引用计算出的WACC，修改各项输入即可联动估值结果
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn write_wacc(
+    worksheet: &mut Worksheet,
+    result: &AnalysisResult,
+    layout: &Layout,
+    sheet: &str,
+    row: u32,
+    col: u16,
+    title: &str,
+    discount_rate: Option<&str>,
+    formats: &ExcelFormats,
+) -> Result<()> {
+    let Some(wacc) = result.sensitivity.as_ref().and_then(|s| s.params.wacc.as_ref()) else {
+        return Ok(());
+    };
+    let (value_col, note_col) = (col + 1, col + 2);
+    worksheet.merge_range(row, col, row, note_col, title, &formats.header)?;
+    write_columns(worksheet, row + 1, col, &["参数名称", "参数值", "说明"], formats)?;
+
+    let beta_note = match &wacc.params.beta_estimate {
+        Some(estimate) => format!("{} 至 {}，{} 个样本回归", estimate.start_date, estimate.end_date, estimate.observations),
+        None => "直接输入".to_string(),
+    };
+    let inputs = [
+        ("无风险收益率", wacc.params.risk_free_rate, &formats.percent, "CAPM".to_string()),
+        ("股权风险溢价", wacc.params.equity_risk_premium, &formats.percent, "CAPM".to_string()),
+        ("β系数", wacc.params.beta, &formats.number, beta_note),
+        ("税前债务成本", wacc.cost_of_debt, &formats.percent, "财务费用/有息负债".to_string()),
+        ("实际所得税率", wacc.tax_rate, &formats.percent, "所得税费用/利润总额".to_string()),
+        ("股权价值", wacc.equity_value.to_f64().unwrap_or(0.0), &formats.number, format!("按{}计", wacc.params.weights.display_name())),
+        ("有息负债", wacc.interest_bearing_debt.to_f64().unwrap_or(0.0), &formats.number, "短期借款+长期借款+应付债券".to_string()),
+    ];
+    let first_input_row = row + 2;
+    for (i, (label, value, format, note)) in inputs.into_iter().enumerate() {
+        let row = first_input_row + i as u32;
+        worksheet.write_string(row, col, label)?;
+        worksheet.write_number_with_format(row, value_col, value, format)?;
+        worksheet.write_string(row, note_col, note)?;
+    }
+    let [rf, erp, beta, kd, tax, equity, debt] = std::array::from_fn(|i| utility::row_col_to_cell(first_input_row + i as u32, value_col));
+
+    let ke_row = first_input_row + 7;
+    let ke = utility::row_col_to_cell(ke_row, value_col);
+    worksheet.write_string(ke_row, col, "股权成本(CAPM)")?;
+    worksheet.write_formula_with_format(ke_row, value_col, format!("={rf}+{beta}*{erp}").as_str(), &formats.percent)?;
+    worksheet.write_string(ke_row, note_col, "无风险收益率+β×股权风险溢价")?;
+
+    let wacc_row = ke_row + 1;
+    worksheet.write_string(wacc_row, col, "WACC")?;
+    worksheet.write_formula_with_format(
+        wacc_row,
+        value_col,
+        format!("=({equity}*{ke}+{debt}*{kd}*(1-{tax}))/({equity}+{debt})").as_str(),
+        &formats.percent,
+    )?;
+    worksheet.write_string(wacc_row, note_col, "按资本权重加权，用作折现率")?;
+    if let Some(key) = discount_rate {
+        let slot = layout.slot(key)?;
+        if slot.sheet != sheet {
+            bail!("wacc 分区的 discount_rate {} 需要在同一工作表", key);
+        }
+        let formula = format!("={}", utility::row_col_to_cell(wacc_row, value_col));
+        worksheet.write_formula_with_format(slot.row, slot.first_col, formula.as_str(), &formats.percent)?;
+    }
+
+    for (i, note) in wacc.notes.iter().enumerate() {
+        let row = wacc_row + 1 + i as u32;
+        worksheet.merge_range(row, col, row, note_col, &format!("注：{}", note), &Format::new())?;
+    }
+    Ok(())
+}
+
+/// 股权估值模型分区的行数：标题、3项参数和各模型；没有DDM/FCFE结果时不输出
+pub(crate) fn equity_model_rows(result: &AnalysisResult) -> Option<u32> {
+    let models = result.sensitivity.as_ref()?.equity_models();
+    (!models.is_empty()).then(|| 4 + models.len() as u32)
+}
+
+/// 写入DDM/FCFE：按分析时的参数计算，修改参数后不会自动更新
+pub(crate) fn write_equity_models(worksheet: &mut Worksheet, result: &AnalysisResult, row: u32, col: u16, title: &str, formats: &ExcelFormats) -> Result<()> {
+    let Some(sensitivity) = &result.sensitivity else {
+        return Ok(());
+    };
+    let params = &sensitivity.params;
+    let (value_col, note_col) = (col + 1, col + 2);
+    worksheet.merge_range(row, col, row, note_col, title, &formats.header)?;
+
+    worksheet.write_string(row + 1, col, "股权成本")?;
+    worksheet.write_number_with_format(row + 1, value_col, params.cost_of_equity, &formats.percent)?;
+    worksheet.write_string(row + 2, col, "高速增长期")?;
+    worksheet.write_number(row + 2, value_col, params.high_growth_years as f64)?;
+    worksheet.write_string(row + 2, note_col, "年")?;
+    worksheet.write_string(row + 3, col, "高速增长率")?;
+    worksheet.write_number_with_format(row + 3, value_col, params.high_growth_rate, &formats.percent)?;
+    worksheet.write_string(row + 3, note_col, "之后按永续增长率增长")?;
+
+    for (i, (model, valuation)) in sensitivity.equity_models().into_iter().enumerate() {
+        let row = row + 4 + i as u32;
+        worksheet.write_string(row, col, model.price_label())?;
+        worksheet.write_number_with_format(row, value_col, valuation.price_per_share.to_f64().unwrap_or(0.0), &formats.number)?;
+        worksheet.write_string(row, note_col, model.cashflow_label())?;
+    }
+    Ok(())
+}
+
+/// 市场隐含增长率分区的行数：标题、当前股价和两项隐含增长率；没有反向DCF结果时不输出
+pub(crate) fn implied_growth_rows(result: &AnalysisResult) -> Option<u32> {
+    result.sensitivity.as_ref()?.implied_growth.as_ref().map(|_| 4)
+}
+
+/// 写入反向DCF：按分析时的参数求解，修改参数后不会自动更新
+pub(crate) fn write_implied_growth(worksheet: &mut Worksheet, result: &AnalysisResult, row: u32, col: u16, title: &str, formats: &ExcelFormats) -> Result<()> {
+    let Some((sensitivity, implied)) = result.sensitivity.as_ref().and_then(|s| s.implied_growth.as_ref().map(|i| (s, i))) else {
+        return Ok(());
+    };
+    let (value_col, note_col) = (col + 1, col + 2);
+    worksheet.merge_range(row, col, row, note_col, title, &formats.header)?;
+
+    worksheet.write_string(row + 1, col, "当前股价")?;
+    worksheet.write_number_with_format(row + 1, value_col, implied.market_price, &formats.number)?;
+    worksheet.write_string(row + 1, note_col, "元/股")?;
+
+    let fcf_note = sensitivity.implied_fcf_growth_note();
+    let rates = [
+        ("隐含FCF增长率", implied.fcf_growth_rate, fcf_note.trim_start_matches("隐含FCF增长率：")),
+        ("隐含净利润增长率", implied.net_profit_growth_rate, "3年按此增长时唐朝低估价等于股价"),
+    ];
+    for (i, (label, rate, note)) in rates.into_iter().enumerate() {
+        let row = row + 2 + i as u32;
+        worksheet.write_string(row, col, label)?;
+        match rate {
+            Some(rate) => worksheet.write_number_with_format(row, value_col, rate, &formats.percent)?,
+            None => worksheet.write_string(row, value_col, "无法求解")?,
+        };
+        worksheet.write_string(row, note_col, note)?;
+    }
+    Ok(())
+}
+
+fn write_columns(worksheet: &mut Worksheet, row: u32, col: u16, captions: &[&str], formats: &ExcelFormats) -> Result<()> {
+    for (i, caption) in captions.iter().enumerate() {
+        worksheet.write_string_with_format(row, col + i as u16, *caption, &formats.subheader)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,488 @@
+//! 报告模板：用 TOML/YAML 描述工作表、分区和行，由 `template_sheet` 渲染
+//!
+//! 默认模板见 `templates/report.toml`（编译进程序），描述了除图表看板和数据来源之外的全部工作表；
+//! 可用 `--template` 指定自己的模板替换。
+//!
+//! 分区按 `kind` 分为：
+//! - `table`：标题行 + 列标题 + 按期排列的行（缺省）
+//! - `grid`：按期排列的行，可用 `row` / `col` / `below` / `right_of` 定位，用于左右并列的明细表
+//! - `values`：单值行（参数、基础数据和估值公式）
+//! - `notes`：说明文字
+//! - `valuation`、`dcf_detail`、`dcf_table`、`wacc`、`equity_models`、`implied_growth`：
+//!   按估值结果生成的区域，行数随预测年数、股权调整项等变化
+//!
+//! 按期行的数据来源四选一：
+//! - `statement` + `account`：报表科目原值（`account` 缺省为 `label`）
+//! - `ref`：引用布局中登记的行，如明细表的 `bs_total_assets`
+//! - `expr`：Excel 公式表达式，`{key}` 为同一期的值，`{key:prev}` 为上一期的值
+//! - `metric`：分析结果中已计算的比率，如 `gross_margin`
+//!
+//! 单值行的数据来源为 `param`、`value`、`ref`、`expr`（只能引用第一期）或 `bridge` 之一。
+
+use super::dashboard_sheet::SHEET_DASHBOARD;
+use super::provenance_sheet::SHEET_PROVENANCE;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// 报告模板
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReportTemplate {
+    #[serde(default)]
+    pub sheets: Vec<SheetTemplate>,
+}
+
+/// 工作表
+#[derive(Debug, Clone, Deserialize)]
+pub struct SheetTemplate {
+    /// 工作表名称（不能重名，也不能与看板、数据来源工作表重名）
+    pub name: String,
+    /// 报告头中的标题，缺省为工作表名称
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub style: SheetStyle,
+    /// 仅在分析结果包含对应数据时生成
+    #[serde(default)]
+    pub requires: Option<Requirement>,
+    /// `table` 分区的指标名称列宽
+    #[serde(default = "default_label_width")]
+    pub label_width: f64,
+    /// 从A列起依次指定的列宽，覆盖缺省列宽
+    #[serde(default)]
+    pub widths: Vec<f64>,
+    #[serde(default)]
+    pub sections: Vec<SectionTemplate>,
+}
+
+/// 工作表样式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SheetStyle {
+    /// 带报告头（股票代码、工作表名称和生成时间）
+    #[default]
+    Report,
+    /// 无报告头，从第一行开始，行高统一为22
+    Plain,
+}
+
+/// 分区
+#[derive(Debug, Clone, Deserialize)]
+pub struct SectionTemplate {
+    /// 供其他分区的 `below` / `right_of` 引用
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub kind: SectionKind,
+    /// 仅在分析结果包含对应数据时输出，不输出的分区不占位置
+    #[serde(default)]
+    pub requires: Option<Requirement>,
+    /// 起始行（从0起），缺省在上一分区之后空 `gap` 行
+    #[serde(default)]
+    pub row: Option<u32>,
+    /// 起始列（从0起），缺省为A列
+    #[serde(default)]
+    pub col: Option<u16>,
+    /// 位于指定分区最后一行之后，空 `gap` 行
+    #[serde(default)]
+    pub below: Option<String>,
+    /// 位于指定分区最后一列之后，空 `col_gap` 列；未指定行时与该分区顶端对齐
+    #[serde(default)]
+    pub right_of: Option<String>,
+    #[serde(default = "default_gap")]
+    pub gap: u32,
+    #[serde(default = "default_col_gap")]
+    pub col_gap: u16,
+    /// `grid`：首列是否为分类列
+    #[serde(default = "default_categories")]
+    pub categories: bool,
+    /// `grid`：名称列与第一期数据之间的空列数
+    #[serde(default)]
+    pub data_gap: u16,
+    /// `values` 等单值分区的列标题
+    #[serde(default)]
+    pub columns: Vec<String>,
+    /// `values`：数值列之后依次输出的字段，缺省为单位和说明
+    #[serde(default)]
+    pub fields: Option<Vec<Field>>,
+    /// `notes`：每行一条说明
+    #[serde(default)]
+    pub lines: Vec<String>,
+    /// `dcf_table`：登记 `<key>_first_pv` 和 `<key>_terminal_pv`（第一年和永续价值的现值单元格）
+    #[serde(default)]
+    pub key: Option<String>,
+    /// `dcf_table`：引用的参数单元格
+    #[serde(default)]
+    pub inputs: Option<DcfInputs>,
+    /// `wacc`：改为引用计算出的WACC的折现率单元格
+    #[serde(default)]
+    pub discount_rate: Option<String>,
+    #[serde(default)]
+    pub rows: Vec<RowTemplate>,
+}
+
+/// 分区类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionKind {
+    #[default]
+    Table,
+    Grid,
+    Values,
+    Notes,
+    /// DCF与唐朝估值的参数和结果（登记 `dcf_*` 和 `tangchao_*`）
+    Valuation,
+    /// `valuation` 分区的DCF逐年预测和股权调整
+    DcfDetail,
+    /// 敏感性分析的DCF逐年预测，增长率可编辑
+    DcfTable,
+    Wacc,
+    EquityModels,
+    ImpliedGrowth,
+}
+
+/// `values` 分区数值列之后的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Unit,
+    Description,
+}
+
+/// DCF逐年预测引用的参数单元格
+#[derive(Debug, Clone, Deserialize)]
+pub struct DcfInputs {
+    pub base_fcf: String,
+    pub discount_rate: String,
+    pub perpetual_growth: String,
+    /// 未设定预测阶段时各年增长率引用的单元格
+    pub fcf_growth: String,
+}
+
+/// 数据行
+#[derive(Debug, Clone, Deserialize)]
+pub struct RowTemplate {
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub label: String,
+    /// 登记到布局中的键，供其他行的表达式引用，同时定义为命名区域
+    #[serde(default)]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub statement: Option<Statement>,
+    #[serde(default)]
+    pub account: Option<String>,
+    #[serde(default, rename = "ref")]
+    pub reference: Option<String>,
+    #[serde(default)]
+    pub expr: Option<String>,
+    #[serde(default)]
+    pub metric: Option<Metric>,
+    /// 单值行：敏感性分析参数或基础数据
+    #[serde(default)]
+    pub param: Option<Param>,
+    /// 单值行：常数
+    #[serde(default)]
+    pub value: Option<f64>,
+    /// 单值行：先逐项列出股权调整，本行为该键的值加全部调整项
+    #[serde(default)]
+    pub bridge: Option<String>,
+    /// `grid` 标题行：不取数据，`periods` 在数据列写各期标题
+    #[serde(default)]
+    pub heading: Option<Heading>,
+    /// 加粗显示名称和/或分类
+    #[serde(default)]
+    pub emphasis: Option<Emphasis>,
+    /// 本行之前空出的行数
+    #[serde(default)]
+    pub skip: u32,
+    #[serde(default)]
+    pub format: CellFormat,
+    #[serde(default)]
+    pub unit: String,
+    /// 说明：与内置指标说明的名称相同时使用内置说明，否则原样输出；缺省按 `label` 查内置说明
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 绝对值小于0.01时显示的文字（如杠杆倍数显示为 "-"）
+    #[serde(default)]
+    pub zero_as: Option<String>,
+    /// 表达式引用的上一期不存在时显示的文字
+    #[serde(default)]
+    pub missing_as: Option<String>,
+}
+
+/// 分区的输出条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Requirement {
+    Leverage,
+    Sensitivity,
+    Wacc,
+    EquityModels,
+    ImpliedGrowth,
+}
+
+/// 报表
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Statement {
+    Balance,
+    Income,
+    Cashflow,
+}
+
+/// 分析结果中按期计算好的比率
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    OperatingAssetRatio,
+    FinancialAssetRatio,
+    GrossMargin,
+    CoreProfitMargin,
+    NetProfitMargin,
+    OperatingLeverage,
+    FinancialLeverage,
+    TotalLeverage,
+}
+
+/// 单值行的参数：敏感性分析参数和估值使用的基础数据
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Param {
+    DiscountRate,
+    PerpetualGrowthRate,
+    FcfGrowthRate,
+    NetProfitGrowthRate,
+    LowRiskFreeRate,
+    HighRiskFreeRate,
+    /// DCF实际使用的基准FCF；名称中的 `{base_fcf_period}` 替换为“最近一年”或“近N年平均”
+    BaseFcf,
+    /// 最近一期净利润
+    NetProfit,
+    TotalShares,
+}
+
+/// `grid` 分区的标题行
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Heading {
+    /// 名称列写 `label`，数据列写各期标题
+    Periods,
+    /// 只写分类和名称
+    Label,
+}
+
+/// 加粗的单元格
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Emphasis {
+    Label,
+    Category,
+    Both,
+}
+
+/// 单元格格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CellFormat {
+    #[default]
+    Number,
+    Percent,
+    /// 黄色高亮的百分比
+    Highlight,
+    /// 黄色高亮的数字
+    HighlightNumber,
+}
+
+/// 表达式中的引用
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placeholder<'a> {
+    pub key: &'a str,
+    /// 是否引用上一期
+    pub prev: bool,
+}
+
+/// 表达式片段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<'a> {
+    Text(&'a str),
+    Ref(Placeholder<'a>),
+}
+
+fn default_label_width() -> f64 {
+    30.0
+}
+
+fn default_gap() -> u32 {
+    1
+}
+
+fn default_col_gap() -> u16 {
+    1
+}
+
+fn default_categories() -> bool {
+    true
+}
+
+impl ReportTemplate {
+    /// 内置默认模板
+    pub fn builtin() -> Self {
+        toml::from_str(include_str!("../../templates/report.toml")).expect("内置报告模板格式错误")
+    }
+
+    /// 按扩展名读取 TOML 或 YAML 模板并检查
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("无法读取报告模板 {}", path.display()))?;
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+        let template: Self = match extension.as_str() {
+            "toml" => toml::from_str(&content).with_context(|| format!("报告模板格式错误: {}", path.display()))?,
+            "yaml" | "yml" => config::Config::builder()
+                .add_source(config::File::from_str(&content, config::FileFormat::Yaml))
+                .build()
+                .and_then(|c| c.try_deserialize())
+                .with_context(|| format!("报告模板格式错误: {}", path.display()))?,
+            _ => bail!("不支持的模板格式: {}（支持 .toml / .yaml / .yml）", path.display()),
+        };
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// 检查工作表不重名、分区的定位和参数完整、每行恰好有一个数据来源、表达式格式正确
+    pub fn validate(&self) -> Result<()> {
+        let mut names = HashSet::from([SHEET_DASHBOARD, SHEET_PROVENANCE]);
+        for sheet in &self.sheets {
+            if !names.insert(sheet.name.as_str()) {
+                bail!("模板中工作表 [{}] 重复或与看板、数据来源工作表重名", sheet.name);
+            }
+            let mut ids = HashSet::new();
+            for section in &sheet.sections {
+                let location = || format!("模板 [{}] {}", sheet.name, section.title);
+                for target in [&section.below, &section.right_of].into_iter().flatten() {
+                    if !ids.contains(target.as_str()) {
+                        bail!("{}: 定位引用的分区 {} 不存在或不在本分区之前", location(), target);
+                    }
+                }
+                if let Some(id) = &section.id {
+                    if !ids.insert(id.as_str()) {
+                        bail!("{}: 分区 id {} 重复", location(), id);
+                    }
+                }
+                section.validate().with_context(location)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SectionTemplate {
+    fn validate(&self) -> Result<()> {
+        match self.kind {
+            SectionKind::Table | SectionKind::Grid | SectionKind::Values => {}
+            SectionKind::Notes if self.lines.is_empty() => bail!("notes 分区缺少 lines"),
+            SectionKind::DcfTable if self.key.is_none() || self.inputs.is_none() => bail!("dcf_table 分区需要 key 和 inputs"),
+            _ if !self.rows.is_empty() => bail!("{:?} 分区不能包含 rows", self.kind),
+            _ => {}
+        }
+        for row in &self.rows {
+            row.validate(self.kind).with_context(|| row.label.clone())?;
+        }
+        Ok(())
+    }
+}
+
+impl RowTemplate {
+    /// 按分区类型检查数据来源：按期行四选一，单值行五选一，`grid` 标题行不取数据
+    fn validate(&self, kind: SectionKind) -> Result<()> {
+        let sources = [
+            ("statement", self.statement.is_some()),
+            ("ref", self.reference.is_some()),
+            ("expr", self.expr.is_some()),
+            ("metric", self.metric.is_some()),
+            ("param", self.param.is_some()),
+            ("value", self.value.is_some()),
+            ("bridge", self.bridge.is_some()),
+        ];
+        let allowed: &[&str] = match kind {
+            SectionKind::Values => &["param", "value", "ref", "expr", "bridge"],
+            _ => &["statement", "ref", "expr", "metric"],
+        };
+        if let Some((name, _)) = sources.iter().find(|(name, set)| *set && !allowed.contains(name)) {
+            bail!("{:?} 分区的行不支持 {}", kind, name);
+        }
+        let count = sources.iter().filter(|(_, set)| *set).count();
+        if self.heading.is_some() {
+            if kind != SectionKind::Grid {
+                bail!("heading 只能用于 grid 分区");
+            }
+            if count > 0 {
+                bail!("标题行不能指定数据来源");
+            }
+        } else if count != 1 {
+            bail!("需要且只能指定一个数据来源（{} 之一）", allowed.join(" / "));
+        }
+        if self.account.is_some() && self.statement.is_none() {
+            bail!("account 需要同时指定 statement");
+        }
+        if let Some(expr) = &self.expr {
+            let tokens = tokenize(expr)?;
+            if kind == SectionKind::Values && tokens.iter().any(|t| matches!(t, Token::Ref(Placeholder { prev: true, .. }))) {
+                bail!("单值行的表达式不能引用上一期: {}", expr);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SheetTemplate {
+    pub fn title(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.name)
+    }
+}
+
+impl RowTemplate {
+    /// 报表科目名称
+    pub fn account(&self) -> &str {
+        self.account.as_deref().unwrap_or(&self.label)
+    }
+}
+
+/// 拆分表达式：`{key}` / `{key:prev}` 为引用，其余原样保留
+pub fn tokenize(expr: &str) -> Result<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut rest = expr;
+    while let Some(start) = rest.find('{') {
+        if rest[..start].contains('}') {
+            bail!("表达式中多余的 }}: {}", expr);
+        }
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+        }
+        let end = rest[start..]
+            .find('}')
+            .map(|end| start + end)
+            .with_context(|| format!("表达式缺少 }}: {}", expr))?;
+        let inner = &rest[start + 1..end];
+        let (key, prev) = match inner.split_once(':') {
+            Some((key, "prev")) => (key, true),
+            Some((_, modifier)) => bail!("表达式中不支持的修饰 :{}（只支持 :prev）: {}", modifier, expr),
+            None => (inner, false),
+        };
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            bail!("表达式中无效的引用 {{{}}}: {}", inner, expr);
+        }
+        tokens.push(Token::Ref(Placeholder { key, prev }));
+        rest = &rest[end + 1..];
+    }
+    if rest.contains('}') {
+        bail!("表达式中多余的 }}: {}", expr);
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+    Ok(tokens)
+}
@@ -0,0 +1,691 @@
+//! 模板工作表：按 `ReportTemplate` 生成工作表
+//!
+//! 先确定每个分区的位置并登记带 `key` 的行和估值分区的单元格，再逐表写入，
+//! 因此表达式既可以引用任意工作表中登记的行，也可以引用后面的工作表。
+
+use super::common::ExcelFormats;
+use super::dcf_table::{base_fcf_period, total_shares, DisplayedDcf};
+use super::layout::Layout;
+use super::template::{
+    Emphasis, Field, Heading, Metric, Param, ReportTemplate, Requirement, RowTemplate, SectionKind, SectionTemplate, SheetStyle,
+    SheetTemplate, Statement, Token,
+};
+use super::{sensitivity_sections, valuation_section};
+use crate::analyzer::BridgeAdjustment;
+use crate::domain::*;
+use crate::excel::{DataHelper, IndicatorDescriptions, SheetBuilder};
+use anyhow::{Context, Result};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_xlsxwriter::*;
+
+/// 报告头占用的行数（标题、元数据、空行），与 `SheetBuilder::write_header` 一致
+const HEADER_ROWS: u32 = 3;
+/// `table` 分区第一期数据所在列（相对分区首列）
+const TABLE_DATA_COL: u16 = 2;
+/// `notes` 分区说明文字横跨的列数
+const NOTE_COLS: u16 = 3;
+/// 无报告头的工作表统一的行高
+const PLAIN_ROW_HEIGHT: f64 = 22.0;
+
+/// 渲染时共用的数据
+struct RenderContext<'a> {
+    result: &'a AnalysisResult,
+    data: DataHelper<'a>,
+    layout: &'a Layout,
+    formats: ExcelFormats,
+    descriptions: IndicatorDescriptions,
+}
+
+/// 分区在工作表中的位置，不输出的分区占0行0列
+struct Placement<'a> {
+    section: &'a SectionTemplate,
+    row: u32,
+    col: u16,
+    rows: u32,
+    cols: u16,
+}
+
+impl Placement<'_> {
+    fn visible(&self) -> bool {
+        self.rows > 0
+    }
+
+    /// 分区之后的第一行
+    fn end_row(&self) -> u32 {
+        self.row + self.rows
+    }
+
+    /// 分区之后的第一列
+    fn end_col(&self) -> u16 {
+        self.col + self.cols
+    }
+}
+
+pub fn write_template_sheets(
+    workbook: &mut Workbook,
+    result: &AnalysisResult,
+    stock_code: &str,
+    template: &ReportTemplate,
+    layout: &mut Layout,
+) -> Result<()> {
+    let periods = layout.periods();
+    let sheets: Vec<_> = template
+        .sheets
+        .iter()
+        .filter(|sheet| sheet.requires.is_none_or(|requirement| satisfies(requirement, result)))
+        .map(|sheet| (sheet, place(sheet, result, periods)))
+        .collect();
+
+    // 第一遍：登记带键的行和估值分区的单元格
+    for (sheet, placements) in &sheets {
+        for placement in placements.iter().filter(|p| p.visible()) {
+            register(layout, result, sheet, placement).with_context(|| location(sheet, placement.section))?;
+        }
+    }
+
+    let context = RenderContext {
+        result,
+        data: DataHelper::new(&result.statements),
+        layout,
+        formats: ExcelFormats::new(),
+        descriptions: IndicatorDescriptions::new(),
+    };
+    for (sheet, placements) in &sheets {
+        write_sheet(workbook, &context, stock_code, sheet, placements).with_context(|| format!("生成模板工作表 {} 失败", sheet.name))?;
+    }
+    Ok(())
+}
+
+fn location(sheet: &SheetTemplate, section: &SectionTemplate) -> String {
+    let name = if section.title.is_empty() { section.id.as_deref().unwrap_or_default() } else { &section.title };
+    format!("模板 [{}] {}", sheet.name, name)
+}
+
+fn satisfies(requirement: Requirement, result: &AnalysisResult) -> bool {
+    match requirement {
+        Requirement::Leverage => result.leverage_analysis.is_some(),
+        Requirement::Sensitivity => result.sensitivity.is_some(),
+        Requirement::Wacc => sensitivity_sections::wacc_rows(result).is_some(),
+        Requirement::EquityModels => sensitivity_sections::equity_model_rows(result).is_some(),
+        Requirement::ImpliedGrowth => sensitivity_sections::implied_growth_rows(result).is_some(),
+    }
+}
+
+/// 依次确定各分区的位置：指定 `row` / `col` 时直接使用，否则按 `below` / `right_of` 相对已放置的分区定位，
+/// 都没有时放在已输出分区之下、空 `gap` 行
+fn place<'a>(sheet: &'a SheetTemplate, result: &AnalysisResult, periods: usize) -> Vec<Placement<'a>> {
+    let top = match sheet.style {
+        SheetStyle::Report => HEADER_ROWS,
+        SheetStyle::Plain => 0,
+    };
+    let mut placements: Vec<Placement> = Vec::new();
+    for section in &sheet.sections {
+        let (rows, cols) = section
+            .requires
+            .is_none_or(|requirement| satisfies(requirement, result))
+            .then(|| extent(section, result, periods))
+            .flatten()
+            .unwrap_or((0, 0));
+        let target = |id: &Option<String>| {
+            id.as_deref().and_then(|id| placements.iter().find(|p| p.section.id.as_deref() == Some(id)))
+        };
+        let below = target(&section.below);
+        let right_of = target(&section.right_of);
+        let next_row = placements.iter().filter(|p| p.visible()).map(|p| p.end_row() + section.gap).max().unwrap_or(top);
+
+        let row = section
+            .row
+            .or(below.map(|target| target.end_row() + section.gap))
+            .or(right_of.map(|target| target.row))
+            .unwrap_or(next_row);
+        let col = section
+            .col
+            .or(right_of.map(|target| target.end_col() + section.col_gap))
+            .or(below.map(|target| target.col))
+            .unwrap_or(0);
+        placements.push(Placement { section, row, col, rows, cols });
+    }
+    placements
+}
+
+/// 分区占用的行数和列数；按估值结果生成的分区在没有对应数据时返回 None
+fn extent(section: &SectionTemplate, result: &AnalysisResult, periods: usize) -> Option<(u32, u16)> {
+    let periods = periods as u16;
+    let extent = match section.kind {
+        // 标题行、列标题和数据行；分类、名称、各期、单位、说明
+        SectionKind::Table => (2 + section.rows.len() as u32, TABLE_DATA_COL + periods + 3),
+        SectionKind::Grid => {
+            let rows = section.rows.iter().map(|row| row.skip + 1).sum();
+            (rows, grid_data_col(section, 0) + periods)
+        }
+        SectionKind::Values => {
+            let adjustments = adjustments(result).len() as u32;
+            let rows: u32 = section
+                .rows
+                .iter()
+                .map(|row| row.skip + 1 + if row.bridge.is_some() { adjustments } else { 0 })
+                .sum();
+            (values_start(section, 0) + rows, 2 + fields(section).len() as u16)
+        }
+        SectionKind::Notes => (section.lines.len() as u32 + u32::from(!section.title.is_empty()), NOTE_COLS),
+        SectionKind::Valuation => (valuation_section::valuation_rows(), valuation_section::VALUATION_COLS),
+        SectionKind::DcfDetail => (valuation_section::detail_rows(result)?, valuation_section::DETAIL_COLS),
+        SectionKind::DcfTable => (sensitivity_sections::dcf_table_rows(result), sensitivity_sections::DCF_TABLE_COLS),
+        SectionKind::Wacc => (sensitivity_sections::wacc_rows(result)?, sensitivity_sections::SECTION_COLS),
+        SectionKind::EquityModels => (sensitivity_sections::equity_model_rows(result)?, sensitivity_sections::SECTION_COLS),
+        SectionKind::ImpliedGrowth => (sensitivity_sections::implied_growth_rows(result)?, sensitivity_sections::SECTION_COLS),
+    };
+    Some(extent)
+}
+
+/// `grid` 分区第一期数据所在列：分类列、名称列和 `data_gap` 个空列之后
+fn grid_data_col(section: &SectionTemplate, col: u16) -> u16 {
+    grid_label_col(section, col) + 1 + section.data_gap
+}
+
+fn grid_label_col(section: &SectionTemplate, col: u16) -> u16 {
+    col + u16::from(section.categories)
+}
+
+/// `grid` 分区各行所在的行号
+fn grid_rows<'a>(placement: &Placement<'a>) -> Vec<(u32, &'a RowTemplate)> {
+    let mut next = placement.row;
+    placement
+        .section
+        .rows
+        .iter()
+        .map(|row| {
+            let at = next + row.skip;
+            next = at + 1;
+            (at, row)
+        })
+        .collect()
+}
+
+/// `values` 分区第一行数据的行号：标题和列标题之后
+fn values_start(section: &SectionTemplate, row: u32) -> u32 {
+    row + u32::from(!section.title.is_empty()) + u32::from(!section.columns.is_empty())
+}
+
+/// `values` 分区各行所在的行号；`bridge` 行之前先列出各项股权调整
+fn value_rows<'a>(placement: &Placement<'a>, adjustments: usize) -> Vec<(u32, &'a RowTemplate)> {
+    let mut next = values_start(placement.section, placement.row);
+    placement
+        .section
+        .rows
+        .iter()
+        .map(|row| {
+            let mut at = next + row.skip;
+            if row.bridge.is_some() {
+                at += adjustments as u32;
+            }
+            next = at + 1;
+            (at, row)
+        })
+        .collect()
+}
+
+fn fields(section: &SectionTemplate) -> &[Field] {
+    section.fields.as_deref().unwrap_or(&[Field::Unit, Field::Description])
+}
+
+/// 工作簿展示的DCF的股权调整项
+fn adjustments(result: &AnalysisResult) -> &[BridgeAdjustment] {
+    DisplayedDcf::from_result(result)
+        .and_then(|displayed| displayed.dcf.bridge.as_ref())
+        .map_or(&[], |bridge| bridge.adjustments.as_slice())
+}
+
+fn register(layout: &mut Layout, result: &AnalysisResult, sheet: &SheetTemplate, placement: &Placement) -> Result<()> {
+    let section = placement.section;
+    let name = sheet.name.as_str();
+    match section.kind {
+        SectionKind::Table => {
+            for (i, row) in section.rows.iter().enumerate() {
+                if let Some(key) = &row.key {
+                    layout.register_row(key, name, placement.row + 2 + i as u32, placement.col + TABLE_DATA_COL)?;
+                }
+            }
+        }
+        SectionKind::Grid => {
+            let data_col = grid_data_col(section, placement.col);
+            for (row, template_row) in grid_rows(placement) {
+                if let Some(key) = &template_row.key {
+                    layout.register_row(key, name, row, data_col)?;
+                }
+            }
+        }
+        SectionKind::Values => {
+            for (row, template_row) in value_rows(placement, adjustments(result).len()) {
+                if let Some(key) = &template_row.key {
+                    layout.register_cell(key, name, row, placement.col + 1)?;
+                }
+            }
+        }
+        SectionKind::Valuation => valuation_section::register_valuation(layout, name, placement.row, placement.col)?,
+        SectionKind::DcfTable => {
+            let key = section.key.as_deref().context("dcf_table 分区缺少 key")?;
+            sensitivity_sections::register_dcf_table(layout, result, key, name, placement.row, placement.col)?;
+        }
+        SectionKind::Notes | SectionKind::DcfDetail | SectionKind::Wacc | SectionKind::EquityModels | SectionKind::ImpliedGrowth => {}
+    }
+    Ok(())
+}
+
+fn write_sheet(
+    workbook: &mut Workbook,
+    context: &RenderContext,
+    stock_code: &str,
+    sheet: &SheetTemplate,
+    placements: &[Placement],
+) -> Result<()> {
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(&sheet.name)?;
+    if sheet.style == SheetStyle::Report {
+        SheetBuilder::new(stock_code).write_header(worksheet, sheet.title())?;
+    }
+
+    for placement in placements.iter().filter(|p| p.visible()) {
+        write_section(worksheet, context, sheet, placement).with_context(|| location(sheet, placement.section))?;
+    }
+
+    set_dimensions(worksheet, context, sheet, placements)
+}
+
+fn write_section(worksheet: &mut Worksheet, context: &RenderContext, sheet: &SheetTemplate, placement: &Placement) -> Result<()> {
+    let section = placement.section;
+    let (row, col) = (placement.row, placement.col);
+    let (result, layout, formats) = (context.result, context.layout, &context.formats);
+    match section.kind {
+        SectionKind::Table => write_table(worksheet, context, sheet, placement),
+        SectionKind::Grid => write_grid(worksheet, context, sheet, placement),
+        SectionKind::Values => write_values_section(worksheet, context, sheet, placement),
+        SectionKind::Notes => write_notes(worksheet, context, placement),
+        SectionKind::Valuation => valuation_section::write_valuation(worksheet, result, layout, &sheet.name, row, col, formats),
+        SectionKind::DcfDetail => valuation_section::write_detail(worksheet, result, layout, &sheet.name, row, col, formats),
+        SectionKind::DcfTable => {
+            let inputs = section.inputs.as_ref().context("dcf_table 分区缺少 inputs")?;
+            sensitivity_sections::write_dcf_table_section(worksheet, result, layout, &sheet.name, row, col, &section.title, inputs, formats)
+        }
+        SectionKind::Wacc => sensitivity_sections::write_wacc(
+            worksheet,
+            result,
+            layout,
+            &sheet.name,
+            row,
+            col,
+            &section.title,
+            section.discount_rate.as_deref(),
+            formats,
+        ),
+        SectionKind::EquityModels => sensitivity_sections::write_equity_models(worksheet, result, row, col, &section.title, formats),
+        SectionKind::ImpliedGrowth => sensitivity_sections::write_implied_growth(worksheet, result, row, col, &section.title, formats),
+    }
+}
+
+/// `table` 分区：标题行、列标题（分类、财务指标、各期、单位、说明）和数据行
+fn write_table(worksheet: &mut Worksheet, context: &RenderContext, sheet: &SheetTemplate, placement: &Placement) -> Result<()> {
+    let section = placement.section;
+    let formats = &context.formats;
+    let (start, col) = (placement.row, placement.col);
+    let num_years = context.layout.periods();
+    let data_col = col + TABLE_DATA_COL;
+    let unit_col = data_col + num_years as u16;
+    let desc_col = unit_col + 1;
+
+    worksheet.merge_range(start, col, start, placement.end_col() - 1, &section.title, &formats.header)?;
+
+    let header_row = start + 1;
+    worksheet.write_string_with_format(header_row, col, "分类", &formats.subheader)?;
+    worksheet.write_string_with_format(header_row, col + 1, "财务指标", &formats.subheader)?;
+    for i in 0..num_years {
+        worksheet.write_string_with_format(header_row, data_col + i as u16, context.result.period_label(i), &formats.subheader)?;
+    }
+    worksheet.write_string_with_format(header_row, unit_col, "单位", &formats.subheader)?;
+    worksheet.write_string_with_format(header_row, desc_col, "说明", &formats.subheader)?;
+
+    for (i, template_row) in section.rows.iter().enumerate() {
+        let row = header_row + 1 + i as u32;
+        write_names(worksheet, context, row, Some(col), col + 1, template_row)?;
+        write_values(worksheet, context, &sheet.name, row, data_col, template_row, true).with_context(|| template_row.label.clone())?;
+        worksheet.write_string(row, unit_col, &template_row.unit)?;
+        worksheet.write_string(row, desc_col, description(context, template_row))?;
+    }
+    Ok(())
+}
+
+/// `grid` 分区：没有标题行和说明列，标题行由 `heading` 行给出
+fn write_grid(worksheet: &mut Worksheet, context: &RenderContext, sheet: &SheetTemplate, placement: &Placement) -> Result<()> {
+    let section = placement.section;
+    let formats = &context.formats;
+    let category_col = section.categories.then_some(placement.col);
+    let label_col = grid_label_col(section, placement.col);
+    let data_col = grid_data_col(section, placement.col);
+
+    for (row, template_row) in grid_rows(placement) {
+        match template_row.heading {
+            Some(heading) => {
+                if let Some(col) = category_col.filter(|_| !template_row.category.is_empty()) {
+                    worksheet.write_string_with_format(row, col, &template_row.category, &formats.subheader)?;
+                }
+                if !template_row.label.is_empty() {
+                    worksheet.write_string_with_format(row, label_col, &template_row.label, &formats.subheader)?;
+                }
+                if heading == Heading::Periods {
+                    for i in 0..context.layout.periods() {
+                        worksheet.write_string_with_format(row, data_col + i as u16, context.result.period_label(i), &formats.header)?;
+                    }
+                }
+            }
+            None => {
+                write_names(worksheet, context, row, category_col, label_col, template_row)?;
+                write_values(worksheet, context, &sheet.name, row, data_col, template_row, false).with_context(|| template_row.label.clone())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 写入分类和名称，`emphasis` 指定的单元格加粗
+fn write_names(
+    worksheet: &mut Worksheet,
+    context: &RenderContext,
+    row: u32,
+    category_col: Option<u16>,
+    label_col: u16,
+    template_row: &RowTemplate,
+) -> Result<()> {
+    let emphasis = template_row.emphasis;
+    let write = |worksheet: &mut Worksheet, col: u16, text: &str, bold: bool| -> Result<()> {
+        if bold {
+            worksheet.write_string_with_format(row, col, text, &context.formats.subheader)?;
+        } else if !text.is_empty() {
+            worksheet.write_string(row, col, text)?;
+        }
+        Ok(())
+    };
+    if let Some(col) = category_col {
+        let bold = matches!(emphasis, Some(Emphasis::Category | Emphasis::Both)) && !template_row.category.is_empty();
+        write(worksheet, col, &template_row.category, bold)?;
+    }
+    write(worksheet, label_col, &label(context, template_row), matches!(emphasis, Some(Emphasis::Label | Emphasis::Both)))
+}
+
+/// 按期写入一行数据，第一期在 data_col；`hide_errors` 时表达式出错（如除数为0）显示为空
+fn write_values(
+    worksheet: &mut Worksheet,
+    context: &RenderContext,
+    sheet: &str,
+    row: u32,
+    data_col: u16,
+    template_row: &RowTemplate,
+    hide_errors: bool,
+) -> Result<()> {
+    let format = context.formats.cell(template_row.format);
+    let num_years = context.layout.periods();
+
+    if let Some(statement) = template_row.statement {
+        let account = template_row.account();
+        for i in 0..num_years {
+            let value = match statement {
+                Statement::Balance => context.data.get_balance_opt(i, account),
+                Statement::Income => context.data.get_income_opt(i, account),
+                Statement::Cashflow => context.data.get_cashflow_opt(i, account),
+            };
+            if let Some(value) = value {
+                write_number(worksheet, row, data_col + i as u16, value, format, template_row)?;
+            }
+        }
+    } else if let Some(metric) = template_row.metric {
+        for (i, value) in metric_values(context.result, metric).iter().take(num_years).enumerate() {
+            let value = value.to_f64().unwrap_or(0.0);
+            write_number(worksheet, row, data_col + i as u16, value, format, template_row)?;
+        }
+    } else if let Some(key) = &template_row.reference {
+        for i in 0..num_years {
+            let formula = format!("={}", context.layout.reference_from(sheet, key, i)?);
+            worksheet.write_formula_with_format(row, data_col + i as u16, formula.as_str(), format)?;
+        }
+    } else if let Some(expr) = &template_row.expr {
+        let tokens = super::template::tokenize(expr)?;
+        for i in 0..num_years {
+            match expand(context.layout, sheet, &tokens, i)? {
+                Some(formula) => {
+                    let formula = if hide_errors { format!("=IFERROR({},\"\")", formula) } else { format!("={}", formula) };
+                    worksheet.write_formula_with_format(row, data_col + i as u16, formula.as_str(), format)?;
+                }
+                None => {
+                    if let Some(text) = &template_row.missing_as {
+                        worksheet.write_string(row, data_col + i as u16, text)?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `values` 分区：标题行、列标题，以及名称、数值和 `fields` 指定的单位、说明
+fn write_values_section(worksheet: &mut Worksheet, context: &RenderContext, sheet: &SheetTemplate, placement: &Placement) -> Result<()> {
+    let section = placement.section;
+    let formats = &context.formats;
+    let (start, col) = (placement.row, placement.col);
+    let value_col = col + 1;
+    let fields = fields(section);
+
+    if !section.title.is_empty() {
+        worksheet.merge_range(start, col, start, placement.end_col() - 1, &section.title, &formats.header)?;
+    }
+    if !section.columns.is_empty() {
+        let header_row = values_start(section, start) - 1;
+        for (i, caption) in section.columns.iter().enumerate() {
+            worksheet.write_string_with_format(header_row, col + i as u16, caption, &formats.subheader)?;
+        }
+    }
+
+    let adjustments = adjustments(context.result);
+    for (row, template_row) in value_rows(placement, adjustments.len()) {
+        let write_fields = |worksheet: &mut Worksheet, row: u32, description: String| -> Result<()> {
+            for (i, field) in fields.iter().enumerate() {
+                let text = match field {
+                    Field::Unit => template_row.unit.clone(),
+                    Field::Description => description.clone(),
+                };
+                worksheet.write_string(row, value_col + 1 + i as u16, text)?;
+            }
+            Ok(())
+        };
+
+        let format = formats.cell(template_row.format);
+        if let Some(base) = &template_row.bridge {
+            // 股权调整逐项列出，本行为基数加全部调整项
+            let first = row - adjustments.len() as u32;
+            for (i, adjustment) in adjustments.iter().enumerate() {
+                let adjustment_row = first + i as u32;
+                worksheet.write_string(adjustment_row, col, valuation_section::adjustment_label(adjustment))?;
+                worksheet.write_number_with_format(adjustment_row, value_col, adjustment.amount.to_f64().unwrap_or(0.0), format)?;
+                write_fields(worksheet, adjustment_row, String::new())?;
+            }
+            let base = context.layout.reference_from(&sheet.name, base, 0)?;
+            let formula = if adjustments.is_empty() {
+                format!("={}", base)
+            } else {
+                format!("={}+SUM({})", base, utility::cell_range(first, value_col, row - 1, value_col))
+            };
+            worksheet.write_formula_with_format(row, value_col, formula.as_str(), format)?;
+        } else {
+            write_value(worksheet, context, &sheet.name, row, value_col, template_row).with_context(|| template_row.label.clone())?;
+        }
+        write_names(worksheet, context, row, None, col, template_row)?;
+        write_fields(worksheet, row, description(context, template_row))?;
+    }
+    Ok(())
+}
+
+/// 单值行的数值：参数、常数、引用第一期或表达式
+fn write_value(worksheet: &mut Worksheet, context: &RenderContext, sheet: &str, row: u32, col: u16, template_row: &RowTemplate) -> Result<()> {
+    let format = context.formats.cell(template_row.format);
+    if let Some(param) = template_row.param {
+        write_number(worksheet, row, col, param_value(context, param)?, format, template_row)?;
+    } else if let Some(value) = template_row.value {
+        write_number(worksheet, row, col, value, format, template_row)?;
+    } else if let Some(key) = &template_row.reference {
+        let formula = format!("={}", context.layout.reference_from(sheet, key, 0)?);
+        worksheet.write_formula_with_format(row, col, formula.as_str(), format)?;
+    } else if let Some(expr) = &template_row.expr {
+        let tokens = super::template::tokenize(expr)?;
+        if let Some(formula) = expand(context.layout, sheet, &tokens, 0)? {
+            worksheet.write_formula_with_format(row, col, format!("={}", formula).as_str(), format)?;
+        }
+    }
+    Ok(())
+}
+
+/// 敏感性分析参数和估值使用的基础数据
+fn param_value(context: &RenderContext, param: Param) -> Result<f64> {
+    let result = context.result;
+    let params = || {
+        result
+            .sensitivity
+            .as_ref()
+            .map(|sensitivity| &sensitivity.params)
+            .context("参数需要敏感性分析结果（工作表或分区可加 requires = \"sensitivity\"）")
+    };
+    let value = match param {
+        Param::DiscountRate => params()?.discount_rate,
+        Param::PerpetualGrowthRate => params()?.perpetual_growth_rate,
+        Param::FcfGrowthRate => params()?.fcf_growth_rate,
+        Param::NetProfitGrowthRate => params()?.net_profit_growth_rate,
+        Param::LowRiskFreeRate => params()?.low_risk_free_rate,
+        Param::HighRiskFreeRate => params()?.high_risk_free_rate,
+        // 没有DCF结果时用最近一年的经营现金流减资本性支出
+        Param::BaseFcf => match DisplayedDcf::from_result(result) {
+            Some(displayed) => displayed.dcf.base_fcf.to_f64().unwrap_or(0.0),
+            None => {
+                context.data.get_cashflow_opt(0, "经营活动产生的现金流量净额").unwrap_or(0.0)
+                    - context.data.get_cashflow_opt(0, "购建固定资产、无形资产和其他长期资产支付的现金").unwrap_or(0.0)
+            }
+        },
+        Param::NetProfit => context.data.get_income_opt(0, "净利润").unwrap_or(0.0),
+        Param::TotalShares => total_shares(result),
+    };
+    Ok(value)
+}
+
+/// `notes` 分区：标题行和逐行说明，均横跨三列
+fn write_notes(worksheet: &mut Worksheet, context: &RenderContext, placement: &Placement) -> Result<()> {
+    let section = placement.section;
+    let (mut row, col) = (placement.row, placement.col);
+    let last_col = placement.end_col() - 1;
+    if !section.title.is_empty() {
+        worksheet.merge_range(row, col, row, last_col, &section.title, &context.formats.header)?;
+        row += 1;
+    }
+    for line in &section.lines {
+        worksheet.merge_range(row, col, row, last_col, line, &Format::new())?;
+        row += 1;
+    }
+    Ok(())
+}
+
+/// 列宽和行高：报告样式按 `table` 分区设置，无报告头的工作表数据列统一加宽并设置行高；`widths` 覆盖缺省列宽
+fn set_dimensions(worksheet: &mut Worksheet, context: &RenderContext, sheet: &SheetTemplate, placements: &[Placement]) -> Result<()> {
+    let visible = || placements.iter().filter(|p| p.visible());
+    match sheet.style {
+        SheetStyle::Report => {
+            let num_years = context.layout.periods() as u16;
+            worksheet.set_column_width(0, 12)?;
+            worksheet.set_column_width(1, sheet.label_width)?;
+            for i in 0..num_years {
+                worksheet.set_column_width(TABLE_DATA_COL + i, 20)?;
+            }
+            worksheet.set_column_width(TABLE_DATA_COL + num_years, 8)?;
+            worksheet.set_column_width(TABLE_DATA_COL + num_years + 1, 30)?;
+        }
+        SheetStyle::Plain => {
+            // 设置更大的列宽以确保完整显示大数字
+            let last_col = visible().map(Placement::end_col).max().unwrap_or(0);
+            worksheet.set_column_width(0, 20)?;
+            worksheet.set_column_width(1, 35)?;
+            for col in 2..=last_col.max(14) {
+                worksheet.set_column_width(col, 30)?;
+            }
+            let last_row = visible().map(Placement::end_row).max().unwrap_or(0);
+            for row in 0..=last_row {
+                worksheet.set_row_height(row, PLAIN_ROW_HEIGHT)?;
+            }
+        }
+    }
+    for (col, width) in sheet.widths.iter().enumerate() {
+        worksheet.set_column_width(col as u16, *width)?;
+    }
+    Ok(())
+}
+
+fn write_number(worksheet: &mut Worksheet, row: u32, col: u16, value: f64, format: &Format, template_row: &RowTemplate) -> Result<()> {
+    match &template_row.zero_as {
+        Some(text) if value.abs() <= 0.01 => worksheet.write_string(row, col, text)?,
+        _ => worksheet.write_number_with_format(row, col, value, format)?,
+    };
+    Ok(())
+}
+
+fn metric_values(result: &AnalysisResult, metric: Metric) -> &[Decimal] {
+    let leverage = result.leverage_analysis.as_ref();
+    match metric {
+        Metric::OperatingAssetRatio => &result.asset_structure.operating_asset_ratio,
+        Metric::FinancialAssetRatio => &result.asset_structure.financial_asset_ratio,
+        Metric::GrossMargin => &result.profit_analysis.gross_margin,
+        Metric::CoreProfitMargin => &result.profit_analysis.core_profit_margin,
+        Metric::NetProfitMargin => &result.profit_analysis.net_profit_margin,
+        Metric::OperatingLeverage => leverage.map(|l| l.operating_leverage.as_slice()).unwrap_or_default(),
+        Metric::FinancialLeverage => leverage.map(|l| l.financial_leverage.as_slice()).unwrap_or_default(),
+        Metric::TotalLeverage => leverage.map(|l| l.total_leverage.as_slice()).unwrap_or_default(),
+    }
+}
+
+/// 展开第 period 期的表达式；引用的上一期不存在时返回 None
+fn expand(layout: &Layout, sheet: &str, tokens: &[Token], period: usize) -> Result<Option<String>> {
+    let mut formula = String::new();
+    for token in tokens {
+        match token {
+            Token::Text(text) => formula.push_str(text),
+            Token::Ref(placeholder) => {
+                let period = if placeholder.prev { period + 1 } else { period };
+                if period >= layout.periods() {
+                    return Ok(None);
+                }
+                formula.push_str(&layout.reference_from(sheet, placeholder.key, period)?);
+            }
+        }
+    }
+    Ok(Some(formula))
+}
+
+/// 名称中的 `{base_fcf_period}` 替换为基准FCF的取值区间
+fn label(context: &RenderContext, template_row: &RowTemplate) -> String {
+    if !template_row.label.contains("{base_fcf_period}") {
+        return template_row.label.clone();
+    }
+    let result = context.result;
+    let years = DisplayedDcf::from_result(result)
+        .map(|displayed| displayed.base_fcf_years)
+        .or(result.sensitivity.as_ref().map(|sensitivity| sensitivity.params.base_fcf_years))
+        .unwrap_or(1);
+    template_row.label.replace("{base_fcf_period}", &base_fcf_period(years))
+}
+
+fn description(context: &RenderContext, template_row: &RowTemplate) -> String {
+    match &template_row.description {
+        Some(text) => {
+            let builtin = context.descriptions.get(text);
+            if builtin.is_empty() {
+                text.clone()
+            } else {
+                builtin
+            }
+        }
+        None => context.descriptions.get(&template_row.label),
+    }
+}
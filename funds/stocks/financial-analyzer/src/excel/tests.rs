@@ -0,0 +1,369 @@
+use super::common::ExcelFormats;
+use super::dcf_table::{write_dcf_table, DcfTableInputs, GrowthInput};
+use super::layout::Layout;
+use super::*;
+use crate::analyzer::{CapitalWeights, FinancialAnalyzer, SensitivityParams, WaccParams};
+use crate::data_source::MockDataSource;
+
+async fn mock_analysis(years: Vec<i32>) -> AnalysisResult {
+    let analyzer = FinancialAnalyzer::new();
+    let mut result = analyzer
+        .analyze("600519.SH", years, &MockDataSource::new())
+        .await
+        .unwrap();
+    analyzer
+        .calculate_sensitivity(&mut result, SensitivityParams::default())
+        .unwrap();
+    result
+}
+
+#[test]
+fn test_layout_references() {
+    let mut layout = Layout::new(3);
+    layout.register_row("bs_total_assets", "资产'表", 20, 2).unwrap();
+    layout.register_row("bs_cash", "资产'表", 3, 2).unwrap();
+    layout.register_cell("dcf_discount_rate", "估值", 16, 7).unwrap();
+
+    assert_eq!(layout.local("bs_total_assets", 0).unwrap(), "C21");
+    assert_eq!(layout.local("bs_total_assets", 2).unwrap(), "E21");
+    assert_eq!(layout.local_span("bs_cash", "bs_total_assets", 1).unwrap(), "D4:D21");
+    assert_eq!(layout.reference("bs_total_assets", 1).unwrap(), "INDEX(bs_total_assets,2)");
+    assert_eq!(layout.reference("dcf_discount_rate", 0).unwrap(), "dcf_discount_rate");
+    assert_eq!(
+        layout.slot("bs_total_assets").unwrap().name_formula(),
+        "='资产''表'!$C$21:$E$21"
+    );
+    assert_eq!(layout.slot("dcf_discount_rate").unwrap().name_formula(), "='估值'!$H$17");
+
+    // 超出期数、未登记、重复登记和无效的键都应报错
+    assert!(layout.local("bs_total_assets", 3).is_err());
+    assert!(layout.reference("dcf_discount_rate", 1).is_err());
+    assert!(layout.reference("bs_unknown", 0).is_err());
+    assert!(layout.register_row("bs_cash", "资产'表", 4, 2).is_err());
+    for key in ["fcf1", "Bs_cash", "货币资金", "bs cash", "_bs"] {
+        assert!(layout.register_row(key, "资产", 30, 2).is_err(), "{}", key);
+    }
+}
+
+#[tokio::test]
+async fn test_three_year_layout_keeps_cell_positions() {
+    let result = mock_analysis(vec![2023, 2022, 2021]).await;
+    let (_, layout) = ExcelWriter::new().build(&result).unwrap();
+
+    assert_eq!(layout.local("bs_cash", 0).unwrap(), "C4");
+    assert_eq!(layout.local("bs_total_assets", 2).unwrap(), "E21");
+    assert_eq!(layout.local("bs_total_equity", 0).unwrap(), "I21");
+    assert_eq!(layout.local("bs_operating_liability_ratio", 0).unwrap(), "I22");
+    assert_eq!(layout.local("is_net_profit", 0).unwrap(), "C19");
+    assert_eq!(layout.local("is_core_profit", 0).unwrap(), "C22");
+    assert_eq!(layout.local("cf_operating_net", 0).unwrap(), "I4");
+    assert_eq!(layout.local("cf_free_cash_flow", 0).unwrap(), "I15");
+    assert_eq!(layout.local("dcf_share_price", 0).unwrap(), "H26");
+    assert_eq!(layout.local("tangchao_sell_price", 0).unwrap(), "I33");
+}
+
+#[tokio::test]
+async fn test_five_year_workbook() {
+    let result = mock_analysis(vec![2023, 2022, 2021, 2020, 2019]).await;
+    assert_eq!(result.asset_structure.years.len(), 5);
+
+    let (mut workbook, layout) = ExcelWriter::new().build(&result).unwrap();
+    assert_eq!(layout.periods(), 5);
+
+    // 每个键覆盖全部5期，同一Sheet内右侧的数据块不与左侧的5列数据重叠
+    let assets = layout.slot("bs_total_assets").unwrap();
+    let liabilities = layout.slot("bs_total_liabilities").unwrap();
+    assert_eq!(assets.width, 5);
+    assert_eq!(assets.name_formula(), "='(经营性&金融性)资产&负债结构分析'!$C$21:$G$21");
+    assert!(liabilities.first_col > assets.col(4) + 2);
+
+    let income = layout.slot("is_net_profit").unwrap();
+    let cashflow = layout.slot("cf_operating_net").unwrap();
+    let valuation = layout.slot("dcf_base_fcf").unwrap();
+    assert!(cashflow.first_col > income.col(4) + 2);
+    assert!(valuation.row > layout.slot("cf_free_cash_flow").unwrap().row);
+    assert_eq!(layout.reference("is_net_profit", 4).unwrap(), "INDEX(is_net_profit,5)");
+
+    let buffer = workbook.save_to_buffer().unwrap();
+    assert!(buffer.starts_with(b"PK"));
+}
+
+#[test]
+fn test_tokenize_expression() {
+    use super::template::{tokenize, Placeholder, Token};
+
+    let tokens = tokenize("({is_net_profit}-{is_net_profit:prev})/ABS({is_net_profit:prev})").unwrap();
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Text("("),
+            Token::Ref(Placeholder { key: "is_net_profit", prev: false }),
+            Token::Text("-"),
+            Token::Ref(Placeholder { key: "is_net_profit", prev: true }),
+            Token::Text(")/ABS("),
+            Token::Ref(Placeholder { key: "is_net_profit", prev: true }),
+            Token::Text(")"),
+        ]
+    );
+
+    for expr in ["{is_net_profit", "is_net_profit}", "{}", "{is_net_profit:next}", "{净利润}"] {
+        assert!(tokenize(expr).is_err(), "{}", expr);
+    }
+}
+
+#[test]
+fn test_builtin_template_is_valid() {
+    let template = ReportTemplate::builtin();
+    template.validate().unwrap();
+    let names: Vec<_> = template.sheets.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "资产&负债结构分析",
+            "(经营性&金融性)资产&负债结构分析",
+            "利润&现金流结构分析",
+            "综合实力分析",
+            "资产负债表分析视角",
+            "敏感性分析",
+            "资产负债表分析(优化版)",
+            "利润&现金流分析(优化版)",
+            "综合实力分析(优化版)",
+            "敏感性分析(优化版)",
+        ]
+    );
+}
+
+#[test]
+fn test_template_validation_errors() {
+    let dir = std::env::temp_dir().join(format!("template_validation_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let cases = [
+        ("no_source.toml", "label = \"货币资金\""),
+        ("two_sources.toml", "label = \"货币资金\"\nstatement = \"balance\"\nref = \"bs_cash\""),
+        ("bad_expr.toml", "label = \"ROE\"\nexpr = \"{is_net_profit/{bs_total_equity}\""),
+        ("account_only.toml", "label = \"现金\"\naccount = \"货币资金\"\nref = \"bs_cash\""),
+    ];
+    for (file, row) in cases {
+        let path = dir.join(file);
+        let content = format!(
+            "[[sheets]]\nname = \"自定义\"\n[[sheets.sections]]\ntitle = \"分区\"\n[[sheets.sections.rows]]\n{}\n",
+            row
+        );
+        std::fs::write(&path, content).unwrap();
+        assert!(ReportTemplate::load(&path).is_err(), "{}", file);
+    }
+    assert!(ReportTemplate::load(&dir.join("template.json")).is_err());
+
+    let sheets = [
+        ("reserved_name.toml", "[[sheets]]\nname = \"数据来源\"\n[[sheets.sections]]\nkind = \"notes\"\nlines = [\"说明\"]\n"),
+        ("unknown_below.toml", "[[sheets]]\nname = \"自定义\"\n[[sheets.sections]]\nkind = \"notes\"\nbelow = \"missing\"\nlines = [\"说明\"]\n"),
+        ("heading_in_table.toml", "[[sheets]]\nname = \"自定义\"\n[[sheets.sections]]\ntitle = \"分区\"\nrows = [{ label = \"年份\", heading = \"periods\" }]\n"),
+        ("dcf_table_without_key.toml", "[[sheets]]\nname = \"自定义\"\n[[sheets.sections]]\nkind = \"dcf_table\"\n"),
+    ];
+    for (file, content) in sheets {
+        let path = dir.join(file);
+        std::fs::write(&path, content).unwrap();
+        assert!(ReportTemplate::load(&path).is_err(), "{}", file);
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_custom_templates() {
+    let dir = std::env::temp_dir().join(format!("template_custom_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let toml_path = dir.join("report.toml");
+    std::fs::write(
+        &toml_path,
+        r#"
+[[sheets]]
+name = "增长分析"
+
+[[sheets.sections]]
+title = "【增长】"
+rows = [
+    { label = "净利润", key = "growth_net_profit", statement = "income" },
+    { label = "净利润增长率", expr = "{growth_net_profit}/{growth_net_profit:prev}-1", format = "percent", unit = "%", description = "自定义说明" },
+    { label = "现金", statement = "balance", account = "货币资金" },
+]
+
+[[sheets.sections]]
+title = "【杠杆】"
+requires = "leverage"
+rows = [{ label = "总杠杆", metric = "total_leverage", zero_as = "-" }]
+"#,
+    )
+    .unwrap();
+
+    let yaml_path = dir.join("report.yaml");
+    std::fs::write(
+        &yaml_path,
+        r#"
+sheets:
+  - name: 偿债
+    sections:
+      - title: 【偿债能力】
+        rows:
+          - label: 负债合计
+            key: debt_total
+            statement: balance
+          - label: 资产总计
+            key: asset_total
+            statement: balance
+          - label: 资产负债率
+            expr: "{debt_total}/{asset_total}"
+            format: percent
+"#,
+    )
+    .unwrap();
+
+    let result = mock_analysis(vec![2023, 2022, 2021]).await;
+
+    let template = ReportTemplate::load(&toml_path).unwrap();
+    let (mut workbook, layout) = ExcelWriter::new().with_template(template).build(&result).unwrap();
+    // 模板中的键登记为布局行：报告头3行 + 分区标题和列标题2行
+    let slot = layout.slot("growth_net_profit").unwrap();
+    assert_eq!((slot.sheet.as_str(), slot.row), ("增长分析", 5));
+    // 自定义模板替换整个默认报告，只保留图表看板
+    assert!(layout.slot("bs_cash").is_err());
+    let names: Vec<String> = workbook.worksheets().iter().map(|w| w.name()).collect();
+    assert_eq!(names, ["图表看板", "增长分析"]);
+    assert!(workbook.save_to_buffer().is_ok());
+
+    let template = ReportTemplate::load(&yaml_path).unwrap();
+    assert_eq!(template.sheets[0].sections[0].rows.len(), 3);
+    let (mut workbook, _) = ExcelWriter::new().with_template(template).build(&result).unwrap();
+    assert!(workbook.worksheet_from_name("偿债").is_ok());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_dashboard_charts() {
+    let result = mock_analysis(vec![2023, 2022, 2021]).await;
+    let (mut workbook, _) = ExcelWriter::new().build(&result).unwrap();
+    assert_eq!(workbook.worksheets()[0].name(), "图表看板");
+
+    // zip 中的文件名不压缩：有估值结果时生成4个图表
+    let buffer = workbook.save_to_buffer().unwrap();
+    let contains = |name: &str| buffer.windows(name.len()).any(|w| w == name.as_bytes());
+    assert!(contains("xl/charts/chart4.xml"));
+    assert!(!contains("xl/charts/chart5.xml"));
+
+    let mut result = result;
+    result.sensitivity = None;
+    result.valuation = None;
+    let (mut workbook, _) = ExcelWriter::new().build(&result).unwrap();
+    let buffer = workbook.save_to_buffer().unwrap();
+    let contains = |name: &str| buffer.windows(name.len()).any(|w| w == name.as_bytes());
+    assert!(contains("xl/charts/chart3.xml"));
+    assert!(!contains("xl/charts/chart4.xml"));
+}
+
+#[tokio::test]
+async fn test_comparison_workbook() {
+    let analyzer = FinancialAnalyzer::new();
+    let mut results = Vec::new();
+    for code in ["600519.SH", "000858.SZ", "000568.SZ"] {
+        results.push(analyzer.analyze(code, vec![2023, 2022], &MockDataSource::new()).await.unwrap());
+    }
+    let comparison = crate::analyzer::Comparison::new(&results).unwrap();
+    let mut workbook = ComparisonWriter::build(&comparison).unwrap();
+
+    let names: Vec<String> = workbook.worksheets().iter().map(|w| w.name()).collect();
+    assert_eq!(names, vec!["指标对比", "指标排名", "共同比报表", "估值对比"]);
+
+    let buffer = workbook.save_to_buffer().unwrap();
+    let contains = |text: &str| buffer.windows(text.len()).any(|w| w == text.as_bytes());
+    assert!(contains("xl/worksheets/sheet4.xml"));
+    assert!(!contains("xl/worksheets/sheet5.xml"));
+}
+
+#[tokio::test]
+async fn test_batch_summary_workbook() {
+    let mut report = crate::batch::BatchReport::default();
+    report.results.push(mock_analysis(vec![2023, 2022]).await);
+    report.fail("000000.SZ", &anyhow::anyhow!("数据源无数据"));
+
+    let mut workbook = BatchSummaryWriter::build(&report).unwrap();
+    let names: Vec<String> = workbook.worksheets().iter().map(|w| w.name()).collect();
+    assert_eq!(names, vec!["批量汇总", "失败列表"]);
+
+    let buffer = workbook.save_to_buffer().unwrap();
+    let contains = |text: &str| buffer.windows(text.len()).any(|w| w == text.as_bytes());
+    assert!(contains("xl/worksheets/sheet2.xml"));
+}
+
+#[tokio::test]
+async fn test_screen_workbook() {
+    let candidates = vec![crate::screen::Candidate {
+        result: mock_analysis(vec![2023, 2022]).await,
+        price: Some(100.0),
+    }];
+    let result = crate::screen::Screener::new("min(roe, 2) > 15% and price > 0", "roe")
+        .unwrap()
+        .run(&candidates);
+    assert_eq!(result.rows.len(), 1);
+
+    let mut workbook = ScreenWriter::build(&result).unwrap();
+    let names: Vec<String> = workbook.worksheets().iter().map(|w| w.name()).collect();
+    assert_eq!(names, vec!["筛选结果"]);
+    assert!(!workbook.save_to_buffer().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_multi_stage_dcf_workbook() {
+    let analyzer = FinancialAnalyzer::new();
+    let mut result = analyzer
+        .analyze("600519.SH", vec![2023, 2022, 2021], &MockDataSource::new())
+        .await
+        .unwrap();
+    let params = SensitivityParams {
+        forecast_stages: vec!["5:15%".parse().unwrap(), "5:~3%".parse().unwrap()],
+        base_fcf_years: 3,
+        ..Default::default()
+    };
+    analyzer.calculate_sensitivity(&mut result, params).unwrap();
+    assert_eq!(result.sensitivity.as_ref().unwrap().dcf_detail.as_ref().unwrap().projections.len(), 10);
+
+    // 10年明细位于杠杆分析之后，不覆盖已登记的单元格；每股价值由股权价值除以总股本
+    let (mut workbook, layout) = ExcelWriter::new().build(&result).unwrap();
+    assert_eq!(layout.local("tangchao_sell_price", 0).unwrap(), "I33");
+    assert_eq!(layout.local("dcf_equity_value", 0).unwrap(), "H25");
+    assert_eq!(layout.local("dcf_share_price", 0).unwrap(), "H26");
+    assert!(workbook.save_to_buffer().unwrap().starts_with(b"PK"));
+
+    // 逐年预测表：10个预测年之后是永续价值，预测期现值合计不含永续价值
+    let formats = ExcelFormats::new();
+    let growth: Vec<(u32, GrowthInput)> = result.sensitivity.as_ref().unwrap().dcf_detail.as_ref().unwrap()
+        .projections
+        .iter()
+        .map(|p| (p.year, GrowthInput::Rate(p.growth_rate)))
+        .collect();
+    let inputs = DcfTableInputs { base_fcf: "B1".to_string(), discount_rate: "B2".to_string(), perpetual_growth: "B3".to_string() };
+    let mut worksheet = rust_xlsxwriter::Worksheet::new();
+    let table = write_dcf_table(&mut worksheet, 5, 0, &growth, &inputs, &formats.number, &formats.percent, &formats.subheader).unwrap();
+    assert_eq!((table.first_row, table.terminal_row), (6, 16));
+    assert_eq!(table.forecast_sum(), "=SUM(E7:E16)");
+    assert_eq!(table.terminal_pv(), "E17");
+}
+
+#[tokio::test]
+async fn test_wacc_sensitivity_workbook() {
+    let analyzer = FinancialAnalyzer::new();
+    let mut result = mock_analysis(vec![2023, 2022, 2021]).await;
+    let params = WaccParams {
+        weights: CapitalWeights::Book,
+        ..Default::default()
+    };
+    let wacc = analyzer.calculate_wacc(&result, params).unwrap();
+    analyzer
+        .calculate_sensitivity(&mut result, SensitivityParams::default().with_wacc(wacc))
+        .unwrap();
+
+    let (mut workbook, _) = ExcelWriter::new().build(&result).unwrap();
+    assert!(workbook.save_to_buffer().unwrap().starts_with(b"PK"));
+}
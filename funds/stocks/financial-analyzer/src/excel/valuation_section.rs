@@ -0,0 +1,232 @@
+//! 估值分区：DCF与唐朝估值的参数和结果（`valuation`），以及DCF逐年预测明细（`dcf_detail`）
+//!
+//! 估值分区纵向排列，依次为分类、名称、数值、每股价、PE倍数五列；
+//! 预测期现值、永续年金现值和股权调整由逐年预测明细的公式汇总后填入估值分区。
+
+use super::common::ExcelFormats;
+use super::dcf_table::{total_shares, write_dcf_table, DcfTableInputs, DisplayedDcf, GrowthInput};
+use super::layout::Layout;
+use crate::analyzer::BridgeAdjustment;
+use crate::domain::*;
+use anyhow::{bail, Result};
+use rust_decimal::prelude::ToPrimitive;
+use rust_xlsxwriter::*;
+
+/// 估值分区的列数
+pub(crate) const VALUATION_COLS: u16 = 5;
+/// 逐年预测明细的列数（与预测表一致）
+pub(crate) const DETAIL_COLS: u16 = 5;
+
+/// DCF部分的键，第一行的名称随基准FCF的取值区间变化
+const DCF_ROWS: [(&str, &str); 11] = [
+    ("", "dcf_base_fcf"),
+    ("折现率(r)", "dcf_discount_rate"),
+    ("永续年金增长率(g)", "dcf_perpetual_growth"),
+    ("总股本", "dcf_total_shares"),
+    ("预测期现值合计", "dcf_forecast_value"),
+    ("永续年金现值", "dcf_terminal_value"),
+    ("DCF企业价值", "dcf_enterprise_value"),
+    ("加：非经营性资产", "dcf_bridge_additions"),
+    ("减：有息负债及少数股东权益", "dcf_bridge_deductions"),
+    ("股权价值", "dcf_equity_value"),
+    ("DCF每股价值", "dcf_share_price"),
+];
+
+const TANGCHAO_ROWS: [(&str, &str); 6] = [
+    ("净利润增长率", "tangchao_profit_growth"),
+    ("无风险收益率(低估区域)", "tangchao_low_rf"),
+    ("无风险收益率(高估区域)", "tangchao_high_rf"),
+    ("低估买入点", "tangchao_buy_value"),
+    ("再打个7折", "tangchao_discount_value"),
+    ("高估卖出点", "tangchao_sell_value"),
+];
+
+/// 估值总额与对应的每股价
+const TANGCHAO_PRICES: [(&str, &str); 3] = [
+    ("tangchao_buy_value", "tangchao_buy_price"),
+    ("tangchao_discount_value", "tangchao_discount_price"),
+    ("tangchao_sell_value", "tangchao_sell_price"),
+];
+
+/// 估值分区的行数：DCF部分、空行、唐朝估值部分
+pub(crate) fn valuation_rows() -> u32 {
+    DCF_ROWS.len() as u32 + 1 + TANGCHAO_ROWS.len() as u32
+}
+
+/// 逐年预测明细的行数：标题、表头、预测年、永续价值和股权调整；没有DCF结果时不输出
+pub(crate) fn detail_rows(result: &AnalysisResult) -> Option<u32> {
+    let dcf = DisplayedDcf::from_result(result)?.dcf;
+    let adjustments = dcf.bridge.as_ref().map_or(0, |bridge| bridge.adjustments.len());
+    Some(3 + dcf.projections.len() as u32 + adjustments as u32)
+}
+
+/// 登记估值分区的单元格，row / col 为分区左上角
+pub(crate) fn register_valuation(layout: &mut Layout, sheet: &str, row: u32, col: u16) -> Result<()> {
+    let value_col = col + 2;
+    for (i, (_, key)) in DCF_ROWS.iter().enumerate() {
+        layout.register_cell(key, sheet, row + i as u32, value_col)?;
+    }
+    let tangchao_start = row + DCF_ROWS.len() as u32 + 1;
+    for (i, (_, key)) in TANGCHAO_ROWS.iter().enumerate() {
+        layout.register_cell(key, sheet, tangchao_start + i as u32, value_col)?;
+    }
+    let row_of = |layout: &Layout, key: &str| layout.slot(key).map(|slot| slot.row);
+    layout.register_cell("tangchao_low_pe", sheet, row_of(layout, "tangchao_low_rf")?, col + 4)?;
+    layout.register_cell("tangchao_high_pe", sheet, row_of(layout, "tangchao_high_rf")?, col + 4)?;
+    for (value_key, price_key) in TANGCHAO_PRICES {
+        layout.register_cell(price_key, sheet, row_of(layout, value_key)?, col + 3)?;
+    }
+    Ok(())
+}
+
+/// 写入估值分区：参数和基准FCF取自实际估值，唐朝估值以三年后净利润乘以无风险收益率对应的PE
+pub(crate) fn write_valuation(
+    worksheet: &mut Worksheet,
+    result: &AnalysisResult,
+    layout: &Layout,
+    sheet: &str,
+    row: u32,
+    col: u16,
+    formats: &ExcelFormats,
+) -> Result<()> {
+    let name_col = col + 1;
+    let displayed = DisplayedDcf::from_result(result);
+    let base_label = displayed.as_ref().map_or_else(|| super::dcf_table::base_fcf_label(1), DisplayedDcf::base_label);
+
+    for (i, (name, key)) in DCF_ROWS.iter().enumerate() {
+        let row = row + i as u32;
+        let name = if i == 0 { base_label.as_str() } else { name };
+        if i == 0 || *key == "dcf_share_price" {
+            worksheet.write_string_with_format(row, col, "DCF估值", &formats.subheader)?;
+        } else {
+            worksheet.write_string(row, col, "DCF估值")?;
+        }
+        if *key == "dcf_share_price" {
+            worksheet.write_string_with_format(row, name_col, name, &formats.subheader)?;
+        } else {
+            worksheet.write_string(row, name_col, name)?;
+        }
+    }
+    let tangchao_start = row + DCF_ROWS.len() as u32 + 1;
+    for (i, (name, key)) in TANGCHAO_ROWS.iter().enumerate() {
+        let row = tangchao_start + i as u32;
+        worksheet.write_string_with_format(row, col, "唐朝估值", &formats.subheader)?;
+        if matches!(*key, "tangchao_buy_value" | "tangchao_sell_value") {
+            worksheet.write_string_with_format(row, name_col, *name, &formats.subheader)?;
+        } else {
+            worksheet.write_string(row, name_col, *name)?;
+        }
+    }
+
+    let cell = |key: &str| layout.local(key, 0);
+    let write = |worksheet: &mut Worksheet, key: &str, formula: String, format: &Format| -> Result<()> {
+        let slot = layout.slot(key)?;
+        worksheet.write_formula_with_format(slot.row, slot.first_col, formula.as_str(), format)?;
+        Ok(())
+    };
+    let write_number = |worksheet: &mut Worksheet, key: &str, value: f64| -> Result<()> {
+        let slot = layout.slot(key)?;
+        worksheet.write_number(slot.row, slot.first_col, value)?;
+        Ok(())
+    };
+
+    // DCF：结果由逐年预测明细的公式汇总
+    let shares = cell("dcf_total_shares")?;
+    write_number(worksheet, "dcf_total_shares", total_shares(result))?;
+    match &displayed {
+        Some(displayed) => {
+            let slot = layout.slot("dcf_base_fcf")?;
+            worksheet.write_number_with_format(slot.row, slot.first_col, displayed.dcf.base_fcf.to_f64().unwrap_or(0.0), &formats.number)?;
+            write_number(worksheet, "dcf_discount_rate", displayed.discount_rate)?;
+            write_number(worksheet, "dcf_perpetual_growth", displayed.perpetual_growth_rate)?;
+            write(worksheet, "dcf_enterprise_value", format!("={}+{}", cell("dcf_forecast_value")?, cell("dcf_terminal_value")?), &formats.number)?;
+            write(
+                worksheet,
+                "dcf_equity_value",
+                format!("=SUM({})", layout.local_span("dcf_enterprise_value", "dcf_bridge_deductions", 0)?),
+                &formats.number,
+            )?;
+            write(worksheet, "dcf_share_price", format!("={}/{}", cell("dcf_equity_value")?, shares), &formats.highlight_number)?;
+        }
+        None => {
+            for (_, key) in DCF_ROWS.iter().filter(|(_, key)| *key != "dcf_total_shares") {
+                let slot = layout.slot(key)?;
+                worksheet.write_string(slot.row, slot.first_col, "-")?;
+            }
+        }
+    }
+
+    // 唐朝估值：三年后净利润 × 无风险收益率对应的PE
+    write_number(worksheet, "tangchao_profit_growth", 0.1)?;
+    write_number(worksheet, "tangchao_low_rf", 0.04)?;
+    write_number(worksheet, "tangchao_high_rf", 0.02)?;
+    write(worksheet, "tangchao_low_pe", format!("=1/{}", cell("tangchao_low_rf")?), &formats.number)?;
+    write(worksheet, "tangchao_high_pe", format!("=1/{}", cell("tangchao_high_rf")?), &formats.number)?;
+
+    let net_profit = layout.reference_from(sheet, "is_net_profit", 0)?;
+    let future_profit = format!("({}*POWER(1+{},3))", net_profit, cell("tangchao_profit_growth")?);
+    write(worksheet, "tangchao_buy_value", format!("={}*{}", future_profit, cell("tangchao_low_pe")?), &formats.highlight_number)?;
+    write(worksheet, "tangchao_discount_value", format!("={}*0.7", cell("tangchao_buy_value")?), &formats.highlight_number)?;
+    write(worksheet, "tangchao_sell_value", format!("={}*{}", future_profit, cell("tangchao_high_pe")?), &formats.highlight_number)?;
+    for (value_key, price_key) in TANGCHAO_PRICES {
+        write(worksheet, price_key, format!("={}/{}", cell(value_key)?, shares), &formats.highlight_number)?;
+    }
+    Ok(())
+}
+
+/// 写入DCF逐年预测明细（敏感性分析优先），并把预测期现值、永续年金现值和股权调整汇总到估值分区
+pub(crate) fn write_detail(
+    worksheet: &mut Worksheet,
+    result: &AnalysisResult,
+    layout: &Layout,
+    sheet: &str,
+    row: u32,
+    col: u16,
+    formats: &ExcelFormats,
+) -> Result<()> {
+    let Some(displayed) = DisplayedDcf::from_result(result) else {
+        return Ok(());
+    };
+    if layout.slot("dcf_forecast_value")?.sheet != sheet {
+        bail!("dcf_detail 分区需要与 valuation 分区在同一工作表");
+    }
+    let dcf = displayed.dcf;
+    let cell = |key: &str| layout.local(key, 0);
+    let write = |worksheet: &mut Worksheet, key: &str, formula: String| -> Result<()> {
+        let slot = layout.slot(key)?;
+        worksheet.write_formula_with_format(slot.row, slot.first_col, formula.as_str(), &formats.number)?;
+        Ok(())
+    };
+
+    worksheet.write_string_with_format(row, col, "DCF预测明细", &formats.subheader)?;
+    let growth: Vec<(u32, GrowthInput)> = dcf.projections.iter().map(|p| (p.year, GrowthInput::Rate(p.growth_rate))).collect();
+    let inputs = DcfTableInputs { base_fcf: cell("dcf_base_fcf")?, discount_rate: cell("dcf_discount_rate")?, perpetual_growth: cell("dcf_perpetual_growth")? };
+    let table = write_dcf_table(worksheet, row + 1, col, &growth, &inputs, &formats.number, &formats.percent, &formats.subheader)?;
+
+    write(worksheet, "dcf_forecast_value", table.forecast_sum())?;
+    write(worksheet, "dcf_terminal_value", format!("={}", table.terminal_pv()))?;
+
+    // 股权调整：加项为正、减项为负，分别汇总到估值分区
+    let mut row = table.terminal_row + 1;
+    let first_adjustment_row = row;
+    for adjustment in dcf.bridge.iter().flat_map(|bridge| &bridge.adjustments) {
+        worksheet.write_string(row, col, adjustment_label(adjustment))?;
+        worksheet.write_number_with_format(row, table.value_col, adjustment.amount.to_f64().unwrap_or(0.0), &formats.number)?;
+        row += 1;
+    }
+    if row == first_adjustment_row {
+        write(worksheet, "dcf_bridge_additions", "=0".to_string())?;
+        write(worksheet, "dcf_bridge_deductions", "=0".to_string())?;
+    } else {
+        let range = utility::cell_range(first_adjustment_row, table.value_col, row - 1, table.value_col);
+        write(worksheet, "dcf_bridge_additions", format!("=SUMIF({range},\">0\")"))?;
+        write(worksheet, "dcf_bridge_deductions", format!("=SUMIF({range},\"<0\")"))?;
+    }
+    Ok(())
+}
+
+/// 股权调整项的名称，如 “加：货币资金”
+pub(crate) fn adjustment_label(adjustment: &BridgeAdjustment) -> String {
+    let sign = if adjustment.amount.is_sign_negative() { "减" } else { "加" };
+    format!("{}：{}", sign, adjustment.item)
+}
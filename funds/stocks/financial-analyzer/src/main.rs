@@ -7,90 +7,136 @@ mod utils;
 mod validation;
 mod report;
 mod error;
+mod mcp;
+mod batch;
+mod screen;
 
 pub use error::{AnalyzerError, Result as AnalyzerResult};
 use anyhow::Result;
 use clap::Parser;
-use cli::{Cli, Commands};
-use data_source::{DataSource, MockDataSource, TushareClient, AkshareClient};
-use analyzer::FinancialAnalyzer;
-use excel::ExcelWriter;
-use utils::Config;
+use cli::{CacheAction, Cli, Commands};
+use data_source::factory::{self, SourceOptions};
+use data_source::{AccountClassifier, CacheStore, CachedDataSource, DataSource, Recorder, StatementStore};
+use analyzer::{Comparison, FinancialAnalyzer};
+use batch::{BatchRunner, Watchlist};
+use excel::{BatchSummaryWriter, ComparisonWriter, ExcelWriter, ReportTemplate, ScreenWriter};
+use utils::{Config, DataSourcesConfig};
 use validation::DataValidator;
-use report::TextReporter;
-use std::path::PathBuf;
+use screen::Screener;
+use report::{HtmlReporter, JsonReporter, MarkdownReporter, OutputFormat, TextReporter};
+use domain::{AnalysisResult, StockCode, StockId};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // 初始化日志
-    tracing_subscriber::fmt()
+    let cli = Cli::parse();
+
+    // 初始化日志（MCP 模式下标准输出只能用于协议消息，日志写到标准错误）
+    let logger = tracing_subscriber::fmt()
         .with_env_filter(
             tracing_subscriber::EnvFilter::from_default_env()
                 .add_directive(tracing::Level::INFO.into())
-        )
-        .init();
-
-    let cli = Cli::parse();
+        );
+    if matches!(cli.command, Commands::Mcp) {
+        logger.with_writer(std::io::stderr).with_ansi(false).init();
+    } else {
+        logger.init();
+    }
 
     match cli.command {
         Commands::Analyze {
             stock,
             years,
             source,
+            data_dir,
+            period,
             output,
+            format,
+            template,
             enable_validation,
+            no_cache,
+            record,
+            replay,
             discount_rate,
             perpetual_growth_rate,
             fcf_growth_rate,
             net_profit_growth_rate,
             low_risk_free_rate,
             high_risk_free_rate,
+            forecast_stages,
+            base_fcf_years,
+            wacc,
+            risk_free_rate,
+            equity_risk_premium,
+            beta,
+            stock_prices,
+            index_prices,
+            price,
+            capital_weights,
+            models,
+            cost_of_equity,
+            high_growth_years,
+            high_growth_rate,
         } => {
-            println!("🔍 分析股票: {}", stock);
+            check_stock_codes(&source, std::slice::from_ref(&stock));
+            match stock.code() {
+                Some(code) => println!("🔍 分析股票: {}（{}{}）", stock, code.exchange().display_name(), code.board().display_name()),
+                None => println!("🔍 分析股票: {}", stock),
+            }
+            let stock_code = stock.to_string();
             println!("📅 年份: {:?}", years);
             println!("📊 数据源: {}", source);
+            println!("🗓️  期间: {}", period.as_str());
 
-            // 创建数据源
-            let data_source: Box<dyn DataSource> = match source.as_str() {
-                "mock" => Box::new(MockDataSource::new()),
-                "tushare" => {
-                    match TushareClient::from_env() {
-                        Ok(client) => {
-                            println!("✓ Tushare客户端已初始化");
-                            Box::new(client)
-                        }
-                        Err(e) => {
-                            eprintln!("❌ Tushare初始化失败: {}", e);
-                            eprintln!("💡 请设置环境变量: export TUSHARE_TOKEN=your_token");
-                            std::process::exit(1);
-                        }
-                    }
+            // 加载配置（科目映射缺失时退回内置映射）
+            let config = Config::load();
+            let classifier = match &config {
+                Ok(config) => AccountClassifier::new(config.account_mapping.clone()),
+                Err(e) => {
+                    eprintln!("⚠️  警告: 无法加载配置文件: {}", e);
+                    eprintln!("   使用内置科目映射");
+                    AccountClassifier::default()
                 }
-                "akshare" => {
-                    println!("✓ AKShare客户端已初始化");
-                    Box::new(AkshareClient::new())
+            };
+
+            // 创建数据源
+            let data_sources_config = DataSourcesConfig::load_or_default();
+            let recorder = match (record, replay) {
+                (Some(dir), _) => {
+                    println!("⏺️  录制原始响应到: {}", dir.display());
+                    Some(Recorder::record(dir))
                 }
-                _ => {
-                    eprintln!("❌ 不支持的数据源: {}", source);
-                    eprintln!("💡 当前支持: mock, tushare, akshare");
-                    std::process::exit(1);
+                (_, Some(dir)) => {
+                    println!("⏯️  从录制目录回放: {}", dir.display());
+                    Some(Recorder::replay(dir))
                 }
+                _ => None,
+            };
+            let options = SourceOptions {
+                data_dir: data_dir.as_deref(),
+                recorder: recorder.clone(),
+            };
+            let data_source = create_data_source(&source, &options, classifier, &data_sources_config);
+            let data_source = if no_cache || !factory::cacheable(&source, &options) {
+                data_source
+            } else {
+                factory::with_cache(data_source, &data_sources_config.cache)
             };
 
             // 创建分析器
-            let mut analyzer = FinancialAnalyzer::new();
+            let mut analyzer = FinancialAnalyzer::new().with_period(period);
 
             // 如果启用验证，加载配置
             if enable_validation {
                 println!("🔐 启用数据验证...");
-                match Config::load() {
+                match config {
                     Ok(config) => {
                         let validator = DataValidator::new(config.validation_rules);
                         analyzer = analyzer.with_validator(validator);
                         println!("✓ 验证规则已加载");
                     }
-                    Err(e) => {
-                        eprintln!("⚠️  警告: 无法加载配置文件: {}", e);
+                    Err(_) => {
                         eprintln!("   继续执行但不进行数据验证");
                     }
                 }
@@ -98,7 +144,7 @@ async fn main() -> Result<()> {
 
             // 执行分析
             println!("⏳ 正在获取数据...");
-            let mut result = analyzer.analyze(&stock, years, data_source.as_ref()).await?;
+            let mut result = analyzer.analyze(&stock_code, years, data_source.as_ref()).await?;
 
             // 默认启用敏感性分析
             println!("🔬 计算敏感性分析...");
@@ -123,13 +169,58 @@ async fn main() -> Result<()> {
             if let Some(high_rf) = high_risk_free_rate {
                 sensitivity_params.high_risk_free_rate = high_rf;
             }
+            sensitivity_params.forecast_stages = forecast_stages;
+            if let Some(years) = base_fcf_years {
+                sensitivity_params.base_fcf_years = years;
+            }
+            sensitivity_params.models = models;
+            if let Some(ke) = cost_of_equity {
+                sensitivity_params.cost_of_equity = ke;
+            }
+            if let Some(years) = high_growth_years {
+                sensitivity_params.high_growth_years = years;
+            }
+            if let Some(g) = high_growth_rate {
+                sensitivity_params.high_growth_rate = g;
+            }
+            sensitivity_params.market_price = price;
+            if wacc {
+                let mut wacc_params = analyzer::WaccParams {
+                    weights: capital_weights,
+                    price,
+                    ..Default::default()
+                };
+                if let Some(rf) = risk_free_rate {
+                    wacc_params.risk_free_rate = rf;
+                }
+                if let Some(erp) = equity_risk_premium {
+                    wacc_params.equity_risk_premium = erp;
+                }
+                if let Some(beta) = beta {
+                    wacc_params.beta = beta;
+                }
+                if let (Some(stock_path), Some(index_path)) = (&stock_prices, &index_prices) {
+                    let stock_closes = analyzer::load_closes(stock_path)?;
+                    let index_closes = analyzer::load_closes(index_path)?;
+                    let estimate = analyzer::BetaEstimate::regress(&stock_closes, &index_closes)?;
+                    println!("📈 β = {:.2}（{} 个收益率样本，{} 至 {}）", estimate.beta, estimate.observations, estimate.start_date, estimate.end_date);
+                    wacc_params = wacc_params.with_beta_estimate(estimate);
+                    if wacc_params.price.is_none() {
+                        wacc_params.price = stock_closes.values().next_back().copied();
+                        sensitivity_params.market_price = wacc_params.price;
+                    }
+                }
+                let wacc = analyzer.calculate_wacc(&result, wacc_params)?;
+                println!("📐 WACC = {:.2}%（股权成本 {:.2}%，税前债务成本 {:.2}%）", wacc.wacc * 100.0, wacc.cost_of_equity * 100.0, wacc.cost_of_debt * 100.0);
+                sensitivity_params = sensitivity_params.with_wacc(wacc);
+            }
             
             analyzer.calculate_sensitivity(&mut result, sensitivity_params)?;
             println!("✓ 敏感性分析完成");
 
             // 确定输出文件名
             let output_path = output.unwrap_or_else(|| {
-                PathBuf::from(format!("../analyzer-report/{}_财务分析.xlsx", stock.replace(".", "_")))
+                PathBuf::from(format!("../analyzer-report/{}_财务分析.xlsx", file_stem(&stock_code)))
             });
 
             // 创建输出目录（如果不存在）
@@ -137,19 +228,344 @@ async fn main() -> Result<()> {
                 std::fs::create_dir_all(parent)?;
             }
 
-            // 生成文本报告（控制台输出 + 保存文件）
-            println!("\n📊 生成文本报告...\n");
-            TextReporter::generate(&result, &stock, output_path.to_str().unwrap_or("output.xlsx"))?;
+            let mut excel_writer = ExcelWriter::new();
+            if let Some(path) = &template {
+                excel_writer = excel_writer.with_template(ReportTemplate::load(path)?);
+                println!("📐 使用报告模板: {}", path.display());
+            }
+            write_reports(&result, &format, &output_path, &excel_writer)?;
+
+            println!("✅ 分析完成！");
+        }
+        Commands::Compare { stocks, years, source, data_dir, period, output, no_cache } => {
+            if stocks.len() < 2 {
+                eprintln!("❌ compare 至少需要两只股票");
+                std::process::exit(1);
+            }
+            check_stock_codes(&source, &stocks);
+            let codes: Vec<&str> = stocks.iter().map(StockId::as_str).collect();
+            println!("🔍 对比股票: {}", codes.join(", "));
+            println!("📅 年份: {:?}", years);
+            println!("📊 数据源: {}", source);
+            println!("🗓️  期间: {}", period.as_str());
+
+            let classifier = match Config::load() {
+                Ok(config) => AccountClassifier::new(config.account_mapping),
+                Err(_) => AccountClassifier::default(),
+            };
+            let data_sources_config = DataSourcesConfig::load_or_default();
+            let options = SourceOptions {
+                data_dir: data_dir.as_deref(),
+                recorder: None,
+            };
+            let data_source = create_data_source(&source, &options, classifier, &data_sources_config);
+            let data_source = if no_cache || !factory::cacheable(&source, &options) {
+                data_source
+            } else {
+                factory::with_cache(data_source, &data_sources_config.cache)
+            };
+
+            // 各公司使用相同的年份和期间，报告期在对比时对齐
+            let analyzer = FinancialAnalyzer::new().with_period(period);
+            let mut results = Vec::with_capacity(codes.len());
+            for code in &codes {
+                println!("⏳ 正在分析: {}", code);
+                results.push(analyzer.analyze(code, years.clone(), data_source.as_ref()).await?);
+            }
+
+            let comparison = Comparison::new(&results)?;
+            let labels: Vec<&str> = comparison.periods.iter().map(|p| p.label.as_str()).collect();
+            println!("📐 共同报告期: {}", labels.join(", "));
+
+            let output_path = output.unwrap_or_else(|| {
+                let stems: Vec<String> = codes.iter().map(|s| file_stem(s)).collect();
+                PathBuf::from(format!("../analyzer-report/{}_对比分析.xlsx", stems.join("_")))
+            });
+            if let Some(parent) = output_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            ComparisonWriter::generate(&comparison, &output_path)?;
+            println!("📄 对比报告已保存到: {}", output_path.display());
+            println!("✅ 对比完成！");
+        }
+        Commands::Batch {
+            watchlist,
+            source,
+            data_dir,
+            period,
+            output_dir,
+            format,
+            jobs,
+            enable_validation,
+            no_cache,
+        } => {
+            let watchlist_path = watchlist;
+            let watchlist = Watchlist::load(&watchlist_path)?;
+            println!("📋 自选股清单: {}（{} 只）", watchlist_path.display(), watchlist.stocks.len());
+            println!("📊 数据源: {}", source);
+            println!("🗓️  期间: {}", period.as_str());
+
+            let config = Config::load();
+            let classifier = match &config {
+                Ok(config) => AccountClassifier::new(config.account_mapping.clone()),
+                Err(_) => AccountClassifier::default(),
+            };
+            let data_sources_config = DataSourcesConfig::load_or_default();
+            let options = SourceOptions {
+                data_dir: data_dir.as_deref(),
+                recorder: None,
+            };
+            let data_source = create_data_source(&source, &options, classifier, &data_sources_config);
+            let data_source = if no_cache || !factory::cacheable(&source, &options) {
+                data_source
+            } else {
+                factory::with_cache(data_source, &data_sources_config.cache)
+            };
+
+            let mut analyzer = FinancialAnalyzer::new().with_period(period);
+            if enable_validation {
+                match config {
+                    Ok(config) => analyzer = analyzer.with_validator(DataValidator::new(config.validation_rules)),
+                    Err(_) => eprintln!("⚠️  无法加载验证规则，继续执行但不进行数据验证"),
+                }
+            }
+
+            println!("⏳ 正在分析（并发 {}）...", jobs.max(1));
+            let mut report = BatchRunner::new(analyzer, Arc::from(data_source))
+                .with_concurrency(jobs)
+                .run(&watchlist)
+                .await;
 
-            // 生成Excel
-            println!("📝 正在生成Excel报告...");
+            // 逐只写报告，写入失败同样记入失败列表
+            std::fs::create_dir_all(&output_dir)?;
             let excel_writer = ExcelWriter::new();
-            excel_writer.generate(&result, &output_path)?;
+            let mut written = Vec::with_capacity(report.results.len());
+            for result in std::mem::take(&mut report.results) {
+                let output_path = output_dir.join(format!("{}_财务分析.xlsx", file_stem(&result.stock_code)));
+                match write_reports(&result, &format, &output_path, &excel_writer) {
+                    Ok(()) => written.push(result),
+                    Err(e) => report.fail(&result.stock_code, &e),
+                }
+            }
+            report.results = written;
 
-            println!("✅ 分析完成！");
-            println!("📄 报告已保存到: {}", output_path.display());
+            let summary_path = output_dir.join("批量汇总.xlsx");
+            BatchSummaryWriter::generate(&report, &summary_path)?;
+            println!("📄 汇总报告已保存到: {}", summary_path.display());
+
+            for failure in &report.failures {
+                eprintln!("❌ {}: {}", failure.code, failure.reason);
+            }
+            println!("✅ 批量分析完成：成功 {} 只，失败 {} 只", report.results.len(), report.failures.len());
         }
+        Commands::Screen {
+            filter,
+            input,
+            prices,
+            sort,
+            ascending,
+            limit,
+            output,
+        } => {
+            let screener = Screener::new(&filter, &sort)?
+                .with_ascending(ascending)
+                .with_limit(limit);
+            let prices = match &prices {
+                Some(path) => screen::load_prices(path)?,
+                None => Default::default(),
+            };
+            let candidates = screen::load_candidates(&input, &prices)?;
+            println!("🔍 筛选条件: {}", filter);
+            println!("📂 报告目录: {}（{} 只股票）", input.display(), candidates.len());
+
+            let result = screener.run(&candidates);
+            if result.rows.is_empty() {
+                println!("📭 没有符合条件的股票");
+            } else {
+                println!("\n{}\n", result.table());
+            }
+
+            if let Some(output_path) = output {
+                if let Some(parent) = output_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(parent)?;
+                }
+                ScreenWriter::generate(&result, &output_path)?;
+                println!("📄 筛选结果已保存到: {}", output_path.display());
+            }
+            println!("✅ 筛选完成：{} 只中入选 {} 只", result.screened, result.rows.len());
+        }
+        Commands::Cache { action } => {
+            let data_sources_config = DataSourcesConfig::load_or_default();
+            let store = CacheStore::from_config(&data_sources_config.cache);
+
+            match action {
+                CacheAction::List => {
+                    let entries = store.list()?;
+                    if entries.is_empty() {
+                        println!("📭 缓存为空: {}", store.dir().display());
+                    }
+                    for entry in &entries {
+                        println!(
+                            "{:<10} {:<12} {:<20} {} ~ {}  {}{}",
+                            entry.source,
+                            entry.stock_code,
+                            entry.report_type.as_str(),
+                            entry.start_date,
+                            entry.end_date,
+                            entry.cached_at.format("%Y-%m-%d %H:%M:%S"),
+                            if store.is_expired(entry) { "  (已过期)" } else { "" }
+                        );
+                    }
+                }
+                CacheAction::Clear { stock } => {
+                    let removed = store.clear(stock.as_ref().map(StockId::as_str))?;
+                    println!("🗑️  已删除 {} 个缓存文件", removed);
+                }
+                CacheAction::Warm { stocks, years, source, period } => {
+                    check_stock_codes(&source, &stocks);
+                    let classifier = match Config::load() {
+                        Ok(config) => AccountClassifier::new(config.account_mapping),
+                        Err(_) => AccountClassifier::default(),
+                    };
+                    let data_source = CachedDataSource::new(
+                        create_data_source(&source, &SourceOptions::default(), classifier, &data_sources_config),
+                        store,
+                    );
+                    let (_, start_date, end_date) = analyzer::report_period(years, period);
+
+                    for stock in &stocks {
+                        println!("⏳ 预热缓存: {}", stock);
+                        data_source.fetch_balance_sheet(stock.as_str(), start_date, end_date).await?;
+                        data_source.fetch_income_statement(stock.as_str(), start_date, end_date).await?;
+                        data_source.fetch_cashflow_statement(stock.as_str(), start_date, end_date).await?;
+                    }
+                    println!("✅ 已缓存 {} 只股票", stocks.len());
+                }
+            }
+        }
+        Commands::Sync { stocks, years, source, period } => {
+            if !matches!(source.as_str(), "akshare" | "tushare") {
+                eprintln!("❌ sync 只支持 akshare 和 tushare 数据源");
+                std::process::exit(1);
+            }
+            check_stock_codes(&source, &stocks);
+
+            let data_sources_config = DataSourcesConfig::load_or_default();
+            let store = StatementStore::from_config(&data_sources_config.store)?;
+            let classifier = match Config::load() {
+                Ok(config) => AccountClassifier::new(config.account_mapping),
+                Err(_) => AccountClassifier::default(),
+            };
+            let data_source = create_data_source(&source, &SourceOptions::default(), classifier, &data_sources_config);
+
+            let (_, start_date, end_date) = analyzer::report_period(years, period);
+            let expected = data_source::store::expected_report_dates(
+                start_date,
+                end_date,
+                period,
+                chrono::Local::now().date_naive(),
+            );
+
+            for stock in &stocks {
+                println!("⏳ 同步: {}", stock);
+                let report = store.sync(data_source.as_ref(), stock.as_str(), &expected).await?;
+                println!("   新增 {} 份报表，{} 个报告期暂无数据", report.saved, report.missing);
+            }
+
+            // 报表库按统一的代码存储
+            for entry in store.summary()?.iter().filter(|e| stocks.iter().any(|s| s.key() == e.stock_code)) {
+                println!(
+                    "{:<12} {:<10} {:<20} {:>3} 期  {} ~ {}",
+                    entry.stock_code, entry.source, entry.report_type, entry.count, entry.earliest, entry.latest
+                );
+            }
+            println!("✅ 报表库: {}", data_sources_config.store.path);
+        }
+        Commands::Mcp => {
+            let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+            mcp::serve(stdin, tokio::io::stdout()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 按输出格式生成报告；json/txt/html/md 文件与 xlsx 同名、扩展名不同
+fn write_reports(
+    result: &AnalysisResult,
+    format: &[OutputFormat],
+    output_path: &Path,
+    excel_writer: &ExcelWriter,
+) -> Result<()> {
+    // 生成文本报告（控制台输出 + 保存文件）
+    if OutputFormat::includes(format, OutputFormat::Txt) {
+        println!("\n📊 生成文本报告...\n");
+        TextReporter::generate(result, &result.stock_code, output_path.to_str().unwrap_or("output.xlsx"))?;
+    }
+
+    // 生成JSON
+    if OutputFormat::includes(format, OutputFormat::Json) {
+        let json_path = output_path.with_extension("json");
+        JsonReporter::write(result, &json_path)?;
+        println!("🧾 JSON报告已保存到: {}", json_path.display());
+    }
+
+    // 生成HTML
+    if OutputFormat::includes(format, OutputFormat::Html) {
+        let html_path = output_path.with_extension("html");
+        HtmlReporter::write(result, &html_path)?;
+        println!("🌐 HTML报告已保存到: {}", html_path.display());
     }
 
+    // 生成Markdown
+    if OutputFormat::includes(format, OutputFormat::Md) {
+        let md_path = output_path.with_extension("md");
+        MarkdownReporter::write(result, &md_path)?;
+        println!("📑 Markdown报告已保存到: {}", md_path.display());
+    }
+
+    // 生成Excel
+    if OutputFormat::includes(format, OutputFormat::Xlsx) {
+        println!("📝 正在生成Excel报告...");
+        excel_writer.generate(result, output_path)?;
+        println!("📄 Excel报告已保存到: {}", output_path.display());
+    }
     Ok(())
 }
+
+/// 检查股票代码能否由数据源查询，不能时退出
+fn check_stock_codes(source: &str, stocks: &[StockId]) {
+    for stock in stocks {
+        if let Err(e) = factory::validate_stock_code(source, stock.as_str()) {
+            eprintln!("❌ 股票代码 {} 无法用于数据源 {}: {:#}", stock, source, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// 报告文件名中的股票代码：可识别的代码统一为 600519_SH，其余标识保留原写法
+fn file_stem(stock: &str) -> String {
+    stock
+        .parse::<StockCode>()
+        .map(|code| code.file_stem())
+        .unwrap_or_else(|_| stock.replace('.', "_"))
+}
+
+/// 创建数据源，失败时提示并退出
+fn create_data_source(
+    source: &str,
+    options: &SourceOptions,
+    classifier: AccountClassifier,
+    config: &DataSourcesConfig,
+) -> Box<dyn DataSource> {
+    match factory::create_data_source(source, options, classifier, config) {
+        Ok(data_source) => {
+            println!("✓ 数据源已初始化: {}", data_source.name());
+            data_source
+        }
+        Err(e) => {
+            eprintln!("❌ {:#}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! MCP（Model Context Protocol）服务：通过标准输入输出收发 JSON-RPC 2.0 消息
+//!
+//! 每行一条消息。工具调用直接返回序列化的分析结果（`structuredContent`），
+//! 调用方不再需要从文本报告中用正则提取数值。
+//! 标准输出只用于协议消息，日志一律写到标准错误。
+
+mod tools;
+#[cfg(test)]
+mod tests;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+/// 默认协议版本（客户端指定时沿用客户端的版本）
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// JSON-RPC 错误码
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// JSON-RPC 错误
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// 逐行读取请求并写回响应，直到输入结束
+pub async fn serve<R, W>(reader: R, mut writer: W) -> Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    tracing::info!("MCP 服务已启动（stdio）");
+    let mut lines = reader.lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(response) = handle_message(&line).await {
+            let mut text = serde_json::to_string(&response)?;
+            text.push('\n');
+            writer.write_all(text.as_bytes()).await?;
+            writer.flush().await?;
+        }
+    }
+
+    tracing::info!("MCP 输入已关闭，服务退出");
+    Ok(())
+}
+
+/// 处理一条消息；通知没有响应
+async fn handle_message(line: &str) -> Option<Value> {
+    let message: Value = match serde_json::from_str(line) {
+        Ok(message) => message,
+        Err(e) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, format!("JSON解析失败: {}", e)))),
+    };
+
+    let request: Request = match serde_json::from_value(message) {
+        Ok(request) => request,
+        Err(e) => return Some(error_response(Value::Null, RpcError::new(INVALID_REQUEST, format!("无效的请求: {}", e)))),
+    };
+
+    let Some(id) = request.id.clone() else {
+        tracing::debug!("收到通知: {}", request.method);
+        return None;
+    };
+
+    tracing::info!("MCP 请求: {}", request.method);
+    Some(match dispatch(&request.method, request.params).await {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => error_response(id, e),
+    })
+}
+
+async fn dispatch(method: &str, params: Value) -> std::result::Result<Value, RpcError> {
+    match method {
+        "initialize" => {
+            let version = params
+                .get("protocolVersion")
+                .and_then(Value::as_str)
+                .unwrap_or(PROTOCOL_VERSION);
+            Ok(json!({
+                "protocolVersion": version,
+                "capabilities": { "tools": { "listChanged": false } },
+                "serverInfo": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            }))
+        }
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools::definitions() })),
+        "tools/call" => {
+            let name = params
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or_else(|| RpcError::new(INVALID_PARAMS, "缺少工具名称 name"))?;
+            let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+
+            match tools::call(name, arguments).await {
+                Ok(value) => Ok(tool_result(value)),
+                Err(tools::CallError::UnknownTool) => Err(RpcError::new(INVALID_PARAMS, format!("未知工具: {}", name))),
+                Err(tools::CallError::InvalidArguments(message)) => {
+                    Err(RpcError::new(INVALID_PARAMS, format!("{} 参数错误: {}", name, message)))
+                }
+                // 工具执行失败按 MCP 约定放在结果中返回，便于模型看到错误信息
+                Err(tools::CallError::Failed(e)) => Ok(json!({
+                    "content": [{ "type": "text", "text": format!("{:#}", e) }],
+                    "isError": true,
+                })),
+            }
+        }
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("不支持的方法: {}", method))),
+    }
+}
+
+/// 工具结果：结构化数据放在 structuredContent，同时附带 JSON 文本供不支持结构化结果的客户端使用
+fn tool_result(value: Value) -> Value {
+    let text = serde_json::to_string_pretty(&value).unwrap_or_default();
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "structuredContent": value,
+        "isError": false,
+    })
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
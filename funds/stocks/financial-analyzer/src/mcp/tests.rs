@@ -0,0 +1,154 @@
+use super::*;
+use crate::domain::AnalysisResult;
+
+/// 依次发送消息，返回全部响应
+async fn exchange(messages: &[Value]) -> Vec<Value> {
+    let input: String = messages.iter().map(|m| format!("{}\n", m)).collect();
+    let mut output = Vec::new();
+    serve(input.as_bytes(), &mut output).await.unwrap();
+
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+async fn call_tool(name: &str, arguments: Value) -> Value {
+    let responses = exchange(&[json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "tools/call",
+        "params": { "name": name, "arguments": arguments },
+    })])
+    .await;
+    responses.into_iter().next().unwrap()
+}
+
+#[tokio::test]
+async fn test_mcp_initialize_and_list_tools() {
+    let responses = exchange(&[
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "protocolVersion": "2024-11-05" } }),
+        json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "unknown/method" }),
+    ])
+    .await;
+
+    // 通知没有响应
+    assert_eq!(responses.len(), 3);
+    assert_eq!(responses[0]["result"]["protocolVersion"], "2024-11-05");
+    assert!(responses[0]["result"]["capabilities"]["tools"].is_object());
+
+    let names: Vec<&str> = responses[1]["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["analyze_stock", "run_sensitivity", "get_statement"]);
+
+    assert_eq!(responses[2]["id"], 3);
+    assert_eq!(responses[2]["error"]["code"], METHOD_NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_mcp_analyze_stock_returns_structured_result() {
+    // 与 analyze_request.json 相同的参数格式
+    let response = call_tool(
+        "analyze_stock",
+        json!({ "stock_code": "600519.SH", "years": "2021,2020,2019", "source": "mock", "no_cache": true }),
+    )
+    .await;
+
+    let result = &response["result"];
+    assert_eq!(result["isError"], false);
+    let analysis: AnalysisResult = serde_json::from_value(result["structuredContent"].clone()).unwrap();
+    assert_eq!(analysis.stock_code, "600519.SH");
+    assert_eq!(analysis.years, vec![2021, 2020, 2019]);
+    assert!(analysis.sensitivity.is_some());
+
+    // 文本内容与结构化结果一致
+    let text: Value = serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap();
+    assert_eq!(text, result["structuredContent"]);
+}
+
+#[tokio::test]
+async fn test_mcp_run_sensitivity_uses_given_params() {
+    let response = call_tool(
+        "run_sensitivity",
+        json!({ "stock_code": "sh600519", "years": [2021, 2020, 2019], "source": "mock", "no_cache": true, "discount_rate": 0.1 }),
+    )
+    .await;
+
+    let content = &response["result"]["structuredContent"];
+    // 代码保留调用方的写法
+    assert_eq!(content["stock_code"], "sh600519");
+    assert_eq!(content["params"]["discount_rate"], 0.1);
+    // 未指定的参数取默认值
+    assert_eq!(content["params"]["perpetual_growth_rate"], 0.04);
+    assert!(content["sensitivity"].is_object());
+}
+
+#[tokio::test]
+async fn test_mcp_get_statement() {
+    let response = call_tool(
+        "get_statement",
+        json!({ "stock_code": "600519", "years": "2021", "source": "mock", "no_cache": true, "statement": "balance_sheet" }),
+    )
+    .await;
+
+    let content = &response["result"]["structuredContent"];
+    assert_eq!(content["statement"], "balance_sheet");
+    let statements = content["statements"].as_array().unwrap();
+    assert!(!statements.is_empty());
+    assert_eq!(statements[0]["statement"]["stock_code"], "600519");
+}
+
+#[tokio::test]
+async fn test_mcp_analyze_unlisted_company_from_files() {
+    let dir = std::env::temp_dir().join(format!("financial-analyzer-mcp-myco-{}", std::process::id()));
+    let company_dir = dir.join("MYCO");
+    std::fs::create_dir_all(&company_dir).unwrap();
+    std::fs::write(company_dir.join("balance_sheet.csv"), "项目,2023,2022\n货币资金,500,400\n资产总计,2000,1800\n负债合计,800,700\n所有者权益合计,1200,1100\n").unwrap();
+    std::fs::write(company_dir.join("income_statement.csv"), "项目,2023,2022\n营业收入,1000,900\n营业成本,600,560\n净利润,150,120\n").unwrap();
+    std::fs::write(company_dir.join("cashflow_statement.csv"), "项目,2023,2022\n经营活动产生的现金流量净额,180,140\n").unwrap();
+
+    // 非交易所代码可用于本地文件数据源，按原写法查找子目录
+    let response = call_tool(
+        "analyze_stock",
+        json!({ "stock_code": "MYCO", "years": "2023,2022", "source": "file", "data_dir": dir }),
+    )
+    .await;
+    assert_eq!(response["result"]["isError"], false, "{}", response);
+    let analysis: AnalysisResult = serde_json::from_value(response["result"]["structuredContent"].clone()).unwrap();
+    assert_eq!(analysis.stock_code, "MYCO");
+    assert_eq!(analysis.years, vec![2023, 2022]);
+
+    // 需要交易所代码的数据源仍然拒绝
+    let rejected = call_tool("analyze_stock", json!({ "stock_code": "MYCO", "source": "tushare" })).await;
+    assert_eq!(rejected["error"]["code"], INVALID_PARAMS);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_mcp_errors() {
+    let invalid = call_tool("analyze_stock", json!({ "stock_code": "600519.SZ", "source": "mock" })).await;
+    assert_eq!(invalid["error"]["code"], INVALID_PARAMS);
+    assert!(invalid["error"]["message"].as_str().unwrap().contains("深交所"));
+
+    let missing = call_tool("get_statement", json!({ "stock_code": "600519.SH", "source": "mock" })).await;
+    assert_eq!(missing["error"]["code"], INVALID_PARAMS);
+
+    let unknown = call_tool("no_such_tool", json!({})).await;
+    assert_eq!(unknown["error"]["code"], INVALID_PARAMS);
+
+    // 数据源错误作为工具结果返回
+    let failed = call_tool("analyze_stock", json!({ "stock_code": "600519.SH", "source": "nope" })).await;
+    assert_eq!(failed["result"]["isError"], true);
+    assert!(failed["result"]["content"][0]["text"].as_str().unwrap().contains("不支持的数据源"));
+
+    let responses = exchange(&[json!("not an object")]).await;
+    assert_eq!(responses[0]["error"]["code"], INVALID_REQUEST);
+}
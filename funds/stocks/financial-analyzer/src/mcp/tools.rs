@@ -0,0 +1,291 @@
+//! MCP 工具定义与实现
+
+use crate::analyzer::{self, FinancialAnalyzer, SensitivityParams};
+use crate::data_source::factory::{self, SourceOptions};
+use crate::data_source::{AccountClassifier, DataSource};
+use crate::domain::{AnalysisResult, ReportPeriod, ReportType, StockId};
+use crate::utils::{Config, DataSourcesConfig};
+use crate::validation::DataValidator;
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+/// 工具调用失败的类型
+pub enum CallError {
+    UnknownTool,
+    /// 参数缺失或格式不正确（协议错误）
+    InvalidArguments(String),
+    /// 工具执行失败（数据源错误等）
+    Failed(anyhow::Error),
+}
+
+impl From<anyhow::Error> for CallError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Failed(e)
+    }
+}
+
+/// 各工具共用的分析参数
+#[derive(Debug, Deserialize)]
+struct AnalysisArgs {
+    stock_code: String,
+    #[serde(default, deserialize_with = "deserialize_years")]
+    years: Vec<i32>,
+    #[serde(default = "default_source")]
+    source: String,
+    #[serde(default)]
+    period: ReportPeriod,
+    #[serde(default)]
+    data_dir: Option<PathBuf>,
+    #[serde(default)]
+    no_cache: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnalyzeArgs {
+    #[serde(flatten)]
+    analysis: AnalysisArgs,
+    #[serde(default)]
+    enable_validation: bool,
+    #[serde(flatten)]
+    sensitivity: SensitivityParams,
+}
+
+#[derive(Debug, Deserialize)]
+struct StatementArgs {
+    #[serde(flatten)]
+    analysis: AnalysisArgs,
+    statement: String,
+}
+
+fn default_source() -> String {
+    "akshare".to_string()
+}
+
+/// 年份既可以是 "2023,2022" 也可以是 [2023, 2022]
+fn deserialize_years<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<i32>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Years {
+        List(Vec<i32>),
+        Text(String),
+    }
+
+    match Years::deserialize(deserializer)? {
+        Years::List(years) => Ok(years),
+        Years::Text(text) => text
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().map_err(|_| serde::de::Error::custom(format!("无效的年份: {}", s))))
+            .collect(),
+    }
+}
+
+/// tools/list 返回的工具列表
+pub fn definitions() -> Value {
+    let analysis_properties = json!({
+        "stock_code": { "type": "string", "description": "股票代码，如 600519.SH、sh600519、00700.HK；file/store 数据源可用任意标识" },
+        "years": {
+            "description": "分析年份，逗号分隔的字符串或整数数组，如 \"2023,2022,2021\"；缺省为最近三年",
+            "oneOf": [
+                { "type": "string" },
+                { "type": "array", "items": { "type": "integer" } }
+            ]
+        },
+        "source": {
+            "type": "string",
+            "description": "数据源: mock, akshare, tushare, file, store；逗号分隔按顺序回退，加号连接逐科目合并",
+            "default": "akshare"
+        },
+        "period": { "type": "string", "enum": ["annual", "quarterly", "ttm"], "default": "annual" },
+        "data_dir": { "type": "string", "description": "本地报表目录（source 为 file 时必填）" },
+        "no_cache": { "type": "boolean", "description": "跳过本地缓存", "default": false }
+    });
+
+    let sensitivity_properties = json!({
+        "discount_rate": { "type": "number", "description": "折现率", "default": 0.08 },
+        "perpetual_growth_rate": { "type": "number", "description": "永续增长率", "default": 0.04 },
+        "fcf_growth_rate": { "type": "number", "description": "FCF增长率", "default": -0.10 },
+        "net_profit_growth_rate": { "type": "number", "description": "净利润增长率", "default": 0.10 },
+        "low_risk_free_rate": { "type": "number", "description": "无风险收益率(低估)", "default": 0.04 },
+        "high_risk_free_rate": { "type": "number", "description": "无风险收益率(高估)", "default": 0.02 },
+        "forecast_stages": {
+            "type": "array",
+            "description": "DCF预测阶段；fade 为 true 时增长率从上一阶段线性渐变。缺省按FCF增长率预测3年",
+            "items": {
+                "type": "object",
+                "required": ["years", "growth_rate"],
+                "properties": {
+                    "years": { "type": "integer", "minimum": 1 },
+                    "growth_rate": { "type": "number" },
+                    "fade": { "type": "boolean", "default": false }
+                }
+            }
+        },
+        "base_fcf_years": { "type": "integer", "description": "基准FCF取最近几年的平均值", "default": 1, "minimum": 1 },
+        "market_price": { "type": "number", "description": "当前股价，提供时反推市场隐含的FCF增长率和净利润增长率（反向DCF）" },
+        "models": {
+            "type": "array",
+            "description": "另行计算的估值模型，与DCF、唐朝估值并列输出",
+            "items": { "type": "string", "enum": ["ddm", "fcfe"] }
+        },
+        "cost_of_equity": { "type": "number", "description": "DDM/FCFE 的股权成本", "default": 0.09 },
+        "high_growth_years": { "type": "integer", "description": "DDM/FCFE 高速增长期年数，0 为 Gordon 单阶段模型", "default": 5, "minimum": 0 },
+        "high_growth_rate": { "type": "number", "description": "DDM/FCFE 高速增长期的增长率", "default": 0.08 }
+    });
+
+    let mut analyze_properties = analysis_properties.clone();
+    merge(&mut analyze_properties, &sensitivity_properties);
+    merge(
+        &mut analyze_properties,
+        &json!({ "enable_validation": { "type": "boolean", "description": "启用数据验证", "default": false } }),
+    );
+
+    let mut sensitivity_tool_properties = analysis_properties.clone();
+    merge(&mut sensitivity_tool_properties, &sensitivity_properties);
+
+    let mut statement_properties = analysis_properties;
+    merge(
+        &mut statement_properties,
+        &json!({
+            "statement": {
+                "type": "string",
+                "enum": ["balance_sheet", "income_statement", "cashflow_statement"],
+                "description": "报表类型"
+            }
+        }),
+    );
+
+    json!([
+        {
+            "name": "analyze_stock",
+            "description": "分析股票财务数据，返回完整的分析结果（资产结构、利润、杠杆、估值、敏感性分析和原始报表）",
+            "inputSchema": { "type": "object", "properties": analyze_properties, "required": ["stock_code"] }
+        },
+        {
+            "name": "run_sensitivity",
+            "description": "按给定参数计算估值敏感性分析，只返回估值和敏感性分析结果",
+            "inputSchema": { "type": "object", "properties": sensitivity_tool_properties, "required": ["stock_code"] }
+        },
+        {
+            "name": "get_statement",
+            "description": "获取单张财务报表（按报告期倒序）",
+            "inputSchema": { "type": "object", "properties": statement_properties, "required": ["stock_code", "statement"] }
+        }
+    ])
+}
+
+fn merge(target: &mut Value, extra: &Value) {
+    if let (Some(target), Some(extra)) = (target.as_object_mut(), extra.as_object()) {
+        target.extend(extra.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+}
+
+/// 执行工具调用
+pub async fn call(name: &str, arguments: Value) -> std::result::Result<Value, CallError> {
+    match name {
+        "analyze_stock" => {
+            let args: AnalyzeArgs = parse_arguments(arguments)?;
+            let result = run_analysis(&args).await?;
+            Ok(serde_json::to_value(&result).map_err(anyhow::Error::from)?)
+        }
+        "run_sensitivity" => {
+            let args: AnalyzeArgs = parse_arguments(arguments)?;
+            let result = run_analysis(&args).await?;
+            Ok(json!({
+                "stock_code": result.stock_code,
+                "params": args.sensitivity,
+                "valuation": result.valuation,
+                "sensitivity": result.sensitivity,
+            }))
+        }
+        "get_statement" => {
+            let args: StatementArgs = parse_arguments(arguments)?;
+            let report_type: ReportType = args.statement.parse().map_err(CallError::InvalidArguments)?;
+            let stock = parse_stock(&args.analysis)?;
+            let statements = fetch_statement(&args.analysis, &stock, report_type).await?;
+            Ok(json!({
+                "stock_code": stock,
+                "statement": report_type.as_str(),
+                "statements": statements,
+            }))
+        }
+        _ => Err(CallError::UnknownTool),
+    }
+}
+
+fn parse_arguments<T: DeserializeOwned>(arguments: Value) -> std::result::Result<T, CallError> {
+    serde_json::from_value(arguments).map_err(|e| CallError::InvalidArguments(e.to_string()))
+}
+
+/// 按数据源检查股票代码，保留调用方的写法（本地文件目录和缓存键均按原写法）
+fn parse_stock(args: &AnalysisArgs) -> std::result::Result<String, CallError> {
+    let stock: StockId = args.stock_code.parse().map_err(CallError::InvalidArguments)?;
+    factory::validate_stock_code(&args.source, stock.as_str())
+        .map_err(|e| CallError::InvalidArguments(format!("{:#}", e)))?;
+    Ok(stock.to_string())
+}
+
+/// 按参数创建数据源（与命令行相同的缓存规则）
+fn open_data_source(args: &AnalysisArgs, classifier: AccountClassifier) -> Result<Box<dyn DataSource>> {
+    let config = DataSourcesConfig::load_or_default();
+    let options = SourceOptions {
+        data_dir: args.data_dir.as_deref(),
+        recorder: None,
+    };
+    let data_source = factory::create_data_source(&args.source, &options, classifier, &config)?;
+
+    Ok(if args.no_cache || !factory::cacheable(&args.source, &options) {
+        data_source
+    } else {
+        factory::with_cache(data_source, &config.cache)
+    })
+}
+
+async fn run_analysis(args: &AnalyzeArgs) -> std::result::Result<AnalysisResult, CallError> {
+    let stock = parse_stock(&args.analysis)?;
+    let config = Config::load();
+    let classifier = match &config {
+        Ok(config) => AccountClassifier::new(config.account_mapping.clone()),
+        Err(_) => AccountClassifier::default(),
+    };
+    let data_source = open_data_source(&args.analysis, classifier)?;
+
+    let mut analyzer = FinancialAnalyzer::new().with_period(args.analysis.period);
+    if args.enable_validation {
+        match config {
+            Ok(config) => analyzer = analyzer.with_validator(DataValidator::new(config.validation_rules)),
+            Err(e) => tracing::warn!("无法加载验证规则，跳过数据验证: {}", e),
+        }
+    }
+
+    let mut result = analyzer
+        .analyze(&stock, args.analysis.years.clone(), data_source.as_ref())
+        .await?;
+    analyzer.calculate_sensitivity(&mut result, args.sensitivity.clone())?;
+    Ok(result)
+}
+
+async fn fetch_statement(args: &AnalysisArgs, stock: &str, report_type: ReportType) -> Result<Value> {
+    let classifier = match Config::load() {
+        Ok(config) => AccountClassifier::new(config.account_mapping),
+        Err(_) => AccountClassifier::default(),
+    };
+    let data_source = open_data_source(args, classifier)?;
+    let (_, start_date, end_date) = analyzer::report_period(args.years.clone(), args.period);
+
+    Ok(match report_type {
+        ReportType::BalanceSheet => {
+            serde_json::to_value(data_source.fetch_balance_sheet(stock, start_date, end_date).await?)?
+        }
+        ReportType::IncomeStatement => {
+            serde_json::to_value(data_source.fetch_income_statement(stock, start_date, end_date).await?)?
+        }
+        ReportType::CashflowStatement => {
+            serde_json::to_value(data_source.fetch_cashflow_statement(stock, start_date, end_date).await?)?
+        }
+    })
+}
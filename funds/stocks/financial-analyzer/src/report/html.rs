@@ -0,0 +1,337 @@
+//! HTML 报告：单个离线文件，样式内联、图表为内联 SVG，不引用任何外部资源
+//!
+//! 章节与文本报告一致：资产&负债结构、利润&现金流、综合实力、估值、敏感性分析和数据验证。
+
+use super::rows::{decimal, equity_model_pairs, implied_growth_pairs, percent, sensitivity_result_pairs, wacc_pairs, ReportRows, Row};
+use super::svg::{self, Series};
+use super::TextReporter;
+use crate::domain::*;
+use anyhow::Result;
+use chrono::Local;
+use std::fmt::Write;
+use std::path::Path;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "PingFang SC", "Microsoft YaHei", sans-serif; color: #262626; margin: 0; background: #F5F7FA; }
+main { max-width: 1080px; margin: 0 auto; padding: 24px; }
+header { border-bottom: 3px solid #1F4E78; margin-bottom: 16px; }
+h1 { color: #1F4E78; margin: 0 0 4px; }
+h2 { color: #1F4E78; border-left: 4px solid #4472C4; padding-left: 8px; margin-top: 32px; }
+h3 { color: #404040; margin: 20px 0 8px; font-size: 15px; }
+.meta { color: #7F7F7F; font-size: 13px; margin: 0 0 12px; }
+nav a { margin-right: 12px; color: #4472C4; text-decoration: none; font-size: 14px; }
+section { background: #FFFFFF; padding: 4px 20px 20px; margin-bottom: 16px; border-radius: 6px; box-shadow: 0 1px 3px rgba(0,0,0,0.08); }
+table { border-collapse: collapse; margin: 8px 0; font-size: 13px; }
+th, td { border: 1px solid #D9D9D9; padding: 4px 10px; }
+th { background: #D9E1F2; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+td.neg { color: #C00000; }
+.charts { display: flex; flex-wrap: wrap; gap: 12px; }
+.charts svg { background: #FFFFFF; border: 1px solid #E0E0E0; max-width: 100%; height: auto; }
+.ok { color: #548235; }
+.bad { color: #C00000; }
+.empty { color: #7F7F7F; }
+.note { color: #7F7F7F; font-size: 0.9em; }
+"#;
+
+/// HTML 报告生成器
+pub struct HtmlReporter;
+
+impl HtmlReporter {
+    pub fn generate(result: &AnalysisResult) -> Result<String> {
+        let rows = ReportRows::new(result);
+        let labels = rows.labels();
+        // 图表按时间顺序从左到右
+        let chart_labels: Vec<String> = labels.iter().rev().cloned().collect();
+
+        let mut html = String::new();
+        write!(
+            html,
+            r##"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>财务分析报告: {code}</title>
+<style>{STYLE}</style>
+</head>
+<body>
+<main>
+<header>
+<h1>财务分析报告: {code}</h1>
+<p class="meta">分析期间: {period}　|　生成时间: {now}</p>
+<nav><a href="#structure">资产&amp;负债结构</a><a href="#profit">利润&amp;现金流</a><a href="#strength">综合实力</a><a href="#valuation">估值</a><a href="#sensitivity">敏感性分析</a><a href="#validation">数据验证</a></nav>
+</header>
+"##,
+            code = escape(&result.stock_code),
+            period = escape(result.period.as_str()),
+            now = Local::now().format("%Y-%m-%d %H:%M:%S"),
+        )?;
+
+        Self::append_structure(&mut html, &rows, &labels, &chart_labels)?;
+        Self::append_profit_cashflow(&mut html, &rows, &labels, &chart_labels)?;
+        Self::append_strength(&mut html, &rows, &labels)?;
+        Self::append_valuation(&mut html, result)?;
+        Self::append_sensitivity(&mut html, result)?;
+        Self::append_validation(&mut html, result)?;
+
+        html.push_str("</main>\n</body>\n</html>\n");
+        Ok(html)
+    }
+
+    /// 写入文件并返回内容
+    pub fn write(result: &AnalysisResult, path: &Path) -> Result<String> {
+        let html = Self::generate(result)?;
+        std::fs::write(path, &html)?;
+        Ok(html)
+    }
+
+    fn append_structure(html: &mut String, rows: &ReportRows, labels: &[String], chart_labels: &[String]) -> Result<()> {
+        html.push_str("<section id=\"structure\">\n<h2>资产&amp;负债结构</h2>\n");
+        Self::period_table(html, "资产", labels, &rows.assets())?;
+        Self::period_table(html, "负债及权益", labels, &rows.liabilities())?;
+
+        let ratios = rows.asset_structure();
+        Self::period_table(html, "资产结构", labels, &ratios)?;
+
+        Self::charts(html, &[svg::column_chart("资产结构", chart_labels, &chart_series(&ratios), true, true)]);
+        html.push_str("</section>\n");
+        Ok(())
+    }
+
+    fn append_profit_cashflow(html: &mut String, rows: &ReportRows, labels: &[String], chart_labels: &[String]) -> Result<()> {
+        html.push_str("<section id=\"profit\">\n<h2>利润&amp;现金流</h2>\n");
+        Self::period_table(html, "利润表", labels, &rows.income())?;
+        Self::period_table(html, "现金流量表", labels, &rows.cashflow())?;
+
+        let margins = rows.margins();
+        Self::period_table(html, "利润率", labels, &margins)?;
+
+        Self::charts(
+            html,
+            &[
+                svg::line_chart("利润率趋势", chart_labels, &chart_series(&margins), true),
+                svg::column_chart("经营现金流 vs 净利润", chart_labels, &chart_series(&rows.cash_quality()), false, false),
+            ],
+        );
+        html.push_str("</section>\n");
+        Ok(())
+    }
+
+    fn append_strength(html: &mut String, rows: &ReportRows, labels: &[String]) -> Result<()> {
+        html.push_str("<section id=\"strength\">\n<h2>综合实力</h2>\n");
+        Self::period_table(html, "盈利与偿债能力", labels, &rows.strength())?;
+
+        match rows.leverage() {
+            Some(leverage) => Self::period_table(html, "杠杆分析", labels, &leverage)?,
+            None => html.push_str("<h3>杠杆分析</h3>\n<p class=\"empty\">数据不足，未计算杠杆</p>\n"),
+        }
+        html.push_str("</section>\n");
+        Ok(())
+    }
+
+    fn append_valuation(html: &mut String, result: &AnalysisResult) -> Result<()> {
+        html.push_str("<section id=\"valuation\">\n<h2>估值</h2>\n");
+        let Some(valuation) = &result.valuation else {
+            html.push_str("<p class=\"empty\">未计算估值</p>\n</section>\n");
+            return Ok(());
+        };
+
+        if let Some(params) = &result.valuation_params {
+            Self::pair_table(
+                html,
+                "估值参数",
+                &[
+                    ("折现率(r)", percent(params.dcf.discount_rate)),
+                    ("永续年金增长率(g)", percent(params.dcf.perpetual_growth_rate)),
+                    ("FCF增长率(G)", percent(params.dcf.fcf_growth_rate)),
+                    ("净利润增长率", percent(params.tangchao.net_profit_growth_rate)),
+                    ("无风险收益率(低估区域)", percent(params.tangchao.low_risk_free_rate)),
+                    ("无风险收益率(高估区域)", percent(params.tangchao.high_risk_free_rate)),
+                    ("安全边际", percent(params.tangchao.safety_margin)),
+                    ("总股本", TextReporter::format_number(decimal(params.total_shares))),
+                ],
+            )?;
+        }
+
+        let prices = [
+            ("安全边际价", decimal(valuation.tangchao.safety_margin_price)),
+            ("唐朝低估价", decimal(valuation.tangchao.low_estimate)),
+            ("DCF每股价值", decimal(valuation.dcf.price_per_share)),
+            ("唐朝高估价", decimal(valuation.tangchao.high_estimate)),
+        ];
+        let mut rows = vec![("DCF企业价值", format!("{}元", TextReporter::format_number(decimal(valuation.dcf.enterprise_value))))];
+        rows.extend(prices.iter().map(|(label, price)| (*label, format!("{:.2}元/股", price))));
+        Self::pair_table(html, "估值结果", &rows)?;
+
+        Self::charts(html, &[svg::bar_chart("估值区间（元/股）", &prices, "元")]);
+        html.push_str("</section>\n");
+        Ok(())
+    }
+
+    fn append_sensitivity(html: &mut String, result: &AnalysisResult) -> Result<()> {
+        html.push_str("<section id=\"sensitivity\">\n<h2>敏感性分析</h2>\n");
+        let Some(sensitivity) = &result.sensitivity else {
+            html.push_str("<p class=\"empty\">未进行敏感性分析</p>\n</section>\n");
+            return Ok(());
+        };
+
+        let params = &sensitivity.params;
+        Self::pair_table(
+            html,
+            "敏感性参数",
+            &[
+                ("折现率(r)", percent(params.discount_rate)),
+                ("永续年金增长率(g)", percent(params.perpetual_growth_rate)),
+                ("FCF增长率(G)", percent(params.fcf_growth_rate)),
+                ("净利润增长率", percent(params.net_profit_growth_rate)),
+                ("无风险收益率(低估区域)", percent(params.low_risk_free_rate)),
+                ("无风险收益率(高估区域)", percent(params.high_risk_free_rate)),
+            ],
+        )?;
+        if let Some(wacc) = &params.wacc {
+            Self::pair_table(html, "折现率(WACC)", &wacc_pairs(wacc))?;
+            for note in &wacc.notes {
+                writeln!(html, "<p class=\"note\">{}</p>", escape(note))?;
+            }
+        }
+        Self::pair_table(
+            html,
+            "估值结果",
+            &sensitivity_result_pairs(sensitivity),
+        )?;
+        for (model, valuation) in sensitivity.equity_models() {
+            let caption = format!("{}估值", model.display_name());
+            Self::pair_table(html, &caption, &equity_model_pairs(valuation))?;
+        }
+        if let Some(pairs) = implied_growth_pairs(sensitivity) {
+            Self::pair_table(html, "市场隐含增长率（反向DCF）", &pairs)?;
+            writeln!(html, "<p class=\"note\">{}</p>", escape(&sensitivity.implied_fcf_growth_note()))?;
+        }
+        html.push_str("</section>\n");
+        Ok(())
+    }
+
+    fn append_validation(html: &mut String, result: &AnalysisResult) -> Result<()> {
+        html.push_str("<section id=\"validation\">\n<h2>数据验证</h2>\n");
+        if result.validation.is_empty() {
+            html.push_str("<p class=\"empty\">未启用数据验证（使用 --enable-validation 启用）</p>\n</section>\n");
+            return Ok(());
+        }
+
+        html.push_str("<table>\n<tr><th>报告期</th><th>报表</th><th>结论</th><th>可靠性评分</th><th>错误</th><th>警告</th></tr>\n");
+        for validation in &result.validation {
+            let (class, verdict) = if validation.result.is_valid { ("ok", "通过") } else { ("bad", "未通过") };
+            writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td class=\"{}\">{}</td><td class=\"num\">{:.0}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                validation.report_date,
+                escape(validation.report_type.display_name()),
+                class,
+                verdict,
+                validation.result.reliability_score,
+                validation.result.errors.len(),
+                validation.result.warnings.len()
+            )?;
+        }
+        html.push_str("</table>\n");
+
+        let findings: Vec<_> = result
+            .validation
+            .iter()
+            .filter(|v| !v.result.errors.is_empty() || !v.result.warnings.is_empty())
+            .collect();
+        if !findings.is_empty() {
+            html.push_str("<h3>问题明细</h3>\n<table>\n<tr><th>报告期</th><th>报表</th><th>级别</th><th>科目</th><th>说明</th></tr>\n");
+            for validation in findings {
+                let date = validation.report_date;
+                let report = escape(validation.report_type.display_name());
+                for error in &validation.result.errors {
+                    writeln!(
+                        html,
+                        "<tr><td>{}</td><td>{}</td><td class=\"bad\">{}</td><td>{}</td><td>{}</td></tr>",
+                        date,
+                        report,
+                        error.severity.display_name(),
+                        escape(&error.field),
+                        escape(&error.message)
+                    )?;
+                }
+                for warning in &validation.result.warnings {
+                    writeln!(
+                        html,
+                        "<tr><td>{}</td><td>{}</td><td>警告</td><td>{}</td><td>{}</td></tr>",
+                        date,
+                        report,
+                        escape(&warning.field),
+                        escape(&warning.message)
+                    )?;
+                }
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str("</section>\n");
+        Ok(())
+    }
+
+    fn period_table(html: &mut String, caption: &str, labels: &[String], rows: &[Row]) -> Result<()> {
+        writeln!(html, "<h3>{}</h3>\n<table>", escape(caption))?;
+        html.push_str("<tr><th>项目</th>");
+        for label in labels {
+            write!(html, "<th>{}</th>", escape(label))?;
+        }
+        html.push_str("</tr>\n");
+
+        for row in rows {
+            write!(html, "<tr><td>{}</td>", escape(&row.label))?;
+            for value in row.values.iter().take(labels.len()) {
+                let class = if value.is_some_and(|v| v < 0.0) { "num neg" } else { "num" };
+                write!(html, "<td class=\"{}\">{}</td>", class, escape(&row.unit.format(*value)))?;
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+        Ok(())
+    }
+
+    fn pair_table(html: &mut String, caption: &str, rows: &[(&str, String)]) -> Result<()> {
+        writeln!(html, "<h3>{}</h3>\n<table>", escape(caption))?;
+        for (label, value) in rows {
+            writeln!(html, "<tr><th>{}</th><td class=\"num\">{}</td></tr>", escape(label), escape(value))?;
+        }
+        html.push_str("</table>\n");
+        Ok(())
+    }
+
+    fn charts(html: &mut String, charts: &[String]) {
+        html.push_str("<div class=\"charts\">\n");
+        for chart in charts {
+            html.push_str(chart);
+            html.push('\n');
+        }
+        html.push_str("</div>\n");
+    }
+}
+
+/// 转义 HTML/SVG 文本中的特殊字符
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 表格按最新一期在前排列，图表按时间顺序
+fn chart_series(rows: &[Row]) -> Vec<Series> {
+    rows.iter()
+        .map(|row| Series::new(&row.label, row.values.iter().rev().copied().collect()))
+        .collect()
+}
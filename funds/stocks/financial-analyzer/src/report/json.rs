@@ -0,0 +1,314 @@
+//! JSON 报告：供下游工具读取的机器可读输出
+//!
+//! 字段结构见 `schemas/analysis_report.v2.schema.json`。新增字段不改变版本号；
+//! 删除或修改已有字段的含义时递增 `SCHEMA_VERSION` 并新增对应的 schema 文件。
+//!
+//! 版本历史：
+//! - v1：DCF 每股价值为企业价值 / 总股本
+//! - v2：DCF 每股价值为股权价值 / 总股本（企业价值经 `bridge` 调整）；读取 v1 报告时按其中的资产负债表换算
+//!
+//! 金额与比率均为十进制字符串（如 `"0.4512"`），避免浮点精度损失。
+
+use crate::analyzer::{RatioCalculator, SensitivityResult, ValuationParams, ValuationResult};
+use crate::domain::{
+    AnalysisResult, AssetStructureAnalysis, FinancialStatement, LeverageAnalysis, ProfitAnalysis, ReportPeriod, ReportType,
+};
+use crate::validation::validator::ValidationResult;
+use crate::validation::StatementValidation;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{Local, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// schema 标识
+pub const SCHEMA_ID: &str = "financial-analyzer/analysis-report";
+/// schema 版本
+pub const SCHEMA_VERSION: u32 = 2;
+/// 仍可读取的最早版本
+pub const MIN_SCHEMA_VERSION: u32 = 1;
+
+/// JSON 报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonReport {
+    pub schema: String,
+    pub schema_version: u32,
+    /// 生成工具及版本
+    pub generator: String,
+    /// 生成时间（RFC 3339）
+    pub generated_at: String,
+    pub stock_code: String,
+    pub period: ReportPeriod,
+    /// 各期标签，与 ratios 的顺序一致（最新一期在前）
+    pub periods: Vec<String>,
+    /// 原始报表（科目按名称排序）
+    pub statements: Vec<JsonStatement>,
+    /// 按期排列的衍生比率
+    pub ratios: Vec<JsonPeriodRatios>,
+    pub valuation: Option<JsonValuation>,
+    pub sensitivity: Option<SensitivityResult>,
+    /// 数据验证结果（未启用验证时为空数组）
+    pub validation: Vec<JsonValidation>,
+}
+
+/// 单张原始报表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonStatement {
+    /// balance_sheet / income_statement / cashflow_statement
+    pub report_type: String,
+    pub report_date: NaiveDate,
+    pub items: BTreeMap<String, Decimal>,
+    /// 各科目的数据来源（仅组合数据源）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sources: BTreeMap<String, String>,
+}
+
+/// 单期衍生比率，无法计算的比率为 null
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonPeriodRatios {
+    pub period: String,
+    pub operating_asset_ratio: Option<Decimal>,
+    pub financial_asset_ratio: Option<Decimal>,
+    pub gross_margin: Option<Decimal>,
+    pub core_profit_margin: Option<Decimal>,
+    pub net_profit_margin: Option<Decimal>,
+    #[serde(default)]
+    pub roe: Option<Decimal>,
+    #[serde(default)]
+    pub roa: Option<Decimal>,
+    #[serde(default)]
+    pub debt_ratio: Option<Decimal>,
+    pub operating_leverage: Option<Decimal>,
+    pub financial_leverage: Option<Decimal>,
+    pub total_leverage: Option<Decimal>,
+}
+
+/// 单张报表的验证结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonValidation {
+    pub report_type: String,
+    pub report_date: NaiveDate,
+    #[serde(flatten)]
+    pub result: ValidationResult,
+}
+
+/// 估值输入与结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonValuation {
+    pub inputs: Option<ValuationParams>,
+    #[serde(flatten)]
+    pub result: ValuationResult,
+}
+
+impl JsonReport {
+    pub fn from_result(result: &AnalysisResult) -> Self {
+        let periods: Vec<String> = if result.period_labels.is_empty() {
+            result.years.iter().map(|year| year.to_string()).collect()
+        } else {
+            result.period_labels.clone()
+        };
+
+        let leverage = result.leverage_analysis.as_ref();
+        let ratios = periods
+            .iter()
+            .enumerate()
+            .map(|(idx, period)| JsonPeriodRatios {
+                period: period.clone(),
+                operating_asset_ratio: result.asset_structure.operating_asset_ratio.get(idx).copied(),
+                financial_asset_ratio: result.asset_structure.financial_asset_ratio.get(idx).copied(),
+                gross_margin: result.profit_analysis.gross_margin.get(idx).copied(),
+                core_profit_margin: result.profit_analysis.core_profit_margin.get(idx).copied(),
+                net_profit_margin: result.profit_analysis.net_profit_margin.get(idx).copied(),
+                roe: result.strength.roe.get(idx).copied().flatten(),
+                roa: result.strength.roa.get(idx).copied().flatten(),
+                debt_ratio: result.strength.debt_ratio.get(idx).copied().flatten(),
+                operating_leverage: leverage.and_then(|l| l.operating_leverage.get(idx).copied()),
+                financial_leverage: leverage.and_then(|l| l.financial_leverage.get(idx).copied()),
+                total_leverage: leverage.and_then(|l| l.total_leverage.get(idx).copied()),
+            })
+            .collect();
+
+        let statements = result
+            .statements
+            .iter()
+            .map(|statement| JsonStatement {
+                report_type: statement.report_type.as_str().to_string(),
+                report_date: statement.report_date,
+                items: statement.items.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+                sources: statement.sources.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            })
+            .collect();
+
+        Self {
+            schema: SCHEMA_ID.to_string(),
+            schema_version: SCHEMA_VERSION,
+            generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            generated_at: Local::now().to_rfc3339(),
+            stock_code: result.stock_code.clone(),
+            period: result.period,
+            periods,
+            statements,
+            ratios,
+            valuation: result.valuation.clone().map(|valuation| JsonValuation {
+                inputs: result.valuation_params.clone(),
+                result: valuation,
+            }),
+            sensitivity: result.sensitivity.clone(),
+            validation: result
+                .validation
+                .iter()
+                .map(|v| JsonValidation {
+                    report_type: v.report_type.as_str().to_string(),
+                    report_date: v.report_date,
+                    result: v.result.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// 还原为分析结果（供筛选等读取已保存报告的功能使用）
+    ///
+    /// 比率从第一个缺失的期间起截断，与分析时按期计算的向量长度一致；
+    /// ROE 等盈利与偿债能力指标按原始报表重新计算，兼容未输出这些字段的旧报告。
+    pub fn into_result(self) -> Result<AnalysisResult> {
+        let years: Vec<i32> = self
+            .periods
+            .iter()
+            .map(|label| {
+                label
+                    .split(|c: char| !c.is_ascii_digit())
+                    .find(|part| part.len() == 4)
+                    .and_then(|year| year.parse().ok())
+                    .ok_or_else(|| anyhow!("无法识别的报告期: {}", label))
+            })
+            .collect::<Result<_>>()?;
+        let series = |value: fn(&JsonPeriodRatios) -> Option<Decimal>| -> Vec<Decimal> {
+            self.ratios.iter().map_while(value).collect()
+        };
+
+        let leverage = LeverageAnalysis {
+            years: years.clone(),
+            operating_leverage: series(|r| r.operating_leverage),
+            financial_leverage: series(|r| r.financial_leverage),
+            total_leverage: series(|r| r.total_leverage),
+        };
+        let asset_structure = AssetStructureAnalysis {
+            years: years.clone(),
+            operating_asset_ratio: series(|r| r.operating_asset_ratio),
+            financial_asset_ratio: series(|r| r.financial_asset_ratio),
+        };
+        let profit_analysis = ProfitAnalysis {
+            years: years.clone(),
+            gross_margin: series(|r| r.gross_margin),
+            core_profit_margin: series(|r| r.core_profit_margin),
+            net_profit_margin: series(|r| r.net_profit_margin),
+        };
+
+        let statements = self
+            .statements
+            .into_iter()
+            .map(|statement| {
+                Ok(FinancialStatement {
+                    stock_code: self.stock_code.clone(),
+                    report_date: statement.report_date,
+                    report_type: statement.report_type.parse().map_err(|e: String| anyhow!(e))?,
+                    items: statement.items.into_iter().collect(),
+                    sources: statement.sources.into_iter().collect(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let strength = RatioCalculator::new().calculate_strength(&statements);
+        let validation = self
+            .validation
+            .into_iter()
+            .map(|v| {
+                Ok(StatementValidation {
+                    report_type: v.report_type.parse().map_err(|e: String| anyhow!(e))?,
+                    report_date: v.report_date,
+                    result: v.result,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let (valuation, valuation_params) = match self.valuation {
+            Some(valuation) => (Some(valuation.result), valuation.inputs),
+            None => (None, None),
+        };
+
+        Ok(AnalysisResult {
+            stock_code: self.stock_code,
+            years: years.clone(),
+            asset_structure,
+            profit_analysis,
+            strength,
+            leverage_analysis: (!leverage.operating_leverage.is_empty()).then_some(leverage),
+            valuation,
+            statements,
+            sensitivity: self.sensitivity,
+            period: self.period,
+            period_labels: self.periods,
+            valuation_params,
+            validation,
+        })
+    }
+}
+
+/// JSON 报告生成器
+pub struct JsonReporter;
+
+impl JsonReporter {
+    pub fn generate(result: &AnalysisResult) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&JsonReport::from_result(result))?)
+    }
+
+    /// 写入文件并返回内容
+    pub fn write(result: &AnalysisResult, path: &Path) -> Result<String> {
+        let json = Self::generate(result)?;
+        std::fs::write(path, &json)?;
+        Ok(json)
+    }
+
+    /// 读取已保存的 JSON 报告；不是本工具生成的报告或版本不兼容时报错
+    pub fn read(path: &Path) -> Result<(JsonReport, AnalysisResult)> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("无法读取 {}", path.display()))?;
+        let value: serde_json::Value = serde_json::from_str(&content).with_context(|| format!("JSON 格式错误: {}", path.display()))?;
+        if value.get("schema").and_then(|s| s.as_str()) != Some(SCHEMA_ID) {
+            bail!("{} 不是分析报告", path.display());
+        }
+        let version = value.get("schema_version").and_then(|v| v.as_u64()).unwrap_or_default();
+        if !(MIN_SCHEMA_VERSION as u64..=SCHEMA_VERSION as u64).contains(&version) {
+            bail!(
+                "{} 的报告版本为 {}，当前支持 {}~{}",
+                path.display(),
+                version,
+                MIN_SCHEMA_VERSION,
+                SCHEMA_VERSION
+            );
+        }
+        let report: JsonReport = serde_json::from_value(value).with_context(|| format!("报告内容错误: {}", path.display()))?;
+        let mut result = report.clone().into_result()?;
+        if version < 2 {
+            Self::upgrade_dcf_to_equity(&mut result);
+        }
+        Ok((report, result))
+    }
+
+    /// v1 报告的 DCF 每股价值按企业价值计算，按报告中最新一期资产负债表换算为股权价值
+    fn upgrade_dcf_to_equity(result: &mut AnalysisResult) {
+        let Some(balance_sheet) = result.statements.iter().find(|s| s.report_type == ReportType::BalanceSheet) else {
+            return;
+        };
+        let Some(total_shares) = result.valuation_params.as_ref().map(|p| p.total_shares).filter(|s| !s.is_zero()) else {
+            return;
+        };
+        if let Some(valuation) = &mut result.valuation {
+            valuation.dcf.bridge_to_equity(balance_sheet, total_shares);
+        }
+        if let Some(sensitivity) = &mut result.sensitivity {
+            if let Some(dcf) = &mut sensitivity.dcf_detail {
+                dcf.bridge_to_equity(balance_sheet, total_shares);
+                sensitivity.dcf_price_per_share = dcf.price_per_share;
+            }
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Markdown 报告：GitHub 风格表格，适合贴到 Wiki 或评审讨论中
+//!
+//! 开头为最新一期关键比率和估值的摘要，各章节每组指标一张表，
+//! 指标说明（`IndicatorDescriptions`）以脚注形式附在文末。
+
+use super::rows::{decimal, equity_model_pairs, implied_growth_pairs, percent, sensitivity_result_pairs, wacc_pairs, ReportRows, Row};
+use super::TextReporter;
+use crate::domain::*;
+use crate::excel::IndicatorDescriptions;
+use anyhow::Result;
+use chrono::Local;
+use std::fmt::Write;
+use std::path::Path;
+
+/// 按首次出现的顺序编号的指标说明脚注
+struct Footnotes {
+    descriptions: IndicatorDescriptions,
+    notes: Vec<(String, String)>,
+}
+
+impl Footnotes {
+    fn new() -> Self {
+        Self {
+            descriptions: IndicatorDescriptions::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    /// 指标的脚注引用，如 `[^3]`；没有说明的指标返回空字符串
+    fn mark(&mut self, key: &str) -> String {
+        let description = self.descriptions.get(key);
+        if description.is_empty() {
+            return String::new();
+        }
+        let index = match self.notes.iter().position(|(k, _)| k == key) {
+            Some(index) => index,
+            None => {
+                self.notes.push((key.to_string(), description));
+                self.notes.len() - 1
+            }
+        };
+        format!("[^{}]", index + 1)
+    }
+
+    fn write(&self, md: &mut String) -> Result<()> {
+        if self.notes.is_empty() {
+            return Ok(());
+        }
+        md.push('\n');
+        for (i, (key, description)) in self.notes.iter().enumerate() {
+            writeln!(md, "[^{}]: {}：{}", i + 1, key, description)?;
+        }
+        Ok(())
+    }
+}
+
+/// Markdown 报告生成器
+pub struct MarkdownReporter;
+
+impl MarkdownReporter {
+    pub fn generate(result: &AnalysisResult) -> Result<String> {
+        let rows = ReportRows::new(result);
+        let labels = rows.labels();
+        let mut notes = Footnotes::new();
+
+        let mut md = String::new();
+        writeln!(md, "# 财务分析报告: {}\n", cell(&result.stock_code))?;
+        writeln!(md, "- 分析期间: {}", result.period.as_str())?;
+        writeln!(md, "- 生成时间: {}\n", Local::now().format("%Y-%m-%d %H:%M:%S"))?;
+
+        Self::append_summary(&mut md, result, &rows, &mut notes)?;
+
+        md.push_str("## 资产&负债结构\n\n");
+        Self::period_table(&mut md, "资产", &labels, &rows.assets(), &mut notes)?;
+        Self::period_table(&mut md, "负债及权益", &labels, &rows.liabilities(), &mut notes)?;
+        Self::period_table(&mut md, "资产结构", &labels, &rows.asset_structure(), &mut notes)?;
+
+        md.push_str("## 利润&现金流\n\n");
+        Self::period_table(&mut md, "利润表", &labels, &rows.income(), &mut notes)?;
+        Self::period_table(&mut md, "现金流量表", &labels, &rows.cashflow(), &mut notes)?;
+        Self::period_table(&mut md, "利润率", &labels, &rows.margins(), &mut notes)?;
+
+        md.push_str("## 综合实力\n\n");
+        Self::period_table(&mut md, "盈利与偿债能力", &labels, &rows.strength(), &mut notes)?;
+        match rows.leverage() {
+            Some(leverage) => Self::period_table(&mut md, "杠杆分析", &labels, &leverage, &mut notes)?,
+            None => md.push_str("### 杠杆分析\n\n数据不足，未计算杠杆\n\n"),
+        }
+
+        Self::append_valuation(&mut md, result, &mut notes)?;
+        Self::append_sensitivity(&mut md, result, &mut notes)?;
+        Self::append_validation(&mut md, result)?;
+
+        notes.write(&mut md)?;
+        Ok(md)
+    }
+
+    /// 写入文件并返回内容
+    pub fn write(result: &AnalysisResult, path: &Path) -> Result<String> {
+        let md = Self::generate(result)?;
+        std::fs::write(path, &md)?;
+        Ok(md)
+    }
+
+    /// 最新一期的关键比率和估值
+    fn append_summary(md: &mut String, result: &AnalysisResult, rows: &ReportRows, notes: &mut Footnotes) -> Result<()> {
+        md.push_str("## 摘要\n\n");
+        let Some(latest) = rows.labels().into_iter().next() else {
+            md.push_str("没有可用的报表数据\n\n");
+            return Ok(());
+        };
+
+        let mut items: Vec<(String, String)> = Vec::new();
+        let key_rows = [rows.margins(), rows.strength(), rows.asset_structure()[..1].to_vec()].concat();
+        for row in &key_rows {
+            let label = format!("{}{}", cell(&row.label), notes.mark(&row.description));
+            items.push((label, row.unit.format(row.values.first().copied().flatten())));
+        }
+
+        // 敏感性分析使用命令行调整后的参数，优先于默认参数的估值
+        let prices = match (&result.sensitivity, &result.valuation) {
+            (Some(s), _) => Some([
+                ("DCF每股价值", s.dcf_price_per_share),
+                ("唐朝低估价", s.tangchao_low_estimate),
+                ("唐朝高估价", s.tangchao_high_estimate),
+                ("唐朝安全边际价", s.tangchao_safety_margin_price),
+            ]),
+            (None, Some(v)) => Some([
+                ("DCF每股价值", v.dcf.price_per_share),
+                ("唐朝低估价", v.tangchao.low_estimate),
+                ("唐朝高估价", v.tangchao.high_estimate),
+                ("唐朝安全边际价", v.tangchao.safety_margin_price),
+            ]),
+            (None, None) => None,
+        };
+        for (label, price) in prices.iter().flatten() {
+            items.push((format!("{}{}", label, notes.mark(label)), format!("{:.2}元/股", decimal(*price))));
+        }
+
+        writeln!(md, "| 指标（{}） | 数值 |\n|------|-----:|", cell(&latest))?;
+        for (label, value) in items {
+            writeln!(md, "| {} | {} |", label, cell(&value))?;
+        }
+        md.push('\n');
+        Ok(())
+    }
+
+    fn append_valuation(md: &mut String, result: &AnalysisResult, notes: &mut Footnotes) -> Result<()> {
+        md.push_str("## 估值\n\n");
+        let Some(valuation) = &result.valuation else {
+            md.push_str("未计算估值\n\n");
+            return Ok(());
+        };
+
+        if let Some(params) = &result.valuation_params {
+            Self::pair_table(
+                md,
+                "估值参数",
+                &[
+                    ("折现率(r)", percent(params.dcf.discount_rate)),
+                    ("永续年金增长率(g)", percent(params.dcf.perpetual_growth_rate)),
+                    ("FCF增长率(G)", percent(params.dcf.fcf_growth_rate)),
+                    ("净利润增长率", percent(params.tangchao.net_profit_growth_rate)),
+                    ("无风险收益率(低估区域)", percent(params.tangchao.low_risk_free_rate)),
+                    ("无风险收益率(高估区域)", percent(params.tangchao.high_risk_free_rate)),
+                    ("安全边际", percent(params.tangchao.safety_margin)),
+                    ("总股本", TextReporter::format_number(decimal(params.total_shares))),
+                ],
+                notes,
+            )?;
+        }
+        Self::pair_table(
+            md,
+            "估值结果",
+            &[
+                ("DCF企业价值", format!("{}元", TextReporter::format_number(decimal(valuation.dcf.enterprise_value)))),
+                ("DCF每股价值", format!("{:.2}元/股", decimal(valuation.dcf.price_per_share))),
+                ("唐朝低估价", format!("{:.2}元/股", decimal(valuation.tangchao.low_estimate))),
+                ("唐朝高估价", format!("{:.2}元/股", decimal(valuation.tangchao.high_estimate))),
+                ("唐朝安全边际价", format!("{:.2}元/股", decimal(valuation.tangchao.safety_margin_price))),
+            ],
+            notes,
+        )
+    }
+
+    fn append_sensitivity(md: &mut String, result: &AnalysisResult, notes: &mut Footnotes) -> Result<()> {
+        md.push_str("## 敏感性分析\n\n");
+        let Some(sensitivity) = &result.sensitivity else {
+            md.push_str("未进行敏感性分析\n\n");
+            return Ok(());
+        };
+
+        let params = &sensitivity.params;
+        Self::pair_table(
+            md,
+            "敏感性参数",
+            &[
+                ("折现率(r)", percent(params.discount_rate)),
+                ("永续年金增长率(g)", percent(params.perpetual_growth_rate)),
+                ("FCF增长率(G)", percent(params.fcf_growth_rate)),
+                ("净利润增长率", percent(params.net_profit_growth_rate)),
+                ("无风险收益率(低估区域)", percent(params.low_risk_free_rate)),
+                ("无风险收益率(高估区域)", percent(params.high_risk_free_rate)),
+            ],
+            notes,
+        )?;
+        if let Some(wacc) = &params.wacc {
+            Self::pair_table(md, "折现率(WACC)", &wacc_pairs(wacc), notes)?;
+            for note in &wacc.notes {
+                writeln!(md, "> {}\n", note)?;
+            }
+        }
+        Self::pair_table(
+            md,
+            "估值结果",
+            &sensitivity_result_pairs(sensitivity),
+            notes,
+        )?;
+        for (model, valuation) in sensitivity.equity_models() {
+            let caption = format!("{}估值", model.display_name());
+            Self::pair_table(md, &caption, &equity_model_pairs(valuation), notes)?;
+        }
+        if let Some(pairs) = implied_growth_pairs(sensitivity) {
+            Self::pair_table(md, "市场隐含增长率（反向DCF）", &pairs, notes)?;
+            writeln!(md, "> {}\n", sensitivity.implied_fcf_growth_note())?;
+        }
+        Ok(())
+    }
+
+    fn append_validation(md: &mut String, result: &AnalysisResult) -> Result<()> {
+        md.push_str("## 数据验证\n\n");
+        if result.validation.is_empty() {
+            md.push_str("未启用数据验证（使用 `--enable-validation` 启用）\n\n");
+            return Ok(());
+        }
+
+        md.push_str("| 报告期 | 报表 | 结论 | 可靠性评分 | 错误 | 警告 |\n|------|------|------|-----:|-----:|-----:|\n");
+        for validation in &result.validation {
+            writeln!(
+                md,
+                "| {} | {} | {} | {:.0} | {} | {} |",
+                validation.report_date,
+                validation.report_type.display_name(),
+                if validation.result.is_valid { "通过" } else { "**未通过**" },
+                validation.result.reliability_score,
+                validation.result.errors.len(),
+                validation.result.warnings.len()
+            )?;
+        }
+        md.push('\n');
+
+        let findings: Vec<_> = result
+            .validation
+            .iter()
+            .filter(|v| !v.result.errors.is_empty() || !v.result.warnings.is_empty())
+            .collect();
+        if !findings.is_empty() {
+            md.push_str("### 问题明细\n\n| 报告期 | 报表 | 级别 | 科目 | 说明 |\n|------|------|------|------|------|\n");
+            for validation in findings {
+                let report = validation.report_type.display_name();
+                for error in &validation.result.errors {
+                    writeln!(
+                        md,
+                        "| {} | {} | {} | {} | {} |",
+                        validation.report_date,
+                        report,
+                        error.severity.display_name(),
+                        cell(&error.field),
+                        cell(&error.message)
+                    )?;
+                }
+                for warning in &validation.result.warnings {
+                    writeln!(
+                        md,
+                        "| {} | {} | 警告 | {} | {} |",
+                        validation.report_date,
+                        report,
+                        cell(&warning.field),
+                        cell(&warning.message)
+                    )?;
+                }
+            }
+            md.push('\n');
+        }
+        Ok(())
+    }
+
+    fn period_table(md: &mut String, caption: &str, labels: &[String], rows: &[Row], notes: &mut Footnotes) -> Result<()> {
+        writeln!(md, "### {}\n", caption)?;
+        md.push_str("| 项目 |");
+        for label in labels {
+            write!(md, " {} |", cell(label))?;
+        }
+        md.push_str("\n|------|");
+        md.push_str(&"-----:|".repeat(labels.len()));
+        md.push('\n');
+
+        for row in rows {
+            write!(md, "| {}{} |", cell(&row.label), notes.mark(&row.description))?;
+            for value in row.values.iter().take(labels.len()) {
+                write!(md, " {} |", row.unit.format(*value))?;
+            }
+            md.push('\n');
+        }
+        md.push('\n');
+        Ok(())
+    }
+
+    fn pair_table(md: &mut String, caption: &str, rows: &[(&str, String)], notes: &mut Footnotes) -> Result<()> {
+        writeln!(md, "### {}\n\n| 项目 | 数值 |\n|------|-----:|", caption)?;
+        for (label, value) in rows {
+            writeln!(md, "| {}{} | {} |", cell(label), notes.mark(label), cell(value))?;
+        }
+        md.push('\n');
+        Ok(())
+    }
+}
+
+/// 表格单元格中的 `|` 和换行需要转义
+pub fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
@@ -5,36 +5,92 @@ use rust_decimal::prelude::ToPrimitive;
 use std::fs::File;
 use std::io::Write;
 
+mod html;
+mod json;
+mod markdown;
+mod rows;
+mod svg;
+#[cfg(test)]
+mod tests;
+
+pub use html::HtmlReporter;
+pub use json::JsonReporter;
+pub use markdown::MarkdownReporter;
+pub use rows::Unit;
+
+/// 报告输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    Xlsx,
+    Txt,
+    /// 单文件离线 HTML
+    Html,
+    /// GitHub 风格 Markdown
+    Md,
+    /// 全部格式
+    All,
+}
+
+impl OutputFormat {
+    /// 是否包含指定格式（All 包含全部）
+    pub fn includes(formats: &[OutputFormat], format: OutputFormat) -> bool {
+        formats.iter().any(|f| *f == format || *f == OutputFormat::All)
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "xlsx" => Ok(Self::Xlsx),
+            "txt" => Ok(Self::Txt),
+            "html" => Ok(Self::Html),
+            "md" | "markdown" => Ok(Self::Md),
+            "all" => Ok(Self::All),
+            _ => Err(format!("不支持的输出格式: {}（可选 json, xlsx, txt, html, md, all）", s)),
+        }
+    }
+}
+
 pub struct TextReporter;
 
 impl TextReporter {
     pub fn generate(result: &AnalysisResult, stock_code: &str, output_path: &str) -> Result<String> {
         let mut report = String::new();
         let now = Local::now().format("%Y-%m-%d %H:%M:%S");
-        let years = &result.asset_structure.years;
+        let labels: Vec<String> = (0..3).map(|i| result.period_label(i)).collect();
         
         // 标题
         report.push_str(&format!("{}\n", "=".repeat(100)));
         report.push_str(&format!("财务分析报告: {}\n", stock_code));
+        report.push_str(&format!("分析期间: {}\n", result.period.as_str()));
         report.push_str(&format!("生成时间: {}\n", now));
         report.push_str(&format!("{}\n\n", "=".repeat(100)));
         
         // Sheet1: 资产&负债结构分析
-        Self::append_sheet1(&mut report, result, years);
+        Self::append_sheet1(&mut report, result, &labels);
         
         // Sheet2: (经营性&金融性)资产&负债结构分析
-        Self::append_sheet2(&mut report, result, years);
+        Self::append_sheet2(&mut report, result, &labels);
         
         // Sheet3: 利润&现金流结构分析
-        Self::append_sheet3(&mut report, result, years);
+        Self::append_sheet3(&mut report, result, &labels);
         
         // Sheet4: 综合实力分析
-        Self::append_sheet4(&mut report, result, years);
+        Self::append_sheet4(&mut report, result, &labels);
         
         // Sheet5: 敏感性分析（如果有）
         if result.sensitivity.is_some() {
             Self::append_sensitivity(&mut report, result);
         }
+
+        // 数据来源（组合数据源）
+        if result.statements.iter().any(|s| !s.sources.is_empty()) {
+            Self::append_provenance(&mut report, result);
+        }
         
         // 输出到控制台
         println!("{}", report);
@@ -48,10 +104,10 @@ impl TextReporter {
         Ok(report)
     }
     
-    fn append_sheet1(report: &mut String, result: &AnalysisResult, years: &[i32]) {
+    fn append_sheet1(report: &mut String, result: &AnalysisResult, labels: &[String]) {
         report.push_str("【Sheet1: 资产&负债结构分析】\n");
         report.push_str(&format!("{}\n", "=".repeat(100)));
-        Self::append_header(report, years);
+        Self::append_header(report, labels);
         
         report.push_str("\n--- 流动资产 ---\n");
         let items = ["货币资金", "应收账款", "存货", "预付款项", "应收票据"];
@@ -75,13 +131,13 @@ impl TextReporter {
         report.push_str("\n--- 负债及权益 ---\n");
         let items = ["负债合计", "所有者权益合计"];
         Self::append_balance_items(report, result, &items);
-        report.push_str("\n");
+        report.push('\n');
     }
     
-    fn append_sheet2(report: &mut String, result: &AnalysisResult, years: &[i32]) {
+    fn append_sheet2(report: &mut String, result: &AnalysisResult, labels: &[String]) {
         report.push_str("【Sheet2: (经营性&金融性)资产&负债结构分析】\n");
         report.push_str(&format!("{}\n", "=".repeat(100)));
-        Self::append_header(report, years);
+        Self::append_header(report, labels);
         
         report.push_str("\n--- 经营性资产 ---\n");
         let items = ["货币资金", "固定资产", "应收票据", "应收账款", "预付款项", "存货", "无形资产"];
@@ -105,13 +161,13 @@ impl TextReporter {
         report.push_str("\n--- 负债及权益 ---\n");
         let items = ["负债合计", "所有者权益合计"];
         Self::append_balance_items(report, result, &items);
-        report.push_str("\n");
+        report.push('\n');
     }
     
-    fn append_sheet3(report: &mut String, result: &AnalysisResult, years: &[i32]) {
+    fn append_sheet3(report: &mut String, result: &AnalysisResult, labels: &[String]) {
         report.push_str("【Sheet3: 利润&现金流结构分析】\n");
         report.push_str(&format!("{}\n", "=".repeat(100)));
-        Self::append_header(report, years);
+        Self::append_header(report, labels);
         
         report.push_str("\n--- 利润表 ---\n");
         let items = ["营业总收入", "营业总成本", "税金及附加", "销售费用", "管理费用", "研发费用", "财务费用"];
@@ -151,13 +207,13 @@ impl TextReporter {
         
         report.push_str("\n--- 唐朝估值 ---\n");
         Self::append_tangchao(report, result);
-        report.push_str("\n");
+        report.push('\n');
     }
     
-    fn append_sheet4(report: &mut String, result: &AnalysisResult, years: &[i32]) {
+    fn append_sheet4(report: &mut String, result: &AnalysisResult, labels: &[String]) {
         report.push_str("【Sheet4: 综合实力分析】\n");
         report.push_str(&format!("{}\n", "=".repeat(100)));
-        Self::append_header(report, years);
+        Self::append_header(report, labels);
         
         report.push_str("\n--- 盈利能力 ---\n");
         // ROE
@@ -201,12 +257,45 @@ impl TextReporter {
         Self::append_income_items(report, result, &["净利润"]);
         let values: Vec<String> = (0..3).map(|i| Self::get_cashflow_value(&result.statements, i, "经营活动产生的现金流量净额")).collect();
         report.push_str(&format!("{:<30} {:>18} {:>18} {:>18}\n", "经营活动现金流量净额", values[0], values[1], values[2]));
-        report.push_str("\n");
+        report.push('\n');
     }
     
-    fn append_header(report: &mut String, years: &[i32]) {
-        report.push_str(&format!("{:<30} {:>18} {:>18} {:>18}\n", "项目",
-            years.get(0).unwrap_or(&0), years.get(1).unwrap_or(&0), years.get(2).unwrap_or(&0)));
+    fn append_provenance(report: &mut String, result: &AnalysisResult) {
+        report.push_str("【数据来源】\n");
+        report.push_str(&format!("{}\n", "=".repeat(100)));
+
+        for statement in result.statements.iter().filter(|s| !s.sources.is_empty()) {
+            let counts = statement.source_counts();
+            let summary: Vec<String> = counts
+                .iter()
+                .map(|(source, count)| format!("{} {}项", source, count))
+                .collect();
+            report.push_str(&format!(
+                "{} {:<8} {}\n",
+                statement.report_date,
+                statement.report_type.display_name(),
+                summary.join(", ")
+            ));
+
+            // 列出提供科目最多的数据源以外各来源的科目
+            for (source, _) in counts.iter().skip(1) {
+                let mut names: Vec<&str> = statement
+                    .sources
+                    .iter()
+                    .filter(|(_, s)| *s == source)
+                    .map(|(name, _)| name.as_str())
+                    .collect();
+                names.sort();
+                let more = if names.len() > 10 { " 等" } else { "" };
+                names.truncate(10);
+                report.push_str(&format!("    来自 {}: {}{}\n", source, names.join("、"), more));
+            }
+        }
+        report.push('\n');
+    }
+
+    fn append_header(report: &mut String, labels: &[String]) {
+        report.push_str(&format!("{:<30} {:>18} {:>18} {:>18}\n", "项目", labels[0], labels[1], labels[2]));
         report.push_str(&format!("{}\n", "-".repeat(100)));
     }
     
@@ -359,39 +448,48 @@ impl TextReporter {
         }
     }
     
+    /// 按默认参数估值的DCF：参数、预测阶段、逐年现值和股权调整均取自实际计算结果
     fn append_dcf(report: &mut String, result: &AnalysisResult) {
-        report.push_str("折现率(r): 8%\n");
-        report.push_str("永续增长率(g): 4%\n");
-        
-        if let (Some(op_cf), Some(capex)) = (
-            Self::get_raw_cashflow_value(&result.statements, 0, "经营活动产生的现金流量净额"),
-            Self::get_raw_cashflow_value(&result.statements, 0, "购建固定资产、无形资产和其他长期资产支付的现金")
-        ) {
-            let fcf = op_cf - capex;
-            report.push_str(&format!("基准FCF (最近一年): {}\n", Self::format_number(fcf)));
-            
-            // 简单DCF计算
-            let r = 0.08;
-            let g = 0.04;
-            let growth = 0.1;
-            let y1 = fcf * (1.0 + growth) / (1.0 + r);
-            let y2 = fcf * (1.0 + growth).powi(2) / (1.0 + r).powi(2);
-            let y3 = fcf * (1.0 + growth).powi(3) / (1.0 + r).powi(3);
-            let terminal = fcf * (1.0 + growth).powi(3) * (1.0 + g) / (r - g) / (1.0 + r).powi(3);
-            let total = y1 + y2 + y3 + terminal;
-            
-            report.push_str(&format!("第1年现值: {}\n", Self::format_number(y1)));
-            report.push_str(&format!("第2年现值: {}\n", Self::format_number(y2)));
-            report.push_str(&format!("第3年现值: {}\n", Self::format_number(y3)));
-            report.push_str(&format!("永续年金现值: {}\n", Self::format_number(terminal)));
-            report.push_str(&format!("企业价值: {}\n", Self::format_number(total)));
-            
-            if let Some(shares) = Self::get_raw_balance_value(&result.statements, 0, "股本") {
-                if shares > 0.0 {
-                    report.push_str(&format!("每股价值: {:.2}元\n", total / shares));
-                }
+        let (Some(valuation), Some(params)) = (&result.valuation, &result.valuation_params) else {
+            report.push_str("无估值结果\n");
+            return;
+        };
+        let dcf = &valuation.dcf;
+        let number = |value: rust_decimal::Decimal| Self::format_number(value.to_f64().unwrap_or(0.0));
+
+        report.push_str(&format!("折现率(r): {:.2}%\n", params.dcf.discount_rate * 100.0));
+        report.push_str(&format!("永续增长率(g): {:.2}%\n", params.dcf.perpetual_growth_rate * 100.0));
+        let stages: Vec<String> = params
+            .dcf
+            .forecast_stages()
+            .iter()
+            .map(|stage| format!("{}年{}{:.2}%", stage.years, if stage.fade { "渐变至" } else { "增长" }, stage.growth_rate * 100.0))
+            .collect();
+        report.push_str(&format!("预测阶段: {}\n", stages.join("，")));
+        let base_label = match params.dcf.base_fcf_years {
+            0 | 1 => "最近一年".to_string(),
+            n => format!("近{}年平均", n),
+        };
+        report.push_str(&format!("基准FCF ({}): {}\n", base_label, number(dcf.base_fcf)));
+
+        for projection in &dcf.projections {
+            report.push_str(&format!(
+                "第{}年现值: {} (增长{:.2}%)\n",
+                projection.year,
+                number(projection.present_value),
+                projection.growth_rate * 100.0
+            ));
+        }
+        report.push_str(&format!("永续年金现值: {}\n", number(dcf.terminal_present_value)));
+        report.push_str(&format!("企业价值: {}\n", number(dcf.enterprise_value)));
+        if let Some(bridge) = &dcf.bridge {
+            for adjustment in &bridge.adjustments {
+                let sign = if adjustment.amount.is_sign_negative() { "减" } else { "加" };
+                report.push_str(&format!("{}：{}: {}\n", sign, adjustment.item, number(adjustment.amount.abs())));
             }
+            report.push_str(&format!("股权价值: {}\n", number(bridge.equity_value)));
         }
+        report.push_str(&format!("每股价值: {:.2}元\n", dcf.price_per_share.to_f64().unwrap_or(0.0)));
     }
     
     fn append_tangchao(report: &mut String, result: &AnalysisResult) {
@@ -449,7 +547,7 @@ impl TextReporter {
             .and_then(|s| s.items.get(account)).map(|d| d.to_string().parse::<f64>().unwrap_or(0.0))
     }
     
-    fn format_number(n: f64) -> String {
+    pub(super) fn format_number(n: f64) -> String {
         if n.abs() >= 1_000_000_000.0 { format!("{:.2}亿", n / 100_000_000.0) }
         else if n.abs() >= 10_000.0 { format!("{:.2}万", n / 10_000.0) }
         else { format!("{:.2}", n) }
@@ -470,6 +568,17 @@ impl TextReporter {
         report.push_str(&format!("{:<30} {:>17.2}%\n", "净利润增长率", sensitivity.params.net_profit_growth_rate * 100.0));
         report.push_str(&format!("{:<30} {:>17.2}%\n", "无风险收益率(低估区域)", sensitivity.params.low_risk_free_rate * 100.0));
         report.push_str(&format!("{:<30} {:>17.2}%\n", "无风险收益率(高估区域)", sensitivity.params.high_risk_free_rate * 100.0));
+
+        if let Some(wacc) = &sensitivity.params.wacc {
+            report.push_str("\n--- 折现率(WACC) ---\n");
+            for (label, value) in rows::wacc_pairs(wacc) {
+                report.push_str(&format!("{:<30} {:>18}\n", label, value));
+            }
+            report.push_str("  WACC = 股权权重 × 股权成本 + 债务权重 × 税后债务成本\n");
+            for note in &wacc.notes {
+                report.push_str(&format!("  注：{}\n", note));
+            }
+        }
         
         report.push_str("\n--- 估值结果 ---\n");
         report.push_str(&format!("{:<30} {:>18} {:>10}\n", "估值方法", "估值结果", "单位"));
@@ -486,42 +595,58 @@ impl TextReporter {
         report.push_str(&format!("{:<30} {:>18.2} {:>10}\n", "唐朝低估价", low_price, "元/股"));
         report.push_str(&format!("{:<30} {:>18.2} {:>10}\n", "唐朝高估价", high_price, "元/股"));
         report.push_str(&format!("{:<30} {:>18.2} {:>10}\n", "唐朝安全边际价", safety_price, "元/股"));
-        
-        // 获取基础FCF用于详细计算说明
-        let base_fcf = result.statements.iter()
-            .find(|s| s.report_type == crate::domain::ReportType::CashflowStatement)
-            .and_then(|s| {
-                let operating = s.items.get("经营活动产生的现金流量净额").copied().unwrap_or_default();
-                let capex = s.items.get("购建固定资产、无形资产和其他长期资产支付的现金").copied().unwrap_or_default();
-                Some((operating - capex).to_f64().unwrap_or(0.0) / 100_000_000.0)
-            })
-            .unwrap_or(0.0);
-        
-        let r = sensitivity.params.discount_rate;
-        let g = sensitivity.params.perpetual_growth_rate;
-        let fcf_g = sensitivity.params.fcf_growth_rate;
-        
-        // 计算3年现值
-        let fcf1 = base_fcf * (1.0 + fcf_g);
-        let fcf2 = base_fcf * (1.0 + fcf_g).powi(2);
-        let fcf3 = base_fcf * (1.0 + fcf_g).powi(3);
-        let pv1 = fcf1 / (1.0 + r);
-        let pv2 = fcf2 / (1.0 + r).powi(2);
-        let pv3 = fcf3 / (1.0 + r).powi(3);
-        let pv_sum = pv1 + pv2 + pv3;
-        
-        // 计算终值
-        let terminal_value = fcf3 * (1.0 + g) / (r - g);
-        let pv_terminal = terminal_value / (1.0 + r).powi(3);
+        for (model, valuation) in sensitivity.equity_models() {
+            report.push_str(&format!("{:<30} {:>18.2} {:>10}\n", model.price_label(), valuation.price_per_share.to_f64().unwrap_or(0.0), "元/股"));
+        }
+
+        if let Some(pairs) = rows::implied_growth_pairs(sensitivity) {
+            report.push_str("\n--- 市场隐含增长率（反向DCF） ---\n");
+            for (label, value) in pairs {
+                report.push_str(&format!("{:<30} {:>18}\n", label, value));
+            }
+            report.push_str(&format!("  {}\n", sensitivity.implied_fcf_growth_note()));
+            report.push_str("  隐含净利润增长率：3年按此增长时，唐朝低估价等于当前股价\n");
+        }
         
         report.push_str("\n--- 计算公式说明 ---\n");
-        report.push_str("DCF估值法（现金流折现模型）：\n");
-        report.push_str(&format!("  基础FCF(最近一年): {:.2}亿元\n", base_fcf));
-        report.push_str(&format!("  前3年现值: {:.2}亿 + {:.2}亿 + {:.2}亿 = {:.2}亿元\n", pv1, pv2, pv3, pv_sum));
-        report.push_str(&format!("  终值现值: {:.2}亿元\n", pv_terminal));
-        report.push_str(&format!("  企业价值 = {:.2}亿 + {:.2}亿 = {:.2}亿元\n", pv_sum, pv_terminal, pv_sum + pv_terminal));
-        report.push_str(&format!("  每股价值 = {:.2}亿 / 总股本 = {:.2}元/股\n\n", pv_sum + pv_terminal, dcf_price));
-        
+        let yi = |value: rust_decimal::Decimal| value.to_f64().unwrap_or(0.0) / 100_000_000.0;
+        if let Some(dcf) = &sensitivity.dcf_detail {
+            let years = dcf.projections.len();
+            let pv_sum: f64 = dcf.projections.iter().map(|p| yi(p.present_value)).sum();
+            let base_label = match sensitivity.params.base_fcf_years {
+                0 | 1 => "最近一年".to_string(),
+                n => format!("近{}年平均", n),
+            };
+
+            report.push_str("DCF估值法（现金流折现模型）：\n");
+            report.push_str(&format!("  基础FCF({}): {:.2}亿元\n", base_label, yi(dcf.base_fcf)));
+            for p in &dcf.projections {
+                report.push_str(&format!(
+                    "  第{}年: FCF {:.2}亿（增长{:.2}%）× 折现系数 {:.4} = {:.2}亿元\n",
+                    p.year,
+                    yi(p.fcf),
+                    p.growth_rate * 100.0,
+                    p.discount_factor.to_f64().unwrap_or(0.0),
+                    yi(p.present_value)
+                ));
+            }
+            report.push_str(&format!("  前{}年现值合计: {:.2}亿元\n", years, pv_sum));
+            report.push_str(&format!("  终值现值: {:.2}亿元\n", yi(dcf.terminal_present_value)));
+            report.push_str(&format!("  企业价值 = {:.2}亿 + {:.2}亿 = {:.2}亿元\n", pv_sum, yi(dcf.terminal_present_value), yi(dcf.enterprise_value)));
+            let equity_value = match &dcf.bridge {
+                Some(bridge) => {
+                    for adjustment in &bridge.adjustments {
+                        let sign = if adjustment.amount.is_sign_negative() { "减" } else { "加" };
+                        report.push_str(&format!("  {}：{} {:.2}亿元\n", sign, adjustment.item, yi(adjustment.amount.abs())));
+                    }
+                    report.push_str(&format!("  股权价值 = {:.2}亿元\n", yi(bridge.equity_value)));
+                    bridge.equity_value
+                }
+                None => dcf.enterprise_value,
+            };
+            report.push_str(&format!("  每股价值 = {:.2}亿 / 总股本 = {:.2}元/股\n\n", yi(equity_value), dcf_price));
+        }
+
         report.push_str("唐朝估值法（PE倍数法）：\n");
         report.push_str(&format!("  3年后净利润 = 当前净利润 × (1 + {}%)^3\n", sensitivity.params.net_profit_growth_rate * 100.0));
         report.push_str(&format!("  低估PE = 1 / {}% = {:.0}倍\n", sensitivity.params.low_risk_free_rate * 100.0, 1.0 / sensitivity.params.low_risk_free_rate));
@@ -529,7 +654,27 @@ impl TextReporter {
         report.push_str(&format!("  低估价 = 3年后净利润 × 低估PE / 总股本 = {:.2}元/股\n", low_price));
         report.push_str(&format!("  高估价 = 3年后净利润 × 高估PE / 总股本 = {:.2}元/股\n", high_price));
         report.push_str(&format!("  安全边际价 = 低估价 × 0.7 = {:.2} × 0.7 = {:.2}元/股\n", low_price, safety_price));
-        
+
+        for (model, valuation) in sensitivity.equity_models() {
+            let params = &sensitivity.params;
+            report.push_str(&format!("\n{}：\n", model.display_name()));
+            report.push_str(&format!("  股权现金流 = {} = {:.2}亿\n", model.cashflow_label(), yi(valuation.base_cashflow)));
+            report.push_str(&format!(
+                "  前{}年按 {:.2}% 增长，之后按 {:.2}% 永续增长，股权成本 {:.2}%\n",
+                params.high_growth_years,
+                params.high_growth_rate * 100.0,
+                params.perpetual_growth_rate * 100.0,
+                params.cost_of_equity * 100.0
+            ));
+            report.push_str(&format!(
+                "  股权价值 = 高速增长期现值 {:.2}亿 + 终值现值 {:.2}亿 = {:.2}亿\n",
+                yi(valuation.high_growth_present_value),
+                yi(valuation.terminal_present_value),
+                yi(valuation.equity_value)
+            ));
+            report.push_str(&format!("  每股价值 = {:.2}元/股\n", valuation.price_per_share.to_f64().unwrap_or(0.0)));
+        }
+
         report.push_str("\n--- 使用说明 ---\n");
         report.push_str("1. 可以通过修改参数重新运行分析，观察估值结果变化\n");
         report.push_str("2. 参数说明：\n");
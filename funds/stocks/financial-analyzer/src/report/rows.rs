@@ -0,0 +1,257 @@
+//! 按期排列的报告行：HTML 与 Markdown 报告共用的章节数据
+//!
+//! 各期按分析结果的顺序排列（最新一期在前），缺失的数值为 `None`。
+
+use super::TextReporter;
+use crate::analyzer::{EquityModelValuation, SensitivityResult, Wacc};
+use crate::domain::*;
+use crate::excel::DataHelper;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+/// 数值的显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    /// 金额（亿/万）
+    Amount,
+    Percent,
+    /// 倍数，绝对值小于0.01显示为 "-"
+    Times,
+}
+
+impl Unit {
+    pub fn format(self, value: Option<f64>) -> String {
+        match (value, self) {
+            (Some(v), Unit::Amount) => TextReporter::format_number(v),
+            (Some(v), Unit::Percent) => format!("{:.2}%", v * 100.0),
+            (Some(v), Unit::Times) if v.abs() >= 0.01 => format!("{:.2}", v),
+            _ => "-".to_string(),
+        }
+    }
+}
+
+/// 按期排列的一行数据
+#[derive(Debug, Clone)]
+pub struct Row {
+    pub label: String,
+    pub values: Vec<Option<f64>>,
+    pub unit: Unit,
+    /// 指标说明的名称（见 `IndicatorDescriptions`），缺省为 `label`
+    pub description: String,
+}
+
+impl Row {
+    pub fn new(label: &str, values: Vec<Option<f64>>, unit: Unit) -> Self {
+        Self {
+            label: label.to_string(),
+            values,
+            unit,
+            description: label.to_string(),
+        }
+    }
+
+    pub fn with_description(mut self, key: &str) -> Self {
+        self.description = key.to_string();
+        self
+    }
+}
+
+/// 从分析结果取各章节的行
+pub struct ReportRows<'a> {
+    result: &'a AnalysisResult,
+    data: DataHelper<'a>,
+    periods: usize,
+}
+
+impl<'a> ReportRows<'a> {
+    pub fn new(result: &'a AnalysisResult) -> Self {
+        Self {
+            result,
+            data: DataHelper::new(&result.statements),
+            periods: result.asset_structure.years.len(),
+        }
+    }
+
+    /// 各期标签（最新一期在前）
+    pub fn labels(&self) -> Vec<String> {
+        (0..self.periods).map(|i| self.result.period_label(i)).collect()
+    }
+
+    fn balance(&self, items: &[&str]) -> Vec<Row> {
+        items
+            .iter()
+            .map(|item| Row::new(item, (0..self.periods).map(|i| self.data.get_balance_opt(i, item)).collect(), Unit::Amount))
+            .collect()
+    }
+
+    fn series(&self, values: &[Decimal]) -> Vec<Option<f64>> {
+        (0..self.periods).map(|i| values.get(i).and_then(|v| v.to_f64())).collect()
+    }
+
+    fn optional_series(&self, values: &[Option<Decimal>]) -> Vec<Option<f64>> {
+        (0..self.periods).map(|i| values.get(i).copied().flatten().and_then(|v| v.to_f64())).collect()
+    }
+
+    pub fn assets(&self) -> Vec<Row> {
+        self.balance(&["货币资金", "应收账款", "存货", "固定资产", "无形资产", "资产总计"])
+    }
+
+    pub fn liabilities(&self) -> Vec<Row> {
+        self.balance(&["短期借款", "应付账款", "长期借款", "应付债券", "负债合计", "所有者权益合计"])
+    }
+
+    pub fn asset_structure(&self) -> Vec<Row> {
+        let asset = &self.result.asset_structure;
+        vec![
+            Row::new("经营性资产占比", self.series(&asset.operating_asset_ratio), Unit::Percent),
+            Row::new("金融性资产占比", self.series(&asset.financial_asset_ratio), Unit::Percent),
+        ]
+    }
+
+    pub fn income(&self) -> Vec<Row> {
+        ["营业总收入", "营业成本", "税金及附加", "销售费用", "管理费用", "研发费用", "财务费用", "营业利润", "净利润"]
+            .iter()
+            .map(|item| Row::new(item, (0..self.periods).map(|i| self.data.get_income_opt(i, item)).collect(), Unit::Amount))
+            .collect()
+    }
+
+    pub fn cashflow(&self) -> Vec<Row> {
+        [
+            ("经营活动现金流量净额", "经营活动产生的现金流量净额"),
+            ("投资活动现金流量净额", "投资活动产生的现金流量净额"),
+            ("筹资活动现金流量净额", "筹资活动产生的现金流量净额"),
+            ("资本支出(购建固定资产等)", "购建固定资产、无形资产和其他长期资产支付的现金"),
+        ]
+        .iter()
+        .map(|(label, account)| {
+            Row::new(label, (0..self.periods).map(|i| self.data.get_cashflow_opt(i, account)).collect(), Unit::Amount)
+                .with_description(account)
+        })
+        .collect()
+    }
+
+    pub fn margins(&self) -> Vec<Row> {
+        let profit = &self.result.profit_analysis;
+        vec![
+            Row::new("毛利率", self.series(&profit.gross_margin), Unit::Percent),
+            Row::new("核心利润率", self.series(&profit.core_profit_margin), Unit::Percent),
+            Row::new("净利润率", self.series(&profit.net_profit_margin), Unit::Percent).with_description("净利率"),
+        ]
+    }
+
+    /// 经营现金流与净利润
+    pub fn cash_quality(&self) -> Vec<Row> {
+        vec![
+            Row::new(
+                "经营活动现金流量净额",
+                (0..self.periods).map(|i| self.data.get_cashflow_opt(i, "经营活动产生的现金流量净额")).collect(),
+                Unit::Amount,
+            )
+            .with_description("经营活动产生的现金流量净额"),
+            Row::new("净利润", (0..self.periods).map(|i| self.data.get_income_opt(i, "净利润")).collect(), Unit::Amount),
+        ]
+    }
+
+    /// 盈利与偿债能力
+    pub fn strength(&self) -> Vec<Row> {
+        let strength = &self.result.strength;
+        vec![
+            Row::new("ROE(净资产收益率)", self.optional_series(&strength.roe), Unit::Percent).with_description("ROE"),
+            Row::new("ROA(总资产收益率)", self.optional_series(&strength.roa), Unit::Percent).with_description("ROA"),
+            Row::new("资产负债率", self.optional_series(&strength.debt_ratio), Unit::Percent),
+        ]
+    }
+
+    /// 杠杆分析，数据不足时为 None
+    pub fn leverage(&self) -> Option<Vec<Row>> {
+        let leverage = self.result.leverage_analysis.as_ref()?;
+        Some(vec![
+            Row::new("经营杠杆(DOL)", self.series(&leverage.operating_leverage), Unit::Times).with_description("经营杠杆DOL"),
+            Row::new("财务杠杆(DFL)", self.series(&leverage.financial_leverage), Unit::Times).with_description("财务杠杆DFL"),
+            Row::new("总杠杆(DTL)", self.series(&leverage.total_leverage), Unit::Times).with_description("总杠杆DTL"),
+        ])
+    }
+}
+
+/// 十进制数转为浮点数（用于显示）
+pub fn decimal(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(0.0)
+}
+
+/// 比率显示为百分数，如 `8.00%`
+pub fn percent(value: f64) -> String {
+    format!("{:.2}%", value * 100.0)
+}
+
+fn amount(value: Decimal) -> String {
+    format!("{}元", TextReporter::format_number(decimal(value)))
+}
+
+/// WACC 的计算过程：各项输入、资本成本与权重
+pub fn wacc_pairs(wacc: &Wacc) -> Vec<(&'static str, String)> {
+    let params = &wacc.params;
+    let beta = match &params.beta_estimate {
+        Some(estimate) => format!(
+            "{:.2}（{} 至 {}，{} 个样本回归）",
+            estimate.beta, estimate.start_date, estimate.end_date, estimate.observations
+        ),
+        None => format!("{:.2}", params.beta),
+    };
+    vec![
+        ("无风险收益率", percent(params.risk_free_rate)),
+        ("股权风险溢价", percent(params.equity_risk_premium)),
+        ("β系数", beta),
+        ("股权成本(CAPM)", percent(wacc.cost_of_equity)),
+        ("财务费用", amount(wacc.interest_expense)),
+        ("有息负债", amount(wacc.interest_bearing_debt)),
+        ("税前债务成本", percent(wacc.cost_of_debt)),
+        ("实际所得税率", percent(wacc.tax_rate)),
+        ("税后债务成本", percent(wacc.after_tax_cost_of_debt())),
+        ("资本权重", params.weights.display_name().to_string()),
+        ("股权价值", amount(wacc.equity_value)),
+        ("股权权重", percent(wacc.equity_weight)),
+        ("债务权重", percent(wacc.debt_weight)),
+        ("WACC", percent(wacc.wacc)),
+    ]
+}
+
+/// 反向DCF：当前股价隐含的增长率与估值假设对比，未提供股价时为 None
+pub fn implied_growth_pairs(sensitivity: &SensitivityResult) -> Option<Vec<(&'static str, String)>> {
+    let implied = sensitivity.implied_growth.as_ref()?;
+    let params = &sensitivity.params;
+    let rate = |value: Option<f64>| value.map_or_else(|| "无法求解".to_string(), percent);
+    Some(vec![
+        ("当前股价", format!("{:.2}元/股", implied.market_price)),
+        ("隐含FCF增长率", rate(implied.fcf_growth_rate)),
+        ("假设FCF增长率", percent(params.fcf_growth_rate)),
+        ("隐含净利润增长率", rate(implied.net_profit_growth_rate)),
+        ("假设净利润增长率", percent(params.net_profit_growth_rate)),
+    ])
+}
+
+/// 敏感性分析的估值结果，已选择的DDM/FCFE每股价值排在唐朝估值之后
+pub fn sensitivity_result_pairs(sensitivity: &SensitivityResult) -> Vec<(&'static str, String)> {
+    let mut pairs = vec![
+        ("DCF企业价值", amount(sensitivity.dcf_enterprise_value)),
+        ("DCF每股价值", format!("{:.2}元/股", decimal(sensitivity.dcf_price_per_share))),
+        ("唐朝低估价", format!("{:.2}元/股", decimal(sensitivity.tangchao_low_estimate))),
+        ("唐朝高估价", format!("{:.2}元/股", decimal(sensitivity.tangchao_high_estimate))),
+        ("唐朝安全边际价", format!("{:.2}元/股", decimal(sensitivity.tangchao_safety_margin_price))),
+    ];
+    for (model, valuation) in sensitivity.equity_models() {
+        pairs.push((model.price_label(), format!("{:.2}元/股", decimal(valuation.price_per_share))));
+    }
+    pairs
+}
+
+/// DDM/FCFE 两阶段折现的计算过程
+pub fn equity_model_pairs(valuation: &EquityModelValuation) -> Vec<(&'static str, String)> {
+    vec![
+        ("股权现金流", amount(valuation.base_cashflow)),
+        ("每股股权现金流", format!("{:.4}元/股", decimal(valuation.per_share_cashflow))),
+        ("高速增长期现值", amount(valuation.high_growth_present_value)),
+        ("终值现值", amount(valuation.terminal_present_value)),
+        ("股权价值", amount(valuation.equity_value)),
+        ("每股价值", format!("{:.2}元/股", decimal(valuation.price_per_share))),
+    ]
+}
@@ -0,0 +1,278 @@
+//! 内联 SVG 图表：柱形图（分组/堆积）、折线图、条形图
+//!
+//! 只生成 `<svg>` 片段，不依赖外部脚本或字体，嵌入 HTML 报告后可离线查看。
+//! 缺失的数据点（`None`）在柱形图中留空，在折线图中断开。
+
+use super::html::escape;
+use std::fmt::Write;
+
+const WIDTH: f64 = 560.0;
+const HEIGHT: f64 = 300.0;
+const LEFT: f64 = 72.0;
+const RIGHT: f64 = 16.0;
+const TOP: f64 = 36.0;
+const BOTTOM: f64 = 64.0;
+const PALETTE: [&str; 6] = ["#4472C4", "#ED7D31", "#A5A5A5", "#FFC000", "#5B9BD5", "#70AD47"];
+
+/// 一组按类别排列的数据
+pub struct Series {
+    pub name: String,
+    pub values: Vec<Option<f64>>,
+}
+
+impl Series {
+    pub fn new(name: &str, values: Vec<Option<f64>>) -> Self {
+        Self {
+            name: name.to_string(),
+            values,
+        }
+    }
+}
+
+/// 数值轴
+struct Axis {
+    low: f64,
+    high: f64,
+    step: f64,
+    percent: bool,
+}
+
+impl Axis {
+    /// 包含0和全部数据的刻度范围，步长取 1/2/2.5/5 × 10^n
+    fn new(values: impl Iterator<Item = f64>, percent: bool) -> Self {
+        let (mut low, mut high) = values.fold((0.0_f64, 0.0_f64), |(lo, hi), v| (lo.min(v), hi.max(v)));
+        if (high - low).abs() < f64::EPSILON {
+            high = low + 1.0;
+        }
+        let raw = (high - low) / 5.0;
+        let magnitude = 10_f64.powf(raw.log10().floor());
+        let step = [1.0, 2.0, 2.5, 5.0, 10.0]
+            .iter()
+            .map(|m| m * magnitude)
+            .find(|s| *s >= raw)
+            .unwrap_or(10.0 * magnitude);
+        low = (low / step).floor() * step;
+        high = (high / step).ceil() * step;
+        Self { low, high, step, percent }
+    }
+
+    fn y(&self, value: f64) -> f64 {
+        TOP + (HEIGHT - TOP - BOTTOM) * (self.high - value) / (self.high - self.low)
+    }
+
+    fn label(&self, value: f64) -> String {
+        if self.percent {
+            trim(value * 100.0, "%")
+        } else if value.abs() >= 100_000_000.0 {
+            trim(value / 100_000_000.0, "亿")
+        } else if value.abs() >= 10_000.0 {
+            trim(value / 10_000.0, "万")
+        } else {
+            trim(value, "")
+        }
+    }
+
+    /// 网格线和刻度
+    fn write(&self, svg: &mut String) {
+        let ticks = ((self.high - self.low) / self.step).round() as usize;
+        for i in 0..=ticks {
+            let value = self.low + self.step * i as f64;
+            let y = self.y(value);
+            let stroke = if value.abs() < self.step / 2.0 { "#595959" } else { "#E0E0E0" };
+            let _ = write!(
+                svg,
+                r##"<line x1="{LEFT}" y1="{y:.1}" x2="{:.1}" y2="{y:.1}" stroke="{stroke}"/><text x="{:.1}" y="{:.1}" text-anchor="end" font-size="11" fill="#595959">{}</text>"##,
+                WIDTH - RIGHT,
+                LEFT - 6.0,
+                y + 4.0,
+                escape(&self.label(value))
+            );
+        }
+    }
+}
+
+/// 保留至多2位小数并去掉末尾的0
+fn trim(value: f64, unit: &str) -> String {
+    let text = format!("{:.2}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    let text = if text == "-0" { "0" } else { text };
+    format!("{}{}", text, unit)
+}
+
+fn open(svg: &mut String, title: &str) {
+    let _ = write!(
+        svg,
+        r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {WIDTH} {HEIGHT}" width="{WIDTH}" height="{HEIGHT}" role="img" aria-label="{0}"><text x="{1}" y="22" text-anchor="middle" font-size="14" font-weight="bold" fill="#1F4E78">{0}</text>"##,
+        escape(title),
+        WIDTH / 2.0
+    );
+}
+
+fn color(index: usize) -> &'static str {
+    PALETTE[index % PALETTE.len()]
+}
+
+/// 类别标签和图例
+fn close(svg: &mut String, categories: &[String], series: &[Series]) {
+    let band = (WIDTH - LEFT - RIGHT) / categories.len().max(1) as f64;
+    for (i, category) in categories.iter().enumerate() {
+        let _ = write!(
+            svg,
+            r##"<text x="{:.1}" y="{:.1}" text-anchor="middle" font-size="11" fill="#595959">{}</text>"##,
+            LEFT + band * (i as f64 + 0.5),
+            HEIGHT - BOTTOM + 16.0,
+            escape(category)
+        );
+    }
+    legend(svg, series.iter().map(|s| s.name.as_str()));
+    svg.push_str("</svg>");
+}
+
+fn legend<'a>(svg: &mut String, names: impl Iterator<Item = &'a str>) {
+    let y = HEIGHT - 18.0;
+    let mut x = LEFT;
+    for (i, name) in names.enumerate() {
+        let _ = write!(
+            svg,
+            r##"<rect x="{x:.1}" y="{:.1}" width="10" height="10" fill="{}"/><text x="{:.1}" y="{y:.1}" font-size="11" fill="#404040">{}</text>"##,
+            y - 9.0,
+            color(i),
+            x + 14.0,
+            escape(name)
+        );
+        // 中文按全角宽度估算
+        x += 28.0 + name.chars().map(|c| if c.is_ascii() { 6.5 } else { 11.0 }).sum::<f64>();
+    }
+}
+
+/// 柱形图：`stacked` 为 true 时同一类别的各系列堆积显示
+pub fn column_chart(title: &str, categories: &[String], series: &[Series], stacked: bool, percent: bool) -> String {
+    let mut extremes = Vec::new();
+    for i in 0..categories.len() {
+        let values = series.iter().filter_map(|s| s.values.get(i).copied().flatten());
+        if stacked {
+            let (positive, negative) = values.fold((0.0, 0.0), |(p, n), v| if v >= 0.0 { (p + v, n) } else { (p, n + v) });
+            extremes.extend([positive, negative]);
+        } else {
+            extremes.extend(values);
+        }
+    }
+    let axis = Axis::new(extremes.into_iter(), percent);
+
+    let mut svg = String::new();
+    open(&mut svg, title);
+    axis.write(&mut svg);
+
+    let band = (WIDTH - LEFT - RIGHT) / categories.len().max(1) as f64;
+    for (i, category) in categories.iter().enumerate() {
+        let (mut positive, mut negative) = (0.0, 0.0);
+        for (j, s) in series.iter().enumerate() {
+            let Some(value) = s.values.get(i).copied().flatten() else {
+                continue;
+            };
+            let (x, width, base) = if stacked {
+                let width = band * 0.5;
+                let base = if value >= 0.0 { positive } else { negative };
+                (LEFT + band * i as f64 + (band - width) / 2.0, width, base)
+            } else {
+                let width = band * 0.7 / series.len() as f64;
+                (LEFT + band * i as f64 + band * 0.15 + width * j as f64, width, 0.0)
+            };
+            let (y1, y2) = (axis.y(base), axis.y(base + value));
+            let _ = write!(
+                svg,
+                r##"<rect x="{x:.1}" y="{:.1}" width="{width:.1}" height="{:.1}" fill="{}"><title>{} {}: {}</title></rect>"##,
+                y1.min(y2),
+                (y1 - y2).abs(),
+                color(j),
+                escape(category),
+                escape(&s.name),
+                escape(&axis.label(value))
+            );
+            if value >= 0.0 {
+                positive += value;
+            } else {
+                negative += value;
+            }
+        }
+    }
+
+    close(&mut svg, categories, series);
+    svg
+}
+
+/// 折线图
+pub fn line_chart(title: &str, categories: &[String], series: &[Series], percent: bool) -> String {
+    let values: Vec<f64> = series.iter().flat_map(|s| s.values.iter().flatten().copied()).collect();
+    let axis = Axis::new(values.into_iter(), percent);
+
+    let mut svg = String::new();
+    open(&mut svg, title);
+    axis.write(&mut svg);
+
+    let band = (WIDTH - LEFT - RIGHT) / categories.len().max(1) as f64;
+    for (j, s) in series.iter().enumerate() {
+        let mut path = String::new();
+        let mut pen_down = false;
+        for (i, value) in s.values.iter().enumerate().take(categories.len()) {
+            match value {
+                Some(value) => {
+                    let (x, y) = (LEFT + band * (i as f64 + 0.5), axis.y(*value));
+                    let _ = write!(path, "{}{:.1},{:.1} ", if pen_down { "L" } else { "M" }, x, y);
+                    let _ = write!(
+                        svg,
+                        r##"<circle cx="{x:.1}" cy="{y:.1}" r="3" fill="{}"><title>{} {}: {}</title></circle>"##,
+                        color(j),
+                        escape(&categories[i]),
+                        escape(&s.name),
+                        escape(&axis.label(*value))
+                    );
+                    pen_down = true;
+                }
+                None => pen_down = false,
+            }
+        }
+        let _ = write!(
+            svg,
+            r##"<path d="{}" fill="none" stroke="{}" stroke-width="2"/>"##,
+            path.trim_end(),
+            color(j)
+        );
+    }
+
+    close(&mut svg, categories, series);
+    svg
+}
+
+/// 水平条形图，每个条目一种颜色，条形末端标注数值
+pub fn bar_chart(title: &str, items: &[(&str, f64)], unit: &str) -> String {
+    let axis = Axis::new(items.iter().map(|(_, v)| *v), false);
+    let label_width = 96.0;
+    let x = |value: f64| LEFT + label_width + (WIDTH - LEFT - label_width - RIGHT - 48.0) * (value - axis.low) / (axis.high - axis.low);
+    let band = (HEIGHT - TOP - 24.0) / items.len().max(1) as f64;
+
+    let mut svg = String::new();
+    open(&mut svg, title);
+    for (i, (label, value)) in items.iter().enumerate() {
+        let y = TOP + band * i as f64;
+        let (x1, x2) = (x(0.0), x(*value));
+        let _ = write!(
+            svg,
+            r##"<text x="{:.1}" y="{:.1}" text-anchor="end" font-size="12" fill="#404040">{}</text><rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"/><text x="{:.1}" y="{:.1}" font-size="11" fill="#404040">{:.2}{}</text>"##,
+            LEFT + label_width - 8.0,
+            y + band / 2.0 + 4.0,
+            escape(label),
+            x1.min(x2),
+            y + band * 0.2,
+            (x2 - x1).abs().max(1.0),
+            band * 0.6,
+            color(i),
+            x1.max(x2) + 4.0,
+            y + band / 2.0 + 4.0,
+            value,
+            escape(unit)
+        );
+    }
+    svg.push_str("</svg>");
+    svg
+}
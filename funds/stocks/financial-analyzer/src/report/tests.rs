@@ -0,0 +1,376 @@
+//! 报告快照测试：回放录制的 AKShare/Tushare 原始响应，走完整的分析和报告流程
+//!
+//! 快照不一致时测试失败；确认改动符合预期后用 `UPDATE_SNAPSHOTS=1 cargo test` 更新快照。
+
+use crate::analyzer::{CapitalWeights, FinancialAnalyzer, SensitivityParams, ValuationModel, WaccParams};
+use crate::data_source::{AkshareClient, DataSource, Recorder, TushareClient};
+use crate::domain::*;
+use crate::excel::ExcelWriter;
+use crate::report::json::{JsonReport, SCHEMA_VERSION};
+use crate::report::html::escape;
+use crate::report::markdown::cell;
+use crate::report::{HtmlReporter, JsonReporter, MarkdownReporter, TextReporter};
+use crate::utils::config::ValidationRules;
+use crate::utils::{AkshareConfig, TushareConfig};
+use crate::validation::DataValidator;
+use crate::data_source::MockDataSource;
+use std::path::{Path, PathBuf};
+
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/replay")
+}
+
+fn output_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("financial-analyzer-snapshot-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(format!("{}.xlsx", name))
+}
+
+/// 与 tests/snapshots/<name>.txt 比较，快照不存在或设置了 UPDATE_SNAPSHOTS 时写入
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots").join(format!("{}.txt", name));
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    assert!(
+        path.exists(),
+        "快照 {} 不存在，确认输出无误后用 UPDATE_SNAPSHOTS=1 cargo test 生成",
+        path.display()
+    );
+    let expected = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        expected == actual,
+        "快照 {} 不一致，确认无误后用 UPDATE_SNAPSHOTS=1 cargo test 更新\n--- 期望 ---\n{}\n--- 实际 ---\n{}",
+        path.display(),
+        expected,
+        actual
+    );
+}
+
+/// 去掉报告中随运行时间变化的行
+fn stable_report(report: &str) -> String {
+    report
+        .lines()
+        .filter(|line| !line.starts_with("生成时间:"))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+async fn replay_analysis(source: &dyn DataSource) -> AnalysisResult {
+    let analyzer = FinancialAnalyzer::new();
+    let mut result = analyzer
+        .analyze("600519.SH", vec![2023, 2022, 2021], source)
+        .await
+        .unwrap();
+    analyzer
+        .calculate_sensitivity(&mut result, SensitivityParams::default())
+        .unwrap();
+    result
+}
+
+fn generate_reports(result: &AnalysisResult, name: &str) -> String {
+    let output = output_path(name);
+    let report = TextReporter::generate(result, &result.stock_code, output.to_str().unwrap()).unwrap();
+
+    ExcelWriter::new().generate(result, &output).unwrap();
+    let workbook = std::fs::read(&output).unwrap();
+    assert!(workbook.starts_with(b"PK"), "Excel文件应为zip格式");
+
+    stable_report(&report)
+}
+
+#[tokio::test]
+async fn test_akshare_replay_report_snapshot() {
+    let source = AkshareClient::from_config(&AkshareConfig::default()).with_recorder(Recorder::replay(fixture_dir()));
+    let result = replay_analysis(&source).await;
+
+    // 回放数据包含 2023Q3，年报模式下应被过滤
+    assert_eq!(result.period_labels, vec!["2023", "2022", "2021"]);
+    assert_snapshot("akshare_600519_annual", &generate_reports(&result, "akshare"));
+}
+
+#[tokio::test]
+async fn test_tushare_replay_report_snapshot() {
+    let client = TushareClient::new(String::new(), &TushareConfig::default())
+        .unwrap()
+        .with_recorder(Recorder::replay(fixture_dir()));
+    let result = replay_analysis(&client).await;
+
+    assert_eq!(result.period_labels, vec!["2023", "2022", "2021"]);
+    assert_snapshot("tushare_600519_annual", &generate_reports(&result, "tushare"));
+}
+
+/// 去掉 JSON 报告中随运行环境变化的字段
+fn stable_json(json: &str) -> String {
+    let mut value: serde_json::Value = serde_json::from_str(json).unwrap();
+    value["generated_at"] = serde_json::Value::Null;
+    value["generator"] = serde_json::Value::Null;
+    format!("{}\n", serde_json::to_string_pretty(&value).unwrap())
+}
+
+#[tokio::test]
+async fn test_json_report_matches_schema() {
+    let rules = ValidationRules::load(Path::new(ValidationRules::default_path())).unwrap();
+    let analyzer = FinancialAnalyzer::new().with_validator(DataValidator::new(rules));
+    let mut result = analyzer
+        .analyze("600519.SH", vec![2023, 2022, 2021], &MockDataSource::new())
+        .await
+        .unwrap();
+    analyzer
+        .calculate_sensitivity(&mut result, SensitivityParams::default())
+        .unwrap();
+
+    let output = output_path("json-schema").with_extension("json");
+    let json = JsonReporter::write(&result, &output).unwrap();
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), json);
+
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let schema: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas/analysis_report.v2.schema.json"))
+            .unwrap(),
+    )
+    .unwrap();
+
+    // 顶层字段与 schema 一致
+    let mut required: Vec<&str> = schema["required"].as_array().unwrap().iter().map(|v| v.as_str().unwrap()).collect();
+    let mut keys: Vec<&str> = value.as_object().unwrap().keys().map(|k| k.as_str()).collect();
+    required.sort();
+    keys.sort();
+    assert_eq!(keys, required);
+    assert_eq!(value["schema_version"], schema["properties"]["schema_version"]["const"]);
+    assert_eq!(value["schema_version"], SCHEMA_VERSION);
+
+    let report: JsonReport = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(report.periods, vec!["2023", "2022", "2021"]);
+    assert_eq!(report.ratios.len(), 3);
+    assert_eq!(report.statements.len(), 9);
+    assert!(report.statements.iter().all(|s| s.report_type == "balance_sheet"
+        || s.report_type == "income_statement"
+        || s.report_type == "cashflow_statement"));
+
+    // 估值输入包含从报表读取的总股本，验证结果按报表记录
+    let valuation = report.valuation.unwrap();
+    assert!(valuation.inputs.is_some());
+    assert_eq!(value["valuation"]["dcf"]["price_per_share"], serde_json::json!(valuation.result.dcf.price_per_share));
+    assert_eq!(report.validation.len(), 6);
+    assert!(value["validation"][0]["reliability_score"].is_number());
+    assert!(report.sensitivity.is_some());
+
+    // 金额为十进制字符串
+    assert!(value["statements"][0]["items"].as_object().unwrap().values().all(|v| v.is_string()));
+
+    // 读回的分析结果与原结果一致
+    let (_, restored) = JsonReporter::read(&output).unwrap();
+    assert_eq!(restored.stock_code, result.stock_code);
+    assert_eq!(restored.period_label(0), result.period_label(0));
+    assert_eq!(restored.statements.len(), result.statements.len());
+    assert_eq!(restored.profit_analysis.gross_margin, result.profit_analysis.gross_margin);
+    assert_eq!(restored.validation.len(), result.validation.len());
+    assert_eq!(
+        restored.sensitivity.map(|s| s.dcf_price_per_share),
+        result.sensitivity.map(|s| s.dcf_price_per_share)
+    );
+}
+
+#[tokio::test]
+async fn test_akshare_replay_json_snapshot() {
+    let source = AkshareClient::from_config(&AkshareConfig::default()).with_recorder(Recorder::replay(fixture_dir()));
+    let result = replay_analysis(&source).await;
+
+    assert_snapshot("akshare_600519_annual_json", &stable_json(&JsonReporter::generate(&result).unwrap()));
+}
+
+#[tokio::test]
+async fn test_html_report_is_self_contained() {
+    let rules = ValidationRules::load(Path::new(ValidationRules::default_path())).unwrap();
+    let analyzer = FinancialAnalyzer::new().with_validator(DataValidator::new(rules));
+    let mut result = analyzer
+        .analyze("600519.SH", vec![2023, 2022, 2021], &MockDataSource::new())
+        .await
+        .unwrap();
+    analyzer
+        .calculate_sensitivity(&mut result, SensitivityParams::default())
+        .unwrap();
+
+    let output = output_path("html").with_extension("html");
+    let html = HtmlReporter::write(&result, &output).unwrap();
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), html);
+
+    for id in ["structure", "profit", "strength", "valuation", "sensitivity", "validation"] {
+        assert!(html.contains(&format!("<section id=\"{}\">", id)), "缺少章节 {}", id);
+    }
+    // 资产结构、利润率、现金流与净利润、估值区间4个图表
+    assert_eq!(html.matches("<svg ").count(), 4);
+    assert!(html.contains("<th>2023年</th>"));
+    assert!(html.contains("可靠性评分"));
+
+    // 不引用外部资源
+    for external in ["<script", "<link", "src=", "url("] {
+        assert!(!html.contains(external), "包含外部资源引用 {}", external);
+    }
+}
+
+#[tokio::test]
+async fn test_reports_show_wacc_and_implied_growth() {
+    let analyzer = FinancialAnalyzer::new();
+    let mut result = analyzer
+        .analyze("600519.SH", vec![2023, 2022, 2021], &MockDataSource::new())
+        .await
+        .unwrap();
+    let wacc = analyzer
+        .calculate_wacc(&result, WaccParams { weights: CapitalWeights::Book, ..Default::default() })
+        .unwrap();
+    let params = SensitivityParams {
+        market_price: Some(0.5),
+        ..Default::default()
+    };
+    analyzer.calculate_sensitivity(&mut result, params.with_wacc(wacc)).unwrap();
+
+    let output = output_path("wacc-implied");
+    let text = TextReporter::generate(&result, &result.stock_code, output.to_str().unwrap()).unwrap();
+    assert!(text.contains("--- 折现率(WACC) ---"));
+    assert!(text.contains("--- 市场隐含增长率（反向DCF） ---"));
+    assert!(text.contains("隐含FCF增长率"));
+
+    let md = MarkdownReporter::generate(&result).unwrap();
+    assert!(md.contains("### 折现率(WACC)"));
+    assert!(md.contains("### 市场隐含增长率（反向DCF）"));
+
+    // JSON 读回后保留 WACC 计算过程和隐含增长率
+    let json_path = output.with_extension("json");
+    let json = JsonReporter::write(&result, &json_path).unwrap();
+
+    // WACC 的字段与 schema 一致
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let schema: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas/analysis_report.v2.schema.json"))
+            .unwrap(),
+    )
+    .unwrap();
+    let wacc_schema = &schema["$defs"]["wacc"];
+    let wacc_json = value["sensitivity"]["params"]["wacc"].as_object().unwrap();
+    let properties = wacc_schema["properties"].as_object().unwrap();
+    assert!(wacc_json.keys().all(|key| properties.contains_key(key)));
+    assert!(wacc_schema["required"].as_array().unwrap().iter().all(|key| wacc_json.contains_key(key.as_str().unwrap())));
+    let (_, restored) = JsonReporter::read(&json_path).unwrap();
+    let sensitivity = restored.sensitivity.unwrap();
+    let expected = result.sensitivity.as_ref().unwrap();
+    assert_eq!(sensitivity.implied_growth, expected.implied_growth);
+    assert_eq!(sensitivity.params.wacc.map(|w| w.wacc), expected.params.wacc.as_ref().map(|w| w.wacc));
+}
+
+#[tokio::test]
+async fn test_reports_show_equity_models() {
+    let analyzer = FinancialAnalyzer::new();
+    let mut result = analyzer
+        .analyze("600519.SH", vec![2023, 2022, 2021], &MockDataSource::new())
+        .await
+        .unwrap();
+    let params = SensitivityParams {
+        models: vec![ValuationModel::Ddm, ValuationModel::Fcfe],
+        ..Default::default()
+    };
+    analyzer.calculate_sensitivity(&mut result, params).unwrap();
+
+    let output = output_path("equity-models");
+    let text = TextReporter::generate(&result, &result.stock_code, output.to_str().unwrap()).unwrap();
+    assert!(text.contains("DDM每股价值"));
+    assert!(text.contains("股权自由现金流(FCFE)："));
+
+    let md = MarkdownReporter::generate(&result).unwrap();
+    assert!(md.contains("| FCFE每股价值"));
+    assert!(md.contains("### 股利折现(DDM)估值"));
+    let html = HtmlReporter::generate(&result).unwrap();
+    assert!(html.contains("<th>DDM每股价值</th>"));
+
+    let json_path = output.with_extension("json");
+    JsonReporter::write(&result, &json_path).unwrap();
+    let (_, restored) = JsonReporter::read(&json_path).unwrap();
+    let expected = result.sensitivity.as_ref().unwrap();
+    let restored = restored.sensitivity.unwrap();
+    assert_eq!(restored.params.models, expected.params.models);
+    assert_eq!(restored.fcfe.map(|v| v.price_per_share), expected.fcfe.as_ref().map(|v| v.price_per_share));
+}
+
+#[tokio::test]
+async fn test_read_v1_report_converts_dcf_to_equity() {
+    let analyzer = FinancialAnalyzer::new();
+    let mut result = analyzer
+        .analyze("600519.SH", vec![2023, 2022, 2021], &MockDataSource::new())
+        .await
+        .unwrap();
+    analyzer
+        .calculate_sensitivity(&mut result, SensitivityParams::default())
+        .unwrap();
+
+    // v1 报告：每股价值为企业价值 / 总股本，没有 bridge
+    let mut value: serde_json::Value = serde_json::from_str(&JsonReporter::generate(&result).unwrap()).unwrap();
+    let total_shares = result.valuation_params.as_ref().unwrap().total_shares;
+    let dcf = &result.valuation.as_ref().unwrap().dcf;
+    let detail = result.sensitivity.as_ref().unwrap().dcf_detail.as_ref().unwrap();
+    value["schema_version"] = serde_json::json!(1);
+    value["valuation"]["dcf"]["price_per_share"] = serde_json::json!(dcf.enterprise_value / total_shares);
+    value["valuation"]["dcf"]["bridge"] = serde_json::Value::Null;
+    value["sensitivity"]["dcf_price_per_share"] = serde_json::json!(detail.enterprise_value / total_shares);
+    value["sensitivity"]["dcf_detail"]["bridge"] = serde_json::Value::Null;
+    let path = output_path("v1-report").with_extension("json");
+    std::fs::write(&path, value.to_string()).unwrap();
+
+    let (report, restored) = JsonReporter::read(&path).unwrap();
+    assert_eq!(report.schema_version, 1);
+    assert!(dcf.bridge.is_some());
+    assert_eq!(restored.valuation.unwrap().dcf.price_per_share, dcf.price_per_share);
+    assert_eq!(
+        restored.sensitivity.unwrap().dcf_price_per_share,
+        result.sensitivity.as_ref().unwrap().dcf_price_per_share
+    );
+
+    value["schema_version"] = serde_json::json!(SCHEMA_VERSION + 1);
+    std::fs::write(&path, value.to_string()).unwrap();
+    assert!(format!("{:#}", JsonReporter::read(&path).unwrap_err()).contains("报告版本"));
+}
+
+#[test]
+fn test_html_escape() {
+    assert_eq!(escape("<a href=\"x\">R&D's</a>"), "&lt;a href=&quot;x&quot;&gt;R&amp;D&#39;s&lt;/a&gt;");
+}
+
+#[tokio::test]
+async fn test_markdown_report_tables_and_footnotes() {
+    let analyzer = FinancialAnalyzer::new();
+    let mut result = analyzer
+        .analyze("600519.SH", vec![2023, 2022, 2021], &MockDataSource::new())
+        .await
+        .unwrap();
+    analyzer
+        .calculate_sensitivity(&mut result, SensitivityParams::default())
+        .unwrap();
+
+    let output = output_path("md").with_extension("md");
+    let md = MarkdownReporter::write(&result, &output).unwrap();
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), md);
+
+    for section in ["## 摘要", "## 资产&负债结构", "## 利润&现金流", "## 综合实力", "## 估值", "## 敏感性分析", "## 数据验证"] {
+        assert!(md.contains(section), "缺少章节 {}", section);
+    }
+    assert!(md.contains("| 项目 | 2023年 | 2022年 | 2021年 |"));
+    assert!(md.contains("| DCF每股价值"));
+
+    // 每个脚注引用都有对应的说明，且说明来自指标说明表
+    let definitions: Vec<&str> = md.lines().filter(|line| line.starts_with("[^")).collect();
+    assert!(!definitions.is_empty());
+    assert!(md.contains("]: 货币资金：企业可随时支配的现金"));
+    for (i, definition) in definitions.iter().enumerate() {
+        let mark = format!("[^{}]", i + 1);
+        assert!(definition.starts_with(&format!("{}:", mark)));
+        assert!(md.matches(&mark).count() >= 2, "脚注 {} 未被引用", mark);
+    }
+}
+
+#[test]
+fn test_markdown_cell_escape() {
+    assert_eq!(cell("a|b\nc"), "a\\|b c");
+}
@@ -0,0 +1,498 @@
+//! 筛选表达式
+//!
+//! ```text
+//! min(roe, 3) > 15% and ocf_to_net_profit > 1 and price < safety_price
+//! ```
+//!
+//! - 数值：`1.5`、`15%`（即 0.15）
+//! - 字段：`roe` 为最新一期，`roe[1]` 为上一期；`min/max/avg(字段, N)` 为最近 N 期的聚合，任一期缺失时为缺失
+//! - 运算：`+ - * /`，比较 `> >= < <= == !=`，逻辑 `and or not`（也可写作 `&& || !`）
+//!
+//! 缺失值参与运算的结果仍为缺失，比较结果为“未知”；逻辑运算按三值逻辑处理，只有结果为真的股票入选。
+
+use super::fields::{Candidate, Field};
+use crate::report::Unit;
+use anyhow::{anyhow, bail, Result};
+use std::fmt;
+
+/// 最近 N 期的聚合方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Min,
+    Max,
+    Avg,
+}
+
+impl Aggregate {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "min" => Some(Self::Min),
+            "max" => Some(Self::Max),
+            "avg" => Some(Self::Avg),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Avg => "avg",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Min => "最低",
+            Self::Max => "最高",
+            Self::Avg => "平均",
+        }
+    }
+}
+
+/// 表达式中对分析结果的一次取值，筛选结果表按它们列出数值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Term {
+    Field { field: Field, offset: usize },
+    Aggregate { func: Aggregate, field: Field, periods: usize },
+}
+
+impl Term {
+    pub fn value(&self, candidate: &Candidate) -> Option<f64> {
+        match *self {
+            Term::Field { field, offset } => field.value(candidate, offset),
+            Term::Aggregate { func, field, periods } => {
+                let values = (0..periods).map(|i| field.value(candidate, i)).collect::<Option<Vec<f64>>>()?;
+                match func {
+                    Aggregate::Min => values.into_iter().reduce(f64::min),
+                    Aggregate::Max => values.into_iter().reduce(f64::max),
+                    Aggregate::Avg => Some(values.iter().sum::<f64>() / values.len() as f64),
+                }
+            }
+        }
+    }
+
+    /// 中文列标题，如 "ROE(上1期)"、"ROE(近3期最低)"
+    pub fn label(&self) -> String {
+        match *self {
+            Term::Field { field, offset: 0 } => field.label().to_string(),
+            Term::Field { field, offset } => format!("{}(上{}期)", field.label(), offset),
+            Term::Aggregate { func, field, periods } => format!("{}(近{}期{})", field.label(), periods, func.label()),
+        }
+    }
+
+    pub fn unit(&self) -> Unit {
+        match self {
+            Term::Field { field, .. } | Term::Aggregate { field, .. } => field.unit(),
+        }
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Term::Field { field, offset: 0 } => write!(f, "{}", field.name()),
+            Term::Field { field, offset } => write!(f, "{}[{}]", field.name(), offset),
+            Term::Aggregate { func, field, periods } => write!(f, "{}({}, {})", func.name(), field.name(), periods),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+/// 表达式语法树
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Term(Term),
+    Neg(Box<Expr>),
+    Arith(ArithOp, Box<Expr>, Box<Expr>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// 解析筛选条件（结果必须是条件）
+    pub fn condition(text: &str) -> Result<Self> {
+        let expr = Self::parse(text)?;
+        if !expr.is_condition() {
+            bail!("筛选条件必须是比较或逻辑表达式: {}", text);
+        }
+        Ok(expr)
+    }
+
+    /// 解析数值表达式（如排序依据）
+    pub fn number(text: &str) -> Result<Self> {
+        let expr = Self::parse(text)?;
+        if expr.is_condition() {
+            bail!("排序依据必须是数值表达式: {}", text);
+        }
+        Ok(expr)
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        let tokens = lex(text)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if let Some((_, at)) = parser.tokens.get(parser.pos) {
+            bail!("表达式错误（第 {} 个字符）: 多余的内容", at + 1);
+        }
+        Ok(expr)
+    }
+
+    fn is_condition(&self) -> bool {
+        matches!(self, Expr::Compare(..) | Expr::Not(_) | Expr::And(..) | Expr::Or(..))
+    }
+
+    /// 数值表达式的值，缺失或除以0时为 None
+    pub fn eval(&self, candidate: &Candidate) -> Option<f64> {
+        match self {
+            Expr::Number(value) => Some(*value),
+            Expr::Term(term) => term.value(candidate),
+            Expr::Neg(expr) => expr.eval(candidate).map(|v| -v),
+            Expr::Arith(op, left, right) => {
+                let (a, b) = (left.eval(candidate)?, right.eval(candidate)?);
+                match op {
+                    ArithOp::Add => Some(a + b),
+                    ArithOp::Sub => Some(a - b),
+                    ArithOp::Mul => Some(a * b),
+                    ArithOp::Div if b == 0.0 => None,
+                    ArithOp::Div => Some(a / b),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// 条件的真假，数据缺失时为 None（未知）
+    pub fn test(&self, candidate: &Candidate) -> Option<bool> {
+        match self {
+            Expr::Compare(op, left, right) => {
+                let (a, b) = (left.eval(candidate)?, right.eval(candidate)?);
+                Some(match op {
+                    CompareOp::Gt => a > b,
+                    CompareOp::Ge => a >= b,
+                    CompareOp::Lt => a < b,
+                    CompareOp::Le => a <= b,
+                    CompareOp::Eq => (a - b).abs() < 1e-9,
+                    CompareOp::Ne => (a - b).abs() >= 1e-9,
+                })
+            }
+            Expr::Not(expr) => expr.test(candidate).map(|v| !v),
+            Expr::And(left, right) => match (left.test(candidate), right.test(candidate)) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            },
+            Expr::Or(left, right) => match (left.test(candidate), right.test(candidate)) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// 表达式引用的取值（去重，按出现顺序）
+    pub fn terms(&self) -> Vec<Term> {
+        let mut terms = Vec::new();
+        self.collect_terms(&mut terms);
+        terms
+    }
+
+    fn collect_terms(&self, terms: &mut Vec<Term>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Term(term) => {
+                if !terms.contains(term) {
+                    terms.push(*term);
+                }
+            }
+            Expr::Neg(expr) | Expr::Not(expr) => expr.collect_terms(terms),
+            Expr::Arith(_, left, right) | Expr::Compare(_, left, right) | Expr::And(left, right) | Expr::Or(left, right) => {
+                left.collect_terms(terms);
+                right.collect_terms(terms);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+/// 词法分析，返回 (词, 起始字符位置)
+fn lex(text: &str) -> Result<Vec<(Token, usize)>> {
+    const SYMBOLS: [&str; 17] = [">=", "<=", "==", "!=", "&&", "||", ">", "<", "+", "-", "*", "/", "!", "(", ")", "[", "]"];
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let literal: String = chars[start..i].iter().collect();
+            let mut value: f64 = literal
+                .parse()
+                .map_err(|_| anyhow!("表达式错误（第 {} 个字符）: 无效的数值 {}", start + 1, literal))?;
+            if chars.get(i) == Some(&'%') {
+                value /= 100.0;
+                i += 1;
+            }
+            tokens.push((Token::Number(value), start));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().collect()), start));
+        } else if c == ',' {
+            tokens.push((Token::Symbol(","), i));
+            i += 1;
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let symbol = SYMBOLS
+                .iter()
+                .find(|s| rest.starts_with(**s))
+                .ok_or_else(|| anyhow!("表达式错误（第 {} 个字符）: 无法识别的字符 '{}'", i + 1, c))?;
+            tokens.push((Token::Symbol(symbol), i));
+            i += symbol.len();
+        }
+    }
+    Ok(tokens)
+}
+
+/// 递归下降解析：or → and → not → 比较 → 加减 → 乘除 → 一元负号 → 基本项
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    /// 当前位置（第几个字符），用于错误提示
+    fn at(&self) -> usize {
+        self.tokens.get(self.pos).map_or_else(
+            || self.tokens.last().map_or(0, |(_, at)| at + 1),
+            |(_, at)| *at,
+        ) + 1
+    }
+
+    fn error(&self, message: impl fmt::Display) -> anyhow::Error {
+        anyhow!("表达式错误（第 {} 个字符）: {}", self.at(), message)
+    }
+
+    /// 当前词是给定符号或关键字之一时前进并返回 true
+    fn eat(&mut self, words: &[&str]) -> bool {
+        let matched = match self.peek() {
+            Some(Token::Symbol(symbol)) => words.contains(symbol),
+            Some(Token::Ident(ident)) => words.contains(&ident.as_str()),
+            _ => false,
+        };
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<()> {
+        if self.eat(&[symbol]) {
+            Ok(())
+        } else {
+            Err(self.error(format!("缺少 '{}'", symbol)))
+        }
+    }
+
+    fn condition(&self, expr: Expr, operator: &str) -> Result<Box<Expr>> {
+        if expr.is_condition() {
+            Ok(Box::new(expr))
+        } else {
+            Err(self.error(format!("'{}' 两侧必须是条件", operator)))
+        }
+    }
+
+    fn value(&self, expr: Expr, operator: &str) -> Result<Box<Expr>> {
+        if expr.is_condition() {
+            Err(self.error(format!("'{}' 两侧必须是数值", operator)))
+        } else {
+            Ok(Box::new(expr))
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut left = self.and()?;
+        while self.eat(&["or", "||"]) {
+            let right = self.and()?;
+            left = Expr::Or(self.condition(left, "or")?, self.condition(right, "or")?);
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut left = self.not()?;
+        while self.eat(&["and", "&&"]) {
+            let right = self.not()?;
+            left = Expr::And(self.condition(left, "and")?, self.condition(right, "and")?);
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.eat(&["not", "!"]) {
+            let expr = self.not()?;
+            return Ok(Expr::Not(self.condition(expr, "not")?));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let left = self.sum()?;
+        let op = match self.peek() {
+            Some(Token::Symbol(">")) => CompareOp::Gt,
+            Some(Token::Symbol(">=")) => CompareOp::Ge,
+            Some(Token::Symbol("<")) => CompareOp::Lt,
+            Some(Token::Symbol("<=")) => CompareOp::Le,
+            Some(Token::Symbol("==")) => CompareOp::Eq,
+            Some(Token::Symbol("!=")) => CompareOp::Ne,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        let right = self.sum()?;
+        Ok(Expr::Compare(op, self.value(left, "比较")?, self.value(right, "比较")?))
+    }
+
+    fn sum(&mut self) -> Result<Expr> {
+        let mut left = self.product()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Symbol("+")) => ArithOp::Add,
+                Some(Token::Symbol("-")) => ArithOp::Sub,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            let right = self.product()?;
+            left = Expr::Arith(op, self.value(left, "+/-")?, self.value(right, "+/-")?);
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr> {
+        let mut left = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Symbol("*")) => ArithOp::Mul,
+                Some(Token::Symbol("/")) => ArithOp::Div,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            let right = self.unary()?;
+            left = Expr::Arith(op, self.value(left, "*/")?, self.value(right, "*/")?);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat(&["-"]) {
+            let expr = self.unary()?;
+            return Ok(Expr::Neg(self.value(expr, "-")?));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.error("表达式不完整"));
+        };
+        match token {
+            Token::Number(value) => {
+                self.pos += 1;
+                Ok(Expr::Number(value))
+            }
+            Token::Symbol("(") => {
+                self.pos += 1;
+                let expr = self.or()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Ident(name) => {
+                if let Some(func) = Aggregate::parse(&name) {
+                    self.pos += 1;
+                    self.expect("(")?;
+                    let field = self.field()?;
+                    if !field.periodic() {
+                        return Err(self.error(format!("{} 不分期，不能聚合", field.name())));
+                    }
+                    self.expect(",")?;
+                    let periods = self.count(1)?;
+                    self.expect(")")?;
+                    return Ok(Expr::Term(Term::Aggregate { func, field, periods }));
+                }
+                let field = self.field()?;
+                let offset = if self.eat(&["["]) {
+                    let offset = self.count(0)?;
+                    self.expect("]")?;
+                    offset
+                } else {
+                    0
+                };
+                if offset > 0 && !field.periodic() {
+                    return Err(self.error(format!("{} 不分期，不能指定期数", field.name())));
+                }
+                Ok(Expr::Term(Term::Field { field, offset }))
+            }
+            Token::Symbol(symbol) => Err(self.error(format!("意外的 '{}'", symbol))),
+        }
+    }
+
+    fn field(&mut self) -> Result<Field> {
+        let name = match self.peek() {
+            Some(Token::Ident(name)) => name.clone(),
+            _ => return Err(self.error("缺少字段名")),
+        };
+        let field = Field::parse(&name)
+            .ok_or_else(|| self.error(format!("未知字段 {}（可用字段: {}）", name, Field::names().join(", "))))?;
+        self.pos += 1;
+        Ok(field)
+    }
+
+    /// 不小于 min 的整数（期数）
+    fn count(&mut self, min: usize) -> Result<usize> {
+        match self.peek() {
+            Some(Token::Number(value)) if value.fract() == 0.0 && *value >= min as f64 => {
+                let count = *value as usize;
+                self.pos += 1;
+                Ok(count)
+            }
+            _ if min > 0 => Err(self.error(format!("期数必须是不小于{}的整数", min))),
+            _ => Err(self.error("期数必须是非负整数")),
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! 筛选表达式可引用的字段
+//!
+//! 按期字段默认取最新一期，可用 `roe[1]` 取上一期、`min(roe, 3)` 取最近几期的聚合；
+//! 估值、股价和可靠性评分不分期。
+
+use crate::domain::AnalysisResult;
+use crate::excel::DataHelper;
+use crate::report::Unit;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+/// 参与筛选的股票：分析结果及其当前股价（来自 `--prices`）
+pub struct Candidate {
+    pub result: AnalysisResult,
+    pub price: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    GrossMargin,
+    CoreMargin,
+    NetMargin,
+    Roe,
+    Roa,
+    DebtRatio,
+    OperatingAssetRatio,
+    FinancialAssetRatio,
+    OcfToNetProfit,
+    Revenue,
+    NetProfit,
+    Ocf,
+    Dol,
+    Dfl,
+    Dtl,
+    DcfValue,
+    DcfPrice,
+    LowPrice,
+    HighPrice,
+    SafetyPrice,
+    Price,
+    Reliability,
+}
+
+struct FieldInfo {
+    field: Field,
+    name: &'static str,
+    label: &'static str,
+    unit: Unit,
+    periodic: bool,
+}
+
+const fn info(field: Field, name: &'static str, label: &'static str, unit: Unit, periodic: bool) -> FieldInfo {
+    FieldInfo {
+        field,
+        name,
+        label,
+        unit,
+        periodic,
+    }
+}
+
+const FIELDS: [FieldInfo; 22] = [
+    info(Field::GrossMargin, "gross_margin", "毛利率", Unit::Percent, true),
+    info(Field::CoreMargin, "core_margin", "核心利润率", Unit::Percent, true),
+    info(Field::NetMargin, "net_margin", "净利润率", Unit::Percent, true),
+    info(Field::Roe, "roe", "ROE", Unit::Percent, true),
+    info(Field::Roa, "roa", "ROA", Unit::Percent, true),
+    info(Field::DebtRatio, "debt_ratio", "资产负债率", Unit::Percent, true),
+    info(Field::OperatingAssetRatio, "operating_asset_ratio", "经营性资产占比", Unit::Percent, true),
+    info(Field::FinancialAssetRatio, "financial_asset_ratio", "金融性资产占比", Unit::Percent, true),
+    info(Field::OcfToNetProfit, "ocf_to_net_profit", "净现比", Unit::Times, true),
+    info(Field::Revenue, "revenue", "营业收入", Unit::Amount, true),
+    info(Field::NetProfit, "net_profit", "净利润", Unit::Amount, true),
+    info(Field::Ocf, "ocf", "经营活动现金流净额", Unit::Amount, true),
+    info(Field::Dol, "dol", "经营杠杆", Unit::Times, true),
+    info(Field::Dfl, "dfl", "财务杠杆", Unit::Times, true),
+    info(Field::Dtl, "dtl", "总杠杆", Unit::Times, true),
+    info(Field::DcfValue, "dcf_value", "DCF企业价值", Unit::Amount, false),
+    info(Field::DcfPrice, "dcf_price", "DCF每股价值", Unit::Times, false),
+    info(Field::LowPrice, "low_price", "唐朝低估价", Unit::Times, false),
+    info(Field::HighPrice, "high_price", "唐朝高估价", Unit::Times, false),
+    info(Field::SafetyPrice, "safety_price", "唐朝安全边际价", Unit::Times, false),
+    info(Field::Price, "price", "股价", Unit::Times, false),
+    info(Field::Reliability, "reliability", "可靠性评分", Unit::Times, false),
+];
+
+impl Field {
+    fn info(self) -> &'static FieldInfo {
+        FIELDS.iter().find(|info| info.field == self).expect("字段未登记")
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        FIELDS.iter().find(|info| info.name == name).map(|info| info.field)
+    }
+
+    /// 全部字段名，用于错误提示
+    pub fn names() -> Vec<&'static str> {
+        FIELDS.iter().map(|info| info.name).collect()
+    }
+
+    pub fn name(self) -> &'static str {
+        self.info().name
+    }
+
+    /// 中文名称，用作表头
+    pub fn label(self) -> &'static str {
+        self.info().label
+    }
+
+    pub fn unit(self) -> Unit {
+        self.info().unit
+    }
+
+    /// 是否按期取值
+    pub fn periodic(self) -> bool {
+        self.info().periodic
+    }
+
+    /// 第 period 期（0 为最新一期）的值；不分期的字段忽略 period
+    pub fn value(self, candidate: &Candidate, period: usize) -> Option<f64> {
+        let result = &candidate.result;
+        let data = DataHelper::new(&result.statements);
+        let series = |values: &[Decimal]| values.get(period).and_then(|v| v.to_f64());
+        let optional_series = |values: &[Option<Decimal>]| values.get(period).copied().flatten().and_then(|v| v.to_f64());
+        let ratio = |numerator: Option<f64>, denominator: Option<f64>| match (numerator, denominator) {
+            (Some(a), Some(b)) if b > 0.0 => Some(a / b),
+            _ => None,
+        };
+        let net_profit = || data.get_income_opt(period, "净利润");
+        let leverage = result.leverage_analysis.as_ref();
+
+        match self {
+            Field::GrossMargin => series(&result.profit_analysis.gross_margin),
+            Field::CoreMargin => series(&result.profit_analysis.core_profit_margin),
+            Field::NetMargin => series(&result.profit_analysis.net_profit_margin),
+            Field::Roe => optional_series(&result.strength.roe),
+            Field::Roa => optional_series(&result.strength.roa),
+            Field::DebtRatio => optional_series(&result.strength.debt_ratio),
+            Field::OperatingAssetRatio => series(&result.asset_structure.operating_asset_ratio),
+            Field::FinancialAssetRatio => series(&result.asset_structure.financial_asset_ratio),
+            Field::OcfToNetProfit => ratio(data.get_cashflow_opt(period, "经营活动产生的现金流量净额"), net_profit()),
+            Field::Revenue => data
+                .get_income_opt(period, "营业收入")
+                .or_else(|| data.get_income_opt(period, "营业总收入")),
+            Field::NetProfit => net_profit(),
+            Field::Ocf => data.get_cashflow_opt(period, "经营活动产生的现金流量净额"),
+            Field::Dol => leverage.and_then(|l| series(&l.operating_leverage)),
+            Field::Dfl => leverage.and_then(|l| series(&l.financial_leverage)),
+            Field::Dtl => leverage.and_then(|l| series(&l.total_leverage)),
+            Field::DcfValue | Field::DcfPrice | Field::LowPrice | Field::HighPrice | Field::SafetyPrice => {
+                Self::valuation(self, result)
+            }
+            Field::Price => candidate.price,
+            // 多张报表时取最低分
+            Field::Reliability => result
+                .validation
+                .iter()
+                .map(|v| v.result.reliability_score)
+                .reduce(f64::min),
+        }
+    }
+
+    /// 估值结果：敏感性分析（分析时指定的参数）优先于默认参数的估值
+    fn valuation(self, result: &AnalysisResult) -> Option<f64> {
+        let values = match (&result.sensitivity, &result.valuation) {
+            (Some(s), _) => [
+                s.dcf_enterprise_value,
+                s.dcf_price_per_share,
+                s.tangchao_low_estimate,
+                s.tangchao_high_estimate,
+                s.tangchao_safety_margin_price,
+            ],
+            (None, Some(v)) => [
+                v.dcf.enterprise_value,
+                v.dcf.price_per_share,
+                v.tangchao.low_estimate,
+                v.tangchao.high_estimate,
+                v.tangchao.safety_margin_price,
+            ],
+            (None, None) => return None,
+        };
+        let index = match self {
+            Field::DcfValue => 0,
+            Field::DcfPrice => 1,
+            Field::LowPrice => 2,
+            Field::HighPrice => 3,
+            _ => 4,
+        };
+        values[index].to_f64()
+    }
+}
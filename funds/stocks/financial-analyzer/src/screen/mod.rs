@@ -0,0 +1,224 @@
+//! 选股筛选：在已保存的分析报告（`--format json`）上按表达式过滤并排名
+//!
+//! 表达式语法见 `expr` 模块，可用字段见 `fields` 模块。股价不在分析结果中，
+//! 通过 `--prices` 指定的 CSV（`code,price`）提供。
+
+mod expr;
+mod fields;
+#[cfg(test)]
+mod tests;
+
+pub use expr::{Expr, Term};
+pub use fields::Candidate;
+
+use crate::domain::stock_key;
+use crate::report::JsonReporter;
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 入选的一只股票
+#[derive(Debug, Clone)]
+pub struct ScreenRow {
+    pub stock_code: String,
+    /// 最新一期的表头文字
+    pub period: String,
+    /// 排序依据的值
+    pub score: Option<f64>,
+    /// 与 `ScreenResult::terms` 对应的取值
+    pub values: Vec<Option<f64>>,
+}
+
+/// 筛选结果，`rows` 已按排序依据排好
+#[derive(Debug, Clone)]
+pub struct ScreenResult {
+    pub condition: String,
+    pub sort: String,
+    /// 条件和排序依据引用的取值，作为结果表的列
+    pub terms: Vec<Term>,
+    pub rows: Vec<ScreenRow>,
+    /// 参与筛选的股票数
+    pub screened: usize,
+}
+
+/// 选股器
+pub struct Screener {
+    condition_text: String,
+    condition: Expr,
+    sort_text: String,
+    sort: Expr,
+    ascending: bool,
+    limit: Option<usize>,
+}
+
+impl Screener {
+    /// 解析筛选条件和排序依据（数值表达式，缺省从高到低）
+    pub fn new(condition: &str, sort: &str) -> Result<Self> {
+        Ok(Self {
+            condition_text: condition.to_string(),
+            condition: Expr::condition(condition)?,
+            sort_text: sort.to_string(),
+            sort: Expr::number(sort)?,
+            ascending: false,
+            limit: None,
+        })
+    }
+
+    /// 按排序依据从低到高排列
+    pub fn with_ascending(mut self, ascending: bool) -> Self {
+        self.ascending = ascending;
+        self
+    }
+
+    /// 只保留前 N 名
+    pub fn with_limit(mut self, limit: Option<usize>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn run(&self, candidates: &[Candidate]) -> ScreenResult {
+        let mut terms = self.condition.terms();
+        for term in self.sort.terms() {
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+
+        let mut rows: Vec<ScreenRow> = candidates
+            .iter()
+            .filter(|candidate| self.condition.test(candidate) == Some(true))
+            .map(|candidate| ScreenRow {
+                stock_code: candidate.result.stock_code.clone(),
+                period: candidate.result.period_label(0),
+                score: self.sort.eval(candidate),
+                values: terms.iter().map(|term| term.value(candidate)).collect(),
+            })
+            .collect();
+
+        // 排序依据缺失的排在最后，相同时按代码排列
+        rows.sort_by(|a, b| {
+            let order = match (a.score, b.score) {
+                (Some(x), Some(y)) if self.ascending => x.total_cmp(&y),
+                (Some(x), Some(y)) => y.total_cmp(&x),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            };
+            order.then_with(|| a.stock_code.cmp(&b.stock_code))
+        });
+        if let Some(limit) = self.limit {
+            rows.truncate(limit);
+        }
+
+        ScreenResult {
+            condition: self.condition_text.clone(),
+            sort: self.sort_text.clone(),
+            terms,
+            rows,
+            screened: candidates.len(),
+        }
+    }
+}
+
+impl ScreenResult {
+    /// 控制台表格
+    pub fn table(&self) -> String {
+        let mut headers = vec!["排名".to_string(), "股票代码".to_string(), "报告期".to_string(), format!("排序: {}", self.sort)];
+        headers.extend(self.terms.iter().map(|term| term.to_string()));
+        let widths: Vec<usize> = headers.iter().map(|h| display_width(h).max(12)).collect();
+
+        let mut lines = vec![Self::line(&headers, &widths)];
+        for (i, row) in self.rows.iter().enumerate() {
+            let mut cells = vec![
+                (i + 1).to_string(),
+                row.stock_code.clone(),
+                row.period.clone(),
+                row.score.map_or_else(|| "-".to_string(), |v| format!("{:.4}", v)),
+            ];
+            cells.extend(self.terms.iter().zip(&row.values).map(|(term, value)| term.unit().format(*value)));
+            lines.push(Self::line(&cells, &widths));
+        }
+        lines.join("\n")
+    }
+
+    fn line(cells: &[String], widths: &[usize]) -> String {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width.saturating_sub(display_width(cell)))))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    }
+}
+
+/// 终端显示宽度（中文按两个字符计）
+fn display_width(text: &str) -> usize {
+    text.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum()
+}
+
+/// 读取股价 CSV（首行为列名，含 `code` 和 `price` 两列），代码按 `stock_key` 统一
+pub fn load_prices(path: &Path) -> Result<HashMap<String, f64>> {
+    #[derive(serde::Deserialize)]
+    struct PriceRow {
+        code: String,
+        price: f64,
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .with_context(|| format!("无法读取股价文件 {}", path.display()))?;
+    let mut prices = HashMap::new();
+    for (i, row) in reader.deserialize().enumerate() {
+        let row: PriceRow = row.with_context(|| format!("股价文件 {} 第 {} 行", path.display(), i + 2))?;
+        prices.insert(stock_key(&row.code), row.price);
+    }
+    Ok(prices)
+}
+
+/// 读取目录（含子目录）中的 JSON 分析报告；同一股票有多份报告时取最新生成的一份
+///
+/// 报告保留分析时的代码写法，按 `stock_key` 归并同一股票并匹配股价。
+/// 无法识别的 JSON 文件跳过并记录警告。
+pub fn load_candidates(dir: &Path, prices: &HashMap<String, f64>) -> Result<Vec<Candidate>> {
+    let mut files = Vec::new();
+    collect_json_files(dir, &mut files).with_context(|| format!("无法读取报告目录 {}", dir.display()))?;
+    files.sort();
+
+    let mut latest: HashMap<String, (i64, Candidate)> = HashMap::new();
+    for file in files {
+        let (report, result) = match JsonReporter::read(&file) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                tracing::warn!("跳过 {}: {:#}", file.display(), e);
+                continue;
+            }
+        };
+        let generated_at = DateTime::parse_from_rfc3339(&report.generated_at).map_or(0, |t| t.timestamp());
+        let key = stock_key(&result.stock_code);
+        if latest.get(&key).is_some_and(|(t, _)| *t > generated_at) {
+            continue;
+        }
+        let price = prices.get(&key).copied();
+        latest.insert(key, (generated_at, Candidate { result, price }));
+    }
+
+    let mut candidates: Vec<Candidate> = latest.into_values().map(|(_, candidate)| candidate).collect();
+    candidates.sort_by(|a, b| a.result.stock_code.cmp(&b.result.stock_code));
+    Ok(candidates)
+}
+
+fn collect_json_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_json_files(&path, files)?;
+        } else if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("json")) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,133 @@
+//! 选股筛选测试
+
+use super::expr::Expr;
+use super::{load_candidates, load_prices, Candidate, Screener};
+use crate::analyzer::{FinancialAnalyzer, SensitivityParams};
+use crate::data_source::MockDataSource;
+use crate::domain::AnalysisResult;
+use crate::report::JsonReporter;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+async fn mock_result(code: &str) -> AnalysisResult {
+    let analyzer = FinancialAnalyzer::new();
+    let mut result = analyzer
+        .analyze(code, vec![2023, 2022, 2021], &MockDataSource::new())
+        .await
+        .unwrap();
+    analyzer.calculate_sensitivity(&mut result, SensitivityParams::default()).unwrap();
+    result
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("financial-analyzer-screen-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_parse_errors() {
+    let message = |text: &str| format!("{:#}", Expr::condition(text).unwrap_err());
+
+    assert!(message("roe > 15% and foo < 1").contains("foo"));
+    assert!(message("roe > 15% )").contains("第 11 个字符"));
+    assert!(message("roe >").contains("第 6 个字符"));
+    assert!(message("price[1] > 10").contains("price"));
+    assert!(message("avg(price, 3) > 10").contains("price"));
+    assert!(message("roe + 1").contains("比较或逻辑"));
+    assert!(message("roe > 15% # 注释").contains("'#'"));
+    assert!(Expr::number("roe > 1").is_err());
+    assert!(message("min(roe, 0) > 0").contains("第 10 个字符"));
+    assert!(message("avg(roe, 0) > 0").contains("不小于1"));
+    assert!(Expr::condition("roe[0] > 0").is_ok());
+
+    assert!(Expr::condition("min(roe, 3) > 15% && !(debt_ratio >= 0.6) || roe[1] > roe").is_ok());
+}
+
+#[tokio::test]
+async fn test_evaluate_against_mock_result() {
+    let candidate = Candidate {
+        result: mock_result("600519.SH").await,
+        price: None,
+    };
+    let test = |text: &str| Expr::condition(text).unwrap().test(&candidate);
+    let eval = |text: &str| Expr::number(text).unwrap().eval(&candidate).unwrap();
+
+    // 模拟数据 ROE 为 40%，净利润率 20%
+    assert!((eval("roe") - 0.4).abs() < 1e-9);
+    assert!((eval("min(net_margin, 3) * 100") - 20.0).abs() < 1e-6);
+    assert!((eval("-roe + 1") - 0.6).abs() < 1e-9);
+    assert_eq!(test("roe > 30% and net_margin == 20%"), Some(true));
+    assert_eq!(test("roe > 50% or not net_margin > 0.1"), Some(false));
+    assert_eq!(test("avg(roe, 3) >= roe[2]"), Some(true));
+    assert_eq!(test("dcf_price > 0 and reliability >= 0"), None);
+
+    // 缺少股价：比较为未知，按三值逻辑参与 and/or
+    assert_eq!(test("price < safety_price"), None);
+    assert_eq!(test("not price < safety_price"), None);
+    assert_eq!(test("price < safety_price or roe > 30%"), Some(true));
+    assert_eq!(test("price < safety_price and roe > 50%"), Some(false));
+    // 超出已有期数时为缺失
+    assert_eq!(test("roe[5] > 0"), None);
+    assert_eq!(test("min(roe, 4) > 0"), None);
+}
+
+#[tokio::test]
+async fn test_screener_ranks_and_limits() {
+    let result = mock_result("600519.SH").await;
+    let candidates: Vec<Candidate> = [("600519.SH", Some(20.0)), ("000858.SZ", None), ("000568.SZ", Some(10.0)), ("600000.SH", Some(30.0))]
+        .into_iter()
+        .map(|(code, price)| {
+            let mut result = result.clone();
+            result.stock_code = code.to_string();
+            Candidate { result, price }
+        })
+        .collect();
+
+    // 缺少股价的股票排在最后
+    let screen = Screener::new("roe > 15%", "price").unwrap().run(&candidates);
+    let codes: Vec<&str> = screen.rows.iter().map(|r| r.stock_code.as_str()).collect();
+    assert_eq!(codes, vec!["600000.SH", "600519.SH", "000568.SZ", "000858.SZ"]);
+    assert_eq!(screen.screened, 4);
+    assert_eq!(screen.terms.iter().map(|t| t.to_string()).collect::<Vec<_>>(), vec!["roe", "price"]);
+    assert_eq!(screen.rows[0].values, vec![Some(0.4), Some(30.0)]);
+
+    let screen = Screener::new("price <= 20", "price")
+        .unwrap()
+        .with_ascending(true)
+        .with_limit(Some(1))
+        .run(&candidates);
+    assert_eq!(screen.rows.len(), 1);
+    assert_eq!(screen.rows[0].stock_code, "000568.SZ");
+    assert!(screen.table().contains("000568.SZ"));
+
+    assert!(Screener::new("roe > 100%", "roe").unwrap().run(&candidates).rows.is_empty());
+}
+
+#[tokio::test]
+async fn test_load_stored_reports() {
+    let dir = temp_dir("load");
+    let result = mock_result("600519.SH").await;
+    JsonReporter::write(&result, &dir.join("600519.json")).unwrap();
+    std::fs::create_dir_all(dir.join("batch")).unwrap();
+    JsonReporter::write(&mock_result("000858.SZ").await, &dir.join("batch/000858.json")).unwrap();
+    std::fs::write(dir.join("other.json"), r#"{"name": "不是报告"}"#).unwrap();
+    std::fs::write(dir.join("prices.csv"), "code,price\nsh600519, 1500\n000858,120.5\n").unwrap();
+
+    let prices = load_prices(&dir.join("prices.csv")).unwrap();
+    assert_eq!(prices, HashMap::from([("600519.SH".to_string(), 1500.0), ("000858.SZ".to_string(), 120.5)]));
+
+    let candidates = load_candidates(&dir, &prices).unwrap();
+    let codes: Vec<&str> = candidates.iter().map(|c| c.result.stock_code.as_str()).collect();
+    assert_eq!(codes, vec!["000858.SZ", "600519.SH"]);
+    assert_eq!(candidates[1].price, Some(1500.0));
+
+    // 读回的结果与分析结果取值一致
+    let expr = Expr::number("roe + min(gross_margin, 3) + dcf_price").unwrap();
+    let original = Candidate { result, price: None };
+    assert_eq!(expr.eval(&candidates[1]), expr.eval(&original));
+    assert_eq!(candidates[1].result.period_label(0), original.result.period_label(0));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -10,6 +10,17 @@ pub struct AccountMapping {
     pub financial_assets: HashMap<String, Vec<String>>,
     pub operating_liabilities: HashMap<String, Vec<String>>,
     pub financial_liabilities: HashMap<String, Vec<String>>,
+    /// 合计、股本等不参与分类的科目
+    #[serde(default)]
+    pub other_accounts: HashMap<String, Vec<String>>,
+    /// 合并列示的科目（如应收票据及应收账款）及其组成的标准科目
+    #[serde(default)]
+    pub combined_accounts: HashMap<String, Vec<String>>,
+    /// 利润表、现金流量表科目的别名
+    #[serde(default)]
+    pub income_statement: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub cashflow_statement: HashMap<String, Vec<String>>,
 }
 
 impl AccountMapping {
@@ -19,6 +30,12 @@ impl AccountMapping {
         Ok(mapping)
     }
 
+    /// 编译期内置的默认映射（配置文件缺失时使用）
+    pub fn builtin() -> Self {
+        toml::from_str(include_str!("../../config/account_mapping.toml"))
+            .expect("内置科目映射配置格式错误")
+    }
+
     pub fn default_path() -> &'static str {
         "config/account_mapping.toml"
     }
@@ -78,6 +95,132 @@ impl ValidationRules {
     }
 }
 
+/// 数据源配置
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DataSourcesConfig {
+    #[serde(default)]
+    pub tushare: TushareConfig,
+    #[serde(default)]
+    pub akshare: AkshareConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub store: StoreConfig,
+}
+
+/// Tushare接口配置（Token从环境变量读取）
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TushareConfig {
+    pub api_url: String,
+    pub timeout_secs: u64,
+    /// 失败后的最大重试次数
+    pub retry_times: u32,
+    /// 首次重试等待时间，之后每次翻倍
+    #[serde(default = "TushareConfig::default_retry_delay_ms")]
+    pub retry_delay_ms: u64,
+    pub requests_per_minute: u32,
+}
+
+impl TushareConfig {
+    fn default_retry_delay_ms() -> u64 {
+        500
+    }
+}
+
+impl Default for TushareConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "http://api.tushare.pro".to_string(),
+            timeout_secs: 30,
+            retry_times: 3,
+            retry_delay_ms: Self::default_retry_delay_ms(),
+            requests_per_minute: 200,
+        }
+    }
+}
+
+/// AKShare配置（通过常驻Python进程调用）
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AkshareConfig {
+    /// Python 解释器
+    pub python_path: String,
+    /// 启动进程并导入 akshare 的超时时间
+    pub startup_timeout_secs: u64,
+    /// 单次请求的超时时间，超时后重启进程
+    pub timeout_secs: u64,
+}
+
+impl Default for AkshareConfig {
+    fn default() -> Self {
+        Self {
+            python_path: if cfg!(windows) { "python" } else { "python3" }.to_string(),
+            startup_timeout_secs: 60,
+            timeout_secs: 120,
+        }
+    }
+}
+
+/// 本地缓存配置
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    pub ttl_secs: u64,
+    #[serde(default = "CacheConfig::default_dir")]
+    pub dir: String,
+}
+
+impl CacheConfig {
+    fn default_dir() -> String {
+        "cache".to_string()
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_secs: 86400,
+            dir: Self::default_dir(),
+        }
+    }
+}
+
+/// 本地报表库配置
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StoreConfig {
+    /// SQLite 数据库文件路径
+    pub path: String,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        Self {
+            path: "store/statements.db".to_string(),
+        }
+    }
+}
+
+impl DataSourcesConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let config: DataSourcesConfig = toml::from_str(&content)?;
+        Ok(config)
+    }
+
+    pub fn default_path() -> &'static str {
+        "config/data_sources.toml"
+    }
+
+    /// 读取默认路径的配置，配置文件缺失时使用默认值
+    pub fn load_or_default() -> Self {
+        Self::load(Path::new(Self::default_path())).unwrap_or_else(|e| {
+            tracing::warn!("无法加载数据源配置，使用默认设置: {}", e);
+            Self::default()
+        })
+    }
+}
+
 /// 系统配置
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -1,3 +1,3 @@
 pub mod config;
 
-pub use config::Config;
+pub use config::{AccountMapping, AkshareConfig, CacheConfig, Config, DataSourcesConfig, StoreConfig, TushareConfig};
@@ -2,4 +2,4 @@ pub mod validator;
 #[cfg(test)]
 mod tests;
 
-pub use validator::{DataValidator, ValidationResult, ValidationError, ValidationWarning, Severity};
+pub use validator::{DataValidator, StatementValidation};
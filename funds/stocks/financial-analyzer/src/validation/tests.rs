@@ -3,15 +3,65 @@
 //! 注意: 完整的验证测试需要真实的财务数据结构，
 //! 应该作为集成测试运行。
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::validation::DataValidator;
+use crate::data_source::AccountClassifier;
+use crate::domain::BalanceSheet;
+use crate::utils::config::ValidationRules;
+use crate::validation::DataValidator;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::path::Path;
 
-    #[test]
-    fn test_validator_creation() {
-        // DataValidator需要ValidationRules参数
-        // 这里只测试基本功能
-        assert!(true);
-    }
+fn validator() -> DataValidator {
+    let rules = ValidationRules::load(Path::new(ValidationRules::default_path())).unwrap();
+    DataValidator::new(rules)
+}
+
+fn balance_sheet(items: &[(&str, i64)]) -> BalanceSheet {
+    let items: HashMap<String, Decimal> = items
+        .iter()
+        .map(|(name, value)| (name.to_string(), Decimal::new(*value, 0)))
+        .collect();
+    AccountClassifier::default().build_balance_sheet(
+        "600519.SH",
+        NaiveDate::from_ymd_opt(2023, 12, 31).unwrap(),
+        items,
+    )
+}
+
+#[test]
+fn test_validator_creation() {
+    let bs = balance_sheet(&[
+        ("资产总计", 4_000_000),
+        ("负债合计", 1_500_000),
+        ("所有者权益合计", 2_500_000),
+        ("货币资金", 1_000_000),
+    ]);
+    let result = validator().validate_balance_sheet(&bs);
+    assert!(result.is_valid);
+}
+
+#[test]
+fn test_unbalanced_sheet_is_invalid() {
+    let bs = balance_sheet(&[
+        ("资产总计", 4_000_000),
+        ("负债合计", 1_000_000),
+        ("所有者权益合计", 2_500_000),
+        ("货币资金", 1_000_000),
+    ]);
+    let result = validator().validate_balance_sheet(&bs);
+    assert!(!result.is_valid);
+}
+
+#[test]
+fn test_tushare_fields_pass_required_accounts() {
+    let bs = balance_sheet(&[
+        ("total_assets", 4_000_000),
+        ("total_liab", 1_500_000),
+        ("total_hldr_eqy_inc_min_int", 2_500_000),
+        ("money_cap", 1_000_000),
+    ]);
+    let result = validator().validate_balance_sheet(&bs);
+    assert!(result.is_valid);
+    assert!(result.errors.is_empty());
 }
@@ -1,6 +1,6 @@
 use crate::domain::*;
 use crate::utils::config::ValidationRules;
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use rust_decimal::Decimal;
 use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
@@ -14,6 +14,15 @@ pub struct ValidationResult {
     pub reliability_score: f64,
 }
 
+/// 单张报表的验证结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatementValidation {
+    pub report_type: ReportType,
+    pub report_date: chrono::NaiveDate,
+    #[serde(flatten)]
+    pub result: ValidationResult,
+}
+
 /// 验证错误
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationError {
@@ -39,6 +48,18 @@ pub enum Severity {
     Low,       // 低风险
 }
 
+impl Severity {
+    /// 中文名称，用于报告
+    pub fn display_name(&self) -> &str {
+        match self {
+            Self::Critical => "致命",
+            Self::High => "高",
+            Self::Medium => "中",
+            Self::Low => "低",
+        }
+    }
+}
+
 /// 数据验证器
 pub struct DataValidator {
     rules: ValidationRules,
@@ -52,7 +73,7 @@ impl DataValidator {
     /// 验证资产负债表
     pub fn validate_balance_sheet(&self, bs: &BalanceSheet) -> ValidationResult {
         let mut errors = Vec::new();
-        let mut warnings = Vec::new();
+        let warnings = Vec::new();
 
         // 1. 会计恒等式验证
         if let Err(e) = self.check_accounting_equation(bs) {